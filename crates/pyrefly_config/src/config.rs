@@ -65,6 +65,7 @@ use crate::base::ExtraConfigs;
 use crate::base::InferReturnTypes;
 use crate::base::Preset;
 use crate::base::RecursionLimitConfig;
+use crate::environment::environment::EnvironmentDiscovery;
 use crate::environment::environment::PythonEnvironment;
 use crate::environment::interpreters::Interpreters;
 use crate::error::ErrorConfig;
@@ -74,7 +75,9 @@ use crate::error_kind::Severity;
 use crate::finder::ConfigError;
 use crate::migration::run::MigratedFromKind;
 use crate::module_wildcard::Match;
+use crate::organize_imports::OrganizeImportsConfig;
 use crate::pyproject::PyProject;
+use crate::script::Script;
 use crate::util::ConfigOrigin;
 
 pub static GENERATED_FILE_CONFIG_OVERRIDE: LazyLock<
@@ -217,6 +220,12 @@ pub enum OutputFormat {
     CodeClimate,
     /// Emit SARIF
     Sarif,
+    /// Emit a reviewdog diagnostic result (rdjson)
+    Rdjson,
+    /// Emit reviewdog diagnostics, one JSON object per line (rdjsonl)
+    Rdjsonl,
+    /// Emit Checkstyle XML (e.g. for the Jenkins Warnings plugin)
+    Checkstyle,
     /// Only show error count, omitting individual errors
     OmitErrors,
 }
@@ -649,6 +658,10 @@ pub struct ConfigFile {
     #[serde(default, skip_serializing_if = "CoverageConfig::is_empty")]
     pub coverage: CoverageConfig,
 
+    /// Options for the `source.organizeImports` code action.
+    #[serde(default, skip_serializing_if = "OrganizeImportsConfig::is_empty")]
+    pub organize_imports: OrganizeImportsConfig,
+
     /// Whether to respect ignore files (.gitignore, .ignore, .git/exclude).
     #[serde(
         default = "ConfigFile::default_true",
@@ -692,6 +705,11 @@ pub struct ConfigFile {
     #[serde(skip)]
     #[derivative(PartialEq = "ignore")]
     pub synthesized_preset_reason: Option<SynthesizedPresetReason>,
+
+    /// Runtime-only metadata. Set on the config derived for a single PEP 723 script, whose
+    /// `requires-python` and environment replace the project's. Never serialized.
+    #[serde(skip)]
+    pub script: Option<Arc<Script>>,
 }
 
 impl Default for ConfigFile {
@@ -707,6 +725,8 @@ impl Default for ConfigFile {
                 python_interpreter_find_command: None,
                 conda_environment: None,
                 skip_interpreter_query: false,
+                environment_discovery: EnvironmentDiscovery::Auto,
+                project_environment: None,
             },
             search_path_from_args: Vec::new(),
             search_path_from_file: Vec::new(),
@@ -720,6 +740,7 @@ impl Default for ConfigFile {
             root: Default::default(),
             sub_configs: Default::default(),
             coverage: Default::default(),
+            organize_imports: Default::default(),
             build_system: Default::default(),
             source_db: Default::default(),
             use_ignore_files: true,
@@ -731,6 +752,7 @@ impl Default for ConfigFile {
             skip_lsp_config_indexing: false,
             extra_file_extensions: Vec::new(),
             synthesized_preset_reason: None,
+            script: None,
         }
     }
 }
@@ -1382,7 +1404,10 @@ impl ConfigFile {
         } else {
             match self.interpreters.find_interpreter(project_root.as_deref()) {
                 Ok(interpreter) => {
-                    let (env, error) = PythonEnvironment::get_interpreter_env(&interpreter);
+                    let (env, error) = PythonEnvironment::get_interpreter_env(
+                        &interpreter,
+                        self.interpreters.environment_discovery,
+                    );
                     self.python_environment.override_empty(env);
                     self.interpreters.python_interpreter_path = Some(interpreter);
                     if let Some(error) = error {
@@ -1758,6 +1783,12 @@ impl ConfigFile {
                     "Extra keys found in coverage config: {extra_keys}"
                 )));
             }
+            if !config.organize_imports.extras.0.is_empty() {
+                let extra_keys = config.organize_imports.extras.0.keys().join(", ");
+                errors.push(ConfigError::warn(anyhow!(
+                    "Extra keys found in organize-imports config: {extra_keys}"
+                )));
+            }
             for sub_config in &config.sub_configs {
                 if !sub_config.settings.extras.0.is_empty() {
                     let extra_keys = sub_config.settings.extras.0.keys().join(", ");
@@ -2037,6 +2068,8 @@ mod tests {
                     python_interpreter_find_command: None,
                     conda_environment: None,
                     skip_interpreter_query: false,
+                    environment_discovery: EnvironmentDiscovery::Auto,
+                    project_environment: None,
                 },
                 root: ConfigBase {
                     extras: Default::default(),
@@ -2106,6 +2139,7 @@ mod tests {
                 skip_lsp_config_indexing: false,
                 extra_file_extensions: Vec::new(),
                 synthesized_preset_reason: None,
+                script: None,
             }
         );
     }
@@ -2412,6 +2446,8 @@ mod tests {
                 python_interpreter_find_command: None,
                 conda_environment: None,
                 skip_interpreter_query: false,
+                environment_discovery: EnvironmentDiscovery::Auto,
+                project_environment: None,
             },
             root: Default::default(),
             source_db: Default::default(),
@@ -2432,6 +2468,7 @@ mod tests {
             skip_lsp_config_indexing: false,
             extra_file_extensions: Vec::new(),
             synthesized_preset_reason: None,
+            script: None,
         };
 
         let current_dir = std::env::current_dir().unwrap();
@@ -2474,6 +2511,8 @@ mod tests {
                 python_interpreter_find_command: None,
                 conda_environment: None,
                 skip_interpreter_query: false,
+                environment_discovery: EnvironmentDiscovery::Auto,
+                project_environment: None,
             },
             search_path_from_args: Vec::new(),
             search_path_from_file: search_path,
@@ -2505,6 +2544,7 @@ mod tests {
             skip_lsp_config_indexing: false,
             extra_file_extensions: Vec::new(),
             synthesized_preset_reason: None,
+            script: None,
         };
         assert_eq!(config, expected_config);
     }
@@ -2594,6 +2634,20 @@ baseline-error-level = "warn"
         assert_eq!(config.baseline_error_level, Some(Severity::Warn));
     }
 
+    #[test]
+    fn test_environment_discovery_config_parsing() {
+        let config = ConfigFile::parse_config("").unwrap();
+        assert_eq!(
+            config.interpreters.environment_discovery,
+            EnvironmentDiscovery::Auto
+        );
+        let config = ConfigFile::parse_config("environment-discovery = \"static\"\n").unwrap();
+        assert_eq!(
+            config.interpreters.environment_discovery,
+            EnvironmentDiscovery::Static
+        );
+    }
+
     #[test]
     fn test_output_format_config_parsing() {
         let config_str = r#"
@@ -2617,6 +2671,19 @@ output-format = "omit-errors"
         assert_eq!(config.output_format, Some(OutputFormat::Sarif));
     }
 
+    #[test]
+    fn test_output_format_reviewdog_and_checkstyle_config_parsing() {
+        for (value, format) in [
+            ("rdjson", OutputFormat::Rdjson),
+            ("rdjsonl", OutputFormat::Rdjsonl),
+            ("checkstyle", OutputFormat::Checkstyle),
+        ] {
+            let config =
+                ConfigFile::parse_config(&format!(r#"output-format = "{value}""#)).unwrap();
+            assert_eq!(config.output_format, Some(format));
+        }
+    }
+
     #[test]
     fn test_output_format_full_text_with_github_config_parsing() {
         let config_str = r#"output-format = "full-text-with-github""#;
@@ -3651,6 +3718,8 @@ output-format = "omit-errors"
                 python_interpreter_find_command: None,
                 conda_environment: None,
                 skip_interpreter_query: false,
+                environment_discovery: EnvironmentDiscovery::Auto,
+                project_environment: None,
             },
             project_includes: ConfigFile::default_project_includes(),
             ..Default::default()
@@ -7,10 +7,17 @@
 
 pub(crate) mod active_environment;
 pub(crate) mod conda;
+pub mod editable;
 #[expect(
     clippy::module_inception,
     reason = "environment is both the module group and public API"
 )]
 pub mod environment;
+pub(crate) mod hatch;
 pub mod interpreters;
+pub(crate) mod layout;
+pub(crate) mod pdm;
+pub(crate) mod pixi;
+pub mod project_environment;
+pub(crate) mod uv;
 pub(crate) mod venv;
@@ -0,0 +1,373 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Editable installs, which make a package's source tree importable without copying it into
+//! site packages. Older installers add the source directory to a `.pth` file, which puts it on
+//! `sys.path`. Newer ones install a finder module that redirects imports with an import hook,
+//! along with a `.pth` file that imports it. Since we never run either, we statically read the
+//! finder modules written by setuptools, hatchling (through `editables`) and scikit-build-core
+//! to recover where each package lives.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+
+use dupe::Dupe;
+use itertools::Itertools;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_util::lock::Mutex;
+use regex::Regex;
+use ruff_python_ast::Expr;
+use ruff_python_ast::PySourceType;
+use ruff_python_ast::Stmt;
+use starlark_map::small_map::SmallMap;
+
+use crate::environment::layout::PthLine;
+use crate::environment::layout::pth_lines;
+use crate::environment::layout::read_pth_files;
+
+/// The modules imported by an `import` line in a `.pth` file, which `site` executes.
+static PTH_IMPORT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^import[ \t]+([\w.]+(?:[ \t]*,[ \t]*[\w.]+)*)").unwrap());
+
+/// A directory an editable install makes importable.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct EditableRoot {
+    /// The only package importable from `root`, or `None` if all of `root` is on `sys.path`.
+    module: Option<ModuleName>,
+    root: PathBuf,
+}
+
+impl EditableRoot {
+    fn provides(&self, module: ModuleName) -> bool {
+        self.module.is_none_or(|provided| {
+            module == provided
+                || module
+                    .as_str()
+                    .strip_prefix(provided.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        })
+    }
+}
+
+/// The directory `module` would be found in if it lives at `path`, which is the package
+/// directory or its `__init__.py` for a package, and the source file for a module. Returns
+/// `None` if the path doesn't end in the module's components, as when a package is installed
+/// under a different name than its directory, which a search root can't express.
+fn search_root(module: ModuleName, path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_str()?;
+    let mut path = if file_name.starts_with("__init__.") {
+        path.parent()?
+    } else {
+        path
+    }
+    .to_path_buf();
+    // Strip extensions, including the platform tags of extension modules (`foo.cpython-312.so`).
+    if let Some(stem) = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.split_once('.'))
+        .map(|(stem, _)| stem.to_owned())
+    {
+        path.set_file_name(stem);
+    }
+    for component in module.components().iter().rev() {
+        if path.file_name()?.to_str()? != component.as_str() {
+            return None;
+        }
+        path.pop();
+    }
+    Some(path)
+}
+
+fn string_literal(expr: &Expr) -> Option<String> {
+    Some(expr.as_string_literal_expr()?.value.to_str().to_owned())
+}
+
+/// Every `module -> path` entry of a dict literal, such as setuptools' `MAPPING`.
+fn dict_mappings(expr: &Expr) -> Vec<(String, String)> {
+    let Expr::Dict(dict) = expr else {
+        return Vec::new();
+    };
+    dict.items
+        .iter()
+        .filter_map(|item| {
+            Some((
+                string_literal(item.key.as_ref()?)?,
+                string_literal(&item.value)?,
+            ))
+        })
+        .collect()
+}
+
+/// The `module -> path` mappings a finder module installs.
+///
+/// - setuptools assigns them to `MAPPING`.
+/// - `editables`, used by hatchling, calls `map_module(name, path)` for each.
+/// - scikit-build-core passes them to `install(...)` as a dict.
+fn finder_mappings(source: &str) -> Vec<(String, String)> {
+    let (ast, _, _) = Ast::parse(source, PySourceType::Python);
+    let mut mappings = Vec::new();
+    for stmt in &ast.body {
+        match stmt {
+            Stmt::Assign(x) => mappings.extend(dict_mappings(&x.value)),
+            Stmt::AnnAssign(x) => {
+                if let Some(value) = &x.value {
+                    mappings.extend(dict_mappings(value));
+                }
+            }
+            Stmt::Expr(x) => {
+                let Expr::Call(call) = x.value.as_ref() else {
+                    continue;
+                };
+                let is_map_module = match call.func.as_ref() {
+                    Expr::Attribute(attr) => attr.attr.as_str() == "map_module",
+                    Expr::Name(name) => name.id.as_str() == "map_module",
+                    _ => false,
+                };
+                if is_map_module {
+                    if let [name, path, ..] = &*call.arguments.args
+                        && let Some(name) = string_literal(name)
+                        && let Some(path) = string_literal(path)
+                    {
+                        mappings.push((name, path));
+                    }
+                } else {
+                    for arg in &call.arguments.args {
+                        mappings.extend(dict_mappings(arg));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    mappings
+}
+
+fn find_editable_roots(site_package: &Path) -> Vec<EditableRoot> {
+    let mut roots = Vec::new();
+    for contents in read_pth_files(site_package) {
+        for line in pth_lines(&contents) {
+            match line {
+                PthLine::Import(line) => {
+                    let Some(imports) = PTH_IMPORT_REGEX.captures(line) else {
+                        continue;
+                    };
+                    for finder in imports[1].split(',') {
+                        let finder = site_package.join(format!("{}.py", finder.trim()));
+                        let Ok(source) = fs::read_to_string(&finder) else {
+                            continue;
+                        };
+                        for (module, path) in finder_mappings(&source) {
+                            let module = ModuleName::from_string(module);
+                            if let Some(root) = search_root(module, Path::new(&path)) {
+                                roots.push(EditableRoot {
+                                    module: Some(module),
+                                    root,
+                                });
+                            }
+                        }
+                    }
+                }
+                PthLine::Dir(dir) => {
+                    let root = site_package.join(dir);
+                    if root.is_dir() {
+                        roots.push(EditableRoot { module: None, root });
+                    }
+                }
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    roots.retain(|root| seen.insert(root.clone()));
+    roots
+}
+
+/// The roots found from the `.pth` files in each site package directory, which a
+/// `ConfigFinder` keeps until its caches are cleared.
+#[derive(Debug, Default)]
+pub struct EditableRoots(Mutex<SmallMap<PathBuf, Arc<Vec<EditableRoot>>>>);
+
+impl EditableRoots {
+    pub fn clear(&self) {
+        self.0.lock().clear();
+    }
+
+    fn get(&self, site_package: &Path) -> Arc<Vec<EditableRoot>> {
+        if let Some(roots) = self.0.lock().get(site_package) {
+            return roots.dupe();
+        }
+        // Read the finder modules without holding the lock.
+        let roots = Arc::new(find_editable_roots(site_package));
+        self.0
+            .lock()
+            .entry(site_package.to_path_buf())
+            .or_insert(roots)
+            .dupe()
+    }
+
+    /// The directories editable installs in `site_package_path` make importable, which might
+    /// contain `module`, in the order Python would find them.
+    pub fn install_roots<'a>(
+        &self,
+        site_package_path: impl Iterator<Item = &'a PathBuf>,
+        module: ModuleName,
+    ) -> Vec<PathBuf> {
+        site_package_path
+            .flat_map(|site_package| self.get(site_package).iter().cloned().collect::<Vec<_>>())
+            .filter(|root| root.provides(module))
+            .map(|root| root.root)
+            .unique()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    fn roots(site_package: &Path, module: &str) -> Vec<PathBuf> {
+        EditableRoots::default().install_roots(
+            [site_package.to_path_buf()].iter(),
+            ModuleName::from_str(module),
+        )
+    }
+
+    #[test]
+    fn test_search_root() {
+        let root =
+            |module: &str, path: &str| search_root(ModuleName::from_str(module), Path::new(path));
+        assert_eq!(root("pkg", "/src/pkg"), Some(PathBuf::from("/src")));
+        assert_eq!(
+            root("pkg", "/src/pkg/__init__.py"),
+            Some(PathBuf::from("/src"))
+        );
+        assert_eq!(
+            root("pkg.sub", "/src/pkg/sub.py"),
+            Some(PathBuf::from("/src"))
+        );
+        assert_eq!(
+            root(
+                "pkg._core",
+                "/build/pkg/_core.cpython-312-x86_64-linux-gnu.so"
+            ),
+            Some(PathBuf::from("/build"))
+        );
+        assert_eq!(root("pkg", "/src/lib"), None);
+    }
+
+    #[test]
+    fn test_setuptools_finder() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let site_packages = tempdir.path();
+        let src = site_packages.join("project").join("src");
+        let finder = format!(
+            "import sys\n\nMAPPING: dict[str, str] = {{'mypkg': {:?}}}\nNAMESPACES: dict[str, list[str]] = {{}}\n",
+            src.join("mypkg").to_str().unwrap()
+        );
+        TestPath::setup_test_directory(
+            site_packages,
+            vec![
+                TestPath::file_with_contents(
+                    "__editable__.mypkg-1.0.pth",
+                    "import __editable___mypkg_1_0_finder; __editable___mypkg_1_0_finder.install()\n",
+                ),
+                TestPath::file_with_contents("__editable___mypkg_1_0_finder.py", &finder),
+            ],
+        );
+
+        assert_eq!(roots(site_packages, "mypkg.sub"), vec![src]);
+        assert_eq!(roots(site_packages, "mypkg_other"), Vec::<PathBuf>::new());
+    }
+
+    #[test]
+    fn test_hatchling_editables_finder() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let site_packages = tempdir.path();
+        let src = site_packages.join("project").join("src");
+        let finder = format!(
+            "from editables.redirector import RedirectingFinder as F\nF.install()\nF.map_module('mypkg', {:?})\nF.map_module('single', {:?})\n",
+            src.join("mypkg").join("__init__.py").to_str().unwrap(),
+            src.join("single.py").to_str().unwrap(),
+        );
+        TestPath::setup_test_directory(
+            site_packages,
+            vec![
+                TestPath::file_with_contents(
+                    "_editable_impl_mypkg.pth",
+                    "import _editable_impl_mypkg\n",
+                ),
+                TestPath::file_with_contents("_editable_impl_mypkg.py", &finder),
+            ],
+        );
+
+        assert_eq!(roots(site_packages, "mypkg"), vec![src.clone()]);
+        assert_eq!(roots(site_packages, "single"), vec![src]);
+    }
+
+    #[test]
+    fn test_scikit_build_core_finder() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let site_packages = tempdir.path();
+        let src = site_packages.join("project").join("src");
+        let build = site_packages.join("project").join("build");
+        let finder = format!(
+            "import importlib.abc\n\ndef install(known_source_files, known_wheel_files, path, rebuild=False):\n    pass\n\ninstall({{'mypkg': {:?}, 'mypkg.util': {:?}}}, {{'mypkg._core': {:?}}}, None)\n",
+            src.join("mypkg").join("__init__.py").to_str().unwrap(),
+            src.join("mypkg").join("util.py").to_str().unwrap(),
+            build
+                .join("mypkg")
+                .join("_core.cpython-312-x86_64-linux-gnu.so")
+                .to_str()
+                .unwrap(),
+        );
+        TestPath::setup_test_directory(
+            site_packages,
+            vec![
+                TestPath::file_with_contents("_mypkg_editable.pth", "import _mypkg_editable\n"),
+                TestPath::file_with_contents("_mypkg_editable.py", &finder),
+            ],
+        );
+
+        assert_eq!(roots(site_packages, "mypkg.util"), vec![src.clone()]);
+        assert_eq!(roots(site_packages, "mypkg._core"), vec![src, build]);
+    }
+
+    #[test]
+    fn test_plain_pth_paths() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let site_packages = tempdir.path();
+        TestPath::setup_test_directory(
+            site_packages,
+            vec![
+                TestPath::file_with_contents(
+                    "_mypkg.pth",
+                    "# a comment\nproject/src\nproject/missing\nimportant\n",
+                ),
+                TestPath::file_with_contents("_other.pth", "project/src\n"),
+                TestPath::dir("project", vec![TestPath::dir("src", vec![])]),
+                TestPath::dir("important", vec![]),
+            ],
+        );
+
+        // Only lines starting with `import ` are executed, so `important` is a directory,
+        // and a directory listed by two files is only searched once.
+        assert_eq!(
+            roots(site_packages, "anything"),
+            vec![
+                site_packages.join("project").join("src"),
+                site_packages.join("important"),
+            ]
+        );
+    }
+}
@@ -26,11 +26,39 @@ use starlark_map::small_map::SmallMap;
 use tracing::warn;
 
 use crate::environment::interpreters::Interpreters;
+use crate::environment::layout;
 
 static INTERPRETER_ENV_REGISTRY: LazyLock<
-    Mutex<SmallMap<PathBuf, Result<PythonEnvironment, String>>>,
+    Mutex<SmallMap<(PathBuf, EnvironmentDiscovery), Result<PythonEnvironment, String>>>,
 > = LazyLock::new(|| Mutex::new(SmallMap::new()));
 
+/// How we learn the version, platform and site package path of an interpreter's environment.
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    Deserialize,
+    Serialize,
+    Clone,
+    Copy,
+    Default
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum EnvironmentDiscovery {
+    /// Run the interpreter, and read the environment from its directory layout if that fails.
+    #[default]
+    Auto,
+    /// Only read the environment from its directory layout, never running the interpreter.
+    Static,
+}
+
+impl EnvironmentDiscovery {
+    pub fn is_auto(&self) -> bool {
+        *self == Self::Auto
+    }
+}
+
 /// Values representing the environment of the Python interpreter.
 /// These values are `None` by default, so we can tell if a config
 /// overrode them, or if we should query a Python interpreter for
@@ -181,16 +209,52 @@ print(json.dumps({'python_platform': platform, 'python_version': version, 'site_
         Ok(deserialized)
     }
 
+    /// Given a path to an interpreter, read its environment from the interpreter's directory
+    /// layout, without running it. See [`layout::read_environment()`].
+    pub fn get_env_from_layout(interpreter: &Path) -> anyhow::Result<PythonEnvironment> {
+        let env = layout::read_environment(interpreter)?;
+        Self::cache_interpreter_stdlib_path(env.interpreter_stdlib_path.clone());
+        Ok(env)
+    }
+
+    fn get_env(
+        interpreter: &Path,
+        discovery: EnvironmentDiscovery,
+    ) -> anyhow::Result<PythonEnvironment> {
+        match discovery {
+            EnvironmentDiscovery::Static => Self::get_env_from_layout(interpreter),
+            EnvironmentDiscovery::Auto => match Self::get_env_from_interpreter(interpreter) {
+                Ok(env) => Ok(env),
+                Err(query_error) => match Self::get_env_from_layout(interpreter) {
+                    Ok(env) => {
+                        warn!(
+                            "Failed to query interpreter at {}, read its environment from its directory layout instead: {query_error:#}",
+                            interpreter.display()
+                        );
+                        Ok(env)
+                    }
+                    Err(layout_error) => Err(anyhow!(
+                        "{query_error:#}\nReading the environment from its directory layout also failed: {layout_error:#}"
+                    )),
+                },
+            },
+        }
+    }
+
     /// Given a path to an interpreter, query the interpreter with
-    /// [`Self::get_env_from_interpreter()`] and cache the result. If a cached
-    /// result already exists, return that.
+    /// [`Self::get_env_from_interpreter()`], or read its layout with
+    /// [`Self::get_env_from_layout()`], as `discovery` selects, and cache the result.
+    /// If a cached result already exists, return that.
     ///
     /// In the case of failure, log an error message and return Pyrefly's
     /// [`PythonEnvironment::default()`].
-    pub fn get_interpreter_env(interpreter: &Path) -> (PythonEnvironment, Option<anyhow::Error>) {
+    pub fn get_interpreter_env(
+        interpreter: &Path,
+        discovery: EnvironmentDiscovery,
+    ) -> (PythonEnvironment, Option<anyhow::Error>) {
         let env = INTERPRETER_ENV_REGISTRY.lock()
-        .entry(interpreter.to_path_buf()).or_insert_with(move || {
-            Self::get_env_from_interpreter(interpreter).map_err(|e| {
+        .entry((interpreter.to_path_buf(), discovery)).or_insert_with(move || {
+            Self::get_env(interpreter, discovery).map_err(|e| {
                 format!("Failed to query interpreter at {}, falling back to default Python environment settings\n{}", interpreter.display(), e)
             })
         }).clone();
@@ -205,11 +269,12 @@ print(json.dumps({'python_platform': platform, 'python_version': version, 'site_
     }
 
     /// [`Self::get_default_interpreter()`] and [`Self::get_interpreter_env()`] with the resulting value,
-    /// or return [`PythonEnvironment::default()`] if `None`.
-    pub fn get_default_interpreter_env() -> PythonEnvironment {
-        Interpreters::get_default_interpreter().map_or_else(Self::pyrefly_default, |path| {
-            Self::get_interpreter_env(path).0
-        })
+    /// found as `discovery` selects, or return [`PythonEnvironment::default()`] if `None`.
+    pub fn get_default_interpreter_env(discovery: EnvironmentDiscovery) -> PythonEnvironment {
+        Interpreters::get_default_interpreter(discovery)
+            .map_or_else(Self::pyrefly_default, |path| {
+                Self::get_interpreter_env(path, discovery).0
+            })
     }
 }
 
@@ -0,0 +1,235 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE;
+use pyrefly_python::script_metadata::normalize_distribution_name;
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::environment::venv;
+
+/// Overrides the location of Hatch's data directory.
+const DATA_DIR_ENV_VAR: &str = "HATCH_DATA_DIR";
+
+/// Hatch's own config file, which takes precedence over `[tool.hatch]` in `pyproject.toml`.
+const CONFIG_FILE: &str = "hatch.toml";
+
+fn read_toml(path: &Path) -> Option<toml::Table> {
+    toml::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+/// The directory Hatch stores its data in, following the platform conventions Hatch uses.
+fn data_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os(DATA_DIR_ENV_VAR) {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join("hatch"))
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|dir| {
+            PathBuf::from(dir)
+                .join("Library")
+                .join("Application Support")
+                .join("hatch")
+        })
+    } else if let Some(dir) = env::var_os("XDG_DATA_HOME") {
+        Some(PathBuf::from(dir).join("hatch"))
+    } else {
+        env::var_os("HOME").map(|dir| {
+            PathBuf::from(dir)
+                .join(".local")
+                .join("share")
+                .join("hatch")
+        })
+    }
+}
+
+/// The id Hatch gives the project at `root`: the start of the URL-safe base64 encoding of the
+/// SHA-256 of its path, with the path case-folded on platforms where paths ignore case.
+fn project_id(root: &Path) -> Option<String> {
+    let path = root.to_str()?;
+    let path = if cfg!(any(windows, target_os = "macos")) {
+        path.to_lowercase()
+    } else {
+        path.to_owned()
+    };
+    let mut id = URL_SAFE.encode(Sha256::digest(path.as_bytes()));
+    id.truncate(8);
+    Some(id)
+}
+
+/// Find the default environment of the project `name` at `root` among those Hatch created
+/// in `data_dir`.
+///
+/// Hatch stores it at `env/virtual/<project>/<project id>/<project>`, where the id is derived
+/// from the project's path, so projects sharing a name each find their own environment.
+fn find_in_data_dir(data_dir: &Path, name: &str, root: &Path) -> Option<PathBuf> {
+    let storage = data_dir.join("env").join("virtual");
+    let mut roots = vec![std::path::absolute(root).ok()?];
+    if let Ok(canonical) = root.canonicalize() {
+        roots.push(canonical);
+    }
+    let ids = roots
+        .iter()
+        .filter_map(|root| project_id(root))
+        .collect::<Vec<_>>();
+    [name.to_owned(), normalize_distribution_name(name)]
+        .iter()
+        .flat_map(|project| {
+            ids.iter()
+                .map(|id| storage.join(project).join(id).join(project))
+        })
+        .find_map(|environment| venv::find_active(&environment))
+}
+
+fn find_interpreter_in(root: &Path, data_dir: Option<&Path>) -> Option<PathBuf> {
+    let pyproject = read_toml(&root.join("pyproject.toml"));
+    let config = read_toml(&root.join(CONFIG_FILE)).or_else(|| {
+        pyproject
+            .as_ref()?
+            .get("tool")?
+            .get("hatch")?
+            .as_table()
+            .cloned()
+    })?;
+    // An explicit location for the default environment, typically inside the project.
+    if let Some(path) = config
+        .get("envs")
+        .and_then(|envs| envs.get("default"))
+        .and_then(|default| default.get("path"))
+        .and_then(|path| path.as_str())
+    {
+        return venv::find_active(&root.join(path));
+    }
+    let name = pyproject?.get("project")?.get("name")?.as_str()?.to_owned();
+    find_in_data_dir(data_dir?, &name, root)
+}
+
+/// Find the interpreter of the default environment Hatch created for the project at `root`.
+pub fn find_interpreter(root: &Path) -> Option<PathBuf> {
+    find_interpreter_in(root, data_dir().as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    fn venv(name: &str) -> TestPath {
+        let bin = if cfg!(windows) { "Scripts" } else { "bin" };
+        let interpreter = if cfg!(windows) {
+            "python.exe"
+        } else {
+            "python"
+        };
+        TestPath::dir(
+            name,
+            vec![
+                TestPath::file("pyvenv.cfg"),
+                TestPath::dir(bin, vec![TestPath::file(interpreter)]),
+            ],
+        )
+    }
+
+    fn interpreter(venv: &Path) -> PathBuf {
+        if cfg!(windows) {
+            venv.join("Scripts").join("python.exe")
+        } else {
+            venv.join("bin").join("python")
+        }
+    }
+
+    #[test]
+    fn test_find_interpreter_in_data_dir() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        let project_id = project_id(&root.join("project")).unwrap();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::dir(
+                    "project",
+                    vec![TestPath::file_with_contents(
+                        "pyproject.toml",
+                        "[project]\nname = \"My_App\"\n\n[tool.hatch.envs.default]\ndependencies = []\n",
+                    )],
+                ),
+                TestPath::dir(
+                    "other",
+                    vec![TestPath::file_with_contents(
+                        "pyproject.toml",
+                        "[project]\nname = \"my-app\"\n\n[tool.hatch.envs.default]\n",
+                    )],
+                ),
+                TestPath::dir(
+                    "unmanaged",
+                    vec![TestPath::file_with_contents(
+                        "pyproject.toml",
+                        "[project]\nname = \"my-app\"\n",
+                    )],
+                ),
+                TestPath::dir(
+                    "data",
+                    vec![TestPath::dir(
+                        "env",
+                        vec![TestPath::dir(
+                            "virtual",
+                            vec![TestPath::dir(
+                                "my-app",
+                                vec![TestPath::dir(&project_id, vec![venv("my-app")])],
+                            )],
+                        )],
+                    )],
+                ),
+            ],
+        );
+        let data = root.join("data");
+
+        assert_eq!(
+            find_interpreter_in(&root.join("project"), Some(&data)),
+            Some(interpreter(
+                &data
+                    .join("env/virtual/my-app")
+                    .join(&project_id)
+                    .join("my-app")
+            ))
+        );
+        // A project with the same name elsewhere has its own environment.
+        assert_eq!(find_interpreter_in(&root.join("other"), Some(&data)), None);
+        // Without any Hatch config, this isn't a Hatch project.
+        assert_eq!(
+            find_interpreter_in(&root.join("unmanaged"), Some(&data)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_find_interpreter_configured_path() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file_with_contents("pyproject.toml", "[project]\nname = \"app\"\n"),
+                TestPath::file_with_contents(CONFIG_FILE, "[envs.default]\npath = \".hatch\"\n"),
+                venv(".hatch"),
+            ],
+        );
+
+        assert_eq!(
+            find_interpreter_in(root, None),
+            Some(interpreter(&root.join(".hatch")))
+        );
+    }
+}
@@ -25,8 +25,9 @@ use which::which_in;
 
 use crate::environment::active_environment::ActiveEnvironment;
 use crate::environment::conda;
+use crate::environment::environment::EnvironmentDiscovery;
 use crate::environment::environment::PythonEnvironment;
-use crate::environment::venv;
+use crate::environment::project_environment::ProjectEnvironment;
 use crate::util::ConfigOrigin;
 
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone)]
@@ -91,6 +92,15 @@ pub struct Interpreters {
     /// Should we do any querying of an interpreter?
     #[serde(default, skip_serializing_if = "crate::util::skip_default_false")]
     pub skip_interpreter_query: bool,
+
+    /// Should we learn about the environment by running the interpreter, or only by reading
+    /// its directory layout?
+    #[serde(default, skip_serializing_if = "EnvironmentDiscovery::is_auto")]
+    pub(crate) environment_discovery: EnvironmentDiscovery,
+
+    /// The uv, PDM, Hatch or Pixi environment the interpreter was found in, if any.
+    #[serde(skip)]
+    pub(crate) project_environment: Option<ProjectEnvironment>,
 }
 
 impl Display for Interpreters {
@@ -131,6 +141,11 @@ impl Display for Interpreters {
                 "conda environment {conda} with interpreter at {}",
                 path.display()
             ),
+            Self {
+                project_environment: Some(project),
+                python_interpreter_path: Some(path),
+                ..
+            } => write!(f, "{project} with interpreter at {}", path.display()),
             Self {
                 python_interpreter_path: Some(path),
                 ..
@@ -152,6 +167,11 @@ impl Interpreters {
             && self.fallback_python_interpreter_name.is_none()
     }
 
+    /// How the environment of the selected interpreter is found.
+    pub fn environment_discovery(&self) -> EnvironmentDiscovery {
+        self.environment_discovery
+    }
+
     pub fn set_lsp_python_interpreter(&mut self, interpreter: PathBuf) {
         self.python_interpreter_path = Some(ConfigOrigin::lsp(interpreter));
     }
@@ -164,13 +184,16 @@ impl Interpreters {
     /// 2. Check for a configured interpreter path, discovery command, or Conda environment.
     /// 3. Check for an IDE / LSP provided `python-interpreter`.
     /// 4. Check for an active venv or Conda environment.
-    /// 5. Check for a `venv` in the current project.
+    /// 5. Check for the nearest environment to the current project, looking in each ancestor
+    ///    for one managed by uv, PDM, Hatch or Pixi, recording it in `project_environment`,
+    ///    and then for a `venv`.
     /// 6. Use an interpreter we can find on the `$PATH`.
     /// 7. Give up and return an error.
     pub(crate) fn find_interpreter(
-        &self,
+        &mut self,
         path: Option<&Path>,
     ) -> anyhow::Result<ConfigOrigin<PathBuf>> {
+        self.project_environment = None;
         let python_interpreter = self.interpreter_path_or_cmd()?;
         if let Some(interpreter @ ConfigOrigin::CommandLine(_)) = python_interpreter {
             return Ok(interpreter);
@@ -224,12 +247,13 @@ impl Interpreters {
         }
 
         if let Some(start_path) = path
-            && let Some(venv) = venv::find(start_path)
+            && let Some((project, interpreter)) = ProjectEnvironment::find(start_path)
         {
-            return Ok(ConfigOrigin::auto(venv));
+            self.project_environment = project;
+            return Ok(ConfigOrigin::auto(interpreter));
         }
 
-        if let Some(interpreter) = Self::get_default_interpreter() {
+        if let Some(interpreter) = Self::get_default_interpreter(self.environment_discovery) {
             return Ok(ConfigOrigin::auto(interpreter.to_path_buf()));
         }
 
@@ -312,23 +336,34 @@ impl Interpreters {
 
     /// Get the first executable interpreter available on the path.
     ///
-    /// Query the interpreter environment as the validation step. The result is cached, so the
-    /// caller's environment lookup does not spawn the same interpreter a second time.
-    pub(crate) fn get_default_interpreter() -> Option<&'static Path> {
-        static SYSTEM_INTERP: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+    /// Look up the interpreter environment, as `discovery` selects, as the validation step. The
+    /// result is cached, so the caller's environment lookup does not spawn the same interpreter
+    /// a second time.
+    pub(crate) fn get_default_interpreter(
+        discovery: EnvironmentDiscovery,
+    ) -> Option<&'static Path> {
+        fn find(discovery: EnvironmentDiscovery) -> Option<PathBuf> {
             // disable query with `which` on wasm
             #[cfg(not(target_arch = "wasm32"))]
             for binary_name in Interpreters::DEFAULT_INTERPRETERS {
                 if let Ok(binary_path) = which(binary_name) {
-                    let (_, error) = PythonEnvironment::get_interpreter_env(&binary_path);
+                    let (_, error) =
+                        PythonEnvironment::get_interpreter_env(&binary_path, discovery);
                     if error.is_none() {
                         return Some(binary_path);
                     }
                 }
             }
             None
-        });
-        SYSTEM_INTERP.as_deref()
+        }
+        static AUTO_INTERP: LazyLock<Option<PathBuf>> =
+            LazyLock::new(|| find(EnvironmentDiscovery::Auto));
+        static STATIC_INTERP: LazyLock<Option<PathBuf>> =
+            LazyLock::new(|| find(EnvironmentDiscovery::Static));
+        match discovery {
+            EnvironmentDiscovery::Auto => AUTO_INTERP.as_deref(),
+            EnvironmentDiscovery::Static => STATIC_INTERP.as_deref(),
+        }
     }
 }
 
@@ -342,6 +377,7 @@ mod test {
     use tempfile::tempdir;
 
     use super::*;
+    use crate::environment::venv;
 
     fn test_venv_interpreter_name() -> &'static str {
         if cfg!(windows) {
@@ -389,7 +425,7 @@ mod test {
         let python_interpreter = ConfigOrigin::cli(PathBuf::from("asdf"));
         let conda_environment = ConfigOrigin::config("somecondaenv".to_owned());
 
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_path: Some(python_interpreter.clone()),
             conda_environment: Some(conda_environment.clone()),
             ..Default::default()
@@ -401,7 +437,7 @@ mod test {
         );
 
         let conda_environment = ConfigOrigin::cli(fake_conda_name());
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_path: Some(ConfigOrigin::config(PathBuf::from("asdf"))),
             conda_environment: Some(conda_environment.clone()),
             ..Default::default()
@@ -421,7 +457,7 @@ mod test {
         let python_interpreter = ConfigOrigin::config(PathBuf::from("asdf"));
         let conda_environment = ConfigOrigin::lsp("somecondaenv".to_owned());
 
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_path: Some(python_interpreter.clone()),
             conda_environment: Some(conda_environment.clone()),
             ..Default::default()
@@ -432,7 +468,7 @@ mod test {
             python_interpreter
         );
 
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_path: Some(python_interpreter.clone()),
             python_interpreter_find_command: Some(discovery_command(&["does-not-exist"])),
             ..Default::default()
@@ -443,7 +479,7 @@ mod test {
         );
 
         let conda_environment = ConfigOrigin::config(fake_conda_name());
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_path: Some(ConfigOrigin::lsp(PathBuf::from("asdf"))),
             conda_environment: Some(conda_environment.clone()),
             ..Default::default()
@@ -463,7 +499,7 @@ mod test {
         let python_interpreter = ConfigOrigin::config(PathBuf::from("asdf"));
         let conda_environment = ConfigOrigin::auto("somecondaenv".to_owned());
 
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_path: Some(python_interpreter.clone()),
             conda_environment: Some(conda_environment.clone()),
             ..Default::default()
@@ -475,7 +511,7 @@ mod test {
         );
 
         let conda_environment = ConfigOrigin::config(fake_conda_name());
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_path: Some(ConfigOrigin::auto(PathBuf::from("asdf"))),
             conda_environment: Some(conda_environment.clone()),
             ..Default::default()
@@ -492,7 +528,7 @@ mod test {
     fn test_find_interpreter_precedence_venv() {
         let tempdir = setup_test_dir();
 
-        let interpreters = Interpreters::default();
+        let mut interpreters = Interpreters::default();
 
         unsafe {
             // clear this variable if it exists, since we can't test that in unit tests.
@@ -511,6 +547,50 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_find_interpreter_precedence_project_environment() {
+        let tempdir = setup_test_dir();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file("uv.lock"),
+                TestPath::dir(
+                    ".venv",
+                    vec![
+                        TestPath::file(test_venv_interpreter_name()),
+                        TestPath::file("pyvenv.cfg"),
+                    ],
+                ),
+                TestPath::dir("member", vec![TestPath::file("pyproject.toml")]),
+            ],
+        );
+
+        let mut interpreters = Interpreters::default();
+
+        unsafe {
+            // see `test_find_interpreter_precedence_venv`
+            std::env::remove_var(venv::ENV_VAR);
+        }
+
+        let interpreter = root.join(".venv").join(test_venv_interpreter_name());
+        assert_eq!(
+            interpreters
+                .find_interpreter(Some(&root.join("member")))
+                .unwrap(),
+            ConfigOrigin::auto(interpreter.clone())
+        );
+        interpreters.python_interpreter_path = Some(ConfigOrigin::auto(interpreter.clone()));
+        assert_eq!(
+            interpreters.to_string(),
+            format!(
+                "uv workspace at {} with interpreter at {}",
+                root.display(),
+                interpreter.display()
+            )
+        );
+    }
+
     #[cfg(any(unix, windows))]
     #[test]
     fn test_interpreter_find_command_resolves_relative_output() {
@@ -521,7 +601,7 @@ mod test {
         #[cfg(windows)]
         let command = discovery_command(&["cmd", "/C", "echo venv\\Scripts\\python.exe"]);
 
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_find_command: Some(command),
             conda_environment: Some(ConfigOrigin::config(fake_conda_name())),
             ..Default::default()
@@ -548,7 +628,7 @@ mod test {
         #[cfg(windows)]
         let command = discovery_command(&["cmd", "/C", "cd"]);
 
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_find_command: Some(command),
             ..Default::default()
         };
@@ -568,7 +648,7 @@ mod test {
         fs::create_dir(&tools).unwrap();
         fs::copy(which("cmd").unwrap(), tools.join("find-python.exe")).unwrap();
 
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_find_command: Some(discovery_command(&[
                 "tools\\find-python.exe",
                 "/C",
@@ -586,7 +666,7 @@ mod test {
     #[cfg(unix)]
     #[test]
     fn test_interpreter_find_command_must_return_one_path() {
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_find_command: Some(discovery_command(&[
                 "sh",
                 "-c",
@@ -602,7 +682,7 @@ mod test {
     #[cfg(unix)]
     #[test]
     fn test_interpreter_find_command_reports_failure() {
-        let interpreters = Interpreters {
+        let mut interpreters = Interpreters {
             python_interpreter_find_command: Some(discovery_command(&[
                 "sh",
                 "-c",
@@ -0,0 +1,453 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Reconstruct a Python environment from its directory layout, without running its interpreter.
+//! This works for environments we can't execute, such as ones built for another architecture,
+//! at the cost of only understanding the layouts `venv`, `virtualenv`, `uv` and conda create.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use anyhow::anyhow;
+use pyrefly_python::sys_info::PythonPlatform;
+use pyrefly_python::sys_info::PythonVersion;
+use regex::Regex;
+
+use crate::environment::environment::PythonEnvironment;
+
+/// The library directory of a POSIX installation, `lib/python3.12`, or `lib/python3.13t` for
+/// a free-threaded build.
+static LIB_DIR_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(?:python|pypy)(\d+)\.(\d+)t?$").unwrap());
+
+/// The metadata conda records for the installed `python` package.
+static CONDA_PYTHON_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^python-(\d+\.\d+\.\d+)-.*\.json$").unwrap());
+
+/// The DLL of a Windows installation, `python312.dll`.
+static WINDOWS_DLL_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^python(\d)(\d+)\.dll$").unwrap());
+
+/// The keys of a `pyvenv.cfg` that we use.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct VenvConfig {
+    /// The directory holding the interpreter the venv was created from.
+    home: Option<PathBuf>,
+    version: Option<PythonVersion>,
+    include_system_site_packages: bool,
+}
+
+impl VenvConfig {
+    fn parse(contents: &str) -> Self {
+        let mut config = Self::default();
+        for (key, value) in contents.lines().filter_map(|line| line.split_once('=')) {
+            let value = value.trim();
+            match key.trim() {
+                "home" => config.home = Some(PathBuf::from(value)),
+                // `venv` writes `version`, `virtualenv` and `uv` write `version_info`, which may
+                // carry a release level, as in `3.12.1.final.0`.
+                "version" | "version_info" => {
+                    let version = value.split('.').take(3).collect::<Vec<_>>().join(".");
+                    config.version = PythonVersion::from_str(&version).ok().or(config.version);
+                }
+                "include-system-site-packages" => {
+                    config.include_system_site_packages = value.eq_ignore_ascii_case("true")
+                }
+                _ => {}
+            }
+        }
+        config
+    }
+
+    fn read(prefix: &Path) -> Option<Self> {
+        Some(Self::parse(
+            &fs::read_to_string(prefix.join("pyvenv.cfg")).ok()?,
+        ))
+    }
+}
+
+/// The installation prefix of an interpreter in `dir`, which is the parent of a `bin` or
+/// `Scripts` directory, and otherwise `dir` itself, as for conda on Windows.
+fn prefix_of(dir: &Path) -> &Path {
+    match dir.file_name().and_then(|name| name.to_str()) {
+        Some("bin" | "Scripts") => dir.parent().unwrap_or(dir),
+        _ => dir,
+    }
+}
+
+/// Where an installation under some prefix keeps its libraries.
+#[derive(Debug, PartialEq, Eq)]
+enum LibDir {
+    /// `Lib`, holding `site-packages` and, for a base installation, the standard library.
+    Windows(PathBuf),
+    /// `lib/pythonX.Y`, along with the version in its name.
+    Posix(PathBuf, PythonVersion),
+}
+
+impl LibDir {
+    fn find(prefix: &Path) -> Option<Self> {
+        // Check for `site-packages` rather than just `Lib`, which would also match `lib` on a
+        // case-insensitive file system.
+        let windows = prefix.join("Lib");
+        if windows.join("site-packages").is_dir() {
+            return Some(Self::Windows(windows));
+        }
+        fs::read_dir(prefix.join("lib"))
+            .ok()?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let captures = LIB_DIR_REGEX.captures(path.file_name()?.to_str()?)?;
+                let version =
+                    PythonVersion::new(captures[1].parse().ok()?, captures[2].parse().ok()?, 0);
+                Some((version, path))
+            })
+            .max()
+            .map(|(version, path)| Self::Posix(path, version))
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            Self::Windows(path) | Self::Posix(path, _) => path,
+        }
+    }
+
+    /// The corresponding directory under another prefix, such as a venv's base installation.
+    fn under(&self, prefix: &Path) -> PathBuf {
+        match self {
+            Self::Windows(_) => prefix.join("Lib"),
+            Self::Posix(path, _) => prefix
+                .join("lib")
+                .join(path.file_name().unwrap_or_default()),
+        }
+    }
+}
+
+/// The Python version conda recorded installing into `prefix`.
+fn conda_version(prefix: &Path) -> Option<PythonVersion> {
+    fs::read_dir(prefix.join("conda-meta"))
+        .ok()?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name();
+            let captures = CONDA_PYTHON_REGEX.captures(name.to_str()?)?;
+            PythonVersion::from_str(&captures[1]).ok()
+        })
+        .max()
+}
+
+/// The Python version of a Windows installation at `prefix`, from the name of its DLL.
+fn windows_dll_version(prefix: &Path) -> Option<PythonVersion> {
+    fs::read_dir(prefix)
+        .ok()?
+        .filter_map(|entry| {
+            let name = entry.ok()?.file_name();
+            let captures = WINDOWS_DLL_REGEX.captures(name.to_str()?)?;
+            Some(PythonVersion::new(
+                captures[1].parse().ok()?,
+                captures[2].parse().ok()?,
+                0,
+            ))
+        })
+        .max()
+}
+
+/// The platform a POSIX standard library at `stdlib` was built for, from the name of its
+/// `_sysconfigdata_*` module, such as `_sysconfigdata__darwin_darwin.py`.
+fn posix_platform(stdlib: &Path) -> Option<PythonPlatform> {
+    fs::read_dir(stdlib).ok()?.find_map(|entry| {
+        let name = entry.ok()?.file_name();
+        let name = name.to_str()?.strip_prefix("_sysconfigdata_")?;
+        if name.contains("darwin") {
+            Some(PythonPlatform::mac())
+        } else if name.contains("linux") {
+            Some(PythonPlatform::linux())
+        } else {
+            None
+        }
+    })
+}
+
+/// A line of a `.pth` file that `site` acts on.
+pub(crate) enum PthLine<'a> {
+    /// Code `site` executes, as the line starts with `import ` or `import\t`.
+    Import(&'a str),
+    /// A directory, relative to the site package directory, that `site` adds to `sys.path`.
+    Dir(&'a str),
+}
+
+/// The contents of the `.pth` files in `site_packages`, in the order `site` processes them.
+pub(crate) fn read_pth_files(site_packages: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(site_packages) else {
+        return Vec::new();
+    };
+    let mut pth_files: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "pth"))
+        .collect();
+    // `site` processes `.pth` files in alphabetical order.
+    pth_files.sort();
+    pth_files
+        .iter()
+        .filter_map(|pth| fs::read_to_string(pth).ok())
+        .collect()
+}
+
+/// The lines of a `.pth` file, skipping blank lines and comments.
+pub(crate) fn pth_lines(contents: &str) -> impl Iterator<Item = PthLine<'_>> {
+    contents
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            if line.starts_with("import ") || line.starts_with("import\t") {
+                PthLine::Import(line)
+            } else {
+                PthLine::Dir(line)
+            }
+        })
+}
+
+/// The directories the `.pth` files in `site_packages` add to `sys.path`, in the order `site`
+/// processes them. Lines that run code can't be followed without executing it.
+fn pth_dirs(site_packages: &Path) -> Vec<PathBuf> {
+    read_pth_files(site_packages)
+        .iter()
+        .flat_map(|contents| {
+            pth_lines(contents)
+                .filter_map(|line| match line {
+                    PthLine::Dir(dir) => Some(site_packages.join(dir)),
+                    PthLine::Import(_) => None,
+                })
+                .filter(|dir| dir.is_dir())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Reconstruct the environment of `interpreter` from the files around it: `pyvenv.cfg`, the
+/// `lib/pythonX.Y/site-packages` or `Lib/site-packages` layout, `conda-meta` and `.pth` files.
+pub fn read_environment(interpreter: &Path) -> anyhow::Result<PythonEnvironment> {
+    let dir = interpreter
+        .parent()
+        .with_context(|| format!("`{}` has no parent directory", interpreter.display()))?;
+    let prefix = prefix_of(dir);
+    let venv = VenvConfig::read(prefix);
+    let base_prefix = venv
+        .as_ref()
+        .and_then(|venv| venv.home.as_deref())
+        .map(prefix_of);
+    let lib = LibDir::find(prefix)
+        .or_else(|| LibDir::find(base_prefix?))
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not find a `lib/pythonX.Y` or `Lib/site-packages` directory under `{}`",
+                prefix.display()
+            )
+        })?;
+
+    let version = venv
+        .as_ref()
+        .and_then(|venv| venv.version)
+        .or_else(|| conda_version(prefix))
+        .or_else(|| match &lib {
+            LibDir::Posix(_, version) => Some(*version),
+            LibDir::Windows(_) => windows_dll_version(base_prefix.unwrap_or(prefix)),
+        })
+        .ok_or_else(|| {
+            anyhow!(
+                "Could not determine the Python version of the environment at `{}`",
+                prefix.display()
+            )
+        })?;
+
+    // A venv's standard library stays in the installation it was created from.
+    let stdlib = match base_prefix {
+        Some(base_prefix) => lib.under(base_prefix),
+        None => lib.path().to_path_buf(),
+    };
+    let platform = match &lib {
+        LibDir::Windows(_) => PythonPlatform::windows(),
+        LibDir::Posix(..) => posix_platform(&stdlib).unwrap_or_default(),
+    };
+
+    let mut site_package_dirs = vec![lib.path().join("site-packages")];
+    if let Some(base_prefix) = base_prefix
+        && venv
+            .as_ref()
+            .is_some_and(|venv| venv.include_system_site_packages)
+    {
+        site_package_dirs.push(lib.under(base_prefix).join("site-packages"));
+    }
+    let mut interpreter_site_package_path = Vec::new();
+    for dir in site_package_dirs.into_iter().filter(|dir| dir.is_dir()) {
+        let pth_dirs = pth_dirs(&dir);
+        interpreter_site_package_path.push(dir);
+        interpreter_site_package_path.extend(pth_dirs);
+    }
+
+    Ok(PythonEnvironment {
+        python_platform: Some(platform),
+        python_version: Some(version),
+        site_package_path: None,
+        interpreter_site_package_path,
+        interpreter_stdlib_path: if stdlib.is_dir() {
+            vec![stdlib]
+        } else {
+            Vec::new()
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_venv_config() {
+        assert_eq!(
+            VenvConfig::parse(
+                "home = /usr/bin\ninclude-system-site-packages = false\nversion = 3.12.1\n"
+            ),
+            VenvConfig {
+                home: Some(PathBuf::from("/usr/bin")),
+                version: Some(PythonVersion::new(3, 12, 1)),
+                include_system_site_packages: false,
+            }
+        );
+        assert_eq!(
+            VenvConfig::parse(
+                "home = /opt/python/bin\nimplementation = CPython\nuv = 0.5.0\nversion_info = 3.11.9.final.0\ninclude-system-site-packages = True\n"
+            ),
+            VenvConfig {
+                home: Some(PathBuf::from("/opt/python/bin")),
+                version: Some(PythonVersion::new(3, 11, 9)),
+                include_system_site_packages: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_posix_venv() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        let base = root.join("base");
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::dir(
+                    "base",
+                    vec![TestPath::dir(
+                        "lib",
+                        vec![TestPath::dir(
+                            "python3.12",
+                            vec![
+                                TestPath::file("os.py"),
+                                TestPath::file("_sysconfigdata__darwin_darwin.py"),
+                                TestPath::dir("site-packages", vec![]),
+                            ],
+                        )],
+                    )],
+                ),
+                TestPath::dir(
+                    "venv",
+                    vec![
+                        TestPath::file_with_contents(
+                            "pyvenv.cfg",
+                            &format!(
+                                "home = {}\nversion_info = 3.12.4.final.0\ninclude-system-site-packages = true\n",
+                                base.join("bin").display()
+                            ),
+                        ),
+                        TestPath::dir("bin", vec![TestPath::file("python")]),
+                        TestPath::dir(
+                            "lib",
+                            vec![TestPath::dir(
+                                "python3.12",
+                                vec![TestPath::dir(
+                                    "site-packages",
+                                    vec![
+                                        TestPath::file_with_contents(
+                                            "project.pth",
+                                            "../../../../project/src\nimport _virtualenv\nmissing\n",
+                                        ),
+                                        TestPath::file("_virtualenv.py"),
+                                    ],
+                                )],
+                            )],
+                        ),
+                    ],
+                ),
+                TestPath::dir("project", vec![TestPath::dir("src", vec![])]),
+            ],
+        );
+        let site_packages = root.join("venv/lib/python3.12/site-packages");
+
+        let env = read_environment(&root.join("venv/bin/python")).unwrap();
+        assert_eq!(env.python_version, Some(PythonVersion::new(3, 12, 4)));
+        assert_eq!(env.python_platform, Some(PythonPlatform::mac()));
+        assert_eq!(
+            env.interpreter_site_package_path,
+            vec![
+                site_packages.clone(),
+                site_packages.join("../../../../project/src"),
+                base.join("lib/python3.12/site-packages"),
+            ]
+        );
+        assert_eq!(
+            env.interpreter_stdlib_path,
+            vec![base.join("lib/python3.12")]
+        );
+    }
+
+    #[test]
+    fn test_read_windows_conda_environment() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file("python.exe"),
+                TestPath::dir(
+                    "conda-meta",
+                    vec![TestPath::file("python-3.10.14-h4de0772_0_cpython.json")],
+                ),
+                TestPath::dir(
+                    "Lib",
+                    vec![
+                        TestPath::file("os.py"),
+                        TestPath::dir("site-packages", vec![]),
+                    ],
+                ),
+            ],
+        );
+
+        let env = read_environment(&root.join("python.exe")).unwrap();
+        assert_eq!(env.python_version, Some(PythonVersion::new(3, 10, 14)));
+        assert_eq!(env.python_platform, Some(PythonPlatform::windows()));
+        assert_eq!(
+            env.interpreter_site_package_path,
+            vec![root.join("Lib/site-packages")]
+        );
+    }
+
+    #[test]
+    fn test_read_unknown_layout() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![TestPath::dir("bin", vec![TestPath::file("python")])],
+        );
+
+        assert!(read_environment(&root.join("bin/python")).is_err());
+    }
+}
@@ -0,0 +1,67 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// The file `pdm use` records the project's interpreter in.
+const INTERPRETER_FILE: &str = ".pdm-python";
+
+/// Find the interpreter `pdm use` selected for the project at `root`.
+pub fn find_interpreter(root: &Path) -> Option<PathBuf> {
+    let contents = fs::read_to_string(root.join(INTERPRETER_FILE)).ok()?;
+    let interpreter = contents.trim();
+    if interpreter.is_empty() {
+        return None;
+    }
+    let interpreter = root.join(interpreter);
+    interpreter.is_file().then_some(interpreter)
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    #[test]
+    fn test_find_interpreter() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::dir(
+                    "project",
+                    vec![
+                        TestPath::file_with_contents(INTERPRETER_FILE, ".venv/bin/python\n"),
+                        TestPath::dir(
+                            ".venv",
+                            vec![TestPath::dir("bin", vec![TestPath::file("python")])],
+                        ),
+                    ],
+                ),
+                TestPath::dir(
+                    "stale",
+                    vec![TestPath::file_with_contents(
+                        INTERPRETER_FILE,
+                        "/does/not/exist/python\n",
+                    )],
+                ),
+            ],
+        );
+        let project = root.join("project");
+
+        assert_eq!(
+            find_interpreter(&project),
+            Some(project.join(".venv/bin/python"))
+        );
+        assert_eq!(find_interpreter(&root.join("stale")), None);
+        assert_eq!(find_interpreter(root), None);
+    }
+}
@@ -0,0 +1,60 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::environment::conda;
+
+/// Find the interpreter in the default environment Pixi installed for the project at `root`.
+/// Pixi environments are conda prefixes, so they share conda's layout.
+pub fn find_interpreter(root: &Path) -> Option<PathBuf> {
+    conda::find(&root.join(".pixi").join("envs").join("default"))
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    #[test]
+    fn test_find_interpreter() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        let relative_interpreter = if cfg!(windows) {
+            "python.exe"
+        } else {
+            "bin/python"
+        };
+        let layout = if cfg!(windows) {
+            vec![TestPath::file("python.exe")]
+        } else {
+            vec![TestPath::dir("bin", vec![TestPath::file("python")])]
+        };
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file("pixi.toml"),
+                TestPath::dir(
+                    ".pixi",
+                    vec![TestPath::dir(
+                        "envs",
+                        vec![TestPath::dir("default", layout)],
+                    )],
+                ),
+            ],
+        );
+        let prefix = root.join(".pixi").join("envs").join("default");
+
+        assert_eq!(
+            find_interpreter(root),
+            Some(prefix.join(relative_interpreter))
+        );
+        assert_eq!(find_interpreter(&prefix), None);
+    }
+}
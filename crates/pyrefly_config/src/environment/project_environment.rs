@@ -0,0 +1,211 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::fmt;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::environment::hatch;
+use crate::environment::pdm;
+use crate::environment::pixi;
+use crate::environment::uv;
+use crate::environment::venv;
+
+/// A tool that manages a project's environment in a location we can find without running it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectTool {
+    Uv,
+    Pdm,
+    Hatch,
+    Pixi,
+}
+
+impl Display for ProjectTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uv => write!(f, "uv workspace"),
+            Self::Pdm => write!(f, "PDM project"),
+            Self::Hatch => write!(f, "Hatch project"),
+            Self::Pixi => write!(f, "Pixi project"),
+        }
+    }
+}
+
+impl ProjectTool {
+    /// The order we check tools in, when one directory has markers for several.
+    const ALL: &[Self] = &[Self::Uv, Self::Pdm, Self::Hatch, Self::Pixi];
+
+    fn find_interpreter(self, root: &Path) -> Option<PathBuf> {
+        match self {
+            Self::Uv => uv::find_workspace_interpreter(root),
+            Self::Pdm => pdm::find_interpreter(root),
+            Self::Hatch => hatch::find_interpreter(root),
+            Self::Pixi => pixi::find_interpreter(root),
+        }
+    }
+}
+
+/// The environment a project tool manages for the project at `root`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectEnvironment {
+    pub tool: ProjectTool,
+    pub root: PathBuf,
+}
+
+impl Display for ProjectEnvironment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.tool, self.root.display())
+    }
+}
+
+impl ProjectEnvironment {
+    /// Find the nearest environment to `project_path`, searching it and then its ancestors,
+    /// along with the environment's interpreter. At each directory we check for an environment
+    /// managed by uv, PDM, Hatch or Pixi, and then for a plain virtual environment, which has no
+    /// project tool.
+    ///
+    /// Searching ancestors lets a member of a uv workspace find the environment shared at the
+    /// workspace root, while a project's own `.venv` still beats a tool's markers further up.
+    pub fn find(project_path: &Path) -> Option<(Option<Self>, PathBuf)> {
+        venv::search_roots(project_path).find_map(|root| {
+            ProjectTool::ALL
+                .iter()
+                .find_map(|&tool| {
+                    let interpreter = tool.find_interpreter(root)?;
+                    Some((
+                        Some(Self {
+                            tool,
+                            root: root.to_path_buf(),
+                        }),
+                        interpreter,
+                    ))
+                })
+                .or_else(|| Some((None, venv::find_in_root(root)?)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    fn venv(name: &str) -> TestPath {
+        let bin = if cfg!(windows) { "Scripts" } else { "bin" };
+        let interpreter = if cfg!(windows) {
+            "python.exe"
+        } else {
+            "python"
+        };
+        TestPath::dir(
+            name,
+            vec![
+                TestPath::file("pyvenv.cfg"),
+                TestPath::dir(bin, vec![TestPath::file(interpreter)]),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_find_uv_workspace_from_member() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file("uv.lock"),
+                TestPath::file("pyproject.toml"),
+                venv(".venv"),
+                TestPath::dir(
+                    "packages",
+                    vec![TestPath::dir(
+                        "member",
+                        vec![
+                            TestPath::file("pyproject.toml"),
+                            TestPath::dir("src", vec![]),
+                        ],
+                    )],
+                ),
+            ],
+        );
+
+        let (environment, interpreter) =
+            ProjectEnvironment::find(&root.join("packages/member/src")).unwrap();
+        assert_eq!(
+            environment,
+            Some(ProjectEnvironment {
+                tool: ProjectTool::Uv,
+                root: root.to_path_buf(),
+            })
+        );
+        assert!(interpreter.starts_with(root.join(".venv")));
+    }
+
+    #[test]
+    fn test_find_nearest_project() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file("uv.lock"),
+                venv(".venv"),
+                TestPath::dir(
+                    "pdm-project",
+                    vec![
+                        TestPath::file_with_contents(".pdm-python", "interpreter"),
+                        TestPath::file("interpreter"),
+                    ],
+                ),
+            ],
+        );
+
+        assert_eq!(
+            ProjectEnvironment::find(&root.join("pdm-project")),
+            Some((
+                Some(ProjectEnvironment {
+                    tool: ProjectTool::Pdm,
+                    root: root.join("pdm-project"),
+                }),
+                root.join("pdm-project/interpreter"),
+            ))
+        );
+    }
+
+    #[test]
+    fn test_find_nearest_venv() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file("uv.lock"),
+                TestPath::file_with_contents(".pdm-python", "interpreter"),
+                TestPath::file("interpreter"),
+                venv(".venv"),
+                TestPath::dir("project", vec![venv(".venv")]),
+            ],
+        );
+
+        // The project's own environment beats the markers of a tool in an ancestor.
+        let (environment, interpreter) = ProjectEnvironment::find(&root.join("project")).unwrap();
+        assert_eq!(environment, None);
+        assert!(interpreter.starts_with(root.join("project/.venv")));
+    }
+
+    #[test]
+    fn test_find_requires_environment() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        // A lockfile without a synced environment doesn't give us an interpreter.
+        TestPath::setup_test_directory(root, vec![TestPath::file("uv.lock")]);
+
+        assert_eq!(ProjectEnvironment::find(root), None);
+    }
+}
@@ -0,0 +1,173 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::env;
+use std::fs;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::Path;
+use std::path::PathBuf;
+
+use seahash::SeaHasher;
+
+use crate::environment::venv;
+
+/// Overrides the location of uv's cache.
+const CACHE_DIR_ENV_VAR: &str = "UV_CACHE_DIR";
+
+/// Overrides the location of a project's environment, relative to the workspace root.
+const PROJECT_ENVIRONMENT_ENV_VAR: &str = "UV_PROJECT_ENVIRONMENT";
+
+/// The lockfile uv writes at the root of a workspace.
+const LOCK_FILE: &str = "uv.lock";
+
+/// The cache bucket uv creates `uv run --script` environments in.
+const ENVIRONMENTS_BUCKET: &str = "environments-v2";
+
+/// The directory uv caches into, following uv's own lookup order.
+fn cache_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os(CACHE_DIR_ENV_VAR) {
+        return Some(PathBuf::from(dir));
+    }
+    if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(|dir| PathBuf::from(dir).join("uv").join("cache"))
+    } else if let Some(dir) = env::var_os("XDG_CACHE_HOME") {
+        Some(PathBuf::from(dir).join("uv"))
+    } else {
+        env::var_os("HOME").map(|dir| PathBuf::from(dir).join(".cache").join("uv"))
+    }
+}
+
+/// The digest uv puts in the name of the environment it builds for the script at `path`,
+/// hashing the path the way uv's cache keys do.
+fn script_digest(path: &Path) -> String {
+    let mut hasher = SeaHasher::new();
+    path.hash(&mut hasher);
+    hasher
+        .finish()
+        .to_le_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// Find the environment `uv run` built for `script`, within uv's cache at `cache_dir`.
+///
+/// uv names a script's environment `<file stem>-<digest of the absolute script path>`, or
+/// just the digest if the stem isn't a valid name, so we match on the digest alone. The
+/// script may have been run through a symlink, so we try its resolved path too.
+fn find_script_environment_in(cache_dir: &Path, script: &Path) -> Option<PathBuf> {
+    let mut digests = vec![script_digest(&std::path::absolute(script).ok()?)];
+    if let Ok(canonical) = script.canonicalize() {
+        digests.push(script_digest(&canonical));
+    }
+    fs::read_dir(cache_dir.join(ENVIRONMENTS_BUCKET))
+        .ok()?
+        .filter_map(Result::ok)
+        .find(|entry| {
+            entry.file_name().to_str().is_some_and(|name| {
+                digests.iter().any(|digest| {
+                    name == digest
+                        || name
+                            .strip_suffix(digest.as_str())
+                            .is_some_and(|stem| stem.ends_with('-'))
+                })
+            })
+        })
+        .map(|entry| entry.path())
+}
+
+/// Find the root of the environment `uv run` built for `script`, if there is one.
+pub fn find_script_environment(script: &Path) -> Option<PathBuf> {
+    find_script_environment_in(&cache_dir()?, script)
+}
+
+/// Find the interpreter of the environment `uv sync` created for the workspace rooted at `root`.
+///
+/// Every member of a workspace shares the one environment at the workspace root, beside the
+/// `uv.lock`, so this only matches the root and not the members' own directories.
+pub fn find_workspace_interpreter(root: &Path) -> Option<PathBuf> {
+    if !root.join(LOCK_FILE).is_file() {
+        return None;
+    }
+    let environment = match env::var_os(PROJECT_ENVIRONMENT_ENV_VAR) {
+        Some(dir) => root.join(dir),
+        None => root.join(".venv"),
+    };
+    venv::find_active(&environment)
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    #[test]
+    fn test_find_script_environment() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        let deploy = Path::new("/scripts/deploy.py");
+        let other_deploy = Path::new("/other/deploy.py");
+        let digest = script_digest(&std::path::absolute(deploy).unwrap());
+        TestPath::setup_test_directory(
+            root,
+            vec![TestPath::dir(
+                ENVIRONMENTS_BUCKET,
+                vec![
+                    TestPath::dir(&format!("deploy-{digest}"), vec![]),
+                    TestPath::dir(
+                        &format!("deploy-{}", script_digest(Path::new("/x.py"))),
+                        vec![],
+                    ),
+                    TestPath::dir("other-cccc", vec![]),
+                ],
+            )],
+        );
+        let bucket = root.join(ENVIRONMENTS_BUCKET);
+
+        assert_eq!(
+            find_script_environment_in(root, deploy),
+            Some(bucket.join(format!("deploy-{digest}")))
+        );
+        // The same name in another directory has its own environment.
+        assert_eq!(find_script_environment_in(root, other_deploy), None);
+        assert_eq!(
+            find_script_environment_in(root, Path::new("/scripts/missing.py")),
+            None
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_script_digest_fixture() {
+        // A fixed digest, rather than one computed with `script_digest`, so that a change in how
+        // the digest is computed is caught. It is the SeaHash of the path, as little-endian hex.
+        // This value was not copied from a real `uv run --script` cache, so compare it against
+        // one when changing how uv environments are found.
+        assert_eq!(
+            script_digest(Path::new("/home/user/scripts/deploy.py")),
+            "27f5476f375affaf"
+        );
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![TestPath::dir(
+                ENVIRONMENTS_BUCKET,
+                vec![TestPath::dir("deploy-27f5476f375affaf", vec![])],
+            )],
+        );
+        assert_eq!(
+            find_script_environment_in(root, Path::new("/home/user/scripts/deploy.py")),
+            Some(
+                root.join(ENVIRONMENTS_BUCKET)
+                    .join("deploy-27f5476f375affaf")
+            )
+        );
+    }
+}
@@ -50,7 +50,9 @@ pub fn find_active(root: &Path) -> Option<PathBuf> {
     }
 }
 
-fn find_in_root(root: &Path) -> Option<PathBuf> {
+/// Find a virtual environment interpreter directly under `root`, by looking for `pyvenv.cfg`
+/// in a known subdirectory (`.venv`, `venv`, `env`), then for an interpreter there.
+pub(crate) fn find_in_root(root: &Path) -> Option<PathBuf> {
     CANDIDATE_DIRS
         .iter()
         .map(|candidate| root.join(candidate))
@@ -58,28 +60,23 @@ fn find_in_root(root: &Path) -> Option<PathBuf> {
         .find_map(|path| find_interpreter(&path))
 }
 
-fn search_roots(project_path: &Path) -> impl Iterator<Item = &Path> {
+pub(crate) fn search_roots(project_path: &Path) -> impl Iterator<Item = &Path> {
     project_path
         .ancestors()
         .take_while(|path| !path.as_os_str().is_empty())
 }
 
-/// Find a virtual environment interpreter starting from `project_path`.
-///
-/// Search order:
-/// 1. Look for `pyvenv.cfg` in a known subdirectory (`.venv`, `venv`, `env`) of
-///    `project_path`, then look for an interpreter there.
-/// 2. Repeat step 1 in each ancestor directory.
-pub fn find(project_path: &Path) -> Option<PathBuf> {
-    search_roots(project_path).find_map(find_in_root)
-}
-
 #[cfg(test)]
 mod tests {
     use pyrefly_util::test_path::TestPath;
 
     use super::*;
 
+    /// Search `project_path` and then each of its ancestors for a virtual environment.
+    fn find(project_path: &Path) -> Option<PathBuf> {
+        search_roots(project_path).find_map(find_in_root)
+    }
+
     fn interp_name(version_suffix: &str) -> String {
         let windows_suffix = if cfg!(windows) { ".exe" } else { "" };
         format!("python{version_suffix}{windows_suffix}")
@@ -416,6 +416,8 @@ pub enum ErrorKind {
     UnknownName,
     /// A variable assigned a value with unknown type without an explicit annotation.
     UnknownVariableType,
+    /// A PEP 723 script imports a third-party package that its metadata doesn't list as a dependency.
+    UnlistedScriptDependency,
     /// Identity comparison (`is` or `is not`) between types that are provably disjoint
     /// or between literals whose comparison result is statically known.
     UnnecessaryComparison,
@@ -593,6 +595,7 @@ impl ErrorKind {
             ErrorKind::ImplicitAnyLambda => Severity::Ignore,
             ErrorKind::UnknownAttributeType => Severity::Ignore,
             ErrorKind::UnknownVariableType => Severity::Ignore,
+            ErrorKind::UnlistedScriptDependency => Severity::Warn,
             ErrorKind::UnnecessaryComparison => Severity::Warn,
             ErrorKind::UnnecessaryTypeConversion => Severity::Warn,
             ErrorKind::Unreachable => Severity::Warn,
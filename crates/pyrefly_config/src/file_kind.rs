@@ -19,6 +19,7 @@ use crate::config::ConfigFile;
 pub enum ConfigFileKind {
     MyPy,
     Pyright,
+    Pyre,
     Pyrefly,
     Pyproject,
 }
@@ -28,6 +29,7 @@ impl ConfigFileKind {
         match self {
             Self::MyPy => "mypy.ini",
             Self::Pyright => "pyrightconfig.json",
+            Self::Pyre => ".pyre_configuration",
             Self::Pyrefly => "pyrefly.toml",
             Self::Pyproject => "pyproject.toml",
         }
@@ -9,18 +9,22 @@ use std::ffi::OsString;
 use std::iter;
 use std::mem;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::OnceLock;
 
 use dupe::Dupe;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_name::ModuleNameWithKind;
 use pyrefly_python::module_path::ModulePath;
 use pyrefly_python::module_path::ModulePathDetails;
+use pyrefly_python::script_metadata::ScriptMetadata;
 use pyrefly_util::absolutize::Absolutize as _;
 use pyrefly_util::arc_id::ArcId;
 use pyrefly_util::lock::Mutex;
 use pyrefly_util::upward_search::FileGroup;
 use pyrefly_util::upward_search::UpwardSearch;
+use starlark_map::small_map::SmallMap;
 use tracing::Level;
 use tracing::debug;
 use tracing::enabled;
@@ -30,7 +34,9 @@ use tracing::warn;
 
 use crate::config::ConfigFile;
 use crate::config::ConfigSource;
+use crate::environment::editable::EditableRoots;
 use crate::error_kind::Severity;
+use crate::script;
 
 pub struct ConfigError {
     severity: Severity,
@@ -38,6 +44,10 @@ pub struct ConfigError {
 }
 
 impl ConfigError {
+    pub fn new(severity: Severity, msg: anyhow::Error) -> Self {
+        Self { severity, msg }
+    }
+
     pub fn error(msg: anyhow::Error) -> Self {
         Self {
             severity: Severity::Error,
@@ -81,6 +91,11 @@ impl ConfigError {
     pub fn get_message(&self) -> String {
         self.msg.to_string()
     }
+
+    /// The message together with its context, as shown by `print`.
+    pub fn get_full_message(&self) -> String {
+        format!("{:#}", self.msg)
+    }
 }
 
 /// When debugging is enabled, log errors.
@@ -115,6 +130,16 @@ pub struct ConfigFinder {
     before: BeforeCallback,
     /// If there is no config file, or loading it fails, use this fallback.
     fallback: FallbackCallback,
+    /// The configs derived for PEP 723 scripts, or `None` for files that aren't scripts.
+    /// Each is computed outside the lock, so that reading a script or querying its
+    /// interpreter doesn't hold up lookups for other files.
+    scripts: Mutex<SmallMap<PathBuf, Arc<OnceLock<Option<ArcId<ConfigFile>>>>>>,
+    /// The contents of files open in an editor, which are read instead of the disk when
+    /// looking for script metadata.
+    script_sources: Mutex<SmallMap<PathBuf, Arc<String>>>,
+    /// The directories editable installs make importable, found from each site package
+    /// directory's `.pth` files.
+    editable_roots: Arc<EditableRoots>,
     clear_extra_caches: Box<dyn Fn() + Send + Sync>,
 }
 
@@ -209,6 +234,9 @@ impl ConfigFinder {
             errors,
             before,
             fallback,
+            scripts: Mutex::new(SmallMap::new()),
+            script_sources: Mutex::new(SmallMap::new()),
+            editable_roots: Arc::new(EditableRoots::default()),
             clear_extra_caches,
         }
     }
@@ -216,10 +244,56 @@ impl ConfigFinder {
     /// Invalidate all data stored in the config.
     pub fn clear(&self) {
         self.search.clear();
+        self.scripts.lock().clear();
+        self.editable_roots.clear();
         (self.clear_extra_caches)();
         *self.errors.lock() = Vec::new();
     }
 
+    /// Forget the script metadata read from `paths`, which have changed on disk.
+    pub fn invalidate_scripts<'a>(&self, paths: impl IntoIterator<Item = &'a Path>) {
+        let mut scripts = self.scripts.lock();
+        for path in paths {
+            scripts.shift_remove(&*path.absolutize());
+        }
+    }
+
+    /// Read the script metadata of the file at `path` from `contents`, the text open in an
+    /// editor, rather than from the disk. `None` goes back to reading the disk.
+    ///
+    /// Most edits leave the metadata alone, so the config derived for the file is kept when the
+    /// metadata in `contents` is the same as before. A new config would invalidate the file's
+    /// imports.
+    pub fn set_script_source(&self, path: &Path, contents: Option<Arc<String>>) {
+        let path = path.absolutize();
+        let cached = self
+            .scripts
+            .lock()
+            .get(&path)
+            .and_then(|cell| cell.get().cloned());
+        let unchanged = if let Some(cached) = cached
+            && let Some(contents) = &contents
+            && let Ok(metadata) = ScriptMetadata::parse(contents)
+        {
+            let script = cached.as_ref().and_then(|x| x.script.as_ref());
+            metadata.as_ref() == script.map(|x| &x.metadata)
+        } else {
+            false
+        };
+        match contents {
+            Some(contents) => self.script_sources.lock().insert(path.clone(), contents),
+            None => self.script_sources.lock().shift_remove(&path),
+        };
+        if !unchanged {
+            self.scripts.lock().shift_remove(&path);
+        }
+    }
+
+    /// The cache of editable install roots, shared by every config this finder produces.
+    pub fn editable_roots(&self) -> Arc<EditableRoots> {
+        self.editable_roots.dupe()
+    }
+
     /// Collect all the current errors that have been produced, and clear them.
     pub fn errors(&self) -> Vec<ConfigError> {
         mem::take(&mut self.errors.lock())
@@ -274,15 +348,42 @@ impl ConfigFinder {
         match path.details() {
             ModulePathDetails::FileSystem(x) | ModulePathDetails::Memory(x) => {
                 let absolute = x.absolutize();
-                f(absolute.parent())
+                let config = f(absolute.parent());
+                self.script_config(&absolute, config)
             }
             ModulePathDetails::Namespace(x) => f(Some(&x.absolutize())),
+            ModulePathDetails::Archive(x) => f(x.absolutize().parent()),
             ModulePathDetails::BundledTypeshed(_) => f(None),
             ModulePathDetails::BundledTypeshedThirdParty(_) => f(None),
             ModulePathDetails::BundledThirdParty(_) => f(None),
         }
     }
 
+    /// If `path` is a PEP 723 script, the config derived for it from `config`, otherwise `config`.
+    fn script_config(&self, path: &Path, config: ArcId<ConfigFile>) -> ArcId<ConfigFile> {
+        // Installed packages are never scripts, so don't pay to read them.
+        if config
+            .site_package_path()
+            .any(|site_package| path.starts_with(site_package))
+        {
+            return config;
+        }
+        let cell = self
+            .scripts
+            .lock()
+            .entry(path.to_owned())
+            .or_default()
+            .dupe();
+        let script = cell.get_or_init(|| {
+            let source = self.script_sources.lock().get(path).cloned();
+            let mut errors = Vec::new();
+            let script = script::script_config(path, source.as_deref(), &config, &mut errors);
+            self.errors.lock().extend(errors);
+            script.map(ArcId::new)
+        });
+        script.as_ref().map_or(config, |script| script.dupe())
+    }
+
     /// If we have an error, print all the errors that the config finder has accumulated. This is used
     /// to ensure that config errors are still surfaced if we exit early.
     pub fn checkpoint<R, E>(&self, result: Result<R, E>) -> Result<R, E> {
@@ -15,6 +15,8 @@ pub mod file_kind;
 pub mod finder;
 pub mod migration;
 pub(crate) mod module_wildcard;
+pub mod organize_imports;
 pub mod pyproject;
 pub mod resolve_unconfigured;
+pub mod script;
 pub(crate) mod util;
@@ -11,6 +11,7 @@ pub(crate) mod ignore_missing_imports;
 pub(crate) mod mypy;
 pub(crate) mod project_excludes;
 pub(crate) mod project_includes;
+pub(crate) mod pyre;
 pub(crate) mod pyright;
 pub(crate) mod python_interpreter;
 pub(crate) mod python_platform;
@@ -19,6 +20,7 @@ pub mod run;
 pub(crate) mod search_path;
 pub(crate) mod site_package_path;
 pub(crate) mod sub_configs;
+pub mod suppression_codes;
 pub(crate) mod untyped_def_behavior;
 
 #[cfg(test)]
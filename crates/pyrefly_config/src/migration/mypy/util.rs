@@ -12,6 +12,7 @@ use configparser::ini::Ini;
 use crate::error::ErrorDisplayConfig;
 use crate::error_kind::ErrorKind;
 use crate::error_kind::Severity;
+use crate::migration::suppression_codes::mypy_error_kinds;
 
 /// Iterate over INI sections and apply a function to each section
 ///
@@ -206,52 +207,14 @@ fn code_to_kind(errors: HashMap<String, Severity>) -> HashMap<ErrorKind, Severit
     };
 
     for (code, severity) in errors {
-        match code.as_str() {
-            "union-attr" | "attr-defined" => add(severity, ErrorKind::MissingAttribute),
-            "arg-type" => add(severity, ErrorKind::BadArgumentType),
-            "assignment" => add(severity, ErrorKind::BadAssignment),
-            "call-arg" => add(severity, ErrorKind::BadArgumentCount),
-            "call-overload" => add(severity, ErrorKind::NoMatchingOverload),
-            "index" => {
-                add(severity, ErrorKind::BadIndex);
-                add(severity, ErrorKind::UnsupportedOperation);
-            }
-            "dict-item" => add(severity, ErrorKind::BadTypedDict),
-            "explicit-any" => add(severity, ErrorKind::ExplicitAny),
-            "operator" => add(severity, ErrorKind::UnsupportedOperation),
-            "typeddict-unknown-key" => add(severity, ErrorKind::BadTypedDictKey),
-            "typeddict-readonly-mutated" => add(severity, ErrorKind::ReadOnly),
-            "name-defined" => add(severity, ErrorKind::UnknownName),
-            "used-before-def" | "possibly-undefined" => add(severity, ErrorKind::UnboundName),
-            "valid-type" => add(severity, ErrorKind::InvalidAnnotation),
-            "type-arg" => add(severity, ErrorKind::ImplicitAnyTypeArgument),
-            "no-untyped-def" => {
-                add(severity, ErrorKind::ImplicitAnyParameter);
-                add(severity, ErrorKind::UnannotatedReturn);
-            }
-            "metaclass" => add(severity, ErrorKind::InvalidInheritance),
-            "override" => add(severity, ErrorKind::BadOverride),
-            "mutable-override" => add(severity, ErrorKind::BadOverrideMutableAttribute),
-            "return" | "return-value" => add(severity, ErrorKind::BadReturn),
-            "type-var" => add(severity, ErrorKind::BadSpecialization),
-            "import" | "import-not-found" => add(severity, ErrorKind::MissingImport),
-            "import-untyped" => add(severity, ErrorKind::UntypedImport),
-            "abstract" => add(severity, ErrorKind::BadInstantiation),
-            "no-overload-impl" => add(severity, ErrorKind::InvalidOverload),
-            "unused-coroutine" | "unused-awaitable" => add(severity, ErrorKind::UnusedCoroutine),
-            "top-level-await" | "await-not-async" => add(severity, ErrorKind::NotAsync),
-            "assert-type" => add(severity, ErrorKind::AssertType),
-            "syntax" => add(severity, ErrorKind::ParseError),
-            "redundant-cast" => add(severity, ErrorKind::RedundantCast),
-            "redundant-expr" | "truthy-function" | "truthy-bool" | "truthy-iterable" => {
-                add(severity, ErrorKind::RedundantCondition)
-            }
-            "deprecated" => add(severity, ErrorKind::Deprecated),
-            "name-match" => add(severity, ErrorKind::NameMismatch),
-            "no-any-return" => add(severity, ErrorKind::NoAnyReturn),
-            _ => tracing::warn!(
+        let kinds = mypy_error_kinds(&code);
+        if kinds.is_empty() {
+            tracing::warn!(
                 "Cannot migrate unsupported or unrecognized mypy error code `{code}`; audit the generated [errors] table"
-            ),
+            );
+        }
+        for kind in kinds {
+            add(severity, *kind);
         }
     }
 
@@ -0,0 +1,477 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Migration from Pyre's `.pyre_configuration` and `.pyre_configuration.local` files.
+//!
+//! A project has one `.pyre_configuration` at its root, and may have `.pyre_configuration.local`
+//! files in subdirectories that Pyre treats as separate projects. Pyrefly checks the whole tree
+//! as one project, so the local configurations become extra includes, search paths and
+//! sub-configs of a single `pyrefly.toml`.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use pyrefly_python::sys_info::PythonVersion;
+use pyrefly_util::fs_anyhow;
+use pyrefly_util::globs::Glob;
+use pyrefly_util::globs::Globs;
+use serde::Deserialize;
+use tracing::warn;
+
+use crate::base::ConfigBase;
+use crate::base::InferReturnTypes;
+use crate::base::Preset;
+use crate::config::ConfigFile;
+use crate::config::SubConfig;
+use crate::error::ErrorDisplayConfig;
+use crate::migration::mypy::regex_converter;
+
+pub const PYRE_CONFIGURATION: &str = ".pyre_configuration";
+pub const PYRE_CONFIGURATION_LOCAL: &str = ".pyre_configuration.local";
+
+/// Pyre expands this to the directory containing the configuration.
+const SOURCE_DIRECTORY_VARIABLE: &str = "${SOURCE_DIRECTORY}";
+
+/// An entry of `source_directories`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SourceDirectory {
+    /// A directory that is also an import root.
+    Path(String),
+    /// A directory to check, whose modules are imported relative to `import_root`.
+    Rooted { import_root: String, source: String },
+    /// The files matching a glob.
+    Glob { glob: String },
+}
+
+/// An entry of `search_path`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum SearchPathElement {
+    Path(String),
+    /// Only `subdirectory` is visible, but it is imported relative to `root`.
+    Subdirectory {
+        root: String,
+        subdirectory: String,
+    },
+    /// A package found in site-packages.
+    SitePackage {
+        #[serde(rename = "site-package")]
+        site_package: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SitePackageSearchStrategy {
+    None,
+    All,
+    Pep561,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct PyreConfig {
+    #[serde(default)]
+    pub source_directories: Vec<SourceDirectory>,
+    /// Buck targets, which have no Pyrefly equivalent outside of a build system integration.
+    #[serde(default)]
+    pub targets: Vec<String>,
+    #[serde(default)]
+    pub search_path: Vec<SearchPathElement>,
+    /// Regexes of paths to skip.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Paths whose errors are never reported.
+    #[serde(default)]
+    pub ignore_all_errors: Vec<String>,
+    pub strict: Option<bool>,
+    pub python_version: Option<PythonVersion>,
+    pub site_package_search_strategy: Option<SitePackageSearchStrategy>,
+}
+
+/// Join a path from a configuration in `dir` onto `dir`, where both are relative to the
+/// project root, which is `.`. Absolute paths are kept as they are.
+fn relative_to(dir: &str, path: &str) -> String {
+    let path = path
+        .strip_prefix(SOURCE_DIRECTORY_VARIABLE)
+        .map_or(path, |rest| rest.trim_start_matches('/'));
+    if Path::new(path).is_absolute() {
+        return path.to_owned();
+    }
+    match (dir, path) {
+        (_, "" | ".") => dir.to_owned(),
+        (".", _) => path.to_owned(),
+        _ => format!("{dir}/{path}"),
+    }
+}
+
+/// The settings that make a directory strict or non-strict, whatever the root says.
+fn strictness(strict: bool) -> ConfigBase {
+    if strict {
+        ConfigBase {
+            check_unannotated_defs: Some(true),
+            infer_return_types: Some(InferReturnTypes::Checked),
+            ..Preset::Strict.apply()
+        }
+    } else {
+        // Undo the strict preset's additions to the root.
+        let errors = Preset::Strict
+            .apply()
+            .errors
+            .map(|errors| {
+                errors
+                    .iter()
+                    .map(|(kind, _)| (kind, kind.default_severity()))
+                    .collect()
+            })
+            .map(ErrorDisplayConfig::new);
+        ConfigBase {
+            errors,
+            strict_callable_subtyping: Some(false),
+            strict_partial_subtyping: Some(false),
+            ..Preset::Legacy.apply()
+        }
+    }
+}
+
+/// The pieces of a single Pyre configuration, with paths relative to the project root.
+#[derive(Default)]
+struct Converted {
+    includes: Vec<String>,
+    search_path: Vec<String>,
+    excludes: Vec<String>,
+    /// Sub-configs for `ignore_all_errors`, which take priority over `strictness`.
+    ignored: Vec<SubConfig>,
+    /// Sub-configs for local configurations that are stricter or laxer than the root.
+    strictness: Vec<SubConfig>,
+}
+
+impl PyreConfig {
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        Ok(serde_jsonrc::from_str::<Self>(text)?)
+    }
+
+    /// Convert the parts of this configuration that a local configuration in `dir` can also
+    /// set. `root_strict` is whether the root configuration is strict.
+    fn convert_paths(&self, dir: &str, root_strict: bool, out: &mut Converted) {
+        if !self.targets.is_empty() {
+            warn!(
+                "Pyre `targets` in `{}` can't be migrated; checking the whole directory instead",
+                relative_to(dir, ".")
+            );
+        }
+        for source in &self.source_directories {
+            match source {
+                SourceDirectory::Path(path) => {
+                    out.includes.push(relative_to(dir, path));
+                    out.search_path.push(relative_to(dir, path));
+                }
+                SourceDirectory::Rooted {
+                    import_root,
+                    source,
+                } => {
+                    out.includes
+                        .push(relative_to(&relative_to(dir, import_root), source));
+                    out.search_path.push(relative_to(dir, import_root));
+                }
+                SourceDirectory::Glob { glob } => out.includes.push(relative_to(dir, glob)),
+            }
+        }
+        if self.source_directories.is_empty() && dir != "." {
+            out.includes.push(dir.to_owned());
+        }
+        for element in &self.search_path {
+            match element {
+                SearchPathElement::Path(path)
+                | SearchPathElement::Subdirectory { root: path, .. } => {
+                    out.search_path.push(relative_to(dir, path))
+                }
+                // Installed packages are found through the interpreter's site-packages.
+                SearchPathElement::SitePackage { .. } => {}
+            }
+        }
+        for regex in &self.exclude {
+            let regex = regex
+                .strip_prefix(SOURCE_DIRECTORY_VARIABLE)
+                .map_or(regex.as_str(), |rest| rest.trim_start_matches('/'));
+            match regex_converter::convert(regex) {
+                Ok(patterns) => out.excludes.extend(patterns),
+                Err(e) => warn!("Could not migrate Pyre exclude `{regex}`: {e}"),
+            }
+        }
+        for path in &self.ignore_all_errors {
+            match Glob::new(relative_to(dir, path)) {
+                Ok(matches) => out.ignored.push(SubConfig {
+                    matches,
+                    settings: ConfigBase {
+                        errors: Preset::Off.apply().errors,
+                        ..Default::default()
+                    },
+                }),
+                Err(e) => warn!("Could not migrate Pyre ignore_all_errors `{path}`: {e}"),
+            }
+        }
+        if dir != "."
+            && let Some(strict) = self.strict
+            && strict != root_strict
+        {
+            match Glob::new(dir.to_owned()) {
+                Ok(matches) => out.strictness.push(SubConfig {
+                    matches,
+                    settings: strictness(strict),
+                }),
+                Err(e) => warn!("Could not migrate Pyre strict setting for `{dir}`: {e}"),
+            }
+        }
+    }
+
+    /// Convert this root configuration and the local configurations beneath it, given as the
+    /// directory containing each, relative to the root.
+    pub fn convert(self, locals: &[(String, PyreConfig)]) -> anyhow::Result<ConfigFile> {
+        let mut cfg = ConfigFile::default();
+        let strict = self.strict.unwrap_or(false);
+        // Pyre only checks the bodies of annotated functions unless it is in strict mode.
+        cfg.preset = Some(if strict {
+            Preset::Strict
+        } else {
+            Preset::Legacy
+        });
+
+        let mut converted = Converted::default();
+        self.convert_paths(".", strict, &mut converted);
+        for (dir, local) in locals {
+            local.convert_paths(dir, strict, &mut converted);
+        }
+        // A directory of the project is already importable, so "." needs no search path.
+        // Earlier entries take priority, so only the first of any duplicates is kept.
+        let mut seen = HashSet::new();
+        converted
+            .search_path
+            .retain(|path| path != "." && seen.insert(path.clone()));
+
+        cfg.project_includes =
+            if converted.includes.is_empty() || converted.includes.iter().any(|x| x == ".") {
+                ConfigFile::default_project_includes()
+            } else {
+                Globs::new(converted.includes)?
+            };
+        if !converted.excludes.is_empty() {
+            cfg.project_excludes = Globs::new(converted.excludes)?;
+        }
+        cfg.search_path_from_file = converted
+            .search_path
+            .into_iter()
+            .map(PathBuf::from)
+            .collect();
+        // The first matching sub-config wins, so ignoring errors beats strictness.
+        cfg.sub_configs = converted.ignored;
+        cfg.sub_configs.extend(converted.strictness);
+        cfg.python_environment.python_version = self.python_version;
+        if self.site_package_search_strategy == Some(SitePackageSearchStrategy::None) {
+            cfg.python_environment.site_package_path = Some(Vec::new());
+            cfg.interpreters.skip_interpreter_query = true;
+        }
+        Ok(cfg)
+    }
+}
+
+/// Find the `.pyre_configuration.local` files beneath `root`, skipping hidden directories,
+/// and subdirectories that can't be read. Returns the directory of each, relative to `root`,
+/// in sorted order.
+fn find_local_configs(root: &Path) -> anyhow::Result<Vec<(String, PyreConfig)>> {
+    fn visit(root: &Path, dir: &Path, found: &mut Vec<(String, PyreConfig)>) -> anyhow::Result<()> {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if dir != root => {
+                warn!(
+                    "Skipping `{}` while searching for Pyre configs: {e}",
+                    dir.display()
+                );
+                return Ok(());
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("While searching `{}`", dir.display()));
+            }
+        };
+        let mut entries = entries.filter_map(Result::ok).collect::<Vec<_>>();
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            let name = entry.file_name();
+            let path = entry.path();
+            if name == PYRE_CONFIGURATION_LOCAL && dir != root {
+                let relative = dir.strip_prefix(root).unwrap_or(dir);
+                let raw = fs_anyhow::read_to_string(&path)?;
+                let local = PyreConfig::parse(&raw)
+                    .with_context(|| format!("While parsing `{}`", path.display()))?;
+                found.push((relative.to_string_lossy().replace('\\', "/"), local));
+            } else if !name.to_string_lossy().starts_with('.')
+                && entry.file_type().is_ok_and(|t| t.is_dir())
+            {
+                visit(root, &path, found)?;
+            }
+        }
+        Ok(())
+    }
+
+    let mut found = Vec::new();
+    visit(root, root, &mut found)?;
+    Ok(found)
+}
+
+/// Migrate the `.pyre_configuration` at `path`, along with any `.pyre_configuration.local`
+/// files in the directories beneath it.
+pub fn parse_pyre_config(path: &Path) -> anyhow::Result<ConfigFile> {
+    let raw = fs_anyhow::read_to_string(path)?;
+    let config = PyreConfig::parse(&raw)
+        .with_context(|| format!("While parsing Pyre config at `{}`", path.display()))?;
+    let root = path.parent().unwrap_or(Path::new("."));
+    let locals = find_local_configs(root)?;
+    config.convert(&locals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error_kind::ErrorKind;
+    use crate::error_kind::Severity;
+
+    fn globs(patterns: &[&str]) -> Globs {
+        Globs::new(patterns.iter().map(|x| (*x).to_owned()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_convert_pyre_config() -> anyhow::Result<()> {
+        let raw = r#"{
+            "source_directories": ["src", {"import_root": ".", "source": "scripts"}],
+            "search_path": ["stubs", {"root": "vendor", "subdirectory": "lib"}, {"site-package": "foo"}],
+            "exclude": [".*/build/.*"],
+            "ignore_all_errors": ["src/generated"],
+            "python_version": "3.10",
+            "site_package_search_strategy": "none",
+        }"#;
+        let cfg = PyreConfig::parse(raw)?.convert(&[])?;
+        assert_eq!(cfg.preset, Some(Preset::Legacy));
+        assert_eq!(cfg.project_includes, globs(&["src", "scripts"]));
+        assert_eq!(
+            cfg.search_path_from_file,
+            vec![
+                PathBuf::from("src"),
+                PathBuf::from("stubs"),
+                PathBuf::from("vendor")
+            ]
+        );
+        assert!(!cfg.project_excludes.is_empty());
+        assert_eq!(cfg.sub_configs.len(), 1);
+        assert_eq!(
+            cfg.sub_configs[0].matches,
+            Glob::new("src/generated".to_owned())?
+        );
+        assert_eq!(
+            cfg.sub_configs[0]
+                .settings
+                .errors
+                .as_ref()
+                .unwrap()
+                .severity(ErrorKind::BadAssignment),
+            Severity::Ignore
+        );
+        assert_eq!(
+            cfg.python_environment.python_version,
+            Some(PythonVersion::new(3, 10, 0))
+        );
+        assert_eq!(cfg.python_environment.site_package_path, Some(Vec::new()));
+        assert!(cfg.interpreters.skip_interpreter_query);
+        Ok(())
+    }
+
+    #[test]
+    fn test_convert_local_configs() -> anyhow::Result<()> {
+        let root = PyreConfig::parse(r#"{"source_directories": ["."], "strict": true}"#)?;
+        let locals = [
+            (
+                "legacy".to_owned(),
+                PyreConfig::parse(r#"{"targets": ["//legacy:lib"], "strict": false}"#)?,
+            ),
+            (
+                "tools".to_owned(),
+                PyreConfig::parse(
+                    r#"{"source_directories": ["bin"], "ignore_all_errors": ["bin/old.py"], "strict": true}"#,
+                )?,
+            ),
+        ];
+        let cfg = root.convert(&locals)?;
+        assert_eq!(cfg.preset, Some(Preset::Strict));
+        // The root covers every directory, so the local includes don't narrow it.
+        assert_eq!(cfg.project_includes, ConfigFile::default_project_includes());
+        assert_eq!(cfg.search_path_from_file, vec![PathBuf::from("tools/bin")]);
+        let matches: Vec<String> = cfg
+            .sub_configs
+            .iter()
+            .map(|sub| sub.matches.to_string())
+            .collect();
+        assert_eq!(matches, vec!["tools/bin/old.py", "legacy"]);
+        assert_eq!(
+            cfg.sub_configs[1].settings.check_unannotated_defs,
+            Some(false)
+        );
+        assert_eq!(
+            cfg.sub_configs[1]
+                .settings
+                .errors
+                .as_ref()
+                .unwrap()
+                .severity(ErrorKind::ImplicitAny),
+            ErrorKind::ImplicitAny.default_severity()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_search_path_duplicates() -> anyhow::Result<()> {
+        let cfg = PyreConfig::parse(r#"{"search_path": ["stubs", "vendor", ".", "stubs"]}"#)?
+            .convert(&[])?;
+        assert_eq!(
+            cfg.search_path_from_file,
+            vec![PathBuf::from("stubs"), PathBuf::from("vendor")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_relative_to() {
+        assert_eq!(relative_to(".", "src"), "src");
+        assert_eq!(relative_to(".", "."), ".");
+        assert_eq!(relative_to("sub", "."), "sub");
+        assert_eq!(relative_to("sub", "src"), "sub/src");
+        assert_eq!(relative_to(".", "${SOURCE_DIRECTORY}/gen"), "gen");
+        let absolute = std::env::temp_dir().join("stubs");
+        let absolute = absolute.to_str().unwrap();
+        assert_eq!(relative_to("sub", absolute), absolute);
+    }
+
+    #[test]
+    fn test_parse_pyre_config_finds_local_configs() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("a/b"))?;
+        std::fs::create_dir_all(root.join(".hidden"))?;
+        fs_anyhow::write(
+            &root.join(PYRE_CONFIGURATION),
+            r#"{"source_directories": ["a/src"]}"#,
+        )?;
+        fs_anyhow::write(
+            &root.join("a/b").join(PYRE_CONFIGURATION_LOCAL),
+            r#"{"source_directories": ["."]}"#,
+        )?;
+        fs_anyhow::write(&root.join(".hidden").join(PYRE_CONFIGURATION_LOCAL), "{}")?;
+        let cfg = parse_pyre_config(&root.join(PYRE_CONFIGURATION))?;
+        assert_eq!(cfg.project_includes, globs(&["a/src", "a/b"]));
+        Ok(())
+    }
+}
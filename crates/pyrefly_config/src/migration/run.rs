@@ -12,6 +12,7 @@
 // - match up the error configurations (best-effort)
 // This script does not otherwise invoke pyrefly. This gives the user time to change anything by hand if needed.
 
+use std::ffi::OsString;
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
@@ -27,6 +28,8 @@ use tracing::warn;
 use crate::config::ConfigFile;
 use crate::migration::mypy;
 use crate::migration::mypy::ini::parse_mypy_config;
+use crate::migration::pyre::PYRE_CONFIGURATION;
+use crate::migration::pyre::parse_pyre_config;
 use crate::migration::pyright;
 use crate::migration::pyright::PyrightConfig;
 use crate::pyproject::PyProject;
@@ -89,6 +92,10 @@ fn has_toml_section(raw: &str, section: &str) -> bool {
 /// memory — no files are written. The result is equivalent to what
 /// `pyrefly init` would produce, only without touching disk.
 ///
+/// Pyre configs are skipped, since Pyre's strictness can differ between
+/// directories and so there is no single preset to report. They are only
+/// migrated by `pyrefly init`.
+///
 /// Returns `Ok(None)` if no migrate-able config exists. Returns `Err` if a
 /// candidate config was found but couldn't be parsed; callers that want to
 /// fall back to a plain `Basic` preset on parse failure should catch the
@@ -96,7 +103,11 @@ fn has_toml_section(raw: &str, section: &str) -> bool {
 pub fn find_and_migrate_in_memory(
     start: &Path,
 ) -> anyhow::Result<Option<(ConfigFile, MigratedFromKind)>> {
-    let Some(path) = find_upward_config(start, MigrationSource::Auto) else {
+    let filenames = upward_config_names(MigrationSource::Auto)
+        .into_iter()
+        .filter(|name| name != PYRE_CONFIGURATION)
+        .collect();
+    let Some(path) = search_upward(start, filenames) else {
         return Ok(None);
     };
     if path.file_name() == Some("pyrightconfig.json".as_ref()) {
@@ -139,7 +150,7 @@ pub fn find_and_migrate_in_memory(
             (false, false) => Ok(None),
         }
     } else {
-        // `find_upward_config(_, Auto)` only returns one of the three
+        // The search above only returns one of the three
         // filenames handled above, so no other filename can reach
         // here. Panic loudly if a future change adds a new search
         // candidate without a matching arm.
@@ -151,35 +162,47 @@ pub fn find_and_migrate_in_memory(
 }
 
 /// Search upward from `start` for the first matching config file. Returns
-/// `None` if nothing is found. Used by both the migration command (which
-/// requires a config to be present) and the in-memory helper (which is
-/// fine with `None`).
+/// `None` if nothing is found. Used by the migration command, which
+/// requires a config to be present.
 fn find_upward_config(start: &Path, migrate_from: MigrationSource) -> Option<PathBuf> {
-    let filenames = match migrate_from {
+    search_upward(start, upward_config_names(migrate_from))
+}
+
+/// The config files that `migrate_from` can be migrated from, in order of preference.
+fn upward_config_names(migrate_from: MigrationSource) -> Vec<OsString> {
+    match migrate_from {
         MigrationSource::MyPy => {
             vec!["mypy.ini".into(), "pyproject.toml".into()]
         }
         MigrationSource::Pyright => {
             vec!["pyrightconfig.json".into(), "pyproject.toml".into()]
         }
+        MigrationSource::Pyre => vec![PYRE_CONFIGURATION.into()],
         MigrationSource::Auto => vec![
             "mypy.ini".into(),
             "pyrightconfig.json".into(),
             "pyproject.toml".into(),
+            PYRE_CONFIGURATION.into(),
         ],
-    };
+    }
+}
+
+/// Search upward from `start` for the nearest directory containing one of `filenames`,
+/// preferring earlier names within a directory.
+fn search_upward(start: &Path, filenames: Vec<OsString>) -> Option<PathBuf> {
     let searcher = UpwardSearch::new(filenames, |p| Arc::new(p.to_path_buf()));
     searcher.directory(start).map(Arc::unwrap_or_clone)
 }
 
 /// Which type checker config to migrate from.
 ///
-/// When set to a specific source (`MyPy` or `Pyright`), only that source is
-/// tried — there is no fallback. `Auto` (the default) tries mypy first, then
-/// pyright, preserving the historical behavior.
+/// When set to a specific source (`MyPy`, `Pyright` or `Pyre`), only that
+/// source is tried — there is no fallback. `Auto` (the default) tries mypy
+/// first, then pyright, then Pyre. Migrating in memory for an unconfigured
+/// project never uses Pyre; see `find_and_migrate_in_memory`.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
 pub enum MigrationSource {
-    /// Automatically detect: try mypy first, then pyright.
+    /// Automatically detect: try mypy first, then pyright, then Pyre.
     #[default]
     #[value(name = "auto")]
     Auto,
@@ -189,6 +212,9 @@ pub enum MigrationSource {
     /// Migrate only from pyright configuration.
     #[value(name = "pyright")]
     Pyright,
+    /// Migrate only from Pyre configuration.
+    #[value(name = "pyre")]
+    Pyre,
 }
 
 /// Migrate the config file at a given location (pyproject, mypy, pyright etc), producing a new file.
@@ -222,7 +248,7 @@ pub fn config_migration(
 struct Args {
     /// The path to the mypy or pyright config file to convert. Optional.
     /// If not provided, or if it's a directory, pyrefly will search upwards for a
-    /// mypy.ini, pyrightconfig.json, or pyproject.toml.
+    /// mypy.ini, pyrightconfig.json, pyproject.toml, or .pyre_configuration.
     original_config_path: PathBuf,
     /// Which type checker config to migrate from.
     migrate_from: MigrationSource,
@@ -257,6 +283,9 @@ impl Args {
         match migrate_from {
             MigrationSource::MyPy => try_mypy(),
             MigrationSource::Pyright => try_pyright(),
+            MigrationSource::Pyre => Err(anyhow::anyhow!(
+                "Pyre is configured in `.pyre_configuration`, not in pyproject.toml"
+            )),
             // Section presence (not parse success) picks the tool, so a
             // malformed `[tool.mypy]` surfaces its own error instead of
             // silently falling back to pyright. Kept in sync with
@@ -320,9 +349,15 @@ impl Args {
         } else if original_config_path.file_name() == Some("pyproject.toml".as_ref()) {
             Self::load_from_pyproject(&original_config_path, self.migrate_from)
                 .context("Failed to load config from pyproject.toml")?
+        } else if original_config_path.file_name() == Some(PYRE_CONFIGURATION.as_ref()) {
+            info!(
+                "Migrating Pyre config file from: `{}`",
+                original_config_path.display()
+            );
+            parse_pyre_config(&original_config_path)?
         } else {
             return Err(anyhow::anyhow!(
-                "Currently only migration from pyrightconfig.json, mypy.ini, and pyproject.toml is supported, not `{}`",
+                "Currently only migration from pyrightconfig.json, mypy.ini, pyproject.toml, and .pyre_configuration is supported, not `{}`",
                 original_config_path.display(),
             ));
         };
@@ -457,6 +492,51 @@ mod tests {
         from_file(&pyrefly_config_path)
     }
 
+    #[test]
+    fn test_run_pyre() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        std::fs::create_dir_all(tmp.path().join("tools"))?;
+        fs_anyhow::write(
+            &tmp.path().join(".pyre_configuration"),
+            r#"{"source_directories": ["src"], "strict": true}"#,
+        )?;
+        fs_anyhow::write(
+            &tmp.path().join("tools/.pyre_configuration.local"),
+            r#"{"strict": false}"#,
+        )?;
+
+        let pyrefly_config_path =
+            config_migration(tmp.path(), MigrationSource::Pyre, false, false)?;
+        assert_eq!(pyrefly_config_path, tmp.path().join("pyrefly.toml"));
+        let output = fs_anyhow::read_to_string(&pyrefly_config_path)?;
+        assert!(
+            output.contains(r#"project-includes = ["src", "tools"]"#),
+            "{output}"
+        );
+        assert!(output.contains(r#"preset = "strict""#), "{output}");
+        assert!(output.contains(r#"matches = "tools""#), "{output}");
+        from_file(&pyrefly_config_path)
+    }
+
+    #[test]
+    fn test_in_memory_skips_pyre_configuration() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        fs_anyhow::write(&tmp.path().join(".pyre_configuration"), "{}")?;
+        assert!(find_and_migrate_in_memory(tmp.path())?.is_none());
+
+        // A nearer Pyre config doesn't hide a mypy config further up.
+        let project = tmp.path().join("project");
+        std::fs::create_dir_all(&project)?;
+        fs_anyhow::write(&tmp.path().join("mypy.ini"), "[mypy]\n")?;
+        fs_anyhow::write(&project.join(".pyre_configuration"), "{}")?;
+        let (_, kind) = find_and_migrate_in_memory(&project)?.unwrap();
+        assert_eq!(
+            kind,
+            MigratedFromKind::Mypy(MigratedConfigSource::DedicatedFile)
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_run_mypy() -> anyhow::Result<()> {
         let tmp = tempfile::tempdir()?;
@@ -0,0 +1,133 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! The Pyrefly error kinds that correspond to another type checker's error codes, as used in
+//! both config migration and suppression comments.
+
+use crate::error_kind::ErrorKind;
+use crate::migration::pyright::RuleOverrides;
+
+/// The error kinds a mypy error code covers. Empty for codes with no Pyrefly equivalent.
+pub fn mypy_error_kinds(code: &str) -> &'static [ErrorKind] {
+    match code {
+        "union-attr" | "attr-defined" => &[ErrorKind::MissingAttribute],
+        "arg-type" => &[ErrorKind::BadArgumentType],
+        "assignment" => &[ErrorKind::BadAssignment],
+        "call-arg" => &[ErrorKind::BadArgumentCount],
+        "call-overload" => &[ErrorKind::NoMatchingOverload],
+        "index" => &[ErrorKind::BadIndex, ErrorKind::UnsupportedOperation],
+        "dict-item" => &[ErrorKind::BadTypedDict],
+        "explicit-any" => &[ErrorKind::ExplicitAny],
+        "operator" => &[ErrorKind::UnsupportedOperation],
+        "typeddict-unknown-key" => &[ErrorKind::BadTypedDictKey],
+        "typeddict-readonly-mutated" => &[ErrorKind::ReadOnly],
+        "name-defined" => &[ErrorKind::UnknownName],
+        "used-before-def" | "possibly-undefined" => &[ErrorKind::UnboundName],
+        "valid-type" => &[ErrorKind::InvalidAnnotation],
+        "type-arg" => &[ErrorKind::ImplicitAnyTypeArgument],
+        "no-untyped-def" => &[
+            ErrorKind::ImplicitAnyParameter,
+            ErrorKind::UnannotatedReturn,
+        ],
+        "metaclass" => &[ErrorKind::InvalidInheritance],
+        "override" => &[ErrorKind::BadOverride],
+        "mutable-override" => &[ErrorKind::BadOverrideMutableAttribute],
+        "return" | "return-value" => &[ErrorKind::BadReturn],
+        "type-var" => &[ErrorKind::BadSpecialization],
+        "import" | "import-not-found" => &[ErrorKind::MissingImport],
+        "import-untyped" => &[ErrorKind::UntypedImport],
+        "abstract" => &[ErrorKind::BadInstantiation],
+        "no-overload-impl" => &[ErrorKind::InvalidOverload],
+        "unused-coroutine" | "unused-awaitable" => &[ErrorKind::UnusedCoroutine],
+        "top-level-await" | "await-not-async" => &[ErrorKind::NotAsync],
+        "assert-type" => &[ErrorKind::AssertType],
+        "syntax" => &[ErrorKind::ParseError],
+        "redundant-cast" => &[ErrorKind::RedundantCast],
+        "redundant-expr" | "truthy-function" | "truthy-bool" | "truthy-iterable" => {
+            &[ErrorKind::RedundantCondition]
+        }
+        "deprecated" => &[ErrorKind::Deprecated],
+        "name-match" => &[ErrorKind::NameMismatch],
+        "no-any-return" => &[ErrorKind::NoAnyReturn],
+        _ => &[],
+    }
+}
+
+/// The error kinds a pyright rule, such as `reportAttributeAccessIssue`, covers. Empty for
+/// rules with no Pyrefly equivalent.
+pub fn pyright_error_kinds(rule: &str) -> Vec<ErrorKind> {
+    // Reuse the config migration's mapping by enabling just this rule.
+    let Ok(overrides) =
+        serde_json::from_value::<RuleOverrides>(serde_json::json!({ rule: "error" }))
+    else {
+        return Vec::new();
+    };
+    let mut kinds: Vec<ErrorKind> = overrides
+        .to_config()
+        .map(|config| config.iter().map(|(kind, _)| kind).collect())
+        .unwrap_or_default();
+    kinds.sort();
+    kinds
+}
+
+/// The error kinds a numeric Pyre error code, as in `# pyre-fixme[6]`, covers. Empty for
+/// codes with no Pyrefly equivalent.
+pub fn pyre_error_kinds(code: &str) -> &'static [ErrorKind] {
+    match code {
+        "3" => &[ErrorKind::UnannotatedReturn],
+        "2" => &[ErrorKind::UnannotatedParameter],
+        "4" => &[ErrorKind::UnannotatedAttribute],
+        "6" => &[ErrorKind::BadArgumentType],
+        "7" => &[ErrorKind::BadReturn],
+        "8" | "9" => &[ErrorKind::BadAssignment],
+        "10" | "18" => &[ErrorKind::UnknownName, ErrorKind::UnboundName],
+        "11" | "31" => &[ErrorKind::InvalidAnnotation, ErrorKind::NotAType],
+        "13" => &[ErrorKind::ImplicitlyDefinedAttribute],
+        "14" | "15" => &[ErrorKind::BadOverride, ErrorKind::BadParamNameOverride],
+        "16" => &[ErrorKind::MissingAttribute],
+        "19" => &[
+            ErrorKind::BadArgumentCount,
+            ErrorKind::UnexpectedPositionalArgument,
+        ],
+        "20" => &[ErrorKind::MissingArgument],
+        "21" => &[ErrorKind::MissingImport],
+        "22" => &[ErrorKind::RedundantCast],
+        "24" => &[ErrorKind::BadSpecialization],
+        "26" | "27" => &[ErrorKind::BadTypedDictKey],
+        "28" => &[ErrorKind::UnexpectedKeyword],
+        "29" => &[ErrorKind::NotCallable],
+        "32" => &[ErrorKind::InvalidArgument],
+        "39" => &[ErrorKind::InvalidInheritance],
+        "45" => &[ErrorKind::BadInstantiation],
+        "56" => &[ErrorKind::InvalidDecorator],
+        "58" => &[ErrorKind::UnsupportedOperation],
+        _ => &[],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_kinds() {
+        assert_eq!(mypy_error_kinds("arg-type"), &[ErrorKind::BadArgumentType]);
+        assert!(mypy_error_kinds("misc").is_empty());
+        assert_eq!(
+            pyright_error_kinds("reportAttributeAccessIssue"),
+            vec![
+                ErrorKind::MissingAttribute,
+                ErrorKind::MissingModuleAttribute,
+                ErrorKind::NoAccess,
+                ErrorKind::ReadOnly,
+            ]
+        );
+        assert!(pyright_error_kinds("reportNotARule").is_empty());
+        assert_eq!(pyre_error_kinds("6"), &[ErrorKind::BadArgumentType]);
+        assert!(pyre_error_kinds("0").is_empty());
+    }
+}
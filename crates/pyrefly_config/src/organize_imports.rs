@@ -0,0 +1,209 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+use serde_with::skip_serializing_none;
+
+use crate::base::ExtraConfigs;
+
+/// A named set of defaults for organizing imports, matching the isort profile of the same name.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ImportProfile {
+    /// isort's own defaults: 79 columns, and long imports wrapped in a grid.
+    #[default]
+    Isort,
+    /// Compatible with the Black formatter: 88 columns, and long imports wrapped one name
+    /// per line with a trailing comma.
+    Black,
+    /// The Google style guide: one name per `from` import, and `import x` and `from x import y`
+    /// sorted together by module.
+    Google,
+}
+
+/// How a `from` import that does not fit on one line is wrapped.
+#[derive(Debug, PartialEq, Eq, Deserialize, Serialize, Clone, Copy)]
+#[serde(rename_all = "kebab-case")]
+pub enum MultiLineOutput {
+    /// isort mode 0: names continue on lines aligned with the opening parenthesis.
+    Grid,
+    /// isort mode 3: one name per indented line, closed by a parenthesis on its own line.
+    VerticalHangingIndent,
+}
+
+/// Options for the `source.organizeImports` code action. Names follow isort, and any option
+/// left unset comes from the `profile`.
+#[skip_serializing_none]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub struct OrganizeImportsConfig {
+    /// The profile providing the defaults for every other option.
+    pub profile: Option<ImportProfile>,
+
+    /// The maximum line length before a `from` import is wrapped.
+    pub line_length: Option<usize>,
+
+    /// How long `from` imports are wrapped.
+    pub multi_line_output: Option<MultiLineOutput>,
+
+    /// Put a trailing comma after the last name of a wrapped `from` import.
+    pub include_trailing_comma: Option<bool>,
+
+    /// Import a single name per `from` import.
+    pub force_single_line: Option<bool>,
+
+    /// Modules whose `from` imports are kept together under `force-single-line`.
+    pub single_line_exclusions: Option<Vec<String>>,
+
+    /// Sort `import x` and `from x import y` together by module, rather than placing all
+    /// `import x` statements first.
+    pub force_sort_within_sections: Option<bool>,
+
+    /// Keep names imported with `as` in the same `from` import as the other names.
+    pub combine_as_imports: Option<bool>,
+
+    /// Order imported names by kind: `CONSTANTS`, then `Classes`, then everything else.
+    pub order_by_type: Option<bool>,
+
+    /// Packages placed in the first-party section, whatever they resolve to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_first_party: Vec<String>,
+
+    /// Packages placed in the third-party section, whatever they resolve to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub known_third_party: Vec<String>,
+
+    /// Any unknown config items
+    #[serde(default, flatten)]
+    pub(crate) extras: ExtraConfigs,
+}
+
+/// The options for organizing imports, with the profile defaults filled in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrganizeImportsSettings {
+    pub line_length: usize,
+    pub multi_line_output: MultiLineOutput,
+    pub include_trailing_comma: bool,
+    pub force_single_line: bool,
+    pub single_line_exclusions: Vec<String>,
+    pub force_sort_within_sections: bool,
+    pub combine_as_imports: bool,
+    pub order_by_type: bool,
+    pub known_first_party: Vec<String>,
+    pub known_third_party: Vec<String>,
+}
+
+impl OrganizeImportsSettings {
+    pub fn from_profile(profile: ImportProfile) -> Self {
+        let isort = Self {
+            line_length: 79,
+            multi_line_output: MultiLineOutput::Grid,
+            include_trailing_comma: false,
+            force_single_line: false,
+            single_line_exclusions: Vec::new(),
+            force_sort_within_sections: false,
+            combine_as_imports: false,
+            order_by_type: true,
+            known_first_party: Vec::new(),
+            known_third_party: Vec::new(),
+        };
+        match profile {
+            ImportProfile::Isort => isort,
+            ImportProfile::Black => Self {
+                line_length: 88,
+                multi_line_output: MultiLineOutput::VerticalHangingIndent,
+                include_trailing_comma: true,
+                ..isort
+            },
+            ImportProfile::Google => Self {
+                line_length: 1000,
+                force_single_line: true,
+                single_line_exclusions: ["collections.abc", "typing", "typing_extensions"]
+                    .map(str::to_owned)
+                    .to_vec(),
+                force_sort_within_sections: true,
+                order_by_type: false,
+                ..isort
+            },
+        }
+    }
+}
+
+impl Default for OrganizeImportsSettings {
+    fn default() -> Self {
+        Self::from_profile(ImportProfile::default())
+    }
+}
+
+impl OrganizeImportsConfig {
+    pub(crate) fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+
+    /// The settings this config describes.
+    pub fn settings(&self) -> OrganizeImportsSettings {
+        let defaults = OrganizeImportsSettings::from_profile(self.profile.unwrap_or_default());
+        OrganizeImportsSettings {
+            line_length: self.line_length.unwrap_or(defaults.line_length),
+            multi_line_output: self.multi_line_output.unwrap_or(defaults.multi_line_output),
+            include_trailing_comma: self
+                .include_trailing_comma
+                .unwrap_or(defaults.include_trailing_comma),
+            force_single_line: self.force_single_line.unwrap_or(defaults.force_single_line),
+            single_line_exclusions: self
+                .single_line_exclusions
+                .clone()
+                .unwrap_or(defaults.single_line_exclusions),
+            force_sort_within_sections: self
+                .force_sort_within_sections
+                .unwrap_or(defaults.force_sort_within_sections),
+            combine_as_imports: self
+                .combine_as_imports
+                .unwrap_or(defaults.combine_as_imports),
+            order_by_type: self.order_by_type.unwrap_or(defaults.order_by_type),
+            known_first_party: self.known_first_party.clone(),
+            known_third_party: self.known_third_party.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigFile;
+
+    #[test]
+    fn test_settings_from_profile_and_overrides() {
+        let config = ConfigFile::parse_config(
+            r#"
+            [organize-imports]
+            profile = "black"
+            line-length = 100
+            known-first-party = ["app"]
+            "#,
+        )
+        .unwrap();
+        let settings = config.organize_imports.settings();
+        assert_eq!(settings.line_length, 100);
+        assert_eq!(
+            settings.multi_line_output,
+            MultiLineOutput::VerticalHangingIndent
+        );
+        assert!(settings.include_trailing_comma);
+        assert!(!settings.force_single_line);
+        assert_eq!(settings.known_first_party, vec!["app".to_owned()]);
+    }
+
+    #[test]
+    fn test_default_settings_are_isort() {
+        assert_eq!(
+            ConfigFile::default().organize_imports.settings(),
+            OrganizeImportsSettings::from_profile(ImportProfile::Isort)
+        );
+    }
+}
@@ -0,0 +1,257 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Configs for standalone scripts with PEP 723 inline metadata, which declare their own
+//! Python version and dependencies and are run in an environment built from them.
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use anyhow::anyhow;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::script_metadata::ScriptMetadata;
+use pyrefly_python::script_metadata::normalize_distribution_name;
+use starlark_map::small_set::SmallSet;
+
+use crate::config::ConfigFile;
+use crate::environment::environment::PythonEnvironment;
+use crate::environment::uv;
+use crate::environment::venv;
+use crate::finder::ConfigError;
+
+/// The inline metadata of a PEP 723 script, along with what its dependencies provide.
+#[derive(Debug)]
+pub struct Script {
+    pub metadata: ScriptMetadata,
+    /// The normalized names of the distributions the script depends on.
+    dependencies: SmallSet<String>,
+    /// The top-level import names the installed distributions in `dependencies` provide, read
+    /// from the site package path on first use.
+    provided: OnceLock<SmallSet<String>>,
+}
+
+impl PartialEq for Script {
+    fn eq(&self, other: &Self) -> bool {
+        self.metadata == other.metadata
+    }
+}
+
+impl Eq for Script {}
+
+impl Script {
+    pub fn new(metadata: ScriptMetadata) -> Self {
+        Self {
+            dependencies: metadata.dependency_names().collect(),
+            metadata,
+            provided: OnceLock::new(),
+        }
+    }
+
+    /// Whether the script's dependencies cover the third-party `module`, found in one of
+    /// `site_package_path`. Import names often differ from distribution names (`yaml` is
+    /// provided by `PyYAML`), so we consult the metadata of installed distributions. A config's
+    /// site package path doesn't change, so that metadata is only read once.
+    pub fn lists_dependency<'a>(
+        &self,
+        module: ModuleName,
+        site_package_path: impl Iterator<Item = &'a PathBuf>,
+    ) -> bool {
+        let top_level = module.first_component();
+        self.dependencies
+            .contains(&normalize_distribution_name(top_level.as_str()))
+            || self
+                .provided
+                .get_or_init(|| provided_top_levels(&self.dependencies, site_package_path))
+                .contains(top_level.as_str())
+    }
+}
+
+/// The interpreter for the environment the script at `path` is run in: the one set in its
+/// `[tool.pyrefly]` table, or else the one `uv run` built for it.
+fn script_interpreter(path: &Path, metadata: &ScriptMetadata) -> Option<PathBuf> {
+    path.parent()
+        .and_then(|dir| metadata.python_interpreter_path(dir))
+        .or_else(|| venv::find_active(&uv::find_script_environment(path)?))
+}
+
+/// Derive the config for the script at `path` from `config`, the config that would otherwise
+/// apply to it. Returns `None` if `path` isn't a script with inline metadata. The script is
+/// read from the disk, unless its contents are given as `source`.
+///
+/// The derived config checks against the Python version from `requires-python` and resolves
+/// third-party imports in the script's own environment, when we can find one. Otherwise, the
+/// project's environment is used.
+pub fn script_config(
+    path: &Path,
+    source: Option<&str>,
+    config: &ConfigFile,
+    errors: &mut Vec<ConfigError>,
+) -> Option<ConfigFile> {
+    if path.extension().is_none_or(|ext| ext != "py") {
+        return None;
+    }
+    let from_disk;
+    let source = match source {
+        Some(source) => source,
+        None => {
+            from_disk = fs::read_to_string(path).ok()?;
+            &from_disk
+        }
+    };
+    let metadata = match ScriptMetadata::parse(source) {
+        Ok(metadata) => metadata?,
+        Err(e) => {
+            errors.push(ConfigError::warn(
+                e.context(format!("While reading `{}`", path.display())),
+            ));
+            return None;
+        }
+    };
+
+    let mut config = config.clone();
+    if !config.interpreters.skip_interpreter_query
+        && let Some(interpreter) = script_interpreter(path, &metadata)
+    {
+        match PythonEnvironment::get_interpreter_env(
+            &interpreter,
+            config.interpreters.environment_discovery,
+        ) {
+            (env, None) => {
+                config.python_environment.python_version = env.python_version;
+                config.python_environment.interpreter_site_package_path =
+                    env.interpreter_site_package_path;
+            }
+            (_, Some(e)) => errors.push(ConfigError::warn(anyhow!(
+                "Using the project's environment for script `{}`: {e:#}",
+                path.display()
+            ))),
+        }
+    }
+    if let Some(version) = metadata.minimum_python_version() {
+        config.python_environment.python_version = Some(version);
+    }
+    config.script = Some(Arc::new(Script::new(metadata)));
+    Some(config)
+}
+
+/// The top-level import names a `*.dist-info` directory says its distribution provides.
+fn distribution_top_levels(dist_info: &Path) -> Vec<String> {
+    if let Ok(top_level) = fs::read_to_string(dist_info.join("top_level.txt")) {
+        return top_level.lines().map(|x| x.trim().to_owned()).collect();
+    }
+    // `top_level.txt` is optional, so fall back to the first component of each installed file.
+    let Ok(record) = fs::read_to_string(dist_info.join("RECORD")) else {
+        return Vec::new();
+    };
+    record
+        .lines()
+        .filter_map(|line| line.split([',', '/']).next())
+        .map(|first| first.trim_end_matches(".py").to_owned())
+        .collect()
+}
+
+/// The top-level import names provided by the distributions in `dependencies` that are
+/// installed in one of `site_package_path`.
+fn provided_top_levels<'a>(
+    dependencies: &SmallSet<String>,
+    site_package_path: impl Iterator<Item = &'a PathBuf>,
+) -> SmallSet<String> {
+    site_package_path
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flatten()
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.strip_suffix(".dist-info"))
+                .and_then(|name| name.split('-').next())
+                .is_some_and(|distribution| {
+                    dependencies.contains(&normalize_distribution_name(distribution))
+                })
+        })
+        .flat_map(|entry| distribution_top_levels(&entry.path()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_util::test_path::TestPath;
+
+    use super::*;
+
+    const SCRIPT: &str = r#"# /// script
+# requires-python = ">=3.10"
+# dependencies = ["requests", "PyYAML>=6"]
+# ///
+import requests
+"#;
+
+    #[test]
+    fn test_script_config() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::file_with_contents("script.py", SCRIPT),
+                TestPath::file_with_contents("module.py", "import requests\n"),
+            ],
+        );
+        let mut config = ConfigFile::default();
+        config.interpreters.skip_interpreter_query = true;
+        config.python_environment.set_empty_to_default();
+        let mut errors = Vec::new();
+
+        assert!(script_config(&root.join("module.py"), None, &config, &mut errors).is_none());
+        let script = script_config(&root.join("script.py"), None, &config, &mut errors).unwrap();
+        assert_eq!(script.python_version().to_string(), "3.10.0");
+        assert!(script.script.is_some());
+        // Unsaved edits take precedence over the disk.
+        let edited = SCRIPT.replace("3.10", "3.12");
+        let script =
+            script_config(&root.join("module.py"), Some(&edited), &config, &mut errors).unwrap();
+        assert_eq!(script.python_version().to_string(), "3.12.0");
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_lists_dependency() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let site_packages = tempdir.path().to_path_buf();
+        TestPath::setup_test_directory(
+            &site_packages,
+            vec![
+                TestPath::dir(
+                    "PyYAML-6.0.2.dist-info",
+                    vec![TestPath::file_with_contents(
+                        "top_level.txt",
+                        "_yaml\nyaml\n",
+                    )],
+                ),
+                TestPath::dir(
+                    "attrs-25.1.0.dist-info",
+                    vec![TestPath::file_with_contents(
+                        "RECORD",
+                        "attr/__init__.py,sha256=x,1\nattrs/__init__.py,sha256=y,2\n",
+                    )],
+                ),
+            ],
+        );
+        let script = Script::new(ScriptMetadata::parse(SCRIPT).unwrap().unwrap());
+        let lists = |module: &str| {
+            script.lists_dependency(ModuleName::from_str(module), [&site_packages].into_iter())
+        };
+        assert!(lists("requests.adapters"));
+        assert!(lists("yaml"));
+        assert!(!lists("attr"));
+        assert!(!lists("numpy"));
+    }
+}
@@ -30,6 +30,7 @@ pub mod module_name;
 pub mod module_path;
 pub mod nesting_context;
 pub mod qname;
+pub mod script_metadata;
 pub mod short_identifier;
 pub mod symbol_kind;
 pub mod sys_info;
@@ -85,6 +85,9 @@ pub enum ModulePathDetails {
     /// The module source comes from custom third-party stubs bundled with Pyrefly.
     /// These are stubs not included in typeshed
     BundledThirdParty(InternedPath),
+    /// The module source is an entry of a zip archive (a `.zip`, `.egg`, `.whl` or `.pyz` file)
+    /// on the search path. The path runs through the archive, e.g. `/site/deps.zip/pkg/mod.py`.
+    Archive(InternedPath),
 }
 
 impl PartialOrd for ModulePath {
@@ -112,7 +115,9 @@ fn is_path_init(path: &Path) -> bool {
 impl Display for ModulePath {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.0 {
-            ModulePathDetails::FileSystem(path) | ModulePathDetails::Namespace(path) => {
+            ModulePathDetails::FileSystem(path)
+            | ModulePathDetails::Namespace(path)
+            | ModulePathDetails::Archive(path) => {
                 write!(f, "{}", path.display())
             }
             ModulePathDetails::Memory(path) => {
@@ -148,7 +153,8 @@ impl Serialize for ModulePath {
         match self.0 {
             ModulePathDetails::FileSystem(path)
             | ModulePathDetails::Memory(path)
-            | ModulePathDetails::Namespace(path) => path.serialize(serializer),
+            | ModulePathDetails::Namespace(path)
+            | ModulePathDetails::Archive(path) => path.serialize(serializer),
             ModulePathDetails::BundledTypeshed(_)
             | ModulePathDetails::BundledTypeshedThirdParty(_)
             | ModulePathDetails::BundledThirdParty(_) => self.to_string().serialize(serializer),
@@ -191,6 +197,10 @@ impl ModulePath {
         )))
     }
 
+    pub fn archive(path: PathBuf) -> Self {
+        Self::new(ModulePathDetails::Archive(InternedPath::new(path)))
+    }
+
     pub fn is_init(&self) -> bool {
         is_path_init(self.as_path())
     }
@@ -261,7 +271,8 @@ impl ModulePath {
             | ModulePathDetails::BundledTypeshedThirdParty(path)
             | ModulePathDetails::BundledThirdParty(path)
             | ModulePathDetails::Memory(path)
-            | ModulePathDetails::Namespace(path) => path,
+            | ModulePathDetails::Namespace(path)
+            | ModulePathDetails::Archive(path) => path,
         }
     }
 
@@ -290,6 +301,7 @@ impl ModulePath {
             ModulePathDetails::BundledThirdParty(path) => {
                 ModulePath::new(ModulePathDetails::BundledThirdParty(*path))
             }
+            ModulePathDetails::Archive(path) => ModulePath::new(ModulePathDetails::Archive(*path)),
         }
     }
 
@@ -311,12 +323,13 @@ impl ModulePath {
     }
 
     /// Whether this module is first-party user code eligible for IDE indexing.
-    /// Excludes bundled typeshed/third-party stubs and site-packages libraries.
+    /// Excludes bundled typeshed/third-party stubs, site-packages libraries and archives.
     pub fn is_first_party_for_indexing(&self) -> bool {
         match &self.0 {
             ModulePathDetails::BundledTypeshed(_)
             | ModulePathDetails::BundledTypeshedThirdParty(_)
-            | ModulePathDetails::BundledThirdParty(_) => false,
+            | ModulePathDetails::BundledThirdParty(_)
+            | ModulePathDetails::Archive(_) => false,
             ModulePathDetails::FileSystem(path)
             | ModulePathDetails::Memory(path)
             | ModulePathDetails::Namespace(path) => {
@@ -0,0 +1,237 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Inline script metadata, as specified by [PEP 723](https://peps.python.org/pep-0723/).
+//!
+//! ```python
+//! # /// script
+//! # requires-python = ">=3.11"
+//! # dependencies = ["requests<3"]
+//! # ///
+//! ```
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::LazyLock;
+
+use anyhow::Context;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::sys_info::PythonVersion;
+
+/// The reference regex from PEP 723, matching one `# /// TYPE` ... `# ///` block.
+static BLOCK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?m)^# /// (?P<type>[a-zA-Z0-9-]+)$\s(?P<content>(^#(| .*)$\s)+)^# ///$").unwrap()
+});
+
+/// The leading name of a dependency specifier, such as `requests` in `requests[socks]>=2`.
+static REQUIREMENT_NAME_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*([A-Za-z0-9][A-Za-z0-9._-]*)").unwrap());
+
+/// The `script` metadata block of a Python file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ScriptMetadata {
+    /// The version specifier for the Python versions the script supports, e.g. `>=3.11`.
+    pub requires_python: Option<String>,
+    /// The dependency specifiers the script's environment is built from.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    tool: ScriptTools,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+struct ScriptTools {
+    #[serde(default)]
+    pyrefly: ScriptPyreflyOptions,
+}
+
+/// Pyrefly options set in the script's `[tool.pyrefly]` table.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct ScriptPyreflyOptions {
+    python_interpreter_path: Option<PathBuf>,
+}
+
+impl ScriptMetadata {
+    /// Parse the `script` block out of `source`. Returns `None` if there is no such block,
+    /// and an error if the block is repeated or isn't valid TOML.
+    pub fn parse(source: &str) -> anyhow::Result<Option<Self>> {
+        // Cheap check first, since almost no files are scripts.
+        if !source.contains("# /// script") {
+            return Ok(None);
+        }
+        let source = source.replace("\r\n", "\n");
+        let mut blocks = BLOCK_REGEX
+            .captures_iter(&source)
+            .filter(|caps| &caps["type"] == "script");
+        let Some(block) = blocks.next() else {
+            return Ok(None);
+        };
+        if blocks.next().is_some() {
+            return Err(anyhow::anyhow!("Multiple `script` metadata blocks"));
+        }
+        let content: String = block["content"]
+            .lines()
+            .map(|line| {
+                let line = line.strip_prefix('#').unwrap_or(line);
+                let line = line.strip_prefix(' ').unwrap_or(line);
+                format!("{line}\n")
+            })
+            .collect();
+        let metadata =
+            toml::from_str(&content).context("Invalid TOML in `script` metadata block")?;
+        Ok(Some(metadata))
+    }
+
+    /// The lowest Python version allowed by `requires-python`, if it has a lower bound.
+    pub fn minimum_python_version(&self) -> Option<PythonVersion> {
+        self.requires_python
+            .as_deref()?
+            .split(',')
+            .filter_map(lower_bound)
+            .max()
+    }
+
+    /// The interpreter configured in the script's `[tool.pyrefly]` table, relative to `script_dir`.
+    pub fn python_interpreter_path(&self, script_dir: &Path) -> Option<PathBuf> {
+        self.tool
+            .pyrefly
+            .python_interpreter_path
+            .as_ref()
+            .map(|path| script_dir.join(path))
+    }
+
+    /// The normalized names of the distributions the script depends on.
+    pub fn dependency_names(&self) -> impl Iterator<Item = String> + '_ {
+        self.dependencies.iter().filter_map(|requirement| {
+            REQUIREMENT_NAME_REGEX
+                .captures(requirement)
+                .map(|caps| normalize_distribution_name(&caps[1]))
+        })
+    }
+}
+
+/// Normalize a distribution name as described in PEP 503, so `Foo.Bar_baz` and `foo-bar-baz`
+/// compare equal.
+pub fn normalize_distribution_name(name: &str) -> String {
+    let mut normalized = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !normalized.ends_with('-') {
+                normalized.push('-');
+            }
+        } else {
+            normalized.push(c.to_ascii_lowercase());
+        }
+    }
+    normalized
+}
+
+/// The lowest version allowed by a single version specifier clause, such as `>=3.10`.
+fn lower_bound(clause: &str) -> Option<PythonVersion> {
+    let clause = clause.trim();
+    let (op, version) = [">=", "~=", "==", ">"]
+        .iter()
+        .find_map(|op| Some((*op, clause.strip_prefix(op)?)))?;
+    let mut parts = version.trim().trim_end_matches(".*").split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().map_or(Some(0), |x| x.parse().ok())?;
+    let micro = parts.next().map_or(Some(0), |x| x.parse().ok())?;
+    let version = PythonVersion {
+        major,
+        minor,
+        micro,
+    };
+    Some(if op == ">" {
+        PythonVersion {
+            micro: micro + 1,
+            ..version
+        }
+    } else {
+        version
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let source = r#"#!/usr/bin/env python
+# /// script
+# requires-python = ">=3.11"
+# dependencies = [
+#   "requests<3",
+#   "Rich[jupyter]>=13",
+# ]
+#
+# [tool.pyrefly]
+# python-interpreter-path = ".venv/bin/python"
+# ///
+import requests
+"#;
+        let metadata = ScriptMetadata::parse(source).unwrap().unwrap();
+        assert_eq!(metadata.requires_python.as_deref(), Some(">=3.11"));
+        assert_eq!(
+            metadata.dependency_names().collect::<Vec<_>>(),
+            vec!["requests", "rich"]
+        );
+        assert_eq!(
+            metadata.python_interpreter_path(Path::new("/scripts")),
+            Some(PathBuf::from("/scripts/.venv/bin/python"))
+        );
+    }
+
+    #[test]
+    fn test_parse_no_block() {
+        assert_eq!(ScriptMetadata::parse("import os\n").unwrap(), None);
+        // Other block types are allowed, and ignored.
+        assert_eq!(
+            ScriptMetadata::parse("# /// pyproject\n# x = 1\n# ///\n").unwrap(),
+            None
+        );
+        // An unterminated block is not a block.
+        assert_eq!(
+            ScriptMetadata::parse("# /// script\n# dependencies = []\n").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(ScriptMetadata::parse("# /// script\n# dependencies = [\n# ///\n").is_err());
+        let twice = "# /// script\n# dependencies = []\n# ///\n";
+        assert!(ScriptMetadata::parse(&format!("{twice}{twice}")).is_err());
+    }
+
+    #[test]
+    fn test_minimum_python_version() {
+        let version = |requires_python: &str| {
+            ScriptMetadata {
+                requires_python: Some(requires_python.to_owned()),
+                ..Default::default()
+            }
+            .minimum_python_version()
+            .map(|v| v.to_string())
+        };
+        assert_eq!(version(">=3.11").as_deref(), Some("3.11.0"));
+        assert_eq!(version("~=3.10.2").as_deref(), Some("3.10.2"));
+        assert_eq!(version(">=3.9, <3.13, >=3.10").as_deref(), Some("3.10.0"));
+        assert_eq!(version("==3.12.*").as_deref(), Some("3.12.0"));
+        assert_eq!(version("<3.13"), None);
+    }
+
+    #[test]
+    fn test_normalize_distribution_name() {
+        assert_eq!(normalize_distribution_name("Foo.Bar__baz"), "foo-bar-baz");
+        assert_eq!(normalize_distribution_name("pyyaml"), "pyyaml");
+    }
+}
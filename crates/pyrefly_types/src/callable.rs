@@ -923,7 +923,7 @@ impl Callable {
 }
 
 impl Param {
-    pub(crate) fn fmt_default(&self, default: &Option<DefaultValue>) -> String {
+    pub fn fmt_default(&self, default: &Option<DefaultValue>) -> String {
         match default {
             Some(DefaultValue {
                 display: Some(text),
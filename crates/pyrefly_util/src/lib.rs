@@ -46,6 +46,7 @@ pub mod no_hash;
 pub mod owner;
 pub mod panic;
 pub mod prelude;
+pub mod profile;
 pub mod recurser;
 pub mod ruff_visitors;
 pub mod small_map1;
@@ -56,6 +57,7 @@ pub mod tarjan;
 pub mod task_heap;
 pub mod telemetry;
 pub mod test_path;
+pub mod text_edits;
 pub mod thread_pool;
 pub mod timer;
 pub mod trace;
@@ -0,0 +1,192 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Timeline profiles of a check, in the Chrome trace-event format.
+//!
+//! Flat per-module timings can't show how work was spread across threads, or which
+//! modules kept other threads waiting. A [`ProfileCollector`] records a span for each
+//! piece of work, on the thread that did it, so the result can be opened in Perfetto
+//! (<https://ui.perfetto.dev>) or `chrome://tracing`.
+//! <https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU>
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use web_time::Instant;
+
+use crate::lock::Mutex;
+use crate::thread_pool::current_worker_index;
+
+/// A span that has finished.
+#[derive(Debug, Clone)]
+struct Span {
+    name: &'static str,
+    category: &'static str,
+    /// What the span was working on, usually a module name.
+    detail: String,
+    /// `None` for threads outside the thread pool.
+    worker: Option<usize>,
+    start: Duration,
+    duration: Duration,
+}
+
+/// A profiling session. Cloning produces another handle to the same session.
+#[derive(Debug, Clone)]
+pub struct ProfileCollector {
+    start: Instant,
+    spans: Arc<Mutex<Vec<Span>>>,
+}
+
+/// The time a span started, from [`ProfileCollector::now`].
+#[derive(Debug, Clone, Copy)]
+pub struct ProfileStart(Duration);
+
+impl Default for ProfileCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProfileCollector {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            spans: Default::default(),
+        }
+    }
+
+    /// The current time, to pass to [`ProfileCollector::finish`] once the work is done.
+    pub fn now(&self) -> ProfileStart {
+        ProfileStart(self.start.elapsed())
+    }
+
+    /// Record a span from `start` until now, on the current thread.
+    pub fn finish(
+        &self,
+        start: ProfileStart,
+        name: &'static str,
+        category: &'static str,
+        detail: impl ToString,
+    ) {
+        let span = Span {
+            name,
+            category,
+            detail: detail.to_string(),
+            worker: current_worker_index(),
+            start: start.0,
+            duration: self.start.elapsed().saturating_sub(start.0),
+        };
+        self.spans.lock().push(span);
+    }
+
+    /// The spans recorded so far as a Chrome trace-event JSON document.
+    pub fn report_json(&self) -> String {
+        #[derive(Serialize)]
+        struct Args<'a> {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            name: Option<String>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            detail: Option<&'a str>,
+        }
+
+        /// One entry of `traceEvents`. Times are in microseconds.
+        #[derive(Serialize)]
+        struct Event<'a> {
+            name: &'a str,
+            #[serde(skip_serializing_if = "str::is_empty")]
+            cat: &'a str,
+            ph: &'static str,
+            pid: u32,
+            tid: usize,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            ts: Option<f64>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            dur: Option<f64>,
+            args: Args<'a>,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Report<'a> {
+            trace_events: Vec<Event<'a>>,
+            display_time_unit: &'static str,
+        }
+
+        fn tid(worker: Option<usize>) -> usize {
+            worker.map_or(0, |x| x + 1)
+        }
+
+        let mut spans = self.spans.lock().clone();
+        spans.sort_by_key(|span| (span.start, tid(span.worker)));
+        let workers: BTreeSet<Option<usize>> = spans.iter().map(|span| span.worker).collect();
+        // Name each thread, so viewers don't show bare thread ids.
+        let mut trace_events: Vec<Event> = workers
+            .into_iter()
+            .map(|worker| Event {
+                name: "thread_name",
+                cat: "",
+                ph: "M",
+                pid: 1,
+                tid: tid(worker),
+                ts: None,
+                dur: None,
+                args: Args {
+                    name: Some(match worker {
+                        None => "main".to_owned(),
+                        Some(x) => format!("worker {x}"),
+                    }),
+                    detail: None,
+                },
+            })
+            .collect();
+        trace_events.extend(spans.iter().map(|span| Event {
+            name: span.name,
+            cat: span.category,
+            ph: "X",
+            pid: 1,
+            tid: tid(span.worker),
+            ts: Some(span.start.as_secs_f64() * 1e6),
+            dur: Some(span.duration.as_secs_f64() * 1e6),
+            args: Args {
+                name: None,
+                detail: Some(&span.detail),
+            },
+        }));
+        serde_json::to_string(&Report {
+            trace_events,
+            display_time_unit: "ms",
+        })
+        .expect("profile report should always serialize")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    #[test]
+    fn test_report_json() {
+        let collector = ProfileCollector::new();
+        let start = collector.now();
+        collector.finish(start, "Solutions", "step", "foo.bar");
+        let report: Value = serde_json::from_str(&collector.report_json()).unwrap();
+        let events = report["traceEvents"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["ph"], "M");
+        assert_eq!(events[0]["args"]["name"], "main");
+        assert_eq!(events[1]["ph"], "X");
+        assert_eq!(events[1]["name"], "Solutions");
+        assert_eq!(events[1]["cat"], "step");
+        assert_eq!(events[1]["tid"], events[0]["tid"]);
+        assert_eq!(events[1]["args"]["detail"], "foo.bar");
+        assert!(events[1]["dur"].as_f64().unwrap() >= 0.0);
+    }
+}
@@ -0,0 +1,378 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Batches of edits to a single file, for commands that rewrite source code outside of an
+//! editor. Edits are grouped, so that a change made of several edits is applied entirely or
+//! not at all.
+
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+/// Number of unchanged lines shown around each change in a unified diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// A set of non-overlapping edits to one file.
+#[derive(Debug, Default, Clone)]
+pub struct TextEdits {
+    /// Sorted by range.
+    edits: Vec<(TextRange, String)>,
+}
+
+/// Whether two edits can't both be applied, because the result would depend on the order.
+fn conflicts(a: &(TextRange, String), b: &(TextRange, String)) -> bool {
+    let (a, b) = (a.0, b.0);
+    if a.start() == b.start() && (a.is_empty() || b.is_empty()) {
+        return true;
+    }
+    a.start() < b.end() && b.start() < a.end()
+}
+
+impl TextEdits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edits.len()
+    }
+
+    /// Whether `add` would accept `edits`.
+    pub fn accepts(&self, edits: &[(TextRange, String)]) -> bool {
+        edits.iter().enumerate().all(|(i, edit)| {
+            self.edits.contains(edit)
+                || !self
+                    .edits
+                    .iter()
+                    .chain(&edits[..i])
+                    .any(|x| x != edit && conflicts(x, edit))
+        })
+    }
+
+    /// Add all of `edits`, or none of them if any overlaps an edit that is already present.
+    /// An edit identical to one that is already present is only applied once.
+    /// Returns `false` if the edits were rejected.
+    pub fn add(&mut self, edits: impl IntoIterator<Item = (TextRange, String)>) -> bool {
+        let mut new: Vec<(TextRange, String)> = Vec::new();
+        for edit in edits {
+            if self.edits.contains(&edit) || new.contains(&edit) {
+                continue;
+            }
+            if self.edits.iter().chain(&new).any(|x| conflicts(x, &edit)) {
+                return false;
+            }
+            new.push(edit);
+        }
+        self.edits.extend(new);
+        self.edits
+            .sort_by_key(|(range, _)| (range.start(), range.end()));
+        true
+    }
+
+    /// The result of applying the edits to `source`.
+    pub fn apply(&self, source: &str) -> String {
+        apply(source, &self.edits, TextSize::default())
+    }
+
+    /// A unified diff from `source` to the result of applying the edits, naming the file
+    /// `path` on both sides. Empty if there are no edits.
+    pub fn unified_diff(&self, path: &str, source: &str) -> String {
+        if self.edits.is_empty() {
+            return String::new();
+        }
+        let lines = Lines::new(source);
+        let blocks = self.blocks(&lines);
+        let mut res = format!("--- {path}\n+++ {path}\n");
+        let mut delta: isize = 0;
+        let mut i = 0;
+        while i < blocks.len() {
+            // Blocks whose context would touch are shown in the same hunk.
+            let mut j = i + 1;
+            while j < blocks.len() && blocks[j].start <= blocks[j - 1].end + 2 * DIFF_CONTEXT {
+                j += 1;
+            }
+            let hunk = &blocks[i..j];
+            let old_start = hunk[0].start.saturating_sub(DIFF_CONTEXT);
+            let old_end = (hunk[j - i - 1].end + DIFF_CONTEXT).min(lines.count);
+            let mut body = String::new();
+            let mut old_len = 0;
+            let mut new_len = 0;
+            let mut line = old_start;
+            for block in hunk {
+                for context in line..block.start {
+                    push_line(&mut body, ' ', lines.line(context));
+                }
+                for old in block.start..block.end {
+                    push_line(&mut body, '-', lines.line(old));
+                }
+                for new in block.new.split_inclusive('\n') {
+                    push_line(&mut body, '+', new);
+                    new_len += 1;
+                }
+                old_len += block.end - block.start;
+                line = block.end;
+            }
+            for context in line..old_end {
+                push_line(&mut body, ' ', lines.line(context));
+            }
+            let context_len = (old_end - old_start) - old_len;
+            let old_len = old_end - old_start;
+            let new_len = context_len + new_len;
+            let new_start = (old_start as isize + delta) as usize;
+            res.push_str(&format!(
+                "@@ -{} +{} @@\n",
+                hunk_range(old_start, old_len),
+                hunk_range(new_start, new_len)
+            ));
+            res.push_str(&body);
+            delta += new_len as isize - old_len as isize;
+            i = j;
+        }
+        res
+    }
+
+    /// Group the edits into runs of whole lines that they change, with the new text of
+    /// each run.
+    fn blocks(&self, lines: &Lines) -> Vec<Block> {
+        let mut res: Vec<(usize, usize, Vec<&(TextRange, String)>)> = Vec::new();
+        for edit in &self.edits {
+            let (range, new_text) = edit;
+            let start = lines.line_of(range.start());
+            // An edit that ends at the start of a line leaves that line alone, unless the
+            // text it inserts would be joined onto it.
+            let ends_at_line_start = lines.is_line_start(range.end())
+                && (!range.is_empty() || new_text.is_empty() || new_text.ends_with('\n'));
+            let end = if ends_at_line_start {
+                lines.line_of(range.end()).max(start)
+            } else {
+                (lines.line_of(range.end()) + 1).min(lines.count)
+            };
+            match res.last_mut() {
+                Some((_, last_end, edits)) if start < *last_end => {
+                    *last_end = (*last_end).max(end);
+                    edits.push(edit);
+                }
+                _ => res.push((start, end, vec![edit])),
+            }
+        }
+        res.into_iter()
+            .map(|(start, end, edits)| {
+                let base = lines.offset(start);
+                let old = lines.source_range(start, end);
+                let edits = edits
+                    .into_iter()
+                    .map(|(range, text)| (*range, text.clone()))
+                    .collect::<Vec<_>>();
+                Block {
+                    start,
+                    end,
+                    new: apply(old, &edits, base),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Apply sorted, non-overlapping `edits` to `source`, which starts at `base` in the file the
+/// edit ranges refer to.
+fn apply(source: &str, edits: &[(TextRange, String)], base: TextSize) -> String {
+    let mut res = String::with_capacity(source.len());
+    let mut pos = 0;
+    for (range, text) in edits {
+        let start = (range.start() - base).to_usize();
+        let end = (range.end() - base).to_usize();
+        res.push_str(&source[pos..start]);
+        res.push_str(text);
+        pos = end;
+    }
+    res.push_str(&source[pos..]);
+    res
+}
+
+/// A run of lines `start..end` (0-based), and the text that replaces them.
+struct Block {
+    start: usize,
+    end: usize,
+    new: String,
+}
+
+/// The lines of a file.
+struct Lines<'a> {
+    source: &'a str,
+    /// Offset of the start of each line. If the file ends with a newline, the last entry is
+    /// the length of the file.
+    starts: Vec<usize>,
+    /// Number of lines, not counting the empty line after a trailing newline.
+    count: usize,
+}
+
+impl<'a> Lines<'a> {
+    fn new(source: &'a str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(
+            source
+                .bytes()
+                .enumerate()
+                .filter(|(_, b)| *b == b'\n')
+                .map(|(i, _)| i + 1),
+        );
+        let count = if starts.last() == Some(&source.len()) {
+            starts.len() - 1
+        } else {
+            starts.len()
+        };
+        Self {
+            source,
+            starts,
+            count,
+        }
+    }
+
+    fn line_of(&self, offset: TextSize) -> usize {
+        self.starts.partition_point(|x| *x <= offset.to_usize()) - 1
+    }
+
+    fn is_line_start(&self, offset: TextSize) -> bool {
+        self.starts.binary_search(&offset.to_usize()).is_ok()
+    }
+
+    fn offset(&self, line: usize) -> TextSize {
+        TextSize::try_from(self.starts.get(line).copied().unwrap_or(self.source.len())).unwrap()
+    }
+
+    fn source_range(&self, start: usize, end: usize) -> &'a str {
+        &self.source[self.offset(start).to_usize()..self.offset(end).to_usize()]
+    }
+
+    fn line(&self, line: usize) -> &'a str {
+        self.source_range(line, line + 1)
+    }
+}
+
+fn push_line(res: &mut String, prefix: char, line: &str) {
+    res.push(prefix);
+    res.push_str(line);
+    if !line.ends_with('\n') {
+        res.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// A range of lines in a hunk header, given the 0-based start line.
+fn hunk_range(start: usize, len: usize) -> String {
+    // An empty range names the line before it.
+    let start = if len == 0 { start } else { start + 1 };
+    if len == 1 {
+        start.to_string()
+    } else {
+        format!("{start},{len}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(TextSize::new(start), TextSize::new(end))
+    }
+
+    fn edit(source: &str, old: &str, new: &str) -> (TextRange, String) {
+        let start = source.find(old).unwrap() as u32;
+        (range(start, start + old.len() as u32), new.to_owned())
+    }
+
+    #[test]
+    fn test_add_conflicts() {
+        let mut edits = TextEdits::new();
+        assert!(edits.add([(range(0, 2), "a".to_owned())]));
+        // Identical edits are only applied once.
+        assert!(edits.add([(range(0, 2), "a".to_owned())]));
+        assert_eq!(edits.len(), 1);
+        // Overlapping.
+        assert!(!edits.add([(range(1, 3), "b".to_owned())]));
+        // An insertion at the start of a replacement is ambiguous.
+        assert!(!edits.add([(range(0, 0), "c".to_owned())]));
+        // Adjacent edits are fine, and the whole group is rejected if one conflicts.
+        assert!(!edits.add([(range(2, 3), "d".to_owned()), (range(1, 1), "e".to_owned())]));
+        assert_eq!(edits.len(), 1);
+        assert!(edits.add([(range(2, 3), "d".to_owned()), (range(5, 5), "e".to_owned())]));
+        assert_eq!(edits.apply("0123456"), "ad34e56");
+    }
+
+    #[test]
+    fn test_accepts() {
+        let mut edits = TextEdits::new();
+        edits.add([(range(0, 2), "a".to_owned())]);
+        assert!(edits.accepts(&[(range(0, 2), "a".to_owned())]));
+        assert!(edits.accepts(&[(range(2, 3), "b".to_owned())]));
+        assert!(!edits.accepts(&[(range(1, 3), "b".to_owned())]));
+        assert!(!edits.accepts(&[(range(3, 5), "b".to_owned()), (range(4, 6), "c".to_owned())]));
+        // Checking does not modify the edits.
+        assert_eq!(edits.len(), 1);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let source = "import os\nimport sys\n\n\n\n\n\n\nx = cast(int, 1)\ny = 2\n";
+        let mut edits = TextEdits::new();
+        edits.add([edit(source, "import os\n", "")]);
+        edits.add([edit(source, "cast(int, 1)", "1")]);
+        assert_eq!(
+            edits.unified_diff("main.py", source),
+            [
+                "--- main.py",
+                "+++ main.py",
+                "@@ -1,4 +1,3 @@",
+                "-import os",
+                " import sys",
+                " ",
+                " ",
+                "@@ -6,5 +5,5 @@",
+                " ",
+                " ",
+                " ",
+                "-x = cast(int, 1)",
+                "+x = 1",
+                " y = 2",
+                "",
+            ]
+            .join("\n")
+        );
+    }
+
+    #[test]
+    fn test_unified_diff_insertion_and_no_trailing_newline() {
+        let source = "class A:\n    def f(self): ...";
+        let mut edits = TextEdits::new();
+        edits.add([(range(0, 0), "from typing import override\n".to_owned())]);
+        edits.add([(
+            TextRange::empty(TextSize::new(13)),
+            "@override\n    ".to_owned(),
+        )]);
+        assert_eq!(
+            edits.apply(source),
+            "from typing import override\nclass A:\n    @override\n    def f(self): ..."
+        );
+        assert_eq!(
+            edits.unified_diff("main.py", source),
+            r#"--- main.py
++++ main.py
+@@ -1,2 +1,4 @@
++from typing import override
+ class A:
+-    def f(self): ...
+\ No newline at end of file
++    @override
++    def f(self): ...
+\ No newline at end of file
+"#
+        );
+    }
+}
@@ -145,3 +145,9 @@ impl ThreadPool {
         }
     }
 }
+
+/// The index of the thread pool worker running the current code, or `None` if it is
+/// running on a thread outside any pool (including when the pool runs work inline).
+pub fn current_worker_index() -> Option<usize> {
+    rayon::current_thread_index()
+}
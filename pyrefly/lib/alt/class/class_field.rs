@@ -4993,19 +4993,7 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         &self,
         member: &WithDefiningClass<Arc<ClassField>>,
     ) -> Option<NoAccessReason> {
-        let lacks_runtime_impl = member
-            .value
-            .ty()
-            .visit_toplevel_func_metadata::<bool>(&|meta| {
-                matches!(meta.flags.body_kind, BodyKind::Ellipsis | BodyKind::Trivial)
-                    && meta.flags.module_style == ModuleStyle::Executable
-            });
-        if (member.value.is_abstract()
-            || self
-                .get_metadata_for_class(&member.defining_class)
-                .is_protocol())
-            && lacks_runtime_impl
-        {
+        if self.lacks_runtime_implementation(&member.value, &member.defining_class) {
             Some(NoAccessReason::SuperMethodNeedsImplementation(
                 member.defining_class.dupe(),
             ))
@@ -5014,6 +5002,17 @@ impl<'a, Ans: LookupAnswer> AnswersSolver<'a, Ans> {
         }
     }
 
+    /// Whether `field`, defined on `defining_class`, is an abstract or protocol method whose
+    /// body is only a placeholder, so there is nothing to call through `super()`.
+    pub fn lacks_runtime_implementation(&self, field: &ClassField, defining_class: &Class) -> bool {
+        let lacks_runtime_impl = field.ty().visit_toplevel_func_metadata::<bool>(&|meta| {
+            matches!(meta.flags.body_kind, BodyKind::Ellipsis | BodyKind::Trivial)
+                && meta.flags.module_style == ModuleStyle::Executable
+        });
+        (field.is_abstract() || self.get_metadata_for_class(defining_class).is_protocol())
+            && lacks_runtime_impl
+    }
+
     /// Gets an attribute from a class definition.
     ///
     /// Returns `None` if there is no such attribute, otherwise an `Attribute` object
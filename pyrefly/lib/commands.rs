@@ -12,15 +12,25 @@ pub mod check;
 pub mod config_finder;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod coverage;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod daemon;
 pub mod dump_config;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod explain;
 pub mod files;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod fix;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod infer;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod init;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod lsp;
 #[cfg(not(target_arch = "wasm32"))]
+pub mod query;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod refactor;
+#[cfg(not(target_arch = "wasm32"))]
 pub mod stubgen;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod suppress;
@@ -19,10 +19,15 @@ use crate::commands::check::SnippetCheckArgs;
 use crate::commands::config_finder::ConfigConfigurerWrapper;
 use crate::commands::coverage::CoverageCommand;
 use crate::commands::coverage::report::ReportArgs;
+use crate::commands::daemon::DaemonCommand;
 use crate::commands::dump_config::DumpConfigArgs;
+use crate::commands::explain::ExplainArgs;
+use crate::commands::fix::FixArgs;
 use crate::commands::infer::InferArgs;
 use crate::commands::init::InitArgs;
 use crate::commands::lsp::LspArgs;
+use crate::commands::query::QueryCommand;
+use crate::commands::refactor::RefactorCommand;
 use crate::commands::stubgen::StubgenArgs;
 use crate::commands::suppress::SuppressArgs;
 use crate::commands::tsp::TspArgs;
@@ -70,8 +75,30 @@ pub enum Command {
     Report(ReportArgs),
     /// Suppress type errors by adding ignore comments, or remove unused ignores.
     Suppress(SuppressArgs),
+    /// Apply every quick fix that needs no human judgement, such as removing unused imports.
+    Fix(FixArgs),
     /// Generate .pyi stub files from Python source files.
     Stubgen(StubgenArgs),
+    /// Keep type-checking state warm in a background process, for `pyrefly check --use-daemon`.
+    Daemon {
+        /// Daemon subcommand to run.
+        #[command(subcommand)]
+        command: DaemonCommand,
+    },
+    /// Explain an error kind, with an example of code that triggers it and how to fix it.
+    Explain(ExplainArgs),
+    /// Query types, definitions and references from the command line, printing JSON.
+    Query {
+        /// Query subcommand to run.
+        #[command(subcommand)]
+        command: QueryCommand,
+    },
+    /// Rename or move a symbol across the whole project.
+    Refactor {
+        /// Refactoring to run.
+        #[command(subcommand)]
+        command: RefactorCommand,
+    },
 }
 
 impl Command {
@@ -125,15 +152,30 @@ impl Command {
                 eprintln!(
                     "warning: `pyrefly report` is deprecated; use `pyrefly coverage report` instead"
                 );
-                Ok((args.run(config_configurer_wrapper, thread_count)?, None))
+                Ok((
+                    args.run(version, config_configurer_wrapper, thread_count)?,
+                    None,
+                ))
             }
             Command::Suppress(args) => Ok((
                 args.run(version, config_configurer_wrapper, thread_count)?,
                 None,
             )),
+            Command::Fix(args) => Ok((args.run(config_configurer_wrapper, thread_count)?, None)),
             Command::Stubgen(args) => {
                 Ok((args.run(config_configurer_wrapper, thread_count)?, None))
             }
+            Command::Daemon { command } => Ok((
+                command.run(version, config_configurer_wrapper, thread_count)?,
+                None,
+            )),
+            Command::Explain(args) => Ok((args.run()?, None)),
+            Command::Query { command } => {
+                Ok((command.run(config_configurer_wrapper, thread_count)?, None))
+            }
+            Command::Refactor { command } => {
+                Ok((command.run(config_configurer_wrapper, thread_count)?, None))
+            }
         }
     }
 }
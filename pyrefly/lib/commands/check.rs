@@ -5,8 +5,14 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+mod cache;
+mod diff;
+mod matrix;
+mod reviewdog;
 mod sarif;
+mod watch_events;
 
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Display;
@@ -32,6 +38,7 @@ use anyhow::ensure;
 use clap::Parser;
 use clap::ValueEnum;
 use dupe::Dupe as _;
+use itertools::Itertools as _;
 use percent_encoding::AsciiSet;
 use percent_encoding::CONTROLS;
 use percent_encoding::utf8_percent_encode;
@@ -47,6 +54,8 @@ use pyrefly_config::migration::run::MigratedFromKind;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_name::ModuleNameWithKind;
 use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::sys_info::PythonPlatform;
+use pyrefly_python::sys_info::PythonVersion;
 use pyrefly_util::absolutize::Absolutize;
 use pyrefly_util::arc_id::ArcId;
 use pyrefly_util::args::clap_env;
@@ -60,6 +69,7 @@ use pyrefly_util::forgetter::Forgetter;
 use pyrefly_util::fs_anyhow;
 use pyrefly_util::includes::Includes;
 use pyrefly_util::memory::MemoryUsageTrace;
+use pyrefly_util::profile::ProfileCollector;
 use pyrefly_util::thread_pool::ThreadCount;
 use pyrefly_util::unix_path::path_to_unix_string;
 use pyrefly_util::watcher::Watcher;
@@ -69,21 +79,37 @@ use starlark_map::small_set::SmallSet;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
+use tracing::warn;
 
+use self::cache::DiagnosticsCache;
+use self::diff::ChangedLines;
+use self::reviewdog::write_error_reviewdog_to_console;
+use self::reviewdog::write_error_reviewdog_to_file;
 use self::sarif::write_error_sarif_to_console;
 use self::sarif::write_error_sarif_to_file;
+use self::watch_events::RecheckedSubscriber;
+use self::watch_events::WatchEvents;
+use self::watch_events::WatchFormat;
 use crate::commands::config_finder::ConfigConfigurerWrapper;
+use crate::commands::daemon::Request;
+use crate::commands::daemon::Response;
+use crate::commands::daemon::SocketArgs;
+use crate::commands::daemon::send_request;
 use crate::commands::files::FilesArgs;
 use crate::commands::files::UpsellDecision;
 use crate::commands::files::get_config_finder_for_snippet;
+use crate::commands::files::get_project_config_for_current_dir;
 use crate::commands::util::CommandExitStatus;
 use crate::config::error_kind::Severity;
 use crate::config::finder::ConfigFinder;
+use crate::error::baseline::normalize_baseline_path;
 use crate::error::code_climate::CodeClimateIssues;
+use crate::error::collector::CollectedErrors;
 use crate::error::error::BaselineStatus;
 use crate::error::error::Error;
 use crate::error::error::ErrorRenderer;
 use crate::error::error::print_error_counts;
+use crate::error::legacy::BaselineError;
 use crate::error::legacy::BaselineErrors;
 use crate::error::legacy::LegacyError;
 use crate::error::legacy::LegacyErrors;
@@ -94,14 +120,18 @@ use crate::error::suppress::CommentLocation;
 use crate::error::suppress::SerializedError;
 use crate::error::suppress::UnusedIgnoreKind;
 use crate::report;
+use crate::state::errors::apply_baseline_file;
 use crate::state::load::FileContents;
+use crate::state::lsp::ImportFormat;
 use crate::state::require::Require;
 use crate::state::require::RequireLevels;
 use crate::state::state::CommittingTransaction;
 use crate::state::state::State;
 use crate::state::state::Transaction;
 use crate::state::steps::Step;
+use crate::state::subscriber::CompositeSubscriber;
 use crate::state::subscriber::ProgressBarStyle;
+use crate::state::subscriber::Subscriber;
 use crate::state::subscriber::TestSubscriber;
 
 /// Result data from a non-watch check run, used for telemetry logging.
@@ -135,9 +165,28 @@ pub struct FullCheckArgs {
 
     /// Watch for file changes and re-check them.
     /// (Warning: This mode is highly experimental!)
-    #[arg(long, conflicts_with = "check_all")]
+    #[arg(
+        long,
+        conflicts_with_all = ["check_all", "matrix_python_version", "matrix_python_platform"]
+    )]
     watch: bool,
 
+    /// How to report each recheck with `--watch`.
+    /// `json-lines` prints one JSON object per recheck, listing the files that were rechecked and
+    /// the errors that were added, moved or resolved since the previous recheck.
+    #[arg(long, value_enum, requires = "watch")]
+    watch_format: Option<WatchFormat>,
+
+    /// Get results from a running `pyrefly daemon` instead of checking in this process.
+    /// The daemon checks the files and configuration it was started with.
+    #[arg(long, conflicts_with_all = ["watch", "files"])]
+    use_daemon: bool,
+
+    /// The socket of the daemon to use with `--use-daemon`.
+    /// Defaults to the socket `pyrefly daemon start` uses in the current directory.
+    #[arg(long, value_name = "PATH", env = clap_env("DAEMON_SOCKET"))]
+    daemon_socket: Option<PathBuf>,
+
     /// Type checking arguments and configuration
     #[command(flatten)]
     args: CheckArgs,
@@ -155,12 +204,23 @@ impl FullCheckArgs {
         thread_count: ThreadCount,
     ) -> anyhow::Result<(CommandExitStatus, Option<CheckResult>)> {
         self.config_override.validate()?;
+        if self.use_daemon {
+            // The daemon owns the configuration used for checking; ours only supplies
+            // output settings such as the baseline and output format.
+            let (config, _) = get_project_config_for_current_dir(self.config_override, wrapper)?;
+            let status = self.args.run_with_daemon(
+                version,
+                &SocketArgs::new(self.daemon_socket),
+                &config,
+            )?;
+            return Ok((status, None));
+        }
         let (files_to_check, config_finder, upsell) =
             self.files.resolve(self.config_override, wrapper)?;
         run_check(
             self.args,
             version,
-            self.watch,
+            self.watch.then(|| self.watch_format.unwrap_or_default()),
             files_to_check,
             config_finder,
             upsell,
@@ -170,6 +230,54 @@ impl FullCheckArgs {
     }
 }
 
+/// The handle whose quick fixes an error in one of `handles` gets, if any.
+///
+/// With `--matrix-python-version` or `--matrix-python-platform` a file has one handle per
+/// configuration. An error found in only some configurations uses the handle of the first of
+/// them, and an error found in all of them uses the first handle of its file. Errors in files
+/// that have no handle, such as configuration errors, get no quick fixes.
+fn quick_fix_handle<'a>(
+    handles: &HashMap<&ModulePath, Vec<&'a Handle>>,
+    error: &Error,
+) -> Option<&'a Handle> {
+    let candidates = handles.get(error.path())?;
+    match error.configurations().first() {
+        Some(sys_info) => candidates
+            .iter()
+            .find(|handle| handle.sys_info() == sys_info)
+            .copied(),
+        None => candidates.first().copied(),
+    }
+}
+
+/// Attach the quick fixes the IDE would offer to each error in one of `handles`, chosen as in
+/// [`quick_fix_handle`]. `errors` must be sorted by path.
+fn attach_quick_fixes(
+    transaction: &Transaction,
+    handles: &[Handle],
+    errors: Vec<Error>,
+) -> Vec<Error> {
+    let mut by_path: HashMap<&ModulePath, Vec<&Handle>> = HashMap::new();
+    for handle in handles {
+        by_path.entry(handle.path()).or_default().push(handle);
+    }
+    let mut res = Vec::with_capacity(errors.len());
+    for (handle, group) in &errors
+        .into_iter()
+        .chunk_by(|error| quick_fix_handle(&by_path, error))
+    {
+        match handle {
+            Some(handle) => res.extend(transaction.with_report_quick_fixes(
+                handle,
+                group.collect(),
+                ImportFormat::default(),
+            )),
+            None => res.extend(group),
+        }
+    }
+    res
+}
+
 /// Resolve the `--relative-to` argument to a concrete path for error reporting.
 fn resolve_relative_to(relative_to: Option<&String>) -> PathBuf {
     relative_to.map_or_else(
@@ -181,13 +289,13 @@ fn resolve_relative_to(relative_to: Option<&String>) -> PathBuf {
 async fn run_check(
     args: CheckArgs,
     version: &str,
-    watch: bool,
+    watch: Option<WatchFormat>,
     files_to_check: Box<dyn Includes>,
     config_finder: ConfigFinder,
     upsell: UpsellDecision,
     thread_count: ThreadCount,
 ) -> anyhow::Result<(CommandExitStatus, Option<CheckResult>)> {
-    if watch {
+    if let Some(watch_format) = watch {
         let roots = files_to_check.roots();
         info!(
             "Watching for files in {}",
@@ -196,6 +304,7 @@ async fn run_check(
         let watcher = Watcher::notify(&roots)?;
         run_watch(
             args,
+            watch_format,
             watcher,
             version,
             files_to_check,
@@ -261,6 +370,9 @@ impl SnippetCheckArgs {
                 suppress_errors: false,
                 expectations: false,
                 remove_unused_ignores: None,
+                diagnostics_cache_dir: None,
+                matrix_python_version: Vec::new(),
+                matrix_python_platform: Vec::new(),
             },
         };
 
@@ -309,6 +421,11 @@ struct OutputArgs {
     /// Process each module individually to figure out how long each step takes.
     #[arg(long, value_name = "OUTPUT_FILE")]
     report_timings: Option<PathBuf>,
+    /// Write a timeline of the check in the Chrome trace-event format, with a span for each
+    /// step of each module on the thread that computed it, and for each wait on another
+    /// thread. Open it with Perfetto or `chrome://tracing`.
+    #[arg(long, value_name = "OUTPUT_FILE")]
+    report_profile: Option<PathBuf>,
     /// Generate a Glean-compatible JSON file for each module
     #[arg(long, value_name = "OUTPUT_FILE")]
     report_glean: Option<PathBuf>,
@@ -407,6 +524,13 @@ struct OutputArgs {
     #[arg(long, group = "baseline_action")]
     update_baseline: bool,
 
+    /// With `--update-baseline`, record how many errors of each kind each file has, rather
+    /// than the individual errors. Later checks allow up to that many errors of the kind in
+    /// the file, wherever they are. Kinds recorded this way in the existing baseline stay
+    /// recorded as counts even without this flag.
+    #[arg(long, requires = "update_baseline")]
+    baseline_counts: bool,
+
     /// Rewrite the baseline file to drop stale entries without recording new errors.
     /// Existing entries for files outside the current check are retained.
     #[arg(long, group = "baseline_action")]
@@ -420,6 +544,33 @@ struct OutputArgs {
     /// Errors below this severity will not be shown. Defaults to "error".
     #[arg(long, value_enum)]
     min_severity: Option<Severity>,
+
+    /// Only report errors on lines changed relative to this git revision (e.g. `origin/main`).
+    /// Untracked files count as entirely changed.
+    #[arg(
+        long,
+        value_name = "GIT_REV",
+        conflicts_with_all = ["diff_file", "update_baseline", "prune_baseline"]
+    )]
+    diff_base: Option<String>,
+
+    /// Only report errors on lines added or modified by this unified diff.
+    /// Paths in the diff are resolved against the current directory. Pass `-` to read the diff from STDIN.
+    #[arg(
+        long,
+        value_name = "DIFF_FILE",
+        conflicts_with_all = ["update_baseline", "prune_baseline"]
+    )]
+    diff_file: Option<PathBuf>,
+
+    /// With `--diff-base` or `--diff-file`, also report errors whose related spans touch a changed line.
+    #[arg(long)]
+    diff_include_related: bool,
+
+    /// Set once the `pyrefly explain` hint has been printed, so `--watch` only prints it
+    /// for the first check that reports errors.
+    #[arg(skip)]
+    explain_hint_printed: bool,
 }
 
 /// A diagnostic output format and destination requested on the CLI.
@@ -520,6 +671,107 @@ impl OutputArgs {
         }
     }
 
+    /// Compute the changed lines requested by `--diff-base` or `--diff-file`, if any.
+    fn changed_lines(&self) -> anyhow::Result<Option<ChangedLines>> {
+        if let Some(base) = &self.diff_base {
+            Ok(Some(ChangedLines::from_git(base)?))
+        } else if let Some(path) = &self.diff_file {
+            Ok(Some(ChangedLines::from_diff_file(path)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Whether an error passes the `--only` and `--diff-base`/`--diff-file` filters.
+    fn shown_filter(&self) -> anyhow::Result<impl Fn(&Error) -> bool + '_> {
+        let only_filter = self
+            .only
+            .as_ref()
+            .map(|only| only.iter().collect::<SmallSet<&ErrorKind>>());
+        let changed_lines = self.changed_lines()?;
+        Ok(move |e: &Error| -> bool {
+            only_filter
+                .as_ref()
+                .is_none_or(|only| only.contains(&e.error_kind()))
+                && changed_lines
+                    .as_ref()
+                    .is_none_or(|changed| changed.touches(e, self.diff_include_related))
+        })
+    }
+
+    /// Write `errors` to each requested output, or to stdout if none were requested.
+    fn write_errors(
+        &self,
+        output_format: OutputFormat,
+        version: &str,
+        relative_to: &Path,
+        errors: &[Error],
+    ) -> anyhow::Result<()> {
+        if self.output.is_empty() {
+            return write_errors_to_console(output_format, version, relative_to, errors);
+        }
+        for output in &self.output {
+            let format = output.format.unwrap_or(output_format);
+            match &output.destination {
+                ErrorOutputDestination::Stdout => {
+                    write_errors_to_console(format, version, relative_to, errors)?
+                }
+                ErrorOutputDestination::File(path) => {
+                    write_errors_to_file(format, path, version, relative_to, errors)?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether any output is in a format that includes quick fixes.
+    fn reports_quick_fixes(&self, output_format: OutputFormat) -> bool {
+        let includes_fixes = |format: OutputFormat| {
+            matches!(
+                format,
+                OutputFormat::Json
+                    | OutputFormat::Sarif
+                    | OutputFormat::Rdjson
+                    | OutputFormat::Rdjsonl
+            )
+        };
+        if self.output.is_empty() {
+            includes_fixes(output_format)
+        } else {
+            self.output
+                .iter()
+                .any(|output| includes_fixes(output.format.unwrap_or(output_format)))
+        }
+    }
+
+    /// Point users at `pyrefly explain` for the first error kind reported, if errors were
+    /// printed to the console as text and the hint has not been printed already.
+    fn print_explain_hint(&self, output_format: OutputFormat, errors: &[Error]) {
+        if self.explain_hint_printed {
+            return;
+        }
+        let text_on_console = |format: OutputFormat| {
+            matches!(
+                format,
+                OutputFormat::MinText | OutputFormat::FullText | OutputFormat::FullTextWithGithub
+            )
+        };
+        let shown = if self.output.is_empty() {
+            text_on_console(output_format)
+        } else {
+            self.output.iter().any(|output| {
+                output.destination == ErrorOutputDestination::Stdout
+                    && text_on_console(output.format.unwrap_or(output_format))
+            })
+        };
+        if shown && let Some(error) = errors.iter().find(|e| !e.error_kind().is_directive()) {
+            info!(
+                "Run `pyrefly explain {}` to learn more about an error kind",
+                error.error_kind().to_name()
+            );
+        }
+    }
+
     /// Resolve the effective progress bar style, taking deprecated flags into account.
     fn progress_bar_style(&self) -> ProgressBarStyle {
         if let Some(style) = &self.progress_bar {
@@ -574,6 +826,32 @@ struct BehaviorArgs {
         default_missing_value = "pyrefly"
     )]
     remove_unused_ignores: Option<UnusedIgnoreKind>,
+    /// Experimental: record each module's diagnostics in this directory, and replay them on the
+    /// next run for modules whose source and dependencies have not changed. This is a cache of
+    /// diagnostics, not of solved modules: the modules that are rechecked still solve their
+    /// dependencies from scratch. Ignored, with a warning, when the requested outputs need every
+    /// module to be solved, e.g. `--check-all`, reports, or output formats with quick fixes.
+    #[arg(long, value_name = "DIR", env = clap_env("DIAGNOSTICS_CACHE_DIR"))]
+    diagnostics_cache_dir: Option<PathBuf>,
+    /// Check against each of these Python versions, e.g. `3.9,3.13`, in one run.
+    /// Errors that only appear in some configurations are annotated with the ones they appear in.
+    /// Combines with `--matrix-python-platform`; when omitted, the configured version is used.
+    #[arg(
+        long,
+        value_name = "VERSION",
+        value_delimiter = ',',
+        conflicts_with_all = ["check_all", "diagnostics_cache_dir"]
+    )]
+    matrix_python_version: Vec<PythonVersion>,
+    /// Check against each of these platforms, e.g. `linux,win32`, in one run.
+    /// Combines with `--matrix-python-version`; when omitted, the configured platform is used.
+    #[arg(
+        long,
+        value_name = "PLATFORM",
+        value_delimiter = ',',
+        conflicts_with_all = ["check_all", "diagnostics_cache_dir"]
+    )]
+    matrix_python_platform: Vec<PythonPlatform>,
 }
 
 fn write_errors_to_file(
@@ -594,6 +872,9 @@ fn write_errors_to_file(
         OutputFormat::JunitXml => write_error_junit_xml_to_file(path, relative_to, errors),
         OutputFormat::CodeClimate => write_error_codeclimate_to_file(path, relative_to, errors),
         OutputFormat::Sarif => write_error_sarif_to_file(path, version, relative_to, errors),
+        OutputFormat::Rdjson => write_error_reviewdog_to_file(path, relative_to, errors, false),
+        OutputFormat::Rdjsonl => write_error_reviewdog_to_file(path, relative_to, errors, true),
+        OutputFormat::Checkstyle => write_error_checkstyle_to_file(path, relative_to, errors),
         OutputFormat::OmitErrors => Ok(()),
     }
 }
@@ -615,6 +896,9 @@ pub(crate) fn write_errors_to_console(
         OutputFormat::JunitXml => write_error_junit_xml_to_console(relative_to, errors),
         OutputFormat::CodeClimate => write_error_codeclimate_to_console(relative_to, errors),
         OutputFormat::Sarif => write_error_sarif_to_console(version, relative_to, errors),
+        OutputFormat::Rdjson => write_error_reviewdog_to_console(relative_to, errors, false),
+        OutputFormat::Rdjsonl => write_error_reviewdog_to_console(relative_to, errors, true),
+        OutputFormat::Checkstyle => write_error_checkstyle_to_console(relative_to, errors),
         OutputFormat::OmitErrors => Ok(()),
     }
 }
@@ -709,8 +993,16 @@ fn write_baseline_errors_to_file(path: &Path, errors: &BaselineErrors) -> anyhow
     f(path, errors).with_context(|| format!("while writing baseline to `{}`", path.display()))
 }
 
-fn write_baseline_to_file(path: &Path, relative_to: &Path, errors: &[Error]) -> anyhow::Result<()> {
-    write_baseline_errors_to_file(path, &BaselineErrors::from_errors(relative_to, errors))
+fn write_baseline_to_file(
+    path: &Path,
+    relative_to: &Path,
+    errors: &[Error],
+    counted: impl Fn(&BaselineError) -> bool,
+) -> anyhow::Result<()> {
+    write_baseline_errors_to_file(
+        path,
+        &BaselineErrors::from_errors(relative_to, errors, counted),
+    )
 }
 
 fn write_error_json_to_console(relative_to: &Path, errors: &[Error]) -> anyhow::Result<()> {
@@ -790,7 +1082,7 @@ fn is_xml_char(c: char) -> bool {
     )
 }
 
-fn xml_escape_attr(s: &str) -> String {
+pub(crate) fn xml_escape_attr(s: &str) -> String {
     let mut out = String::with_capacity(s.len());
     for c in s.chars() {
         match c {
@@ -898,6 +1190,76 @@ fn write_error_junit_xml_to_console(relative_to: &Path, errors: &[Error]) -> any
     buffered_write_error_junit_xml(stdout(), relative_to, errors)
 }
 
+/// Render diagnostics as a Checkstyle `<checkstyle>` report, with one `<file>` element
+/// per file in order of first appearance. Ignored diagnostics are not findings, so
+/// they are left out.
+fn write_error_checkstyle<W: Write>(
+    mut writer: W,
+    relative_to: &Path,
+    errors: &[Error],
+) -> anyhow::Result<()> {
+    let mut files: SmallMap<&Path, Vec<&Error>> = SmallMap::new();
+    for err in errors {
+        if err.severity().is_enabled() {
+            files.entry(err.path().as_path()).or_default().push(err);
+        }
+    }
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<checkstyle version="4.3">"#)?;
+    for (error_path, errors) in files {
+        let path = error_path
+            .strip_prefix(relative_to)
+            .unwrap_or(error_path)
+            .to_string_lossy()
+            .into_owned();
+        writeln!(writer, r#"  <file name="{}">"#, xml_escape_attr(&path))?;
+        for err in errors {
+            let range = err.display_range();
+            let severity = match err.severity() {
+                Severity::Error => "error",
+                Severity::Warn => "warning",
+                Severity::Info | Severity::Ignore => "info",
+            };
+            writeln!(
+                writer,
+                r#"    <error line="{}" column="{}" severity="{severity}" message="{}" source="pyrefly.{}"/>"#,
+                range.start.line_within_file().get(),
+                range.start.column().get(),
+                xml_escape_attr(&err.msg()),
+                err.error_kind().to_name(),
+            )?;
+        }
+        writeln!(writer, "  </file>")?;
+    }
+    writeln!(writer, "</checkstyle>")?;
+    Ok(())
+}
+
+fn buffered_write_error_checkstyle(
+    writer: impl Write,
+    relative_to: &Path,
+    errors: &[Error],
+) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    write_error_checkstyle(&mut writer, relative_to, errors)?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn write_error_checkstyle_to_file(
+    path: &Path,
+    relative_to: &Path,
+    errors: &[Error],
+) -> anyhow::Result<()> {
+    let file = File::create(path)?;
+    buffered_write_error_checkstyle(file, relative_to, errors)
+}
+
+fn write_error_checkstyle_to_console(relative_to: &Path, errors: &[Error]) -> anyhow::Result<()> {
+    buffered_write_error_checkstyle(stdout(), relative_to, errors)
+}
+
 fn severity_to_github_command(severity: Severity) -> Option<&'static str> {
     let normalized = severity_to_str(severity);
     match normalized.as_str() {
@@ -1052,7 +1414,7 @@ impl Handles {
     }
 }
 
-async fn get_watcher_events(watcher: &mut Watcher) -> anyhow::Result<CategorizedEvents> {
+pub(crate) async fn get_watcher_events(watcher: &mut Watcher) -> anyhow::Result<CategorizedEvents> {
     loop {
         let events = CategorizedEvents::new_notify(
             watcher
@@ -1310,16 +1672,30 @@ impl IncrementalChecker {
 struct IncrementalCheckCommand {
     args: CheckArgs,
     checker: IncrementalChecker,
+    /// Set when each check is reported as a JSON event rather than as errors.
+    watch_events: Option<WatchEvents>,
 }
 
 impl IncrementalCheckCommand {
     fn new(
-        args: CheckArgs,
+        mut args: CheckArgs,
+        watch_format: WatchFormat,
         files_to_check: Box<dyn Includes>,
         config_finder: ConfigFinder,
         thread_count: ThreadCount,
     ) -> anyhow::Result<Self> {
         args.output.validate_outputs()?;
+        let watch_events = match watch_format {
+            WatchFormat::Text => None,
+            WatchFormat::JsonLines => {
+                // The events are written to stdout, so keep the errors off it unless they
+                // were explicitly sent somewhere.
+                if args.output.output.is_empty() {
+                    args.output.output_format = Some(OutputFormat::OmitErrors);
+                }
+                Some(WatchEvents::new())
+            }
+        };
         let require_levels = args.get_required_levels();
         Ok(Self {
             args,
@@ -1329,6 +1705,7 @@ impl IncrementalCheckCommand {
                 config_finder,
                 thread_count,
             )?,
+            watch_events,
         })
     }
 
@@ -1339,6 +1716,7 @@ impl IncrementalCheckCommand {
         upsell: UpsellDecision,
     ) -> anyhow::Result<(CommandExitStatus, Vec<Error>)> {
         let timings = Timings::new();
+        let start = timings.start;
         let args = &self.args;
         let mut check = self.checker.prepare_check(events);
         let transaction = check.transaction.as_mut();
@@ -1350,9 +1728,18 @@ impl IncrementalCheckCommand {
             )
         });
         let defaults = args.output.resolve(config.as_deref());
-        let run = args.prepare_cli_run(timings, transaction, handles, &defaults)?;
-        check.run(|transaction, handles, sourcedb_errors| {
-            args.finish_cli_run(
+        let rechecked = self.watch_events.is_some().then(RecheckedSubscriber::new);
+        let run = args.prepare_cli_run(
+            timings,
+            transaction,
+            handles,
+            &defaults,
+            rechecked
+                .as_ref()
+                .map(|x| Box::new(x.dupe()) as Box<dyn Subscriber>),
+        )?;
+        let (result, rechecked) = check.run(|transaction, handles, sourcedb_errors| {
+            let result = args.finish_cli_run(
                 run,
                 transaction,
                 version,
@@ -1360,13 +1747,30 @@ impl IncrementalCheckCommand {
                 &defaults,
                 sourcedb_errors,
                 upsell,
-            )
-        })
+            );
+            let rechecked = rechecked.map(|x| x.rechecked(handles));
+            (result, rechecked)
+        });
+        let (status, errors) = result?;
+        if errors.iter().any(|e| !e.error_kind().is_directive()) {
+            self.args.output.explain_hint_printed = true;
+        }
+        if let Some(watch_events) = &mut self.watch_events {
+            watch_events.report(
+                &resolve_relative_to(args.output.relative_to.as_ref()),
+                events,
+                &rechecked.unwrap_or_default(),
+                &errors,
+                start.elapsed(),
+            )?;
+        }
+        Ok((status, errors))
     }
 }
 
 async fn run_watch(
     args: CheckArgs,
+    watch_format: WatchFormat,
     mut watcher: Watcher,
     version: &str,
     files_to_check: Box<dyn Includes>,
@@ -1376,8 +1780,13 @@ async fn run_watch(
 ) -> anyhow::Result<()> {
     // TODO: We currently make 1 unrealistic assumptions, which should be fixed in the future:
     // - Config search is stable across incremental runs.
-    let mut command =
-        IncrementalCheckCommand::new(args, files_to_check, config_finder, thread_count)?;
+    let mut command = IncrementalCheckCommand::new(
+        args,
+        watch_format,
+        files_to_check,
+        config_finder,
+        thread_count,
+    )?;
     if let Err(e) = command.check(version, &CategorizedEvents::default(), upsell) {
         eprintln!("{e:#}");
     }
@@ -1389,12 +1798,24 @@ async fn run_watch(
     }
 }
 
+/// The errors of a check, split by how they are displayed.
+struct DisplayedErrors {
+    ordinary: Vec<Error>,
+    /// Errors matched by the baseline, at the severity they are displayed with.
+    baseline: Vec<Error>,
+    directives: Vec<Error>,
+    /// Errors below the minimum severity, which are only counted in the summary.
+    hidden: Vec<Error>,
+}
+
 /// CLI-owned state that must remain live across a type-checking run.
 struct PreparedCliRun {
     timings: Timings,
     memory_trace: MemoryUsageTrace,
     demand_tree_subscriber: Option<TestSubscriber>,
     type_check_start: Instant,
+    /// The on-disk diagnostics cache, with the diagnostics it replayed for modules that are not rechecked.
+    cache: Option<(DiagnosticsCache, CollectedErrors)>,
 }
 
 impl CheckArgs {
@@ -1447,6 +1868,32 @@ impl CheckArgs {
         let defaults = self.output.resolve(config.as_deref());
 
         let checked_file_count = loaded_handles.len();
+        let loaded_handles = if self.is_matrix() {
+            matrix::expand_handles(
+                loaded_handles,
+                &self.behavior.matrix_python_version,
+                &self.behavior.matrix_python_platform,
+            )
+        } else {
+            loaded_handles
+        };
+        let (loaded_handles, cache) = match self.diagnostics_cache_dir(&defaults) {
+            Some(dir) => {
+                let mut cache = DiagnosticsCache::open(
+                    dir,
+                    version,
+                    &loaded_handles,
+                    state.as_ref().config_finder(),
+                )?;
+                let (to_check, replayed) = cache.partition(loaded_handles);
+                debug!(
+                    "Replaying cached diagnostics for {}",
+                    count(cache.reused_count(), "module")
+                );
+                (to_check, Some((cache, replayed)))
+            }
+            None => (loaded_handles, None),
+        };
         let relative_to = resolve_relative_to(self.output.relative_to.as_ref());
         let (status, errors) = self.run_inner(
             timings,
@@ -1457,6 +1904,7 @@ impl CheckArgs {
             sourcedb_errors,
             require_levels.specified,
             upsell,
+            cache,
         )?;
         let check_result = CheckResult::from_errors(&errors, &relative_to, checked_file_count);
         Ok((status, errors, check_result))
@@ -1512,10 +1960,179 @@ impl CheckArgs {
             require_levels.specified,
             // Snippet checks are interactive ad-hoc inputs — never upsell.
             UpsellDecision::Skip,
+            None,
         )?;
         Ok((status, CheckResult::from_errors(&errors, &relative_to, 1)))
     }
 
+    /// Report the results of a running daemon as if they came from checking in this process.
+    fn run_with_daemon(
+        &self,
+        version: &str,
+        socket: &SocketArgs,
+        config: &ConfigFile,
+    ) -> anyhow::Result<CommandExitStatus> {
+        if let Some(flag) = self.daemon_incompatible_flag() {
+            bail!("`{flag}` cannot be used with `--use-daemon`");
+        }
+        self.output.validate_outputs()?;
+        let defaults = self.output.resolve(Some(config));
+        let (modules, config_errors) = match send_request(socket, &Request::Check)? {
+            Response::Check {
+                version: daemon_version,
+                modules,
+                config_errors,
+            } => {
+                ensure!(
+                    daemon_version == version,
+                    "The daemon is running pyrefly {daemon_version}, but this is {version}; \
+                    restart it with `pyrefly daemon stop` and `pyrefly daemon start`"
+                );
+                (modules, config_errors)
+            }
+            Response::Error { message } => bail!("The daemon failed to check: {message}"),
+            response => bail!("Unexpected response from the daemon: {response:?}"),
+        };
+
+        let mut config_errors_count = 0;
+        for error in config_errors {
+            let error = ConfigError::new(error.severity, anyhow::anyhow!(error.message));
+            error.print();
+            if error.severity() >= Severity::Error {
+                config_errors_count += 1;
+            }
+        }
+        let mut errors = Vec::new();
+        for module in &modules {
+            let Some(restored) = module.restore() else {
+                bail!(
+                    "`{}` changed while the daemon was checking it; run the check again",
+                    module.path.display()
+                );
+            };
+            errors.extend(restored);
+        }
+
+        let relative_to = resolve_relative_to(self.output.relative_to.as_ref());
+        let (directives, ordinary): (Vec<_>, Vec<_>) = errors
+            .into_iter()
+            .partition(|e| e.error_kind().is_directive());
+        let mut collected = CollectedErrors {
+            ordinary,
+            directives,
+            ..Default::default()
+        };
+        let (baseline_status, _, _) = apply_baseline_file(
+            &mut collected,
+            defaults.baseline.as_deref(),
+            &relative_to,
+            None,
+        )
+        .resolve(false)?;
+        let displayed = self.displayed_errors(
+            collected.ordinary,
+            &collected.baseline,
+            collected.directives,
+            baseline_status,
+            &defaults,
+        )?;
+        let (diagnostics_count, _) = self.report_errors(
+            displayed,
+            config_errors_count,
+            0,
+            &defaults,
+            version,
+            &relative_to,
+            " (from the daemon)",
+            |errors| errors,
+        )?;
+        if diagnostics_count > 0 {
+            Ok(CommandExitStatus::UserError)
+        } else {
+            Ok(CommandExitStatus::Success)
+        }
+    }
+
+    /// A flag that needs this process to do the check itself, and so cannot be
+    /// combined with `--use-daemon`.
+    fn daemon_incompatible_flag(&self) -> Option<&'static str> {
+        let behavior = &self.behavior;
+        let output = &self.output;
+        [
+            (behavior.check_all, "--check-all"),
+            (behavior.suppress_errors, "--suppress-errors"),
+            (behavior.expectations, "--expectations"),
+            (
+                behavior.remove_unused_ignores.is_some(),
+                "--remove-unused-ignores",
+            ),
+            (
+                !behavior.matrix_python_version.is_empty(),
+                "--matrix-python-version",
+            ),
+            (
+                !behavior.matrix_python_platform.is_empty(),
+                "--matrix-python-platform",
+            ),
+            (output.update_baseline, "--update-baseline"),
+            (output.prune_baseline, "--prune-baseline"),
+            (output.error_stale_baseline, "--error-stale-baseline"),
+            (output.debug_info.is_some(), "--debug-info"),
+            (
+                output.report_binding_memory.is_some(),
+                "--report-binding-memory",
+            ),
+            (output.report_trace.is_some(), "--report-trace"),
+            (output.dependency_graph.is_some(), "--dependency-graph"),
+            (output.report_timings.is_some(), "--report-timings"),
+            (output.report_profile.is_some(), "--report-profile"),
+            (output.report_glean.is_some(), "--report-glean"),
+            (output.report_pysa.is_some(), "--report-pysa"),
+            (output.report_demand_tree.is_some(), "--report-demand-tree"),
+            (output.report_cinderx.is_some(), "--report-cinderx"),
+        ]
+        .into_iter()
+        .find_map(|(set, flag)| set.then_some(flag))
+    }
+
+    /// Whether we check against a matrix of Python versions and platforms.
+    fn is_matrix(&self) -> bool {
+        !self.behavior.matrix_python_version.is_empty()
+            || !self.behavior.matrix_python_platform.is_empty()
+    }
+
+    /// The directory of the on-disk diagnostics cache, if one was requested and the requested
+    /// outputs can be produced without solving every checked module. Quick fixes need the
+    /// solved module, so they can't be attached to replayed diagnostics.
+    fn diagnostics_cache_dir(&self, defaults: &OutputDefaults) -> Option<&Path> {
+        let dir = self.behavior.diagnostics_cache_dir.as_deref()?;
+        let levels = self.get_required_levels();
+        let compatible = levels.specified == Require::Errors
+            && levels.default == Require::Exports
+            && !self.behavior.suppress_errors
+            && !self.behavior.expectations
+            && self.behavior.remove_unused_ignores.is_none()
+            && !self.output.prune_baseline
+            && !self.output.error_stale_baseline
+            && self.output.report_timings.is_none()
+            && self.output.dependency_graph.is_none()
+            && self.output.report_demand_tree.is_none();
+        if !compatible {
+            warn!(
+                "Not using `--diagnostics-cache-dir`, since the requested outputs need a full check"
+            );
+            return None;
+        }
+        if self.output.reports_quick_fixes(defaults.output_format) {
+            warn!(
+                "Not using `--diagnostics-cache-dir`, since the json, sarif, rdjson and rdjsonl \
+                 output formats include quick fixes, which need every module to be solved"
+            );
+            return None;
+        }
+        Some(dir)
+    }
+
     fn get_required_levels(&self) -> RequireLevels {
         let retain = self.output.report_binding_memory.is_some()
             || self.output.debug_info.is_some()
@@ -1550,8 +2167,10 @@ impl CheckArgs {
         sourcedb_errors: Vec<ConfigError>,
         require: Require,
         upsell: UpsellDecision,
+        cache: Option<(DiagnosticsCache, CollectedErrors)>,
     ) -> anyhow::Result<(CommandExitStatus, Vec<Error>)> {
-        let run = self.prepare_cli_run(timings, transaction, handles, defaults)?;
+        let mut run = self.prepare_cli_run(timings, transaction, handles, defaults, None)?;
+        run.cache = cache;
         transaction.run(handles, require, None);
         self.finish_cli_run(
             run,
@@ -1570,6 +2189,7 @@ impl CheckArgs {
         transaction: &mut Transaction,
         handles: &[Handle],
         defaults: &OutputDefaults,
+        extra_subscriber: Option<Box<dyn Subscriber>>,
     ) -> anyhow::Result<PreparedCliRun> {
         // Baseline maintenance actions are mutually exclusive.
         let baseline_action = if self.output.update_baseline {
@@ -1628,25 +2248,207 @@ impl CheckArgs {
             transaction.set_cinderx_reporter(Some(cinderx_reporter));
         }
 
+        if self.output.report_profile.is_some() {
+            transaction.set_profile_collector(Some(ProfileCollector::new()));
+        }
         let type_check_start = Instant::now();
-        let demand_tree_subscriber = if self.output.report_demand_tree.is_some() {
+        let (subscriber, demand_tree_subscriber) = if self.output.report_demand_tree.is_some() {
             transaction.set_demand_collector(Some(DemandCollector::new()));
             let sub = TestSubscriber::new();
-            transaction.set_subscriber(Some(Box::new(sub.dupe())));
-            Some(sub)
+            (Some(Box::new(sub.dupe()) as Box<dyn Subscriber>), Some(sub))
         } else {
-            transaction.set_subscriber(self.output.progress_bar_style().make_subscriber());
-            None
+            (self.output.progress_bar_style().make_subscriber(), None)
         };
+        transaction.set_subscriber(match (subscriber, extra_subscriber) {
+            (Some(a), Some(b)) => {
+                Some(Box::new(CompositeSubscriber::new(vec![a, b])) as Box<dyn Subscriber>)
+            }
+            (a, b) => a.or(b),
+        });
 
         Ok(PreparedCliRun {
             timings,
             memory_trace,
             demand_tree_subscriber,
             type_check_start,
+            cache: None,
+        })
+    }
+
+    /// Split the errors of a check into what is displayed and what is hidden, applying
+    /// `--only`, the baseline error level and the minimum severity.
+    fn displayed_errors(
+        &self,
+        ordinary: Vec<Error>,
+        baseline: &[Error],
+        directives: Vec<Error>,
+        baseline_status: BaselineStatus,
+        defaults: &OutputDefaults,
+    ) -> anyhow::Result<DisplayedErrors> {
+        let is_shown = self.output.shown_filter()?;
+        let with_status = |errors: Vec<Error>, status: BaselineStatus| -> Vec<Error> {
+            errors
+                .into_iter()
+                .filter(|e| is_shown(e))
+                .map(|e| e.with_baseline_status(status))
+                .collect()
+        };
+
+        let directives = with_status(directives, baseline_status);
+        let ordinary = with_status(ordinary, baseline_status);
+
+        // Baseline matches are cloned for display. Baseline maintenance uses the
+        // original severity and baseline representation.
+        let baseline_error_level = defaults.baseline_error_level;
+        let displayed_baseline_errors = if baseline_error_level == Severity::Ignore {
+            Vec::new()
+        } else {
+            baseline
+                .iter()
+                .filter(|e| is_shown(e))
+                .map(|e| {
+                    e.with_severity(e.severity().min(baseline_error_level))
+                        .with_baseline_status(BaselineStatus::Matched)
+                })
+                .collect()
+        };
+
+        // Filter by minimum severity. Directives are not subject to this
+        // filter — they are merged separately when reporting.
+        // This must run before `--suppress-errors` so suppression respects
+        // the user's severity threshold: a finding the user asked to hide
+        // via `--min-severity` should not get a suppression comment written
+        // into source.
+        let min_severity = defaults.min_severity;
+        let (ordinary, mut hidden): (Vec<_>, Vec<_>) = ordinary
+            .into_iter()
+            .partition(|e| e.severity() >= min_severity);
+        let (baseline, hidden_baseline): (Vec<_>, Vec<_>) = displayed_baseline_errors
+            .into_iter()
+            .partition(|e| e.severity() >= min_severity);
+        hidden.extend(hidden_baseline);
+        Ok(DisplayedErrors {
+            ordinary,
+            baseline,
+            directives,
+            hidden,
         })
     }
 
+    /// Write `displayed` to the requested outputs, then print the error counts and the
+    /// summary line, followed by `summary_note`. `add_quick_fixes` runs on the errors
+    /// before they are written. Returns the number of diagnostics that fail the check,
+    /// and the errors as written.
+    fn report_errors(
+        &self,
+        displayed: DisplayedErrors,
+        config_errors_count: usize,
+        suppressed_count: usize,
+        defaults: &OutputDefaults,
+        version: &str,
+        relative_to: &Path,
+        summary_note: &str,
+        add_quick_fixes: impl FnOnce(Vec<Error>) -> Vec<Error>,
+    ) -> anyhow::Result<(usize, Vec<Error>)> {
+        let DisplayedErrors {
+            ordinary,
+            baseline,
+            directives,
+            hidden,
+        } = displayed;
+        let output_format = defaults.output_format;
+        let min_severity = defaults.min_severity;
+
+        // Directives always display, but only affect the exit code when they
+        // meet the user's severity threshold.
+        let baselined_diagnostics_count = baseline.len();
+        let diagnostics_count = config_errors_count
+            + ordinary.len()
+            + baseline.len()
+            + directives
+                .iter()
+                .filter(|e| e.severity() >= min_severity)
+                .count();
+
+        // Merge directives into the display list, re-sorting by module
+        // name, path, and source range so output preserves file/line
+        // interleaving across modules.
+        let mut output_errors = ordinary;
+        output_errors.extend(baseline);
+        output_errors.extend(directives);
+        output_errors.sort_by_cached_key(|e| {
+            (
+                e.module().name(),
+                e.path().dupe(),
+                e.range().start(),
+                e.range().end(),
+            )
+        });
+
+        let output_errors = add_quick_fixes(output_errors);
+        self.output
+            .write_errors(output_format, version, relative_to, &output_errors)?;
+        if let Some(limit) = self.output.count_errors {
+            print_error_counts(&output_errors, limit);
+        }
+        if self.output.summarize_errors.is_some() {
+            print_error_summary(&output_errors);
+        }
+
+        if self.output.summary != Summary::None {
+            let label = if min_severity < Severity::Error {
+                "diagnostic"
+            } else {
+                "error"
+            };
+            let mut parts = vec![count(diagnostics_count, label)];
+            if suppressed_count > 0 {
+                parts.push(format!("{} suppressed", number_thousands(suppressed_count)));
+            }
+            let reports_omit_errors = if self.output.output.is_empty() {
+                output_format == OutputFormat::OmitErrors
+            } else {
+                self.output.output.iter().any(|output| {
+                    output.format.unwrap_or(output_format) == OutputFormat::OmitErrors
+                })
+            };
+            if reports_omit_errors && baselined_diagnostics_count > 0 {
+                parts.push(format!(
+                    "{} baselined",
+                    number_thousands(baselined_diagnostics_count)
+                ));
+            }
+            if !hidden.is_empty() {
+                let mut hidden_warnings = 0;
+                let mut hidden_info = 0;
+                for e in hidden {
+                    match e.severity() {
+                        Severity::Error => panic!("Error-level findings can never be hidden"),
+                        Severity::Warn => hidden_warnings += 1,
+                        Severity::Info => hidden_info += 1,
+                        Severity::Ignore => {}
+                    }
+                }
+                let mut hidden_parts = Vec::new();
+                if hidden_warnings > 0 {
+                    hidden_parts.push(count(hidden_warnings, "warning"));
+                }
+                if hidden_info > 0 {
+                    hidden_parts.push(count(hidden_info, "info message"));
+                }
+                parts.push(format!("{} not shown", hidden_parts.join(" and ")));
+            }
+            if parts.len() == 1 {
+                info!("{}{summary_note}", parts[0]);
+            } else {
+                info!("{} ({}){summary_note}", parts[0], parts[1..].join(", "));
+            }
+            self.output
+                .print_explain_hint(output_format, &output_errors);
+        }
+        Ok((diagnostics_count, output_errors))
+    }
+
     fn finish_cli_run(
         &self,
         run: PreparedCliRun,
@@ -1662,6 +2464,7 @@ impl CheckArgs {
             mut memory_trace,
             demand_tree_subscriber,
             type_check_start,
+            cache,
         } = run;
         transaction.set_subscriber(None);
 
@@ -1689,88 +2492,56 @@ impl CheckArgs {
         );
         let output_format = defaults.output_format;
 
-        let mut collected = loads.collect_errors();
-        // Pass pre-collected errors to avoid redundant error collection.
-        let unused_ignore_errors = loads.collect_unused_ignore_errors_for_display(&collected);
-        collected.ordinary.extend(unused_ignore_errors.ordinary);
+        let mut collected = if self.is_matrix() {
+            matrix::collect_errors(transaction, handles)
+        } else {
+            let mut collected = loads.collect_errors();
+            // Pass pre-collected errors to avoid redundant error collection.
+            let unused_ignore_errors = loads.collect_unused_ignore_errors_for_display(&collected);
+            collected.ordinary.extend(unused_ignore_errors.ordinary);
+            collected
+        };
+
+        let mut reused_module_count = 0;
+        if let Some((cache, replayed)) = cache {
+            reused_module_count = cache.reused_count();
+            if let Err(e) = cache.save(transaction, handles, &collected) {
+                error!("Failed to update the check cache: {e:#}");
+            }
+            collected.ordinary.extend(replayed.ordinary);
+            collected.directives.extend(replayed.directives);
+            collected.suppressed.extend(replayed.suppressed);
+            collected.disabled.extend(replayed.disabled);
+        }
 
         let baseline_apply_result = loads.apply_baseline(
             &mut collected,
             defaults.baseline.as_deref(),
             relative_to.as_path(),
-            self.output.prune_baseline || self.output.error_stale_baseline,
+            // `--update-baseline` uses the retained per-file limits to keep them as counts.
+            self.output.prune_baseline
+                || self.output.error_stale_baseline
+                || self.output.update_baseline,
         );
 
         let (baseline_status, unused_baseline_entries, retained_baseline_entries) =
             baseline_apply_result.resolve(self.output.update_baseline)?;
         let errors = collected;
-        let only_filter = self
-            .output
-            .only
-            .as_ref()
-            .map(|only| only.iter().collect::<SmallSet<&ErrorKind>>());
-
-        let with_status = |errors: Vec<Error>, status: BaselineStatus| -> Vec<Error> {
-            if let Some(only) = &only_filter {
-                errors
-                    .into_iter()
-                    .filter(|e| only.contains(&e.error_kind()))
-                    .map(|e| e.with_baseline_status(status))
-                    .collect()
-            } else {
-                errors
-                    .into_iter()
-                    .map(|e| e.with_baseline_status(status))
-                    .collect()
-            }
-        };
-
-        let directives = with_status(errors.directives, baseline_status);
-        let ordinary_errors = with_status(errors.ordinary, baseline_status);
-
-        // Baseline matches are cloned for display. Baseline maintenance uses the
-        // original severity and baseline representation.
-        let baseline_error_level = defaults.baseline_error_level;
-        let displayed_baseline_errors = if baseline_error_level == Severity::Ignore {
-            Vec::new()
-        } else {
-            errors
-                .baseline
-                .iter()
-                .filter(|e| {
-                    if let Some(only) = &only_filter {
-                        only.contains(&e.error_kind())
-                    } else {
-                        true
-                    }
-                })
-                .map(|e| {
-                    e.with_severity(e.severity().min(baseline_error_level))
-                        .with_baseline_status(BaselineStatus::Matched)
-                })
-                .collect()
-        };
-
-        // Filter by minimum severity. Directives are not subject to this
-        // filter — they are merged separately in the output step below.
-        // This must run before `--suppress-errors` so suppression respects
-        // the user's severity threshold: a finding the user asked to hide
-        // via `--min-severity` should not get a suppression comment written
-        // into source.
+        let displayed = self.displayed_errors(
+            errors.ordinary,
+            &errors.baseline,
+            errors.directives,
+            baseline_status,
+            defaults,
+        )?;
         let min_severity = defaults.min_severity;
-        let (ordinary_errors, mut hidden_errors): (Vec<_>, Vec<_>) = ordinary_errors
-            .into_iter()
-            .partition(|e| e.severity() >= min_severity);
-        let (baseline_errors, hidden_baseline_errors): (Vec<_>, Vec<_>) = displayed_baseline_errors
-            .into_iter()
-            .partition(|e| e.severity() >= min_severity);
-        hidden_errors.extend(hidden_baseline_errors);
 
         // Suppress operates on ordinary diagnostics only — directives are
-        // structurally excluded since they live in `directives`, not `ordinary_errors`.
+        // structurally excluded since they live in `directives`, not `ordinary`.
         if self.behavior.suppress_errors {
             // TODO: Deprecate this in favor of `pyrefly suppress`
-            let serialized_errors: Vec<SerializedError> = ordinary_errors
+            let serialized_errors: Vec<SerializedError> = displayed
+                .ordinary
                 .iter()
                 .filter(|e| e.error_kind().is_suppressable())
                 .filter_map(SerializedError::from_error)
@@ -1786,7 +2557,7 @@ impl CheckArgs {
 
         // We update the baseline file if requested, after reporting any new
         // errors using the old baseline. Directives are structurally excluded
-        // — they live in `directives`, not `ordinary_errors`.
+        // — they live in `directives`, not `ordinary`.
         // `--prune-baseline` rewrites the file only when there is something to drop.
         let rewriting_baseline = self.output.prune_baseline && unused_baseline_entries > 0;
         if self.output.update_baseline {
@@ -1797,7 +2568,7 @@ impl CheckArgs {
             // The baseline only tracks errors that meet the min-severity threshold.
             let mut new_baseline = errors.baseline;
             new_baseline.retain(|e| e.severity() >= min_severity);
-            new_baseline.extend(ordinary_errors.iter().cloned());
+            new_baseline.extend(displayed.ordinary.iter().cloned());
             new_baseline.sort_by_cached_key(|error| {
                 (
                     error.path().to_string(),
@@ -1806,18 +2577,34 @@ impl CheckArgs {
                     error.error_kind(),
                 )
             });
-            write_baseline_to_file(baseline_path, relative_to.as_path(), &new_baseline)?;
+            let counted: HashSet<(String, &str)> = retained_baseline_entries
+                .limits
+                .iter()
+                .map(|limit| {
+                    (
+                        normalize_baseline_path(Path::new(&limit.path), &relative_to),
+                        limit.name.as_str(),
+                    )
+                })
+                .collect();
+            write_baseline_to_file(
+                baseline_path,
+                relative_to.as_path(),
+                &new_baseline,
+                |error| {
+                    self.output.baseline_counts
+                        || counted.contains(&(
+                            normalize_baseline_path(Path::new(&error.path), &relative_to),
+                            error.name.as_str(),
+                        ))
+                },
+            )?;
         } else if rewriting_baseline {
             let baseline_path = defaults
                 .baseline
                 .as_ref()
                 .expect("a baseline action requires a baseline path");
-            write_baseline_errors_to_file(
-                baseline_path,
-                &BaselineErrors {
-                    errors: retained_baseline_entries,
-                },
-            )?;
+            write_baseline_errors_to_file(baseline_path, &retained_baseline_entries)?;
         }
         if rewriting_baseline {
             info!(
@@ -1838,125 +2625,32 @@ impl CheckArgs {
             );
         }
 
-        // Directives always display, but only affect the exit code when they
-        // meet the user's severity threshold.
-        let baselined_diagnostics_count = baseline_errors.len();
-        let diagnostics_count = config_errors_count
-            + ordinary_errors.len()
-            + baseline_errors.len()
-            + directives
-                .iter()
-                .filter(|e| e.severity() >= min_severity)
-                .count();
-
-        // Merge directives into the display list, re-sorting by module
-        // name, path, and source range so output preserves file/line
-        // interleaving across modules.
-        let mut output_errors = ordinary_errors;
-        output_errors.extend(baseline_errors);
-        output_errors.extend(directives);
-        output_errors.sort_by_cached_key(|e| {
-            (
-                e.module().name(),
-                e.path().dupe(),
-                e.range().start(),
-                e.range().end(),
-            )
-        });
-
-        if self.output.output.is_empty() {
-            write_errors_to_console(
-                output_format,
-                version,
-                relative_to.as_path(),
-                &output_errors,
-            )?;
-        } else {
-            for output in &self.output.output {
-                let format = output.format.unwrap_or(output_format);
-                match &output.destination {
-                    ErrorOutputDestination::Stdout => write_errors_to_console(
-                        format,
-                        version,
-                        relative_to.as_path(),
-                        &output_errors,
-                    )?,
-                    ErrorOutputDestination::File(path) => write_errors_to_file(
-                        format,
-                        path,
-                        version,
-                        relative_to.as_path(),
-                        &output_errors,
-                    )?,
+        let (diagnostics_count, output_errors) = self.report_errors(
+            displayed,
+            config_errors_count,
+            errors.suppressed.len(),
+            defaults,
+            version,
+            relative_to.as_path(),
+            "",
+            |errors| {
+                if self.output.reports_quick_fixes(output_format) {
+                    attach_quick_fixes(transaction, handles, errors)
+                } else {
+                    errors
                 }
-            }
-        }
+            },
+        )?;
         memory_trace.stop();
-        if let Some(limit) = self.output.count_errors {
-            print_error_counts(&output_errors, limit);
-        }
-        if self.output.summarize_errors.is_some() {
-            print_error_summary(&output_errors);
-        }
         timings.report_errors = report_errors_start.elapsed();
 
-        if self.output.summary != Summary::None {
-            let suppress_count = errors.suppressed.len();
-            let label = if min_severity < Severity::Error {
-                "diagnostic"
-            } else {
-                "error"
-            };
-            let mut parts = vec![count(diagnostics_count, label)];
-            if suppress_count > 0 {
-                parts.push(format!("{} suppressed", number_thousands(suppress_count)));
-            }
-            let reports_omit_errors = if self.output.output.is_empty() {
-                output_format == OutputFormat::OmitErrors
-            } else {
-                self.output.output.iter().any(|output| {
-                    output.format.unwrap_or(output_format) == OutputFormat::OmitErrors
-                })
-            };
-            if reports_omit_errors && baselined_diagnostics_count > 0 {
-                parts.push(format!(
-                    "{} baselined",
-                    number_thousands(baselined_diagnostics_count)
-                ));
-            }
-            if !hidden_errors.is_empty() {
-                let mut hidden_warnings = 0;
-                let mut hidden_info = 0;
-                for e in hidden_errors {
-                    match e.severity() {
-                        Severity::Error => panic!("Error-level findings can never be hidden"),
-                        Severity::Warn => hidden_warnings += 1,
-                        Severity::Info => hidden_info += 1,
-                        Severity::Ignore => {}
-                    }
-                }
-                let mut hidden_parts = Vec::new();
-                if hidden_warnings > 0 {
-                    hidden_parts.push(count(hidden_warnings, "warning"));
-                }
-                if hidden_info > 0 {
-                    hidden_parts.push(count(hidden_info, "info message"));
-                }
-                parts.push(format!("{} not shown", hidden_parts.join(" and ")));
-            }
-            if parts.len() == 1 {
-                info!("{}", parts[0]);
-            } else {
-                info!("{} ({})", parts[0], parts[1..].join(", "));
-            }
-        }
         if self.output.summary == Summary::Full {
             let user_handles: HashSet<&Handle> = handles.iter().collect();
             let (user_lines, dep_lines) = transaction.split_line_count(&user_handles);
             info!(
                 "{} ({}); {} ({} in your project, {} in dependencies); \
                 took {timings}; memory ({})",
-                count(handles.len(), "module"),
+                count(handles.len() + reused_module_count, "module"),
                 count(
                     transaction.module_count() - handles.len(),
                     "dependent module"
@@ -2022,6 +2716,11 @@ impl CheckArgs {
         if let Some(path) = &self.output.report_trace {
             fs_anyhow::write(path, report::trace::trace(transaction))?;
         }
+        if let Some(path) = &self.output.report_profile
+            && let Some(profile) = transaction.take_profile_collector()
+        {
+            fs_anyhow::write(path, profile.report_json())?;
+        }
         if let Some(path) = &self.output.dependency_graph {
             fs_anyhow::write(
                 path,
@@ -2063,6 +2762,7 @@ mod tests {
     use pyrefly_python::module::Module;
     use pyrefly_python::module_name::ModuleName;
     use pyrefly_python::module_path::ModulePath;
+    use pyrefly_python::sys_info::SysInfo;
     use ruff_text_size::TextRange;
     use ruff_text_size::TextSize;
     use tempfile::TempDir;
@@ -2155,6 +2855,25 @@ mod tests {
         )
     }
 
+    #[test]
+    fn quick_fixes_use_the_handle_of_the_error_configuration() {
+        let error = sample_error("msg".to_owned());
+        let path = error.path().dupe();
+        let linux = |version: &str| SysInfo::new(version.parse().unwrap(), PythonPlatform::linux());
+        let handle =
+            |version: &str| Handle::new(error.module().name(), path.dupe(), linux(version));
+        let (old, new) = (handle("3.9"), handle("3.12"));
+        let handles = HashMap::from([(&path, vec![&old, &new])]);
+
+        // Errors found in every configuration use the first handle of their file.
+        assert_eq!(quick_fix_handle(&handles, &error), Some(&old));
+        let only_new = error.clone().with_configurations(vec![linux("3.12")]);
+        assert_eq!(quick_fix_handle(&handles, &only_new), Some(&new));
+        let unchecked = error.with_configurations(vec![linux("3.10")]);
+        assert_eq!(quick_fix_handle(&handles, &unchecked), None);
+        assert_eq!(quick_fix_handle(&HashMap::new(), &only_new), None);
+    }
+
     #[test]
     fn incremental_checker_rechecks_modified_files() {
         let temp = TempDir::new().unwrap();
@@ -2451,6 +3170,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn checkstyle_output_format_groups_errors_by_file() {
+        let errors = vec![
+            sample_error("first <error>".into()),
+            sample_error("second".into()).with_severity(Severity::Warn),
+            sample_error("ignored".into()).with_severity(Severity::Ignore),
+        ];
+        let mut buf = Vec::new();
+        write_error_checkstyle(&mut buf, Path::new("/"), &errors).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(
+            output.starts_with(r#"<?xml version="1.0" encoding="UTF-8"?>"#),
+            "missing XML declaration: {output}"
+        );
+        assert_eq!(output.matches("<file name=").count(), 1, "{output}");
+        assert!(output.contains(r#"<file name="repo/foo.py">"#), "{output}");
+        assert!(
+            output.contains(
+                r#"severity="error" message="first &lt;error&gt;" source="pyrefly.bad-assignment""#
+            ),
+            "{output}"
+        );
+        assert!(
+            output.contains(r#"severity="warning" message="second""#),
+            "{output}"
+        );
+        assert!(!output.contains("ignored"), "{output}");
+        assert!(output.ends_with("</checkstyle>\n"), "{output}");
+    }
+
     #[test]
     fn output_args_parse_multiple_destinations() {
         let output = OutputArgs::parse_from([
@@ -2554,6 +3303,53 @@ mod tests {
         unique.validate_outputs().unwrap();
     }
 
+    #[test]
+    fn output_args_diff_scope_flags() {
+        let output = OutputArgs::parse_from([
+            "pyrefly-check",
+            "--diff-base",
+            "origin/main",
+            "--diff-include-related",
+        ]);
+        assert_eq!(output.diff_base.as_deref(), Some("origin/main"));
+        assert!(output.diff_include_related);
+
+        assert!(
+            OutputArgs::try_parse_from([
+                "pyrefly-check",
+                "--diff-base=HEAD",
+                "--diff-file=changes.diff",
+            ])
+            .is_err()
+        );
+        assert!(
+            OutputArgs::try_parse_from([
+                "pyrefly-check",
+                "--diff-file=changes.diff",
+                "--update-baseline",
+            ])
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn use_daemon_rejects_local_only_flags() {
+        let args =
+            FullCheckArgs::parse_from(["pyrefly-check", "--use-daemon", "--only=bad-return"]);
+        assert!(args.use_daemon);
+        assert_eq!(args.args.daemon_incompatible_flag(), None);
+
+        let args = FullCheckArgs::parse_from(["pyrefly-check", "--use-daemon", "--check-all"]);
+        assert_eq!(args.args.daemon_incompatible_flag(), Some("--check-all"));
+
+        assert!(
+            FullCheckArgs::try_parse_from(["pyrefly-check", "--use-daemon", "foo.py"]).is_err()
+        );
+        assert!(
+            FullCheckArgs::try_parse_from(["pyrefly-check", "--use-daemon", "--watch"]).is_err()
+        );
+    }
+
     #[test]
     fn output_args_inherit_output_format_from_config() {
         let output = OutputArgs::parse_from(["pyrefly-check"]);
@@ -0,0 +1,583 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! An opt-in on-disk cache of diagnostics that lets `check` skip modules that have not changed.
+//!
+//! Only diagnostics are cached, not exports or solved answers. We record each checked
+//! module's diagnostics together with the content hash of every file they were computed
+//! from and the dependency edges between those files. On the next run, a module whose own
+//! source and transitive dependencies are unchanged is not handed to the transaction at all
+//! and its diagnostics are replayed instead. Dependencies of the modules that are rechecked
+//! are still solved from scratch, as usual.
+//!
+//! Content hashes cannot notice a file being added, which can change how an import resolves
+//! (e.g. adding `pkg/sub.py` fixes `import pkg.sub`). So we also record the modification
+//! time of the directory holding every hashed file, and of every namespace package a module
+//! resolved to, and treat a changed directory like a changed file.
+//!
+//! Everything that affects results globally (the Pyrefly version, the configurations,
+//! the `SysInfo` of each module, the set of checked files and the top-level contents of
+//! the import roots) feeds into a fingerprint, and a fingerprint mismatch discards the
+//! whole cache.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use anyhow::Context as _;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module_path::ModulePathDetails;
+use pyrefly_util::fs_anyhow;
+use serde::Deserialize;
+use serde::Serialize;
+use starlark_map::small_map::SmallMap;
+
+use crate::config::finder::ConfigFinder;
+use crate::error::collector::CollectedErrors;
+use crate::error::persisted::PersistedError;
+use crate::error::persisted::load_module;
+use crate::error::persisted::persistable_path;
+use crate::state::state::Transaction;
+
+const CACHE_FILE: &str = "diagnostics-cache.json";
+
+/// Bumped whenever the layout of `CacheFile` changes.
+const CACHE_FORMAT: u32 = 2;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheFile {
+    format: u32,
+    fingerprint: String,
+    /// Content hash of every file the cached diagnostics were computed from.
+    hashes: BTreeMap<PathBuf, String>,
+    /// Modification time of the directory of every hashed file, and of every namespace
+    /// package in `deps`, in nanoseconds since the Unix epoch.
+    dirs: BTreeMap<PathBuf, u128>,
+    /// Direct dependencies of each file, restricted to files and namespace packages on disk.
+    deps: BTreeMap<PathBuf, Vec<PathBuf>>,
+    /// Diagnostics of each checked module.
+    modules: BTreeMap<PathBuf, CachedModule>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedModule {
+    name: String,
+    errors: Vec<CachedError>,
+}
+
+/// Which part of `CollectedErrors` a cached error belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+enum ErrorCategory {
+    Ordinary,
+    Directive,
+    Suppressed,
+    Disabled,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedError {
+    category: ErrorCategory,
+    #[serde(flatten)]
+    error: PersistedError,
+}
+
+impl CachedModule {
+    /// Rebuild the diagnostics of the module at `path`, or `None` if its source can no
+    /// longer be read or no longer fits the cached ranges.
+    fn replay(&self, path: &Path, into: &mut CollectedErrors) -> Option<()> {
+        let module = load_module(&self.name, path)?;
+        let len = module.lined_buffer().contents().len();
+        if !self.errors.iter().all(|e| e.error.fits(len)) {
+            return None;
+        }
+        for cached in &self.errors {
+            let error = cached.error.restore(&module);
+            match cached.category {
+                ErrorCategory::Ordinary => into.ordinary.push(error),
+                ErrorCategory::Directive => into.directives.push(error),
+                ErrorCategory::Suppressed => into.suppressed.push(error),
+                ErrorCategory::Disabled => into.disabled.push(error),
+            }
+        }
+        Some(())
+    }
+}
+
+fn hash_file(path: &Path) -> Option<String> {
+    fs::read(path)
+        .ok()
+        .map(|contents| blake3::hash(&contents).to_string())
+}
+
+fn modified(path: &Path) -> Option<u128> {
+    let modified = fs::metadata(path).and_then(|x| x.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_nanos())
+}
+
+/// Hash everything that affects the results of every module at once.
+fn fingerprint(
+    version: &str,
+    handles: &[Handle],
+    config_finder: &ConfigFinder,
+) -> anyhow::Result<String> {
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&CACHE_FORMAT.to_le_bytes());
+    hasher.update(version.as_bytes());
+
+    let mut configs = SmallMap::new();
+    let mut checked = Vec::with_capacity(handles.len());
+    for handle in handles {
+        let config = config_finder.python_file(handle.module_kind(), handle.path());
+        configs.entry(config.id()).or_insert(config);
+        checked.push(format!(
+            "{}\0{:?}\0{:?}",
+            handle.path().as_path().display(),
+            handle.sys_info().version(),
+            handle.sys_info().platform(),
+        ));
+    }
+    checked.sort();
+    for entry in checked {
+        hasher.update(entry.as_bytes());
+        hasher.update(b"\n");
+    }
+
+    let mut configs = configs
+        .into_iter()
+        .map(|(_, config)| {
+            let mut res = serde_json::to_string(&*config).with_context(|| {
+                format!("while fingerprinting the configuration {:?}", config.source)
+            })?;
+            // New files at the top of an import root can change how imports resolve
+            // (e.g. installing a package), which content hashes alone would not notice.
+            for root in config.search_path().chain(config.site_package_path()) {
+                res.push_str(&format!("\0{}\0{:?}", root.display(), modified(root)));
+            }
+            Ok(res)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    configs.sort();
+    for config in configs {
+        hasher.update(config.as_bytes());
+        hasher.update(b"\n");
+    }
+    Ok(hasher.finalize().to_string())
+}
+
+/// The on-disk diagnostics cache for a single run.
+pub(super) struct DiagnosticsCache {
+    path: PathBuf,
+    fingerprint: String,
+    previous: CacheFile,
+    /// The current hash of every file hashed so far, or `None` if it cannot be read.
+    current_hashes: HashMap<PathBuf, Option<String>>,
+    /// Modules whose diagnostics are replayed from `previous` rather than rechecked.
+    reused: HashSet<PathBuf>,
+}
+
+impl DiagnosticsCache {
+    /// Open the cache in `dir` for a run checking `handles`. A missing, unreadable or
+    /// outdated cache behaves as an empty one.
+    pub(super) fn open(
+        dir: &Path,
+        version: &str,
+        handles: &[Handle],
+        config_finder: &ConfigFinder,
+    ) -> anyhow::Result<Self> {
+        let fingerprint = fingerprint(version, handles, config_finder)?;
+        let path = dir.join(CACHE_FILE);
+        let previous = fs::read(&path)
+            .ok()
+            .and_then(|contents| serde_json::from_slice::<CacheFile>(&contents).ok())
+            .filter(|cache| cache.format == CACHE_FORMAT && cache.fingerprint == fingerprint)
+            .unwrap_or_default();
+        Ok(Self {
+            path,
+            fingerprint,
+            previous,
+            current_hashes: HashMap::new(),
+            reused: HashSet::new(),
+        })
+    }
+
+    fn current_hash(&mut self, path: &Path) -> Option<&str> {
+        self.current_hashes
+            .entry(path.to_path_buf())
+            .or_insert_with(|| hash_file(path))
+            .as_deref()
+    }
+
+    /// Files whose cached results cannot be trusted: those that changed since the previous
+    /// run, those in a directory that changed, and everything that transitively depends on them.
+    fn stale_paths(&mut self) -> HashSet<PathBuf> {
+        let previous_hashes = self
+            .previous
+            .hashes
+            .iter()
+            .map(|(path, hash)| (path.clone(), hash.clone()))
+            .collect::<Vec<_>>();
+        let changed_dirs = self
+            .previous
+            .dirs
+            .iter()
+            .filter(|(dir, mtime)| modified(dir) != Some(**mtime))
+            .map(|(dir, _)| dir.clone())
+            .collect::<HashSet<_>>();
+        let mut work = Vec::new();
+        for (path, hash) in previous_hashes {
+            let in_changed_dir = path.parent().is_some_and(|dir| changed_dirs.contains(dir));
+            if in_changed_dir || self.current_hash(&path) != Some(hash.as_str()) {
+                work.push(path);
+            }
+        }
+        // Modules that resolved to a changed namespace package.
+        work.extend(changed_dirs);
+        let mut rdeps: HashMap<&Path, Vec<&Path>> = HashMap::new();
+        for (module, deps) in &self.previous.deps {
+            for dep in deps {
+                rdeps
+                    .entry(dep.as_path())
+                    .or_default()
+                    .push(module.as_path());
+            }
+        }
+        let mut stale = HashSet::new();
+        while let Some(path) = work.pop() {
+            if let Some(dependents) = rdeps.get(path.as_path()) {
+                work.extend(
+                    dependents
+                        .iter()
+                        .filter(|x| !stale.contains(**x))
+                        .map(|x| x.to_path_buf()),
+                );
+            }
+            stale.insert(path);
+        }
+        stale
+    }
+
+    /// Split `handles` into the ones that must be checked, replaying the cached
+    /// diagnostics of the rest.
+    pub(super) fn partition(&mut self, handles: Vec<Handle>) -> (Vec<Handle>, CollectedErrors) {
+        let stale = self.stale_paths();
+        let mut to_check = Vec::new();
+        let mut replayed = CollectedErrors::default();
+        for handle in handles {
+            let reused = persistable_path(handle.path()).is_some_and(|path| {
+                !stale.contains(path)
+                    && self.previous.hashes.contains_key(path)
+                    && self
+                        .previous
+                        .modules
+                        .get(path)
+                        .is_some_and(|module| module.replay(path, &mut replayed).is_some())
+            });
+            if reused {
+                self.reused.insert(handle.path().as_path().to_path_buf());
+            } else {
+                to_check.push(handle);
+            }
+        }
+        (to_check, replayed)
+    }
+
+    /// The number of modules whose diagnostics were replayed.
+    pub(super) fn reused_count(&self) -> usize {
+        self.reused.len()
+    }
+
+    /// Record the diagnostics of the `checked` modules, keep the entries of the reused
+    /// ones, and write the cache back to disk. `collected` must not contain replayed errors.
+    pub(super) fn save(
+        mut self,
+        transaction: &Transaction,
+        checked: &[Handle],
+        collected: &CollectedErrors,
+    ) -> anyhow::Result<()> {
+        let mut modules = BTreeMap::new();
+        for path in &self.reused {
+            if let Some(module) = self.previous.modules.remove(path) {
+                modules.insert(path.clone(), module);
+            }
+        }
+        for handle in checked {
+            if let Some(path) = persistable_path(handle.path()) {
+                modules.insert(
+                    path.to_path_buf(),
+                    CachedModule {
+                        name: handle.module().as_str().to_owned(),
+                        errors: Vec::new(),
+                    },
+                );
+            }
+        }
+        for (category, errors) in [
+            (ErrorCategory::Ordinary, &collected.ordinary),
+            (ErrorCategory::Directive, &collected.directives),
+            (ErrorCategory::Suppressed, &collected.suppressed),
+            (ErrorCategory::Disabled, &collected.disabled),
+        ] {
+            for error in errors {
+                let path = error.path().as_path();
+                if !self.reused.contains(path)
+                    && let Some(module) = modules.get_mut(path)
+                {
+                    module.errors.push(CachedError {
+                        category,
+                        error: PersistedError::new(error),
+                    });
+                }
+            }
+        }
+
+        // Edges recorded by this transaction supersede those from the previous run.
+        let mut deps = std::mem::take(&mut self.previous.deps);
+        for handle in transaction.handles() {
+            if let ModulePathDetails::FileSystem(_) = handle.path().details() {
+                let module_deps = transaction
+                    .get_deps(&handle)
+                    .iter()
+                    .filter(|dep| {
+                        matches!(
+                            dep.path().details(),
+                            ModulePathDetails::FileSystem(_) | ModulePathDetails::Namespace(_)
+                        )
+                    })
+                    .map(|dep| dep.path().as_path().to_path_buf())
+                    .collect();
+                deps.insert(handle.path().as_path().to_path_buf(), module_deps);
+            }
+        }
+
+        // Only keep the files that the cached modules can reach.
+        let mut file = CacheFile {
+            format: CACHE_FORMAT,
+            fingerprint: self.fingerprint.clone(),
+            ..Default::default()
+        };
+        let mut work = modules.keys().cloned().collect::<Vec<_>>();
+        while let Some(path) = work.pop() {
+            if file.hashes.contains_key(&path) {
+                continue;
+            }
+            let Some(hash) = self.current_hash(&path).map(str::to_owned) else {
+                // A namespace package is validated by its modification time instead. An
+                // unreadable file cannot be validated next time, so its dependents will
+                // simply be rechecked.
+                if path.is_dir()
+                    && let Some(mtime) = modified(&path)
+                {
+                    file.dirs.insert(path, mtime);
+                }
+                continue;
+            };
+            if let Some(module_deps) = deps.remove(&path) {
+                work.extend(module_deps.iter().cloned());
+                file.deps.insert(path.clone(), module_deps);
+            }
+            if let Some(dir) = path.parent()
+                && let Some(mtime) = modified(dir)
+            {
+                file.dirs.insert(dir.to_path_buf(), mtime);
+            }
+            file.hashes.insert(path, hash);
+        }
+        file.modules = modules;
+
+        let dir = self
+            .path
+            .parent()
+            .expect("the cache file is always inside the cache directory");
+        fs_anyhow::create_dir_all(dir)?;
+        // Write to a temporary file first so an interrupted run cannot leave a truncated cache.
+        let temp = self.path.with_extension("json.tmp");
+        fs_anyhow::write(&temp, serde_json::to_vec(&file)?)?;
+        fs::rename(&temp, &self.path).with_context(|| {
+            format!(
+                "while writing the diagnostics cache `{}`",
+                self.path.display()
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_config::error_kind::ErrorKind;
+    use pyrefly_config::error_kind::Severity;
+    use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::module_path::ModulePath;
+    use pyrefly_python::sys_info::SysInfo;
+    use pyrefly_util::arc_id::ArcId;
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::config::config::ConfigFile;
+
+    fn handle(path: &Path, name: &str) -> Handle {
+        Handle::new(
+            ModuleName::from_str(name),
+            ModulePath::filesystem(path.to_path_buf()),
+            SysInfo::default(),
+        )
+    }
+
+    fn cache_with(
+        dir: &Path,
+        modules: &[(&Path, &str)],
+        deps: &[(&Path, &Path)],
+    ) -> DiagnosticsCache {
+        let mut cache = DiagnosticsCache {
+            path: dir.join(CACHE_FILE),
+            fingerprint: String::new(),
+            previous: CacheFile::default(),
+            current_hashes: HashMap::new(),
+            reused: HashSet::new(),
+        };
+        for (path, name) in modules {
+            cache
+                .previous
+                .hashes
+                .insert(path.to_path_buf(), hash_file(path).unwrap());
+            let dir = path.parent().unwrap();
+            cache
+                .previous
+                .dirs
+                .insert(dir.to_path_buf(), modified(dir).unwrap());
+            cache.previous.modules.insert(
+                path.to_path_buf(),
+                CachedModule {
+                    name: (*name).to_owned(),
+                    errors: vec![CachedError {
+                        category: ErrorCategory::Ordinary,
+                        error: PersistedError {
+                            start: 0,
+                            end: 1,
+                            kind: ErrorKind::BadAssignment,
+                            severity: Severity::Error,
+                            header: "bad".to_owned(),
+                            details: Some("  more".to_owned()),
+                            annotations: Vec::new(),
+                            quick_fixes: Vec::new(),
+                        },
+                    }],
+                },
+            );
+        }
+        for (from, to) in deps {
+            cache
+                .previous
+                .deps
+                .entry(from.to_path_buf())
+                .or_default()
+                .push(to.to_path_buf());
+        }
+        cache
+    }
+
+    #[test]
+    fn unchanged_modules_are_replayed() {
+        let temp = TempDir::new().unwrap();
+        let a = temp.path().join("a.py");
+        fs::write(&a, "x: int = ''\n").unwrap();
+        let mut cache = cache_with(temp.path(), &[(&a, "a")], &[]);
+
+        let (to_check, replayed) = cache.partition(vec![handle(&a, "a")]);
+        assert!(to_check.is_empty());
+        assert_eq!(cache.reused_count(), 1);
+        assert_eq!(replayed.ordinary.len(), 1);
+        let error = &replayed.ordinary[0];
+        assert_eq!(error.path().as_path(), a);
+        assert_eq!(error.msg(), "bad\n  more");
+        assert_eq!(error.error_kind(), ErrorKind::BadAssignment);
+    }
+
+    #[test]
+    fn changes_invalidate_dependents() {
+        let temp = TempDir::new().unwrap();
+        let a = temp.path().join("a.py");
+        let b = temp.path().join("b.py");
+        let c = temp.path().join("c.py");
+        fs::write(&a, "import b\n").unwrap();
+        fs::write(&b, "import c\n").unwrap();
+        fs::write(&c, "x = 1\n").unwrap();
+        let mut cache = cache_with(
+            temp.path(),
+            &[(&a, "a"), (&b, "b"), (&c, "c")],
+            &[(&a, &b), (&b, &c)],
+        );
+        fs::write(&b, "import c\ny = 2\n").unwrap();
+
+        let (to_check, replayed) =
+            cache.partition(vec![handle(&a, "a"), handle(&b, "b"), handle(&c, "c")]);
+        let to_check = to_check
+            .iter()
+            .map(|h| h.module().as_str().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(to_check, vec!["a", "b"]);
+        assert_eq!(replayed.ordinary.len(), 1);
+        assert_eq!(replayed.ordinary[0].path().as_path(), c);
+    }
+
+    #[test]
+    fn outdated_cache_files_are_ignored() {
+        let temp = TempDir::new().unwrap();
+        let a = temp.path().join("a.py");
+        fs::write(&a, "x = 1\n").unwrap();
+        let mut file = CacheFile {
+            format: CACHE_FORMAT,
+            fingerprint: "something else".to_owned(),
+            ..Default::default()
+        };
+        file.hashes.insert(a.clone(), hash_file(&a).unwrap());
+        fs::write(
+            temp.path().join(CACHE_FILE),
+            serde_json::to_vec(&file).unwrap(),
+        )
+        .unwrap();
+        let cache = DiagnosticsCache::open(
+            temp.path(),
+            "0.0.0",
+            &[handle(&a, "a")],
+            &ConfigFinder::new_constant(ArcId::new(ConfigFile::default())),
+        )
+        .unwrap();
+        assert!(cache.previous.hashes.is_empty());
+    }
+
+    #[test]
+    fn new_files_invalidate_modules_in_their_directory() {
+        let temp = TempDir::new().unwrap();
+        let pkg = temp.path().join("pkg");
+        fs::create_dir(&pkg).unwrap();
+        let init = pkg.join("__init__.py");
+        let main = temp.path().join("main.py");
+        fs::write(&init, "").unwrap();
+        fs::write(&main, "import pkg.sub\n").unwrap();
+        let mut cache = cache_with(
+            temp.path(),
+            &[(&main, "main"), (&init, "pkg")],
+            &[(&main, &init)],
+        );
+        // Stands in for `pkg/sub.py` being created since the previous run.
+        cache.previous.dirs.insert(pkg.clone(), 0);
+
+        let (to_check, replayed) =
+            cache.partition(vec![handle(&main, "main"), handle(&init, "pkg")]);
+        let to_check = to_check
+            .iter()
+            .map(|h| h.module().as_str().to_owned())
+            .collect::<Vec<_>>();
+        assert_eq!(to_check, vec!["main", "pkg"]);
+        assert!(replayed.ordinary.is_empty());
+    }
+}
@@ -0,0 +1,350 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Restrict `check` output to diagnostics that touch changed lines.
+//!
+//! Changes come either from `git diff` against a local revision or from a
+//! unified diff supplied by the caller. Only the new side of each hunk matters:
+//! we record the lines that were added or modified, plus the line that now sits
+//! where a deletion happened, since an error there is often caused by the removal.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::io::Read as _;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::Context as _;
+use anyhow::bail;
+use pyrefly_util::absolutize::Absolutize;
+use pyrefly_util::fs_anyhow;
+use pyrefly_util::lined_buffer::DisplayRange;
+
+use crate::error::error::Error;
+
+/// How much of a file is considered changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum FileChange {
+    /// The file is new to version control, so every line counts as changed.
+    Whole,
+    /// One-indexed line numbers in the new version of the file.
+    Lines(BTreeSet<u32>),
+}
+
+/// The changed lines of every file touched by a diff, keyed by absolute path.
+#[derive(Debug, Clone, Default)]
+pub(super) struct ChangedLines {
+    files: HashMap<PathBuf, FileChange>,
+}
+
+impl ChangedLines {
+    /// Compute the lines changed in the working tree relative to `base`, a git revision.
+    /// Untracked (but not ignored) files are treated as entirely changed.
+    pub(super) fn from_git(base: &str) -> anyhow::Result<Self> {
+        let root = PathBuf::from(
+            run_git(None, &["rev-parse", "--show-toplevel"])
+                .context("`--diff-base` must be used inside a git repository")?
+                .trim_end(),
+        );
+        let diff = run_git(
+            Some(&root),
+            &[
+                "diff",
+                "--no-color",
+                "--no-ext-diff",
+                "--unified=0",
+                base,
+                "--",
+            ],
+        )
+        .with_context(|| format!("while computing the diff against `{base}`"))?;
+        let mut res = Self::from_unified_diff(&diff, &root);
+        let untracked = run_git(
+            Some(&root),
+            &["ls-files", "--others", "--exclude-standard", "-z"],
+        )
+        .context("while listing untracked files")?;
+        for path in untracked.split('\0').filter(|x| !x.is_empty()) {
+            res.files
+                .insert(Path::new(path).absolutize_from(&root), FileChange::Whole);
+        }
+        Ok(res)
+    }
+
+    /// Read a unified diff from `path` (or stdin, if `path` is `-`). Paths in the diff
+    /// are resolved against the current directory.
+    pub(super) fn from_diff_file(path: &Path) -> anyhow::Result<Self> {
+        let diff = if path == Path::new("-") {
+            let mut diff = String::new();
+            std::io::stdin()
+                .read_to_string(&mut diff)
+                .context("while reading a diff from stdin")?;
+            diff
+        } else {
+            fs_anyhow::read_to_string(path)?
+        };
+        let root = std::env::current_dir().context("while resolving paths in the diff")?;
+        Ok(Self::from_unified_diff(&diff, &root))
+    }
+
+    /// Parse a unified diff, as produced by `git diff` or `diff -u`, resolving
+    /// the destination paths against `root`.
+    fn from_unified_diff(diff: &str, root: &Path) -> Self {
+        let mut res = Self::default();
+        let mut current: Option<PathBuf> = None;
+        // Lines remaining in the current hunk on the old and new sides. While either is
+        // non-zero, lines starting with `---`/`+++` are hunk content, not file headers.
+        let mut old_remaining = 0u32;
+        let mut new_remaining = 0u32;
+        let mut new_line = 0u32;
+        for line in diff.lines() {
+            if old_remaining > 0 || new_remaining > 0 {
+                let changed = match line.as_bytes().first() {
+                    Some(b'+') => {
+                        new_remaining = new_remaining.saturating_sub(1);
+                        new_line += 1;
+                        Some(new_line - 1)
+                    }
+                    Some(b'-') => {
+                        old_remaining = old_remaining.saturating_sub(1);
+                        // Attribute a deletion to the line that now follows it.
+                        Some(new_line.max(1))
+                    }
+                    Some(b'\\') => None,
+                    _ => {
+                        old_remaining = old_remaining.saturating_sub(1);
+                        new_remaining = new_remaining.saturating_sub(1);
+                        new_line += 1;
+                        None
+                    }
+                };
+                if let Some(changed) = changed
+                    && let Some(path) = &current
+                {
+                    res.add_line(path, changed);
+                }
+            } else if let Some(header) = line.strip_prefix("+++ ") {
+                current = parse_diff_path(header).map(|path| path.absolutize_from(root));
+            } else if let Some(header) = line.strip_prefix("@@ ")
+                && let Some((old, new)) = parse_hunk_header(header)
+            {
+                (old_remaining, new_remaining) = (old.1, new.1);
+                new_line = new.0;
+                if new.1 == 0 {
+                    // A pure deletion reports the line *before* the removed lines,
+                    // so step past it to point at the line that now follows them.
+                    new_line += 1;
+                }
+            }
+        }
+        res
+    }
+
+    fn add_line(&mut self, path: &Path, line: u32) {
+        match self
+            .files
+            .entry(path.to_path_buf())
+            .or_insert_with(|| FileChange::Lines(BTreeSet::new()))
+        {
+            FileChange::Whole => {}
+            FileChange::Lines(lines) => {
+                lines.insert(line);
+            }
+        }
+    }
+
+    fn touches_range(&self, path: &Path, range: &DisplayRange) -> bool {
+        match self.files.get(path) {
+            None => false,
+            Some(FileChange::Whole) => true,
+            Some(FileChange::Lines(lines)) => {
+                let start = range.start.line_within_file().get();
+                let end = range.end.line_within_file().get().max(start);
+                lines.range(start..=end).next().is_some()
+            }
+        }
+    }
+
+    /// Whether `error` should be reported: its primary range intersects a changed line,
+    /// or, if `include_related` is set, one of its secondary annotations does.
+    pub(super) fn touches(&self, error: &Error, include_related: bool) -> bool {
+        let path = error.path().as_path().absolutize();
+        self.touches_range(&path, error.display_range())
+            || (include_related
+                && error.secondary_annotations().iter().any(|annotation| {
+                    self.touches_range(&path, &error.module().display_range(annotation.range))
+                }))
+    }
+}
+
+fn run_git(cwd: Option<&Path>, args: &[&str]) -> anyhow::Result<String> {
+    let mut command = Command::new("git");
+    if let Some(cwd) = cwd {
+        command.current_dir(cwd);
+    }
+    let output = command.args(args).output().context("failed to run `git`")?;
+    if !output.status.success() {
+        bail!(
+            "`git {}` failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Extract the destination path from the text after `+++ `. Returns `None` for
+/// deleted files.
+fn parse_diff_path(header: &str) -> Option<PathBuf> {
+    // `diff -u` appends a tab-separated timestamp; git may quote unusual paths.
+    let path = header.split('\t').next()?.trim_end().trim_matches('"');
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(PathBuf::from(path.strip_prefix("b/").unwrap_or(path)))
+}
+
+/// Parse the text after `@@ ` in a header like `@@ -1,2 +3,4 @@ def f():` into
+/// `(start, count)` pairs for the old and new sides.
+fn parse_hunk_header(header: &str) -> Option<((u32, u32), (u32, u32))> {
+    fn side(x: &str) -> Option<(u32, u32)> {
+        match x.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((x.parse().ok()?, 1)),
+        }
+    }
+    let mut parts = header.split(' ');
+    let old = side(parts.next()?.strip_prefix('-')?)?;
+    let new = side(parts.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use pyrefly_config::error_kind::ErrorKind;
+    use pyrefly_python::module::Module;
+    use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::module_path::ModulePath;
+    use ruff_text_size::TextRange;
+    use ruff_text_size::TextSize;
+
+    use super::*;
+
+    const SOURCE: &str = "a = 1\nb = 2\nc = 3\nd = 4\n";
+
+    fn lines(changed: &ChangedLines, path: &str) -> Option<Vec<u32>> {
+        match changed.files.get(Path::new(path))? {
+            FileChange::Whole => None,
+            FileChange::Lines(lines) => Some(lines.iter().copied().collect()),
+        }
+    }
+
+    /// An error on the given (zero-indexed) line of `SOURCE` in `/repo/foo.py`.
+    fn error_on_line(line: u32) -> Error {
+        let module = Module::new(
+            ModuleName::from_str("foo"),
+            ModulePath::filesystem(PathBuf::from("/repo/foo.py")),
+            Arc::new(SOURCE.to_owned()),
+        );
+        let start = TextSize::from(line * 6);
+        Error::new(
+            module,
+            TextRange::new(start, start + TextSize::from(1)),
+            "bad".to_owned(),
+            Vec::new(),
+            ErrorKind::BadAssignment,
+        )
+    }
+
+    #[test]
+    fn parses_git_diff_without_context() {
+        let diff = "\
+diff --git a/foo.py b/foo.py
+index 1111111..2222222 100644
+--- a/foo.py
++++ b/foo.py
+@@ -2 +2 @@
+-b = 0
++b = 2
+@@ -5,0 +6,2 @@ def f():
++e = 5
++f = 6
+";
+        let changed = ChangedLines::from_unified_diff(diff, Path::new("/repo"));
+        assert_eq!(lines(&changed, "/repo/foo.py"), Some(vec![2, 6, 7]));
+    }
+
+    #[test]
+    fn parses_context_and_deletions() {
+        // Removed lines that themselves look like file headers must not be mistaken for them.
+        let diff = "\
+--- foo.py\t2024-01-01 00:00:00
++++ foo.py\t2024-01-01 00:00:01
+@@ -1,4 +1,3 @@
+ a = 1
+--- not a header
+ b = 2
+ c = 3
+@@ -9,2 +8,0 @@
+-x = 1
+-y = 2
+--- /dev/null
++++ b/bar.py
+@@ -0,0 +1 @@
++z = 1
+";
+        let changed = ChangedLines::from_unified_diff(diff, Path::new("/repo"));
+        assert_eq!(lines(&changed, "/repo/foo.py"), Some(vec![2, 9]));
+        assert_eq!(lines(&changed, "/repo/bar.py"), Some(vec![1]));
+        assert_eq!(changed.files.len(), 2);
+    }
+
+    #[test]
+    fn deleted_files_are_ignored() {
+        let diff = "\
+--- a/gone.py
++++ /dev/null
+@@ -1 +0,0 @@
+-x = 1
+";
+        let changed = ChangedLines::from_unified_diff(diff, Path::new("/repo"));
+        assert!(changed.files.is_empty());
+    }
+
+    #[test]
+    fn filters_errors_by_primary_and_related_ranges() {
+        let diff = "\
+--- a/foo.py
++++ b/foo.py
+@@ -3 +3 @@
+-c = 0
++c = 3
+";
+        let changed = ChangedLines::from_unified_diff(diff, Path::new("/repo"));
+        assert!(changed.touches(&error_on_line(2), false));
+        assert!(!changed.touches(&error_on_line(0), false));
+
+        let related = error_on_line(0).with_annotation(
+            TextRange::new(TextSize::from(12), TextSize::from(13)),
+            "defined here".to_owned(),
+        );
+        assert!(!changed.touches(&related, false));
+        assert!(changed.touches(&related, true));
+    }
+
+    #[test]
+    fn untracked_files_match_everywhere() {
+        let mut changed = ChangedLines::default();
+        changed
+            .files
+            .insert(PathBuf::from("/repo/foo.py"), FileChange::Whole);
+        assert!(changed.touches(&error_on_line(3), false));
+    }
+}
@@ -0,0 +1,271 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Check a project against a matrix of Python versions and platforms in one run.
+//!
+//! Each checked file gets one handle per configuration, all solved by the same `State`.
+//! Afterwards the diagnostics of every configuration are merged, and those that don't appear
+//! in every configuration their file was checked in are annotated with the configurations they
+//! appear in.
+
+use std::collections::BTreeMap;
+
+use dupe::Dupe;
+use itertools::Itertools;
+use pyrefly_build::handle::Handle;
+use pyrefly_config::error_kind::ErrorKind;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::sys_info::PythonPlatform;
+use pyrefly_python::sys_info::PythonVersion;
+use pyrefly_python::sys_info::SysInfo;
+use ruff_text_size::TextRange;
+use starlark_map::small_map::SmallMap;
+
+use crate::error::collector::CollectedErrors;
+use crate::error::error::Error;
+use crate::state::state::Transaction;
+
+/// Errors from different configurations are the same if they agree on all of these.
+type MergeKey = (ModulePath, TextRange, ErrorKind, String, Option<String>);
+
+fn merge_key(error: &Error) -> MergeKey {
+    (
+        error.path().dupe(),
+        error.range(),
+        error.error_kind(),
+        error.msg_header().to_owned(),
+        error.msg_details().map(str::to_owned),
+    )
+}
+
+/// Replace each handle with one per configuration. An empty `versions` or `platforms` keeps
+/// the value the handle was configured with.
+pub fn expand_handles(
+    handles: Vec<Handle>,
+    versions: &[PythonVersion],
+    platforms: &[PythonPlatform],
+) -> Vec<Handle> {
+    handles
+        .into_iter()
+        .flat_map(|handle| {
+            let sys_info = handle.sys_info().dupe();
+            let versions = if versions.is_empty() {
+                vec![sys_info.version()]
+            } else {
+                versions.to_vec()
+            };
+            let platforms = if platforms.is_empty() {
+                vec![sys_info.platform().clone()]
+            } else {
+                platforms.to_vec()
+            };
+            versions
+                .into_iter()
+                .cartesian_product(platforms)
+                .map(move |(version, platform)| {
+                    let sys_info = if sys_info.type_checking() {
+                        SysInfo::new(version, platform)
+                    } else {
+                        SysInfo::new_without_type_checking(version, platform)
+                    };
+                    Handle::new(handle.module(), handle.path().dupe(), sys_info)
+                })
+        })
+        .unique()
+        .collect()
+}
+
+/// Merge the errors found in each configuration. Errors that appear in every configuration their
+/// file was checked in, according to `checked_in`, are reported once, as usual. The rest are
+/// annotated with the configurations they appear in, or dropped if `everywhere` is set.
+fn merge(
+    configurations: Vec<(SysInfo, Vec<Error>)>,
+    checked_in: &SmallMap<ModulePath, usize>,
+    everywhere: bool,
+) -> Vec<Error> {
+    let total = configurations.len();
+    let mut merged: SmallMap<MergeKey, (Error, Vec<SysInfo>)> = SmallMap::new();
+    for (sys_info, errors) in configurations {
+        for error in errors {
+            let found_in = &mut merged
+                .entry(merge_key(&error))
+                .or_insert_with(|| (error, Vec::new()))
+                .1;
+            if !found_in.contains(&sys_info) {
+                found_in.push(sys_info.dupe());
+            }
+        }
+    }
+    merged
+        .into_values()
+        .filter_map(|(error, found_in)| {
+            let checked_in = checked_in.get(error.path()).copied().unwrap_or(total);
+            if found_in.len() >= checked_in {
+                Some(error)
+            } else if everywhere {
+                None
+            } else {
+                Some(error.with_configurations(found_in))
+            }
+        })
+        .collect()
+}
+
+/// Collect the errors of every configuration `handles` were expanded into and merge them.
+///
+/// An ignore comment is only reported as unused if it is unused in every configuration, since
+/// the error it suppresses may only appear in some of them.
+pub fn collect_errors(transaction: &Transaction, handles: &[Handle]) -> CollectedErrors {
+    let by_sys_info: BTreeMap<SysInfo, Vec<&Handle>> = handles
+        .iter()
+        .into_group_map_by(|handle| handle.sys_info().dupe())
+        .into_iter()
+        .collect();
+    // An omitted dimension comes from each file's own config, so a file may not be checked in
+    // every configuration.
+    let mut checked_in: SmallMap<ModulePath, usize> = SmallMap::new();
+    for (path, _) in handles
+        .iter()
+        .map(|handle| (handle.path(), handle.sys_info()))
+        .unique()
+    {
+        *checked_in.entry(path.dupe()).or_default() += 1;
+    }
+    let mut ordinary = Vec::new();
+    let mut directives = Vec::new();
+    let mut suppressed = Vec::new();
+    let mut disabled = Vec::new();
+    let mut baseline = Vec::new();
+    let mut unused_ignores = Vec::new();
+    let mut unused_ignores_disabled = Vec::new();
+    for (sys_info, handles) in by_sys_info {
+        let loads = transaction.get_errors(handles);
+        let collected = loads.collect_errors();
+        let unused = loads.collect_unused_ignore_errors_for_display(&collected);
+        ordinary.push((sys_info.dupe(), collected.ordinary));
+        directives.push((sys_info.dupe(), collected.directives));
+        suppressed.push((sys_info.dupe(), collected.suppressed));
+        disabled.push((sys_info.dupe(), collected.disabled));
+        baseline.push((sys_info.dupe(), collected.baseline));
+        unused_ignores.push((sys_info.dupe(), unused.ordinary));
+        unused_ignores_disabled.push((sys_info, unused.disabled));
+    }
+    let mut collected = CollectedErrors {
+        ordinary: merge(ordinary, &checked_in, false),
+        directives: merge(directives, &checked_in, false),
+        suppressed: merge(suppressed, &checked_in, false),
+        disabled: merge(disabled, &checked_in, false),
+        baseline: merge(baseline, &checked_in, false),
+    };
+    collected
+        .ordinary
+        .extend(merge(unused_ignores, &checked_in, true));
+    collected
+        .disabled
+        .extend(merge(unused_ignores_disabled, &checked_in, true));
+    collected
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use pyrefly_python::module::Module;
+    use pyrefly_python::module_name::ModuleName;
+    use ruff_text_size::TextSize;
+
+    use super::*;
+
+    fn error(module: &Module, start: u32, header: &str) -> Error {
+        Error::new(
+            module.dupe(),
+            TextRange::new(TextSize::new(start), TextSize::new(start + 1)),
+            header.to_owned(),
+            Vec::new(),
+            ErrorKind::BadAssignment,
+        )
+    }
+
+    fn sys_info(version: &str, platform: &str) -> SysInfo {
+        SysInfo::new(version.parse().unwrap(), PythonPlatform::new(platform))
+    }
+
+    #[test]
+    fn test_expand_handles() {
+        let module = ModuleName::from_str("foo");
+        let path = ModulePath::filesystem(PathBuf::from("/repo/foo.py"));
+        let configured = sys_info("3.12", "linux");
+        let handles = vec![Handle::new(module, path.dupe(), configured.dupe())];
+
+        let versions = ["3.9".parse().unwrap(), "3.12".parse().unwrap()];
+        let expanded = expand_handles(handles.clone(), &versions, &[]);
+        assert_eq!(
+            expanded
+                .iter()
+                .map(|handle| handle.sys_info().dupe())
+                .collect::<Vec<_>>(),
+            vec![sys_info("3.9", "linux"), configured.dupe()]
+        );
+
+        let platforms = [PythonPlatform::new("linux"), PythonPlatform::new("win32")];
+        let expanded = expand_handles(handles, &versions, &platforms);
+        assert_eq!(expanded.len(), 4);
+        assert!(expanded.iter().all(|handle| handle.path() == &path));
+    }
+
+    #[test]
+    fn test_merge() {
+        let module = Module::new(
+            ModuleName::from_str("foo"),
+            ModulePath::filesystem(PathBuf::from("/repo/foo.py")),
+            Arc::new("x = 1\ny = 2\nz = 3\n".to_owned()),
+        );
+        let linux = sys_info("3.12", "linux");
+        let win32 = sys_info("3.12", "win32");
+        let configurations = vec![
+            (
+                linux.dupe(),
+                vec![error(&module, 0, "everywhere"), error(&module, 6, "linux")],
+            ),
+            (
+                win32.dupe(),
+                vec![error(&module, 0, "everywhere"), error(&module, 12, "win32")],
+            ),
+        ];
+
+        let checked_in = SmallMap::from_iter([(module.path().dupe(), 2)]);
+        let merged = merge(configurations.clone(), &checked_in, false);
+        assert_eq!(
+            merged
+                .iter()
+                .map(|e| (e.msg_header(), e.configuration_names()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("everywhere", Vec::new()),
+                ("linux", vec!["3.12/linux".to_owned()]),
+                ("win32", vec!["3.12/win32".to_owned()]),
+            ]
+        );
+
+        let everywhere = merge(configurations.clone(), &checked_in, true);
+        assert_eq!(everywhere.len(), 1);
+        assert_eq!(everywhere[0].msg_header(), "everywhere");
+
+        // A file only checked on Linux, as a sub-config pins its platform, has no errors
+        // specific to a configuration.
+        let checked_in = SmallMap::from_iter([(module.path().dupe(), 1)]);
+        let linux_only = merge(configurations[..1].to_vec(), &checked_in, true);
+        assert_eq!(
+            linux_only
+                .iter()
+                .map(|e| e.msg_header())
+                .collect::<Vec<_>>(),
+            vec!["everywhere", "linux"]
+        );
+    }
+}
@@ -0,0 +1,354 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! reviewdog diagnostic output for the `check` command, as a single result (rdjson)
+//! or as one diagnostic per line (rdjsonl).
+//! <https://github.com/reviewdog/reviewdog/tree/master/proto/rdf>
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::Path;
+
+use anstream::stdout;
+use anyhow::Context as _;
+use pyrefly_config::error_kind::Severity;
+use pyrefly_python::module::Module;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use serde::Serialize;
+
+use crate::error::error::Error;
+
+const SOURCE: Source = Source {
+    name: "pyrefly",
+    url: "https://pyrefly.org/",
+};
+
+#[derive(Serialize)]
+struct DiagnosticResult {
+    source: Source,
+    diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+struct Source {
+    name: &'static str,
+    url: &'static str,
+}
+
+#[derive(Serialize)]
+struct Diagnostic {
+    message: String,
+    location: Location,
+    severity: RdSeverity,
+    /// Only set for rdjsonl, where each line stands alone.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<Source>,
+    code: Code,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<Suggestion>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    related_locations: Vec<RelatedLocation>,
+}
+
+#[derive(Serialize)]
+struct Location {
+    path: String,
+    range: Range,
+}
+
+#[derive(Serialize)]
+struct Range {
+    start: Position,
+    end: Position,
+}
+
+/// A one-indexed position. reviewdog counts columns in UTF-8 bytes.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+struct Position {
+    line: u32,
+    column: u32,
+}
+
+#[derive(Serialize)]
+struct Code {
+    value: &'static str,
+    url: String,
+}
+
+#[derive(Serialize)]
+struct Suggestion {
+    range: Range,
+    text: String,
+}
+
+#[derive(Serialize)]
+struct RelatedLocation {
+    message: String,
+    location: Location,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum RdSeverity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// `None` for ignored diagnostics, which are not findings.
+fn severity(severity: Severity) -> Option<RdSeverity> {
+    match severity {
+        Severity::Ignore => None,
+        Severity::Info => Some(RdSeverity::Info),
+        Severity::Warn => Some(RdSeverity::Warning),
+        Severity::Error => Some(RdSeverity::Error),
+    }
+}
+
+/// The line and column of `offset` in the file, which for notebooks is the concatenation of
+/// the cells, so the two always agree.
+fn position(module: &Module, offset: TextSize) -> Position {
+    let pos = module.display_pos(offset);
+    let line_start = module.lined_buffer().line_start(pos.line_within_file());
+    Position {
+        line: pos.line_within_file().get(),
+        column: (offset - line_start).to_u32() + 1,
+    }
+}
+
+fn range(module: &Module, range: TextRange) -> Range {
+    Range {
+        start: position(module, range.start()),
+        end: position(module, range.end()),
+    }
+}
+
+fn location(module: &Module, path: &str, x: TextRange) -> Location {
+    Location {
+        path: path.to_owned(),
+        range: range(module, x),
+    }
+}
+
+fn to_diagnostic(error: &Error, relative_to: &Path, standalone: bool) -> Option<Diagnostic> {
+    let severity = severity(error.severity())?;
+    let module = error.module();
+    let error_path = error.path().as_path();
+    let path = error_path
+        .strip_prefix(relative_to)
+        .unwrap_or(error_path)
+        .to_string_lossy()
+        .into_owned();
+    let kind = error.error_kind();
+    Some(Diagnostic {
+        message: error.msg(),
+        location: location(module, &path, error.range()),
+        severity,
+        source: standalone.then_some(SOURCE),
+        code: Code {
+            value: kind.to_name(),
+            url: kind.docs_url(),
+        },
+        suggestions: error
+            .quick_fix_edits()
+            .into_iter()
+            .map(|(x, text)| Suggestion {
+                range: range(module, x),
+                text: text.to_owned(),
+            })
+            .collect(),
+        related_locations: error
+            .secondary_annotations()
+            .iter()
+            .map(|annotation| RelatedLocation {
+                message: annotation.label.to_string(),
+                location: location(module, &path, annotation.range),
+            })
+            .collect(),
+    })
+}
+
+fn write_error_rdjson(
+    writer: &mut impl Write,
+    relative_to: &Path,
+    errors: &[Error],
+) -> anyhow::Result<()> {
+    let result = DiagnosticResult {
+        source: SOURCE,
+        diagnostics: errors
+            .iter()
+            .filter_map(|error| to_diagnostic(error, relative_to, false))
+            .collect(),
+    };
+    serde_json::to_writer_pretty(&mut *writer, &result)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+fn write_error_rdjsonl(
+    writer: &mut impl Write,
+    relative_to: &Path,
+    errors: &[Error],
+) -> anyhow::Result<()> {
+    for diagnostic in errors
+        .iter()
+        .filter_map(|error| to_diagnostic(error, relative_to, true))
+    {
+        serde_json::to_writer(&mut *writer, &diagnostic)?;
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+fn buffered_write_error_reviewdog(
+    writer: impl Write,
+    relative_to: &Path,
+    errors: &[Error],
+    lines: bool,
+) -> anyhow::Result<()> {
+    let mut writer = BufWriter::new(writer);
+    if lines {
+        write_error_rdjsonl(&mut writer, relative_to, errors)?;
+    } else {
+        write_error_rdjson(&mut writer, relative_to, errors)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Write rdjson, or rdjsonl if `lines` is set, to `path`.
+pub(crate) fn write_error_reviewdog_to_file(
+    path: &Path,
+    relative_to: &Path,
+    errors: &[Error],
+    lines: bool,
+) -> anyhow::Result<()> {
+    buffered_write_error_reviewdog(File::create(path)?, relative_to, errors, lines)
+        .with_context(|| format!("while writing reviewdog errors to `{}`", path.display()))
+}
+
+/// Write rdjson, or rdjsonl if `lines` is set, to stdout.
+pub(crate) fn write_error_reviewdog_to_console(
+    relative_to: &Path,
+    errors: &[Error],
+    lines: bool,
+) -> anyhow::Result<()> {
+    buffered_write_error_reviewdog(stdout(), relative_to, errors, lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use pyrefly_config::error_kind::ErrorKind;
+    use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::module_path::ModulePath;
+
+    use super::*;
+    use crate::error::error::ErrorQuickFix;
+
+    fn sample_error(source: &str, start: u32, end: u32) -> Error {
+        let module = Module::new(
+            ModuleName::from_str("sample"),
+            ModulePath::filesystem(PathBuf::from("/repo/foo.py")),
+            Arc::new(source.to_owned()),
+        );
+        Error::new(
+            module,
+            TextRange::new(TextSize::from(start), TextSize::from(end)),
+            "bad".to_owned(),
+            Vec::new(),
+            ErrorKind::BadAssignment,
+        )
+    }
+
+    fn to_json(errors: &[Error], lines: bool) -> String {
+        let mut buf = Vec::new();
+        if lines {
+            write_error_rdjsonl(&mut buf, Path::new("/repo"), errors).unwrap();
+        } else {
+            write_error_rdjson(&mut buf, Path::new("/repo"), errors).unwrap();
+        }
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[test]
+    fn rdjson_maps_locations_and_severities() {
+        let errors = vec![
+            sample_error("x = 1\ny = 2\n", 6, 7).with_severity(Severity::Warn),
+            sample_error("x = 1\n", 0, 1).with_severity(Severity::Ignore),
+        ];
+        let value: serde_json::Value = serde_json::from_str(&to_json(&errors, false)).unwrap();
+        assert_eq!(value["source"]["name"], "pyrefly");
+        let diagnostics = value["diagnostics"].as_array().unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic["severity"], "WARNING");
+        assert_eq!(diagnostic["code"]["value"], "bad-assignment");
+        assert_eq!(diagnostic["location"]["path"], "foo.py");
+        assert_eq!(
+            diagnostic["location"]["range"]["start"],
+            serde_json::json!({"line": 2, "column": 1})
+        );
+        assert_eq!(
+            diagnostic["location"]["range"]["end"],
+            serde_json::json!({"line": 2, "column": 2})
+        );
+        assert!(diagnostic.get("source").is_none());
+    }
+
+    #[test]
+    fn rdjson_columns_count_bytes() {
+        let module_source = "s = 'é' + x\n";
+        // `x` is the 11th character but starts at byte 11 (zero-indexed), after the two-byte `é`.
+        let errors = vec![sample_error(module_source, 11, 12)];
+        let value: serde_json::Value = serde_json::from_str(&to_json(&errors, false)).unwrap();
+        assert_eq!(
+            value["diagnostics"][0]["location"]["range"]["start"]["column"],
+            12
+        );
+    }
+
+    #[test]
+    fn rdjson_includes_quick_fix_suggestions() {
+        let source = "f('red')\n";
+        let errors =
+            vec![
+                sample_error(source, 3, 6).with_quick_fix(ErrorQuickFix::ReplaceWithEnumMember {
+                    replacement: "Color.RED".to_owned(),
+                }),
+            ];
+        let value: serde_json::Value = serde_json::from_str(&to_json(&errors, false)).unwrap();
+        assert_eq!(
+            value["diagnostics"][0]["suggestions"],
+            serde_json::json!([{
+                "range": {"start": {"line": 1, "column": 3}, "end": {"line": 1, "column": 8}},
+                "text": "Color.RED",
+            }])
+        );
+    }
+
+    #[test]
+    fn rdjsonl_writes_one_standalone_diagnostic_per_line() {
+        let errors = vec![
+            sample_error("x = 1\ny = 2\n", 0, 1),
+            sample_error("x = 1\ny = 2\n", 6, 7),
+        ];
+        let output = to_json(&errors, true);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["source"]["name"], "pyrefly");
+            assert_eq!(value["severity"], "ERROR");
+        }
+    }
+}
@@ -8,7 +8,7 @@
 //! SARIF diagnostic output for the `check` command.
 //!
 //! Only the subset of the SARIF 2.1.0 object model that Pyrefly populates is
-//! modelled here; the report is purely diagnostic and carries no fixes,
+//! modelled here; the report carries diagnostics and their quick fixes, but no
 //! fingerprints or code flows.
 //! <https://docs.oasis-open.org/sarif/sarif/v2.1.0/sarif-v2.1.0.html>
 
@@ -95,6 +95,44 @@ struct SarifResult {
     related_locations: Vec<Location>,
     #[serde(skip_serializing_if = "Option::is_none")]
     baseline_state: Option<SarifBaselineState>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<Fix>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    properties: Option<ResultProperties>,
+}
+
+/// Pyrefly-specific data in a result's property bag.
+#[derive(Serialize)]
+struct ResultProperties {
+    /// The matrix configurations the result appears in, such as `3.9/win32`.
+    configurations: Vec<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Fix {
+    description: Message,
+    artifact_changes: Vec<ArtifactChange>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ArtifactChange {
+    artifact_location: ArtifactLocation,
+    replacements: Vec<Replacement>,
+}
+
+/// An empty `deleted_region` makes the replacement an insertion.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Replacement {
+    deleted_region: Region,
+    inserted_content: ArtifactContent,
+}
+
+#[derive(Serialize)]
+struct ArtifactContent {
+    text: String,
 }
 
 #[derive(Serialize)]
@@ -330,6 +368,30 @@ fn errors_to_sarif(version: &str, relative_to: &Path, errors: &[Error]) -> anyho
                     BaselineStatus::Matched => Some(SarifBaselineState::Unchanged),
                     BaselineStatus::NotConfigured | BaselineStatus::NotCompared => None,
                 },
+                fixes: error
+                    .quick_fixes_with_edits()
+                    .into_iter()
+                    .map(|(title, edits)| Fix {
+                        description: Message { text: title },
+                        artifact_changes: vec![ArtifactChange {
+                            artifact_location: ArtifactLocation {
+                                uri: base_uri.clone(),
+                            },
+                            replacements: edits
+                                .into_iter()
+                                .map(|(range, text)| Replacement {
+                                    deleted_region: region(&error.module().display_range(range)),
+                                    inserted_content: ArtifactContent {
+                                        text: text.to_owned(),
+                                    },
+                                })
+                                .collect(),
+                        }],
+                    })
+                    .collect(),
+                properties: (!error.configurations().is_empty()).then(|| ResultProperties {
+                    configurations: error.configuration_names(),
+                }),
             }
         })
         .collect();
@@ -392,6 +454,9 @@ mod tests {
     use pyrefly_python::module::Module;
     use pyrefly_python::module_name::ModuleName;
     use pyrefly_python::module_path::ModulePath;
+    use pyrefly_python::sys_info::PythonPlatform;
+    use pyrefly_python::sys_info::PythonVersion;
+    use pyrefly_python::sys_info::SysInfo;
     use ruff_notebook::Cell;
     use ruff_notebook::CellMetadata;
     use ruff_notebook::CodeCell;
@@ -403,6 +468,8 @@ mod tests {
     use ruff_text_size::TextSize;
 
     use super::*;
+    use crate::error::error::ErrorQuickFix;
+    use crate::error::error::QuickFixEdit;
 
     const VERSION: &str = "0.0.0";
 
@@ -809,6 +876,80 @@ mod tests {
         assert_eq!(result["locations"][0].get("message"), None);
     }
 
+    #[test]
+    fn results_include_matrix_configurations() {
+        let error = sample_error(
+            PathBuf::from("/repo/foo.py"),
+            "x: int = ''\n",
+            9,
+            11,
+            "bad",
+            ErrorKind::BadAssignment,
+        );
+        assert!(
+            to_json(Path::new("/repo"), std::slice::from_ref(&error))["runs"][0]["results"][0]
+                .get("properties")
+                .is_none()
+        );
+
+        let error = error.with_configurations(vec![SysInfo::new(
+            PythonVersion::new(3, 9, 0),
+            PythonPlatform::new("win32"),
+        )]);
+        assert_eq!(
+            to_json(Path::new("/repo"), &[error])["runs"][0]["results"][0]["properties"],
+            serde_json::json!({"configurations": ["3.9/win32"]})
+        );
+    }
+
+    #[test]
+    fn results_include_quick_fixes() {
+        let error = sample_error(
+            PathBuf::from("/repo/foo.py"),
+            "x = cast(int, 1)\n",
+            4,
+            16,
+            "redundant",
+            ErrorKind::RedundantCast,
+        );
+        assert!(
+            to_json(Path::new("/repo"), std::slice::from_ref(&error))["runs"][0]["results"][0]
+                .get("fixes")
+                .is_none()
+        );
+
+        let error = error.with_quick_fix(ErrorQuickFix::Edits {
+            title: "Remove redundant cast".to_owned(),
+            edits: vec![
+                QuickFixEdit::new(
+                    TextRange::new(TextSize::from(4), TextSize::from(16)),
+                    "1".to_owned(),
+                ),
+                QuickFixEdit::new(TextRange::empty(TextSize::from(0)), "# hi\n".to_owned()),
+            ],
+        });
+        let json = to_json(Path::new("/repo"), &[error]);
+        assert_eq!(
+            json["runs"][0]["results"][0]["fixes"],
+            serde_json::json!([{
+                "description": {"text": "Remove redundant cast"},
+                "artifactChanges": [{
+                    "artifactLocation": {"uri": "foo.py"},
+                    "replacements": [
+                        {
+                            "deletedRegion": {"startLine": 1, "startColumn": 5, "endLine": 1, "endColumn": 17},
+                            "insertedContent": {"text": "1"},
+                        },
+                        {
+                            "deletedRegion": {"startLine": 1, "startColumn": 1, "endLine": 1, "endColumn": 1},
+                            "insertedContent": {"text": "# hi\n"},
+                        },
+                    ],
+                }],
+            }])
+        );
+    }
+
     #[test]
     fn report_uses_version_not_semantic_version() {
         let json = to_json(Path::new("/repo"), &[]);
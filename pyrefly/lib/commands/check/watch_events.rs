@@ -0,0 +1,348 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Machine-readable output for `check --watch`.
+//!
+//! Each recheck cycle prints one JSON object on its own line, listing the diagnostics that
+//! appeared and disappeared since the previous cycle. Diagnostics are keyed by an identity
+//! that ignores their position, so edits elsewhere in a file don't report them as resolved
+//! and added again. Instead, a diagnostic whose position changed is reported as moved.
+
+use std::hash::Hash as _;
+use std::hash::Hasher as _;
+use std::io::Write;
+use std::io::stdout;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_util::absolutize::Absolutize;
+use pyrefly_util::events::CategorizedEvents;
+use pyrefly_util::lined_buffer::DisplayRange;
+use pyrefly_util::lock::Mutex;
+use ruff_text_size::Ranged;
+use serde::Serialize;
+use starlark_map::small_map::Entry;
+use starlark_map::small_map::SmallMap;
+use starlark_map::small_set::SmallSet;
+use xxhash_rust::xxh64::Xxh64;
+
+use crate::error::error::Error;
+use crate::error::legacy::LegacyError;
+use crate::state::load::Load;
+use crate::state::state::Transaction;
+use crate::state::subscriber::Subscriber;
+
+/// How `check --watch` reports the result of each recheck.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum WatchFormat {
+    /// Print the errors in the usual output format after every recheck.
+    #[default]
+    Text,
+    /// Print one JSON object per recheck, describing how the errors changed.
+    JsonLines,
+}
+
+/// Records which modules had any work done on them during a run.
+#[derive(Debug, Default, Clone, Dupe)]
+pub(super) struct RecheckedSubscriber(Arc<Mutex<SmallSet<Handle>>>);
+
+impl Subscriber for RecheckedSubscriber {
+    fn start_work(&self, handle: &Handle) {
+        self.0.lock().insert(handle.dupe());
+    }
+
+    fn finish_work(&self, _: &Transaction<'_>, _: &Handle, _: &Arc<Load>, _: bool) {}
+}
+
+impl RecheckedSubscriber {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    /// The members of `handles` that were worked on.
+    pub(super) fn rechecked(&self, handles: &[Handle]) -> Vec<Handle> {
+        let started = self.0.lock();
+        handles
+            .iter()
+            .filter(|h| started.contains(*h))
+            .map(|h| h.dupe())
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WatchDiagnostic {
+    id: String,
+    #[serde(flatten)]
+    error: LegacyError,
+}
+
+/// The JSON object printed after each recheck.
+#[derive(Debug, Serialize)]
+struct WatchEvent {
+    /// Counts from 0, which is the initial check.
+    cycle: usize,
+    /// Files reported by the watcher as created, modified or removed.
+    changed: Vec<String>,
+    /// Project files that were checked again.
+    rechecked: Vec<String>,
+    added: Vec<WatchDiagnostic>,
+    /// Diagnostics reported earlier that are now at a different position, keeping their ids.
+    moved: Vec<WatchDiagnostic>,
+    /// The ids of diagnostics reported earlier that no longer occur.
+    resolved: Vec<String>,
+    /// The number of diagnostics after this recheck.
+    total: usize,
+    duration_ms: u128,
+}
+
+/// The diagnostics reported so far, so each cycle only reports what changed.
+#[derive(Debug, Default)]
+pub(super) struct WatchEvents {
+    cycle: usize,
+    /// Ids of the diagnostics reported by the previous cycle, in reporting order, with the
+    /// position they were reported at.
+    previous: SmallMap<String, DisplayRange>,
+}
+
+fn display_path(path: &Path, relative_to: &Path) -> String {
+    path.relativize_from(relative_to)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// An identity for each error that stays the same while the error's path, kind, message and
+/// the code it points at are unchanged. Errors that agree on all of those are numbered in
+/// order.
+fn diagnostic_ids(relative_to: &Path, errors: &[Error]) -> Vec<String> {
+    let mut seen: SmallMap<u64, u64> = SmallMap::new();
+    errors
+        .iter()
+        .map(|error| {
+            let mut hasher = Xxh64::new(0);
+            display_path(error.path().as_path(), relative_to).hash(&mut hasher);
+            error.error_kind().to_name().hash(&mut hasher);
+            error.msg().hash(&mut hasher);
+            error.module().code_at(error.range()).hash(&mut hasher);
+            let key = hasher.finish();
+            let occurrence = match seen.entry(key) {
+                Entry::Vacant(e) => *e.insert(0),
+                Entry::Occupied(mut e) => {
+                    *e.get_mut() += 1;
+                    *e.get()
+                }
+            };
+            occurrence.hash(&mut hasher);
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+impl WatchEvents {
+    pub(super) fn new() -> Self {
+        Self::default()
+    }
+
+    fn event(
+        &mut self,
+        relative_to: &Path,
+        events: &CategorizedEvents,
+        rechecked: &[Handle],
+        errors: &[Error],
+        duration: Duration,
+    ) -> WatchEvent {
+        let ids = diagnostic_ids(relative_to, errors);
+        let mut added = Vec::new();
+        let mut moved = Vec::new();
+        for (id, error) in ids.iter().zip(errors) {
+            let diagnostic = || WatchDiagnostic {
+                id: id.clone(),
+                error: LegacyError::from_error(relative_to, error),
+            };
+            match self.previous.get(id) {
+                None => added.push(diagnostic()),
+                Some(range) if range != error.display_range() => moved.push(diagnostic()),
+                Some(_) => {}
+            }
+        }
+        let current: SmallMap<String, DisplayRange> = ids
+            .into_iter()
+            .zip(errors)
+            .map(|(id, error)| (id, error.display_range().clone()))
+            .collect();
+        let resolved = self
+            .previous
+            .keys()
+            .filter(|id| !current.contains_key(*id))
+            .cloned()
+            .collect();
+        let mut changed: Vec<String> = events
+            .created
+            .iter()
+            .chain(&events.modified)
+            .chain(&events.removed)
+            .map(|path| display_path(path, relative_to))
+            .collect();
+        changed.sort();
+        changed.dedup();
+        let mut rechecked: Vec<String> = rechecked
+            .iter()
+            .map(|handle| display_path(handle.path().as_path(), relative_to))
+            .collect();
+        rechecked.sort();
+        rechecked.dedup();
+        let event = WatchEvent {
+            cycle: self.cycle,
+            changed,
+            rechecked,
+            added,
+            moved,
+            resolved,
+            total: current.len(),
+            duration_ms: duration.as_millis(),
+        };
+        self.cycle += 1;
+        self.previous = current;
+        event
+    }
+
+    /// Print the event for a recheck as a single line on stdout.
+    pub(super) fn report(
+        &mut self,
+        relative_to: &Path,
+        events: &CategorizedEvents,
+        rechecked: &[Handle],
+        errors: &[Error],
+        duration: Duration,
+    ) -> anyhow::Result<()> {
+        let event = self.event(relative_to, events, rechecked, errors, duration);
+        let mut out = stdout().lock();
+        serde_json::to_writer(&mut out, &event)?;
+        writeln!(out)?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pyrefly_config::error_kind::ErrorKind;
+    use pyrefly_python::module::Module;
+    use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::module_path::ModulePath;
+    use ruff_text_size::TextRange;
+    use ruff_text_size::TextSize;
+
+    use super::*;
+
+    /// An error on the first occurrence of `code` in `source`.
+    fn sample_error(source: &str, code: &str, msg: &str) -> Error {
+        let module = Module::new(
+            ModuleName::from_str("sample"),
+            ModulePath::filesystem(PathBuf::from("/repo/foo.py")),
+            Arc::new(source.to_owned()),
+        );
+        let start = source.find(code).unwrap() as u32;
+        Error::new(
+            module,
+            TextRange::new(
+                TextSize::from(start),
+                TextSize::from(start + code.len() as u32),
+            ),
+            msg.to_owned(),
+            Vec::new(),
+            ErrorKind::BadAssignment,
+        )
+    }
+
+    fn event(watch: &mut WatchEvents, errors: &[Error]) -> WatchEvent {
+        watch.event(
+            Path::new("/repo"),
+            &CategorizedEvents::default(),
+            &[],
+            errors,
+            Duration::ZERO,
+        )
+    }
+
+    #[test]
+    fn test_ids_ignore_position() {
+        let before = sample_error("x: int = ''\n", "''", "bad");
+        let after = sample_error("\n\nx: int = ''\n", "''", "bad");
+        let other = sample_error("x: int = ''\n", "x", "bad");
+        let relative_to = Path::new("/repo");
+        let ids = diagnostic_ids(relative_to, &[before]);
+        assert_eq!(ids, diagnostic_ids(relative_to, &[after]));
+        assert_ne!(ids, diagnostic_ids(relative_to, &[other]));
+    }
+
+    #[test]
+    fn test_duplicates_get_distinct_ids() {
+        let source = "x: int = ''\ny: int = ''\n";
+        let errors = [
+            sample_error(source, "''", "bad"),
+            sample_error(source, "''", "bad"),
+        ];
+        let ids = diagnostic_ids(Path::new("/repo"), &errors);
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn test_added_and_resolved() {
+        let source = "x: int = ''\ny: str = 1\n";
+        let first = sample_error(source, "''", "first");
+        let second = sample_error(source, "1", "second");
+        let mut watch = WatchEvents::new();
+
+        let initial = event(&mut watch, &[first.clone()]);
+        assert_eq!(initial.cycle, 0);
+        assert_eq!(initial.added.len(), 1);
+        assert_eq!(initial.added[0].error.path, "foo.py");
+        let first_id = initial.added[0].id.clone();
+
+        let next = event(&mut watch, &[first.clone(), second]);
+        assert_eq!(next.cycle, 1);
+        assert_eq!(next.added.len(), 1);
+        assert_ne!(next.added[0].id, first_id);
+        assert!(next.resolved.is_empty());
+        assert_eq!(next.total, 2);
+        let second_id = next.added[0].id.clone();
+
+        let last = event(&mut watch, &[first]);
+        assert!(last.added.is_empty());
+        assert_eq!(last.resolved, vec![second_id]);
+        assert_eq!(last.total, 1);
+    }
+
+    #[test]
+    fn test_moved() {
+        let mut watch = WatchEvents::new();
+        let initial = event(&mut watch, &[sample_error("x: int = ''\n", "''", "bad")]);
+        let id = initial.added[0].id.clone();
+
+        let moved = event(
+            &mut watch,
+            &[sample_error("\n\nx: int = ''\n", "''", "bad")],
+        );
+        assert!(moved.added.is_empty());
+        assert!(moved.resolved.is_empty());
+        assert_eq!(moved.moved.len(), 1);
+        assert_eq!(moved.moved[0].id, id);
+
+        let unchanged = event(
+            &mut watch,
+            &[sample_error("\n\nx: int = ''\n", "''", "bad")],
+        );
+        assert!(unchanged.moved.is_empty());
+    }
+}
@@ -289,7 +289,9 @@ pub fn standard_config_finder(
                 // path that is still useful for this import by including all of its parents.
                 _ => {
                     let parent = match path.details() {
-                        ModulePathDetails::FileSystem(x) | ModulePathDetails::Memory(x) => {
+                        ModulePathDetails::FileSystem(x)
+                        | ModulePathDetails::Memory(x)
+                        | ModulePathDetails::Archive(x) => {
                             if let Some(path) = x.parent() {
                                 path
                             } else {
@@ -406,7 +408,9 @@ mod tests {
                 ModuleNameWithKind::guaranteed(ModuleName::unknown()),
                 &ModulePath::filesystem("".into()),
             );
-        let env = PythonEnvironment::get_default_interpreter_env();
+        let env = PythonEnvironment::get_default_interpreter_env(
+            config.interpreters.environment_discovery(),
+        );
         if let Some(paths) = env.site_package_path {
             for p in paths {
                 assert!(config.site_package_path().collect::<Vec<_>>().contains(&&p));
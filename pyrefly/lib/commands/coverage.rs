@@ -7,6 +7,7 @@
 
 mod check;
 mod collect;
+mod export;
 pub(crate) mod report;
 mod types;
 
@@ -36,7 +37,9 @@ impl CoverageCommand {
         thread_count: ThreadCount,
     ) -> anyhow::Result<CommandExitStatus> {
         match self {
-            CoverageCommand::Report(args) => args.run(config_configurer_wrapper, thread_count),
+            CoverageCommand::Report(args) => {
+                args.run(version, config_configurer_wrapper, thread_count)
+            }
             CoverageCommand::Check(args) => {
                 args.run(version, config_configurer_wrapper, thread_count)
             }
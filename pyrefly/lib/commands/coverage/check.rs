@@ -5,17 +5,27 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
 use anstream::eprintln;
+use anyhow::Context as _;
 use clap::Parser;
 use pyrefly_config::args::ConfigOverrideArgs;
 use pyrefly_config::config::OutputFormat;
 use pyrefly_config::error_kind::Severity;
 use pyrefly_util::display::number_thousands;
+use pyrefly_util::fs_anyhow;
 use pyrefly_util::thread_pool::ThreadCount;
+use serde::Deserialize;
 
 use crate::commands::check::write_errors_to_console;
 use crate::commands::config_finder::ConfigConfigurerWrapper;
 use crate::commands::coverage::collect::collect_module_reports;
+use crate::commands::coverage::types::ModuleReport;
 use crate::commands::coverage::types::SlotCounts;
 use crate::commands::files::FilesArgs;
 use crate::commands::util::CommandExitStatus;
@@ -37,8 +47,15 @@ pub struct CheckArgs {
     strict: bool,
 
     /// Minimum coverage percentage; exit non-zero when coverage is below it.
-    #[clap(long, short = 'f', value_name = "PERCENT", default_value_t = 100.0)]
-    fail_under: f64,
+    /// Defaults to 100, unless `--previous-report` is given.
+    #[clap(long, short = 'f', value_name = "PERCENT")]
+    fail_under: Option<f64>,
+
+    /// A report from `pyrefly coverage report` to compare against. Exit non-zero when the
+    /// coverage of any module or package is lower than in the report, or when a symbol that
+    /// was fully typed no longer is.
+    #[clap(long, value_name = "REPORT_JSON")]
+    previous_report: Option<PathBuf>,
 
     /// Prefer `.pyi` stubs over `.py` files when both are present.
     #[clap(long, default_value_t = true, action = clap::ArgAction::Set)]
@@ -61,12 +78,22 @@ impl CheckArgs {
         thread_count: ThreadCount,
     ) -> anyhow::Result<CommandExitStatus> {
         self.config_override.validate()?;
-        if !(0.0..=100.0).contains(&self.fail_under) {
-            anyhow::bail!(
-                "--fail-under must be between 0 and 100, got {}",
-                self.fail_under
-            );
+        if let Some(fail_under) = self.fail_under
+            && !(0.0..=100.0).contains(&fail_under)
+        {
+            anyhow::bail!("--fail-under must be between 0 and 100, got {fail_under}");
         }
+        // Read the previous report first, so a bad path fails before the slow part.
+        let previous = self
+            .previous_report
+            .as_deref()
+            .map(PreviousReport::read)
+            .transpose()?;
+        let fail_under = match (self.fail_under, &previous) {
+            (Some(fail_under), _) => Some(fail_under),
+            (None, Some(_)) => None,
+            (None, None) => Some(100.0),
+        };
 
         let (files_to_check, config_finder, _) =
             self.files
@@ -106,16 +133,403 @@ impl CheckArgs {
         let root = std::env::current_dir().unwrap_or_default();
         write_errors_to_console(self.output_format, version, &root, &errors)?;
 
-        if coverage + 1e-9 >= self.fail_under {
-            eprintln!("{} {summary}", Severity::Info.painted());
-            Ok(CommandExitStatus::Success)
+        let mut status = CommandExitStatus::Success;
+        if let (Some(previous), Some(path)) = (previous, &self.previous_report) {
+            let current = module_reports
+                .iter()
+                .map(ModuleCoverage::from_report)
+                .collect::<Vec<_>>();
+            let regressions = Regressions::new(&previous.module_reports, &current, self.strict);
+            if !regressions.is_empty() {
+                regressions.print(path);
+                status = CommandExitStatus::UserError;
+            }
+        }
+
+        match fail_under {
+            Some(fail_under) if coverage + 1e-9 < fail_under => {
+                eprintln!(
+                    "{} {summary} is below the {fail_under:.2}% threshold",
+                    Severity::Error.painted(),
+                );
+                status = CommandExitStatus::UserError;
+            }
+            _ => eprintln!("{} {summary}", Severity::Info.painted()),
+        }
+        Ok(status)
+    }
+}
+
+/// The parts of a `pyrefly coverage report` needed to look for regressions.
+#[derive(Debug, Deserialize)]
+struct PreviousReport {
+    module_reports: Vec<ModuleCoverage>,
+}
+
+impl PreviousReport {
+    fn read(path: &Path) -> anyhow::Result<Self> {
+        serde_json::from_str(&fs_anyhow::read_to_string(path)?).with_context(|| {
+            format!(
+                "`{}` is not a report from `pyrefly coverage report`",
+                path.display()
+            )
+        })
+    }
+}
+
+/// The coverage of a module and of each of its symbols.
+#[derive(Debug, Deserialize)]
+struct ModuleCoverage {
+    name: String,
+    #[serde(flatten)]
+    slots: SlotCounts,
+    #[serde(rename = "symbol_reports")]
+    symbols: Vec<SymbolCoverage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SymbolCoverage {
+    /// The fully qualified name.
+    name: String,
+    #[serde(flatten)]
+    slots: SlotCounts,
+}
+
+impl ModuleCoverage {
+    fn from_report(report: &ModuleReport) -> Self {
+        Self {
+            name: report.name.clone(),
+            slots: report.slots,
+            symbols: report
+                .symbol_reports
+                .iter()
+                .map(|symbol| SymbolCoverage {
+                    name: symbol.name().to_owned(),
+                    slots: *symbol.slots(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn percentage(slots: &SlotCounts, strict: bool) -> f64 {
+    if strict {
+        slots.strict_coverage()
+    } else {
+        slots.coverage()
+    }
+}
+
+fn fully_typed(slots: &SlotCounts, strict: bool) -> bool {
+    slots.n_typable > 0
+        && if strict {
+            slots.n_typed == slots.n_typable
         } else {
+            slots.n_untyped == 0
+        }
+}
+
+/// The total coverage of each module, and of each package that contains a module. A package
+/// includes its `__init__` module, which has the same name.
+fn coverage_by_name(modules: &[ModuleCoverage]) -> BTreeMap<(String, bool), SlotCounts> {
+    let packages: BTreeSet<&str> = modules
+        .iter()
+        .flat_map(|module| {
+            module
+                .name
+                .match_indices('.')
+                .map(|(i, _)| &module.name[..i])
+        })
+        .collect();
+    let mut res: BTreeMap<(String, bool), SlotCounts> = BTreeMap::new();
+    for module in modules {
+        let entry = res.entry((module.name.clone(), false)).or_default();
+        *entry = entry.merge(module.slots);
+        let prefixes = module
+            .name
+            .match_indices('.')
+            .map(|(i, _)| &module.name[..i])
+            .chain([module.name.as_str()]);
+        for package in prefixes.filter(|prefix| packages.contains(prefix)) {
+            let entry = res.entry((package.to_owned(), true)).or_default();
+            *entry = entry.merge(module.slots);
+        }
+    }
+    res
+}
+
+/// A module or package whose coverage went down.
+#[derive(Debug, PartialEq)]
+struct CoverageDrop {
+    name: String,
+    is_package: bool,
+    before: f64,
+    after: f64,
+}
+
+/// How coverage got worse since a previous report. Modules, packages and symbols that are new
+/// or were removed are not compared.
+#[derive(Debug, Default)]
+struct Regressions {
+    /// Sorted by name.
+    drops: Vec<CoverageDrop>,
+    /// Symbols that were fully typed, and now aren't.
+    symbols: Vec<String>,
+}
+
+impl Regressions {
+    fn new(previous: &[ModuleCoverage], current: &[ModuleCoverage], strict: bool) -> Self {
+        let before = coverage_by_name(previous);
+        let drops = coverage_by_name(current)
+            .into_iter()
+            .filter_map(|(key, slots)| {
+                let before = percentage(before.get(&key)?, strict);
+                let after = percentage(&slots, strict);
+                let (name, is_package) = key;
+                (after + 1e-9 < before).then_some(CoverageDrop {
+                    name,
+                    is_package,
+                    before,
+                    after,
+                })
+            })
+            .collect();
+        let previously_typed: HashMap<(&str, &str), bool> = previous
+            .iter()
+            .flat_map(|module| {
+                module.symbols.iter().map(|symbol| {
+                    (
+                        (module.name.as_str(), symbol.name.as_str()),
+                        fully_typed(&symbol.slots, strict),
+                    )
+                })
+            })
+            .collect();
+        let mut symbols: Vec<String> = current
+            .iter()
+            .flat_map(|module| {
+                module
+                    .symbols
+                    .iter()
+                    .filter(|symbol| {
+                        previously_typed
+                            .get(&(module.name.as_str(), symbol.name.as_str()))
+                            .is_some_and(|typed| *typed)
+                            && !fully_typed(&symbol.slots, strict)
+                    })
+                    .map(|symbol| symbol.name.clone())
+            })
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        Self { drops, symbols }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.drops.is_empty() && self.symbols.is_empty()
+    }
+
+    /// A table of the modules and packages whose coverage went down.
+    fn table(&self) -> String {
+        let labels = self
+            .drops
+            .iter()
+            .map(|drop| {
+                if drop.is_package {
+                    format!("{} (package)", drop.name)
+                } else {
+                    drop.name.clone()
+                }
+            })
+            .collect::<Vec<_>>();
+        let width = labels
+            .iter()
+            .map(|label| label.len())
+            .chain(["MODULE".len()])
+            .max()
+            .unwrap_or_default();
+        let mut res = format!("  {:<width$}  {:>7}  {:>7}\n", "MODULE", "BEFORE", "AFTER");
+        for (label, drop) in labels.iter().zip(&self.drops) {
+            res.push_str(&format!(
+                "  {label:<width$}  {:>6.2}%  {:>6.2}%\n",
+                drop.before, drop.after
+            ));
+        }
+        res
+    }
+
+    fn print(&self, previous_report: &Path) {
+        if !self.drops.is_empty() {
             eprintln!(
-                "{} {summary} is below the {:.2}% threshold",
+                "{} Coverage decreased since `{}`:\n{}",
                 Severity::Error.painted(),
-                self.fail_under
+                previous_report.display(),
+                self.table().trim_end(),
             );
-            Ok(CommandExitStatus::UserError)
         }
+        if !self.symbols.is_empty() {
+            eprintln!(
+                "{} Symbols no longer fully typed since `{}`:",
+                Severity::Error.painted(),
+                previous_report.display(),
+            );
+            for symbol in &self.symbols {
+                eprintln!("  {symbol}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slots(n_typed: usize, n_any: usize, n_untyped: usize) -> SlotCounts {
+        SlotCounts {
+            n_typable: n_typed + n_any + n_untyped,
+            n_typed,
+            n_any,
+            n_untyped,
+        }
+    }
+
+    fn module(name: &str, symbols: &[(&str, SlotCounts)]) -> ModuleCoverage {
+        ModuleCoverage {
+            name: name.to_owned(),
+            slots: symbols
+                .iter()
+                .fold(SlotCounts::default(), |acc, (_, x)| acc.merge(*x)),
+            symbols: symbols
+                .iter()
+                .map(|(symbol, slots)| SymbolCoverage {
+                    name: format!("{name}.{symbol}"),
+                    slots: *slots,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_previous_report_from_json() {
+        let report: PreviousReport = serde_json::from_value(serde_json::json!({
+            "schema_version": "0.2",
+            "module_reports": [{
+                "name": "robot.legs",
+                "path": "robot/legs.py",
+                "names": ["walk"],
+                "line_count": 2,
+                "symbol_reports": [{
+                    "kind": "function",
+                    "name": "robot.legs.walk",
+                    "n_typable": 3,
+                    "n_typed": 2,
+                    "n_any": 0,
+                    "n_untyped": 1,
+                    "location": {"line": 1, "column": 1},
+                }],
+                "type_ignores": [],
+                "n_typable": 3,
+                "n_typed": 2,
+                "n_any": 0,
+                "n_untyped": 1,
+                "coverage": 66.67,
+                "strict_coverage": 66.67,
+                "n_functions": 1,
+            }],
+            "summary": {},
+        }))
+        .unwrap();
+        let module = &report.module_reports[0];
+        assert_eq!(module.name, "robot.legs");
+        assert_eq!(module.slots.n_untyped, 1);
+        assert_eq!(module.symbols[0].name, "robot.legs.walk");
+        assert_eq!(module.symbols[0].slots.n_typed, 2);
+    }
+
+    #[test]
+    fn test_regressions() {
+        let previous = [
+            module("robot", &[("VERSION", slots(1, 0, 0))]),
+            module(
+                "robot.legs",
+                &[("walk", slots(2, 0, 0)), ("stop", slots(0, 0, 1))],
+            ),
+            module("robot.arms", &[("wave", slots(1, 0, 0))]),
+        ];
+        let current = [
+            module("robot", &[("VERSION", slots(1, 0, 0))]),
+            module(
+                "robot.legs",
+                &[
+                    ("walk", slots(1, 0, 1)),
+                    ("stop", slots(0, 0, 1)),
+                    ("run", slots(1, 0, 0)),
+                ],
+            ),
+            module("robot.arms", &[("wave", slots(1, 0, 0))]),
+            // New modules aren't compared, but count towards their package.
+            module("robot.head", &[("nod", slots(0, 0, 3))]),
+        ];
+        let regressions = Regressions::new(&previous, &current, false);
+        assert_eq!(
+            regressions.drops,
+            vec![
+                CoverageDrop {
+                    name: "robot".to_owned(),
+                    is_package: true,
+                    before: 80.0,
+                    after: 4.0 / 9.0 * 100.0,
+                },
+                CoverageDrop {
+                    name: "robot.legs".to_owned(),
+                    is_package: false,
+                    before: 2.0 / 3.0 * 100.0,
+                    after: 50.0,
+                },
+            ]
+        );
+        assert_eq!(regressions.symbols, vec!["robot.legs.walk".to_owned()]);
+        assert!(Regressions::new(&previous, &previous, false).is_empty());
+    }
+
+    #[test]
+    fn test_regressions_strict() {
+        let previous = [module("m", &[("f", slots(1, 0, 0))])];
+        let current = [module("m", &[("f", slots(0, 1, 0))])];
+        assert!(Regressions::new(&previous, &current, false).is_empty());
+        let regressions = Regressions::new(&previous, &current, true);
+        assert_eq!(regressions.drops.len(), 1);
+        assert_eq!(regressions.symbols, vec!["m.f".to_owned()]);
+    }
+
+    #[test]
+    fn test_table() {
+        let regressions = Regressions {
+            drops: vec![
+                CoverageDrop {
+                    name: "robot".to_owned(),
+                    is_package: true,
+                    before: 80.0,
+                    after: 50.0,
+                },
+                CoverageDrop {
+                    name: "robot.legs".to_owned(),
+                    is_package: false,
+                    before: 100.0,
+                    after: 5.5,
+                },
+            ],
+            symbols: Vec::new(),
+        };
+        assert_eq!(
+            regressions.table(),
+            [
+                "  MODULE            BEFORE    AFTER",
+                "  robot (package)   80.00%   50.00%",
+                "  robot.legs       100.00%    5.50%",
+                "",
+            ]
+            .join("\n")
+        );
     }
 }
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::path::Path;
@@ -25,6 +26,7 @@ use pyrefly_python::short_identifier::ShortIdentifier;
 use pyrefly_types::class::Class;
 use pyrefly_types::class::ClassType;
 use pyrefly_types::function::PropertyRole;
+use pyrefly_types::types::AnyStyle;
 use pyrefly_types::types::Type;
 use pyrefly_util::forgetter::Forgetter;
 use pyrefly_util::includes::Includes;
@@ -232,20 +234,22 @@ fn classify_annotation_rank(has_annotation: bool, resolved_is_known: Option<bool
     }
 }
 
-/// Slot classification for an optional annotation binding.
+/// Slot classification for an optional annotation binding, along with how the annotation
+/// became `Any` if it did.
 fn classify_annotation(
     bindings: &Bindings,
     answers: &Answers,
     annotation_idx: Option<Idx<KeyAnnotation>>,
-) -> SlotCounts {
+) -> (SlotCounts, Option<AnyStyle>) {
     let has_annotation = annotation_idx
         .is_some_and(|idx| matches!(bindings.get(idx), BindingAnnotation::AnnotateExpr(..)));
     let resolved_ty = annotation_idx.and_then(|idx| {
         answers
             .get_idx(idx)
-            .and_then(|awt| awt.annotation.ty.as_ref().map(is_type_known))
+            .and_then(|awt| awt.annotation.ty.clone())
     });
-    classify_annotation_rank(has_annotation, resolved_ty).into()
+    let rank = classify_annotation_rank(has_annotation, resolved_ty.as_ref().map(is_type_known));
+    (rank.into(), resolved_ty.as_ref().and_then(any_style))
 }
 
 /// Returns true if the name is public: does not start with `_`, or is a dunder (`__x__`).
@@ -504,20 +508,20 @@ fn parse_variables(
             Some(ExportLocation::ThisModule(export)) => export.location,
             _ => continue,
         };
-        let slots = match binding {
+        let (slots, any_style) = match binding {
             BindingExport::AnnotatedForward(annot_idx, key_idx) => {
                 // IMPLICIT: type aliases are type-level constructs with 0 slots.
                 if matches!(
                     bindings.get(*key_idx),
                     Binding::TypeAlias(_) | Binding::TypeAliasRef(_)
                 ) {
-                    SlotCounts::default()
+                    (SlotCounts::default(), None)
                 } else {
                     classify_annotation(bindings, answers, Some(*annot_idx))
                 }
             }
             BindingExport::Forward(idx) | BindingExport::PromoteForward(idx) => {
-                match bindings.get(*idx) {
+                let slots = match bindings.get(*idx) {
                     // Skip injected implicit globals
                     Binding::Global(_) => continue,
                     // IMPLICIT: special type forms and type aliases have 0 slots
@@ -548,12 +552,14 @@ fn parse_variables(
                         continue;
                     }
                     _ => SlotCounts::untyped(),
-                }
+                };
+                (slots, None)
             }
         };
         variables.push(Variable {
             name: qualified_name,
             slots,
+            any_style,
             location: range_to_location(module, range),
             range,
         });
@@ -582,7 +588,7 @@ fn inherited_annotation_slots(
     handle: &Handle,
     class_idx: Idx<KeyClass>,
     field_name: &Name,
-) -> Option<SlotCounts> {
+) -> Option<(SlotCounts, Option<AnyStyle>)> {
     let class = answers.get_idx(class_idx).and_then(|r| r.0.clone())?;
     class_mro(bindings, answers, &class)
         .ancestors_no_object()
@@ -663,7 +669,7 @@ fn parse_instance_attrs(
         // Only count instance attrs from recognized methods (__init__, etc.)
         // or a schema class body field. We handle recognized-method fields with full
         // slot classification, and schema body fields as IMPLICIT (0 typable).
-        let slots = match &field.definition {
+        let (slots, any_style) = match &field.definition {
             ClassFieldDefinition::DefinedInMethod {
                 annotation, method, ..
             } => {
@@ -671,7 +677,7 @@ fn parse_instance_attrs(
                     continue;
                 }
                 if annotation.is_none()
-                    && let Some(slots) = inherited_annotation_slots(
+                    && let Some(inherited) = inherited_annotation_slots(
                         bindings,
                         answers,
                         transaction,
@@ -680,7 +686,7 @@ fn parse_instance_attrs(
                         &field.name,
                     )
                 {
-                    slots
+                    inherited
                 } else {
                     classify_annotation(bindings, answers, *annotation)
                 }
@@ -692,7 +698,7 @@ fn parse_instance_attrs(
             | ClassFieldDefinition::AssignedInBody { .. }
                 if is_schema_class(bindings, answers, cls_binding) =>
             {
-                SlotCounts::default()
+                (SlotCounts::default(), None)
             }
             // Non-schema fields only count when initialized in a recognized method,
             // or declared in a stub.
@@ -713,6 +719,7 @@ fn parse_instance_attrs(
         attrs.push(Variable {
             name: format!("{}.{}", class_name, field.name),
             slots,
+            any_style,
             location: range_to_location(module, range),
             range,
         });
@@ -817,7 +824,7 @@ fn parse_functions(
             let return_idx = bindings.key_to_idx(&Key::ReturnType(*id));
             let is_return_annotated = has_return_annotation(bindings, return_idx);
             let resolved_return_ty = if is_return_annotated {
-                answers.get_type_at(return_idx).map(|t| is_type_known(&t))
+                answers.get_type_at(return_idx)
             } else {
                 None
             };
@@ -842,8 +849,15 @@ fn parse_functions(
             let return_rank = if skip_return {
                 SlotRank::Skip
             } else {
-                classify_annotation_rank(is_return_annotated, resolved_return_ty)
+                classify_annotation_rank(
+                    is_return_annotated,
+                    resolved_return_ty.as_ref().map(is_type_known),
+                )
             };
+            let return_any_style = resolved_return_ty
+                .as_ref()
+                .and_then(any_style)
+                .filter(|_| return_rank == SlotRank::Any);
             let mut func_slots = SlotCounts::from(return_rank);
             let mut n_params = 0usize;
             let mut non_self_index = 0usize;
@@ -859,11 +873,14 @@ fn parse_functions(
                     bindings
                         .key_to_idx_hashed_opt(Hashed::new(&annot_key))
                         .and_then(|annot_idx| answers.get_idx(annot_idx))
-                        .and_then(|awt| awt.annotation.ty.as_ref().map(is_type_known))
+                        .and_then(|awt| awt.annotation.ty.clone())
                 } else {
                     None
                 };
-                let rank = classify_annotation_rank(param.annotation.is_some(), resolved_param_ty);
+                let rank = classify_annotation_rank(
+                    param.annotation.is_some(),
+                    resolved_param_ty.as_ref().map(is_type_known),
+                );
 
                 // Implicit dunder params are always excluded, even when annotated.
                 let is_implicit_param = !is_self
@@ -890,12 +907,14 @@ fn parse_functions(
                 parameters.push(Parameter {
                     merge_key: effective_key,
                     rank,
+                    any_style: resolved_param_ty.as_ref().and_then(any_style),
                 });
             }
 
             functions.push(Function {
                 name: func_name,
                 return_rank,
+                return_any_style,
                 parameters,
                 property_role,
                 n_params,
@@ -933,6 +952,7 @@ fn parse_functions(
                     location,
                     range,
                     return_rank: target_func.return_rank,
+                    return_any_style: target_func.return_any_style,
                     parameters: target_func.parameters.clone(),
                     property_role: target_func.property_role.clone(),
                     n_params: target_func.n_params,
@@ -957,6 +977,14 @@ fn is_type_known(ty: &Type) -> bool {
     !ty.is_any()
 }
 
+/// How `ty` became `Any`, if it is a bare `Any`.
+fn any_style(ty: &Type) -> Option<AnyStyle> {
+    match ty {
+        Type::Any(style) => Some(*style),
+        _ => None,
+    }
+}
+
 /// Dunder methods whose return type is fully determined by the protocol
 /// and therefore don't need an explicit annotation for coverage.
 fn is_implicit_dunder_return(name: &str) -> bool {
@@ -1523,6 +1551,7 @@ fn build_module_report(
         strict_coverage: total_slots.strict_coverage(),
         slots: total_slots,
         symbols,
+        files: Vec::new(),
     }
 }
 
@@ -1589,6 +1618,112 @@ fn collect_untyped_errors(
     }
 }
 
+/// The worst annotation quality among `slots`, or `None` if there is nothing to annotate.
+fn slots_rank(slots: &SlotCounts) -> Option<SlotRank> {
+    if slots.n_typable == 0 {
+        None
+    } else if slots.n_untyped > 0 {
+        Some(SlotRank::Untyped)
+    } else if slots.n_any > 0 {
+        Some(SlotRank::Any)
+    } else {
+        Some(SlotRank::Typed)
+    }
+}
+
+/// What an annotation of `rank` lacks, and where its `Any` comes from, or `None` if it is
+/// fully typed.
+fn rank_problem(rank: SlotRank, any_style: Option<AnyStyle>) -> Option<&'static str> {
+    match (rank, any_style) {
+        (SlotRank::Untyped, _) => Some("has no annotation, so it is implicitly `Any`"),
+        (SlotRank::Any, Some(AnyStyle::Explicit)) => Some("is annotated as `Any`"),
+        (SlotRank::Any, _) => Some(
+            "is annotated with a name that is `Any`, such as one from an untyped or unresolved import",
+        ),
+        (SlotRank::Typed | SlotRank::Skip, _) => None,
+    }
+}
+
+/// Explain which annotations of `func` are missing or contain `Any`.
+fn function_note(func: &Function) -> String {
+    let mut problems = Vec::new();
+    for param in &func.parameters {
+        let (Some(key), Some(problem)) =
+            (&param.merge_key, rank_problem(param.rank, param.any_style))
+        else {
+            continue;
+        };
+        let param = match key {
+            ParamKey::Positional(i) => format!("positional parameter {i}"),
+            ParamKey::Named(name) => format!("parameter `{name}`"),
+            ParamKey::VarPositional => "the `*` parameter".to_owned(),
+            ParamKey::VarKeyword => "the `**` parameter".to_owned(),
+        };
+        problems.push(format!("{param} {problem}"));
+    }
+    if let Some(problem) = rank_problem(func.return_rank, func.return_any_style) {
+        problems.push(format!("the return type {problem}"));
+    }
+    if problems.is_empty() {
+        format!("`{}` is fully typed", func.name)
+    } else {
+        format!("`{}`: {}", func.name, problems.join("; "))
+    }
+}
+
+/// The lines of `module` that define the public `functions` and `variables`. Symbols merged
+/// from a `.py` file into its stub are `missing_from_stub`, and count as untyped.
+fn collect_file_coverage(
+    module: &Module,
+    functions: &[Function],
+    variables: &[Variable],
+    missing_from_stub: bool,
+    public_fqns: Option<&HashSet<String>>,
+) -> FileCoverage {
+    let module_prefix = format!("{}.", module.name());
+    let is_public =
+        |name: &str| public_fqns.is_none_or(|fqns| is_public_fqn(name, &module_prefix, fqns));
+    let symbols = functions
+        .iter()
+        .filter(|func| is_public(&func.name))
+        .filter_map(|func| {
+            let rank = slots_rank(&func.slots)?;
+            Some((func.location.line, rank, &func.name, function_note(func)))
+        })
+        .chain(
+            variables
+                .iter()
+                .filter(|var| is_public(&var.name))
+                .filter_map(|var| {
+                    let rank = slots_rank(&var.slots)?;
+                    let note = match rank_problem(rank, var.any_style) {
+                        Some(problem) => format!("`{}` {problem}", var.name),
+                        None => format!("`{}` is fully typed", var.name),
+                    };
+                    Some((var.location.line, rank, &var.name, note))
+                }),
+        );
+    let mut lines: BTreeMap<usize, LineCoverage> = BTreeMap::new();
+    for (line, rank, name, note) in symbols {
+        let note = if missing_from_stub {
+            format!("`{name}` is missing from the stub")
+        } else {
+            note
+        };
+        let entry = lines.entry(line).or_insert(LineCoverage {
+            line,
+            rank,
+            notes: Vec::new(),
+        });
+        entry.rank = entry.rank.min(rank);
+        entry.notes.push(note);
+    }
+    FileCoverage {
+        module: module.dupe(),
+        lines: lines.into_values().collect(),
+    }
+}
+
 pub fn collect_module_reports(
     files_to_check: Box<dyn Includes>,
     config_finder: ConfigFinder,
@@ -1761,6 +1896,13 @@ pub fn collect_module_reports(
                 public_fqns.as_ref(),
             );
         }
+        let mut files = vec![collect_file_coverage(
+            &symbols.module,
+            &symbols.functions,
+            &symbols.variables,
+            false,
+            public_fqns.as_ref(),
+        )];
 
         // When a .pyi stub shadows a .py file, include uncovered .py symbols.
         if let Some(py_handle) = pyi_to_py.get(&handle.path().as_path().to_path_buf())
@@ -1780,12 +1922,19 @@ pub fn collect_module_reports(
                     public_fqns.as_ref(),
                 );
             }
+            files.push(collect_file_coverage(
+                &py_module,
+                &symbols.functions[own_functions..],
+                &symbols.variables[own_variables..],
+                true,
+                public_fqns.as_ref(),
+            ));
         }
 
         let derived_name = handle.module().to_string();
         let name = module_name_override.clone().unwrap_or(derived_name.clone());
         let path = handle.path().as_path().display().to_string();
-        module_reports.push(build_module_report(
+        let mut report = build_module_report(
             name,
             path,
             &derived_name,
@@ -1794,7 +1943,9 @@ pub fn collect_module_reports(
             &symbols.variables,
             &symbols.classes,
             symbols.suppressions,
-        ));
+        );
+        report.files = files;
+        module_reports.push(report);
     }
 
     if let Some(public_fqns) = &public_fqns {
@@ -2343,6 +2494,54 @@ mod tests {
         compare_snapshot("any_detection.expected.json", &report);
     }
 
+    /// The line notes of the HTML report say where each `Any` comes from.
+    #[test]
+    fn test_file_coverage_notes() {
+        let code = "\
+from typing import Any
+from missing import Thing  # pyrefly: ignore
+
+def f(a, /, b: Any, c: Thing) -> int: ...
+
+x: Any = 1
+";
+        let (state, handle_fn) = TestEnv::one("test", code)
+            .with_default_require_level(Require::Everything)
+            .to_state();
+        let symbols =
+            ModuleSymbols::collect(&state.transaction(), &handle_fn("test"), false).unwrap();
+        let file = collect_file_coverage(
+            &symbols.module,
+            &symbols.functions,
+            &symbols.variables,
+            false,
+            None,
+        );
+        let notes = file
+            .lines
+            .iter()
+            .map(|line| (line.line, line.rank, line.notes.join("\n")))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            notes,
+            vec![
+                (
+                    4,
+                    SlotRank::Untyped,
+                    "`test.f`: positional parameter 1 has no annotation, so it is implicitly `Any`; \
+                     parameter `b` is annotated as `Any`; \
+                     parameter `c` is annotated with a name that is `Any`, such as one from an untyped or unresolved import"
+                        .to_owned()
+                ),
+                (
+                    6,
+                    SlotRank::Any,
+                    "`test.x` is annotated as `Any`".to_owned()
+                ),
+            ]
+        );
+    }
+
     #[test]
     fn test_report_multi_tool_suppressions() {
         let report = build_module_report_for_test("multi_tool_suppressions.py");
@@ -2706,6 +2905,7 @@ def g(x: int) -> int:
                 n_properties: 0,
                 n_type_ignores: 0,
             },
+            files: Vec::new(),
         };
 
         let public_fqns: HashSet<String> = ["pkg.Foo", "pkg.bar"]
@@ -0,0 +1,343 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Type coverage in the formats of code coverage tools, so that it shows up in the same CI
+//! views as test coverage. A line counts as covered when every annotation of the symbols it
+//! defines is fully typed, with no `Any`. Only the lines that define public functions and
+//! variables are counted: statements in function bodies are not, even when they use values
+//! that are `Any`.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use pyrefly_python::module::Module;
+use pyrefly_util::absolutize::Absolutize;
+use pyrefly_util::fs_anyhow;
+
+use crate::commands::check::xml_escape_attr;
+use crate::commands::coverage::types::LineCoverage;
+use crate::commands::coverage::types::ModuleReport;
+use crate::commands::coverage::types::SlotRank;
+
+/// A source file and the lines in it that define symbols.
+struct SourceFile<'a> {
+    /// Relative to the root, with forward slashes.
+    path: String,
+    module: &'a Module,
+    /// Sorted by line.
+    lines: Vec<LineCoverage>,
+}
+
+impl SourceFile<'_> {
+    fn covered(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| line.rank == SlotRank::Typed)
+            .count()
+    }
+}
+
+/// The files of all the reports, sorted by path. A file that appears in more than one report
+/// is listed once.
+fn source_files<'a>(reports: &'a [ModuleReport], root: &Path) -> Vec<SourceFile<'a>> {
+    let mut files: BTreeMap<String, (&Module, BTreeMap<usize, LineCoverage>)> = BTreeMap::new();
+    for file in reports.iter().flat_map(|report| &report.files) {
+        let path = file
+            .module
+            .path()
+            .as_path()
+            .relativize_from(root)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let (_, lines) = files
+            .entry(path)
+            .or_insert_with(|| (&file.module, BTreeMap::new()));
+        for line in &file.lines {
+            lines.entry(line.line).or_insert_with(|| line.clone());
+        }
+    }
+    files
+        .into_iter()
+        .map(|(path, (module, lines))| SourceFile {
+            path,
+            module,
+            lines: lines.into_values().collect(),
+        })
+        .collect()
+}
+
+/// `covered / valid`, or 1 if there is nothing to cover.
+fn rate(covered: usize, valid: usize) -> String {
+    let rate = if valid == 0 {
+        1.0
+    } else {
+        covered as f64 / valid as f64
+    };
+    format!("{rate:.4}")
+}
+
+/// A Cobertura XML report, as read by most CI systems.
+pub fn cobertura(reports: &[ModuleReport], root: &Path, version: &str, timestamp: u64) -> String {
+    let files = source_files(reports, root);
+    let covered: usize = files.iter().map(SourceFile::covered).sum();
+    let valid: usize = files.iter().map(|file| file.lines.len()).sum();
+    // Cobertura groups classes, which are files for Python, into packages, which are
+    // directories.
+    let mut packages: BTreeMap<String, Vec<&SourceFile>> = BTreeMap::new();
+    for file in &files {
+        let package = match file.path.rsplit_once('/') {
+            Some((dir, _)) => dir.replace('/', "."),
+            None => ".".to_owned(),
+        };
+        packages.entry(package).or_default().push(file);
+    }
+
+    let mut res = String::from("<?xml version=\"1.0\" ?>\n");
+    res.push_str(&format!(
+        "<coverage version=\"{}\" timestamp=\"{timestamp}\" lines-valid=\"{valid}\" lines-covered=\"{covered}\" line-rate=\"{}\" branches-valid=\"0\" branches-covered=\"0\" branch-rate=\"0\" complexity=\"0\">\n",
+        xml_escape_attr(version),
+        rate(covered, valid),
+    ));
+    res.push_str(&format!(
+        "  <sources>\n    <source>{}</source>\n  </sources>\n  <packages>\n",
+        xml_escape_attr(&root.to_string_lossy()),
+    ));
+    for (package, files) in &packages {
+        let covered: usize = files.iter().map(|file| file.covered()).sum();
+        let valid: usize = files.iter().map(|file| file.lines.len()).sum();
+        res.push_str(&format!(
+            "    <package name=\"{}\" line-rate=\"{}\" branch-rate=\"0\" complexity=\"0\">\n      <classes>\n",
+            xml_escape_attr(package),
+            rate(covered, valid),
+        ));
+        for file in files {
+            let name = file.path.rsplit('/').next().unwrap_or(&file.path);
+            res.push_str(&format!(
+                "        <class name=\"{}\" filename=\"{}\" line-rate=\"{}\" branch-rate=\"0\" complexity=\"0\">\n          <methods/>\n          <lines>\n",
+                xml_escape_attr(name),
+                xml_escape_attr(&file.path),
+                rate(file.covered(), file.lines.len()),
+            ));
+            for line in &file.lines {
+                res.push_str(&format!(
+                    "            <line number=\"{}\" hits=\"{}\" branch=\"false\"/>\n",
+                    line.line,
+                    u8::from(line.rank == SlotRank::Typed),
+                ));
+            }
+            res.push_str("          </lines>\n        </class>\n");
+        }
+        res.push_str("      </classes>\n    </package>\n");
+    }
+    res.push_str("  </packages>\n</coverage>\n");
+    res
+}
+
+/// An LCOV tracefile, as read by `genhtml` and most coverage services.
+pub fn lcov(reports: &[ModuleReport], root: &Path) -> String {
+    let mut res = String::new();
+    for file in source_files(reports, root) {
+        res.push_str(&format!("TN:\nSF:{}\n", file.path));
+        for line in &file.lines {
+            res.push_str(&format!(
+                "DA:{},{}\n",
+                line.line,
+                u8::from(line.rank == SlotRank::Typed)
+            ));
+        }
+        res.push_str(&format!(
+            "LF:{}\nLH:{}\nend_of_record\n",
+            file.lines.len(),
+            file.covered()
+        ));
+    }
+    res
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { padding: 0 0.5em; text-align: left; }
+table.source { font-family: monospace; }
+table.source pre { margin: 0; }
+td.lineno { color: #888; text-align: right; user-select: none; }
+.typed { background: #dfd; }
+.any { background: #ffd; }
+.untyped { background: #fdd; }
+";
+
+fn html_page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n{body}</body>\n</html>\n",
+        xml_escape_attr(title)
+    )
+}
+
+fn rank_class(rank: SlotRank) -> &'static str {
+    match rank {
+        SlotRank::Typed => "typed",
+        SlotRank::Any => "any",
+        SlotRank::Untyped | SlotRank::Skip => "untyped",
+    }
+}
+
+/// The percentage of the lines of `file` that are fully typed.
+fn percent_covered(file: &SourceFile) -> f64 {
+    if file.lines.is_empty() {
+        100.0
+    } else {
+        file.covered() as f64 / file.lines.len() as f64 * 100.0
+    }
+}
+
+/// A page showing the source of `file`, with each line that defines symbols colored by how
+/// well they are typed, and a tooltip explaining why.
+fn html_file(file: &SourceFile) -> String {
+    let mut body = format!(
+        "<p><a href=\"index.html\">Index</a></p>\n<h1>{}</h1>\n<p>{} of {} lines fully typed ({:.2}%). Lines are <span class=\"typed\">fully typed</span>, <span class=\"any\">use <code>Any</code></span> or are <span class=\"untyped\">missing annotations</span>; hover over a line for details.</p>\n<table class=\"source\">\n",
+        xml_escape_attr(&file.path),
+        file.covered(),
+        file.lines.len(),
+        percent_covered(file),
+    );
+    let mut lines = file.lines.iter().peekable();
+    for (i, source) in file.module.contents().lines().enumerate() {
+        let number = i + 1;
+        let coverage = lines.next_if(|line| line.line == number);
+        let attrs = match coverage {
+            Some(line) => format!(
+                " class=\"{}\" title=\"{}\"",
+                rank_class(line.rank),
+                xml_escape_attr(&line.notes.join("\n"))
+            ),
+            None => String::new(),
+        };
+        body.push_str(&format!(
+            "<tr{attrs}><td class=\"lineno\">{number}</td><td><pre>{}</pre></td></tr>\n",
+            xml_escape_attr(source)
+        ));
+    }
+    body.push_str("</table>\n");
+    html_page(&file.path, &body)
+}
+
+/// The name of the page for the file at `path`.
+fn html_file_name(path: &str) -> String {
+    format!("{}.html", path.replace(['/', ':'], "."))
+}
+
+/// Write an HTML report to `dir`: an `index.html` listing every file, and a page for each.
+pub fn write_html(reports: &[ModuleReport], root: &Path, dir: &Path) -> anyhow::Result<()> {
+    let files = source_files(reports, root);
+    fs_anyhow::create_dir_all(dir)?;
+    let mut body = String::from(
+        "<h1>Type coverage</h1>\n<table>\n<tr><th>File</th><th>Lines</th><th>Fully typed</th><th>Any</th><th>Untyped</th><th>Coverage</th></tr>\n",
+    );
+    for file in &files {
+        let count = |rank| file.lines.iter().filter(|line| line.rank == rank).count();
+        let name = html_file_name(&file.path);
+        body.push_str(&format!(
+            "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+            xml_escape_attr(&name),
+            xml_escape_attr(&file.path),
+            file.lines.len(),
+            count(SlotRank::Typed),
+            count(SlotRank::Any),
+            count(SlotRank::Untyped),
+            format!("{:.2}%", percent_covered(file)),
+        ));
+        fs_anyhow::write(&dir.join(name), html_file(file))?;
+    }
+    body.push_str("</table>\n");
+    fs_anyhow::write(&dir.join("index.html"), html_page("Type coverage", &body))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::module_path::ModulePath;
+
+    use super::*;
+    use crate::commands::coverage::collect::calculate_summary;
+    use crate::commands::coverage::types::FileCoverage;
+
+    fn report(lines: Vec<LineCoverage>) -> ModuleReport {
+        let module = Module::new(
+            ModuleName::from_str("robot.legs"),
+            ModulePath::filesystem(PathBuf::from("/repo/robot/legs.py")),
+            Arc::new(
+                "def walk(speed, direction: str) -> None: ...\n\ndef stop() -> None: ...\n"
+                    .to_owned(),
+            ),
+        );
+        let summary = calculate_summary(&[]);
+        ModuleReport {
+            name: "robot.legs".to_owned(),
+            path: "/repo/robot/legs.py".to_owned(),
+            names: Vec::new(),
+            line_count: 3,
+            symbol_reports: Vec::new(),
+            type_ignores: Vec::new(),
+            slots: summary.slots,
+            coverage: summary.coverage,
+            strict_coverage: summary.strict_coverage,
+            symbols: summary.symbols,
+            files: vec![FileCoverage { module, lines }],
+        }
+    }
+
+    fn sample() -> ModuleReport {
+        report(vec![
+            LineCoverage {
+                line: 1,
+                rank: SlotRank::Untyped,
+                notes: vec!["`robot.legs.walk`: parameter `speed` has no annotation".to_owned()],
+            },
+            LineCoverage {
+                line: 3,
+                rank: SlotRank::Typed,
+                notes: vec!["`robot.legs.stop` is fully typed".to_owned()],
+            },
+        ])
+    }
+
+    #[test]
+    fn test_cobertura() {
+        let xml = cobertura(&[sample()], Path::new("/repo"), "1.0", 0);
+        assert!(xml.contains(
+            "<coverage version=\"1.0\" timestamp=\"0\" lines-valid=\"2\" lines-covered=\"1\" line-rate=\"0.5000\""
+        ));
+        assert!(xml.contains("<package name=\"robot\" line-rate=\"0.5000\""));
+        assert!(xml.contains("<class name=\"legs.py\" filename=\"robot/legs.py\""));
+        assert!(xml.contains("<line number=\"1\" hits=\"0\" branch=\"false\"/>"));
+        assert!(xml.contains("<line number=\"3\" hits=\"1\" branch=\"false\"/>"));
+    }
+
+    #[test]
+    fn test_lcov() {
+        assert_eq!(
+            lcov(&[sample()], Path::new("/repo")),
+            "TN:\nSF:robot/legs.py\nDA:1,0\nDA:3,1\nLF:2\nLH:1\nend_of_record\n"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let report = sample();
+        let files = source_files(std::slice::from_ref(&report), Path::new("/repo"));
+        let page = html_file(&files[0]);
+        assert!(page.contains(
+            "<tr class=\"untyped\" title=\"`robot.legs.walk`: parameter `speed` has no annotation\"><td class=\"lineno\">1</td><td><pre>def walk(speed, direction: str) -&gt; None: ...</pre></td></tr>"
+        ));
+        assert!(page.contains("<tr><td class=\"lineno\">2</td><td><pre></pre></td></tr>"));
+        assert!(page.contains("<tr class=\"typed\""));
+        assert_eq!(html_file_name(&files[0].path), "robot.legs.py.html");
+    }
+}
@@ -5,13 +5,20 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
 use clap::Parser;
+use clap::ValueEnum;
 use pyrefly_config::args::EnvironmentArgs;
+use pyrefly_util::fs_anyhow;
 use pyrefly_util::thread_pool::ThreadCount;
 
 use crate::commands::config_finder::ConfigConfigurerWrapper;
 use crate::commands::coverage::collect::calculate_summary;
 use crate::commands::coverage::collect::collect_module_reports;
+use crate::commands::coverage::export;
 use crate::commands::coverage::types::FullReport;
 use crate::commands::files::FilesArgs;
 use crate::commands::util::CommandExitStatus;
@@ -20,6 +27,22 @@ use crate::config::config::ConfigScope;
 /// `(major, minor)` version for the report JSON schema.
 const REPORT_SCHEMA_VERSION: (u32, u32) = (0, 2);
 
+/// The format of a coverage report.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// JSON with statistics for each module and symbol.
+    #[default]
+    Json,
+    /// Cobertura XML. A line is covered when the symbols it defines are fully typed, with no
+    /// `Any`.
+    Cobertura,
+    /// An LCOV tracefile, with the same covered lines as `cobertura`.
+    Lcov,
+    /// A static HTML page for each file, coloring each line by how well it is typed. Requires
+    /// `--output`, the directory to write to.
+    Html,
+}
+
 /// Generate reports from pyrefly type checking results.
 #[deny(clippy::missing_docs_in_private_items)]
 #[derive(Debug, Clone, Parser)]
@@ -46,11 +69,21 @@ pub struct ReportArgs {
     /// Only report symbols reachable from public modules via re-export chains.
     #[clap(long)]
     public_only: bool,
+
+    /// The format of the report.
+    #[arg(long, value_enum, default_value_t)]
+    output_format: ReportFormat,
+
+    /// Write the report to this file, or for `--output-format=html`, this directory, rather
+    /// than to stdout.
+    #[arg(long, short = 'o', value_name = "PATH")]
+    output: Option<PathBuf>,
 }
 
 impl ReportArgs {
     pub fn run(
         self,
+        version: &str,
         wrapper: Option<ConfigConfigurerWrapper>,
         thread_count: ThreadCount,
     ) -> anyhow::Result<CommandExitStatus> {
@@ -59,6 +92,9 @@ impl ReportArgs {
         if self.public_only && self.module.is_some() {
             anyhow::bail!("--module and --public-only cannot be combined");
         }
+        if self.output_format == ReportFormat::Html && self.output.is_none() {
+            anyhow::bail!("--output-format=html requires --output, the directory to write to");
+        }
 
         let (files_to_check, config_finder, _) = self.files.resolve_scoped(
             self.config_override.into(),
@@ -74,12 +110,36 @@ impl ReportArgs {
             None,
             thread_count,
         )?;
-        let full_report = FullReport {
-            schema_version: format!("{}.{}", REPORT_SCHEMA_VERSION.0, REPORT_SCHEMA_VERSION.1),
-            summary: calculate_summary(&module_reports),
-            module_reports,
+        let root = std::env::current_dir().unwrap_or_default();
+        let report = match self.output_format {
+            ReportFormat::Json => {
+                let full_report = FullReport {
+                    schema_version: format!(
+                        "{}.{}",
+                        REPORT_SCHEMA_VERSION.0, REPORT_SCHEMA_VERSION.1
+                    ),
+                    summary: calculate_summary(&module_reports),
+                    module_reports,
+                };
+                format!("{}\n", serde_json::to_string_pretty(&full_report)?)
+            }
+            ReportFormat::Cobertura => {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0, |x| x.as_millis() as u64);
+                export::cobertura(&module_reports, &root, version, timestamp)
+            }
+            ReportFormat::Lcov => export::lcov(&module_reports, &root),
+            ReportFormat::Html => {
+                let dir = self.output.as_deref().expect("checked above");
+                export::write_html(&module_reports, &root, dir)?;
+                return Ok(CommandExitStatus::Success);
+            }
         };
-        println!("{}", serde_json::to_string_pretty(&full_report)?);
+        match &self.output {
+            Some(path) => fs_anyhow::write(path, report)?,
+            None => print!("{report}"),
+        }
 
         Ok(CommandExitStatus::Success)
     }
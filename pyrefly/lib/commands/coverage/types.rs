@@ -6,8 +6,11 @@
  */
 
 use pyrefly_python::ignore::Tool;
+use pyrefly_python::module::Module;
 use pyrefly_types::function::PropertyRole;
+use pyrefly_types::types::AnyStyle;
 use ruff_text_size::TextRange;
+use serde::Deserialize;
 use serde::Serialize;
 
 /// Slot-level annotation counts for a symbol.
@@ -17,7 +20,7 @@ use serde::Serialize;
 /// three buckets: typed (concrete annotation with no `Any`), any (annotation
 /// that resolves to or contains `Any`), or untyped (no annotation at all).
 /// `n_typable` is always the sum of the other three.
-#[derive(Debug, Serialize, Default, Clone, Copy)]
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy)]
 pub struct SlotCounts {
     /// Total number of annotation sites.
     pub n_typable: usize,
@@ -136,6 +139,8 @@ pub struct Parameter {
     /// Overload merge key (`None` for self/cls and implicit params).
     pub merge_key: Option<ParamKey>,
     pub rank: SlotRank,
+    /// How the annotation became `Any`, when `rank` is `Any`.
+    pub any_style: Option<AnyStyle>,
 }
 
 /// Renamed from `Suppression` to avoid collision with `pyrefly_python::ignore::Suppression`.
@@ -151,6 +156,8 @@ pub struct ReportSuppression {
 pub struct Function {
     pub name: String,
     pub return_rank: SlotRank,
+    /// How the return annotation became `Any`, when `return_rank` is `Any`.
+    pub return_any_style: Option<AnyStyle>,
     pub parameters: Vec<Parameter>,
     /// Property role if this function is a property accessor, `None` otherwise.
     pub property_role: Option<PropertyRole>,
@@ -172,6 +179,8 @@ pub struct ReportClass {
 pub struct Variable {
     pub name: String,
     pub slots: SlotCounts,
+    /// How the annotation became `Any`, when it resolves to `Any`.
+    pub any_style: Option<AnyStyle>,
     pub location: Location,
     /// Byte span of the symbol, for rendering diagnostics.
     pub range: TextRange,
@@ -270,6 +279,27 @@ impl SymbolCounts {
     }
 }
 
+/// The annotations of the symbols defined on one line.
+#[derive(Debug, Clone)]
+pub struct LineCoverage {
+    /// 1-based.
+    pub line: usize,
+    /// The worst rank of any annotation on the line. Never `Skip`.
+    pub rank: SlotRank,
+    /// A description of each symbol on the line, explaining which annotations are missing or
+    /// contain `Any`.
+    pub notes: Vec<String>,
+}
+
+/// The lines of a source file that define symbols, for line-based coverage formats. A stub
+/// that merges symbols from its `.py` file has one of these for each file.
+#[derive(Debug, Clone)]
+pub struct FileCoverage {
+    pub module: Module,
+    /// Sorted by line.
+    pub lines: Vec<LineCoverage>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ModuleReport {
     /// Fully-qualified module name (e.g. "mypackage.submodule").
@@ -287,6 +317,9 @@ pub struct ModuleReport {
     pub strict_coverage: f64,
     #[serde(flatten)]
     pub symbols: SymbolCounts,
+    /// Per-line coverage, for the formats other than JSON.
+    #[serde(skip)]
+    pub files: Vec<FileCoverage>,
 }
 
 #[derive(Debug, Serialize)]
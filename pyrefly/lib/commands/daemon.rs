@@ -0,0 +1,683 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A background process that keeps type-checking state warm between runs of
+//! `pyrefly check --use-daemon`.
+//!
+//! The daemon watches the project for changes and rechecks incrementally when a client
+//! asks for results. Clients talk to it over a Unix domain socket, one newline-delimited
+//! JSON request and response per connection. Errors are sent as `PersistedModuleErrors`,
+//! which the client restores against the files on disk and reports like `pyrefly check`.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use clap::Parser;
+use clap::Subcommand;
+use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_config::error_kind::Severity;
+use pyrefly_util::args::clap_env;
+use pyrefly_util::thread_pool::ThreadCount;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::commands::config_finder::ConfigConfigurerWrapper;
+use crate::commands::files::FilesArgs;
+use crate::commands::util::CommandExitStatus;
+use crate::error::persisted::PersistedModuleErrors;
+
+/// Subcommands of `pyrefly daemon`.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Subcommand)]
+pub enum DaemonCommand {
+    /// Start a daemon for the project in the background and wait until it accepts requests.
+    Start(DaemonArgs),
+    /// Stop a running daemon.
+    Stop(SocketArgs),
+    /// Report whether a daemon is running.
+    Status(SocketArgs),
+}
+
+/// Where to find the daemon.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct SocketArgs {
+    /// The Unix domain socket the daemon listens on.
+    /// Defaults to a path derived from the current directory, inside `$XDG_RUNTIME_DIR` or a
+    /// directory in the temporary directory that only the current user can access. The daemon
+    /// and its clients must be run from the same directory unless this is set.
+    #[arg(long, value_name = "PATH", env = clap_env("DAEMON_SOCKET"))]
+    socket: Option<PathBuf>,
+}
+
+impl SocketArgs {
+    pub(crate) fn new(socket: Option<PathBuf>) -> Self {
+        Self { socket }
+    }
+
+    pub(crate) fn path(&self) -> anyhow::Result<PathBuf> {
+        match &self.socket {
+            Some(socket) => Ok(socket.clone()),
+            None => {
+                let cwd = std::env::current_dir().context("cannot identify current dir")?;
+                Ok(socket_dir()?.join(socket_file_name(&cwd.canonicalize().unwrap_or(cwd))))
+            }
+        }
+    }
+}
+
+/// The file name of the socket used for a project in `dir` when none is given explicitly.
+fn socket_file_name(dir: &Path) -> String {
+    let hash = blake3::hash(dir.as_os_str().as_encoded_bytes()).to_hex();
+    format!("pyrefly-{}.sock", &hash[..16])
+}
+
+/// The directory holding the sockets of daemons started without an explicit socket.
+fn socket_dir() -> anyhow::Result<PathBuf> {
+    #[cfg(unix)]
+    {
+        unix::private_socket_dir()
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(std::env::temp_dir())
+    }
+}
+
+/// Arguments for starting a daemon.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct DaemonArgs {
+    /// Which files to check.
+    #[command(flatten)]
+    files: FilesArgs,
+
+    /// Where to listen.
+    #[command(flatten)]
+    socket: SocketArgs,
+
+    /// Configuration override options
+    #[command(flatten, next_help_heading = "Config Overrides")]
+    config_override: ConfigOverrideArgs,
+
+    /// Run the daemon in this process instead of in the background.
+    /// `start` launches the background daemon with this set.
+    #[arg(long, env = clap_env("DAEMON_FOREGROUND"))]
+    foreground: bool,
+}
+
+/// A request from a client.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "kebab-case")]
+pub(crate) enum Request {
+    /// Bring the results up to date with the files on disk and return them.
+    Check,
+    Status,
+    Stop,
+}
+
+/// A configuration error, as sent to clients.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct DaemonConfigError {
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// The daemon's reply to a `Request`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "response", rename_all = "kebab-case")]
+pub(crate) enum Response {
+    Check {
+        /// The version of the daemon, which must match the client's for the errors to
+        /// be interpreted in the same way.
+        version: String,
+        modules: Vec<PersistedModuleErrors>,
+        config_errors: Vec<DaemonConfigError>,
+    },
+    Status {
+        version: String,
+        pid: u32,
+        /// The directory the daemon was started from.
+        cwd: PathBuf,
+        /// The number of checks served since the daemon started.
+        checks: usize,
+    },
+    Stopping,
+    Error {
+        message: String,
+    },
+}
+
+impl DaemonCommand {
+    pub fn run(
+        self,
+        version: &str,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        #[cfg(unix)]
+        {
+            unix::run(self, version, wrapper, thread_count)
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = (version, wrapper, thread_count);
+            anyhow::bail!("`pyrefly daemon` is only supported on Unix platforms")
+        }
+    }
+}
+
+/// Send `request` to the daemon listening on `socket` and wait for its response.
+pub(crate) fn send_request(socket: &SocketArgs, request: &Request) -> anyhow::Result<Response> {
+    #[cfg(unix)]
+    {
+        let path = socket.path()?;
+        unix::send_request(&path, request).with_context(|| {
+            format!(
+                "could not reach a pyrefly daemon at `{}`; start one with `pyrefly daemon start`",
+                path.display()
+            )
+        })
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (socket, request);
+        anyhow::bail!("`--use-daemon` is only supported on Unix platforms")
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::fs;
+    use std::fs::DirBuilder;
+    use std::fs::File;
+    use std::io;
+    use std::io::BufRead as _;
+    use std::io::BufReader;
+    use std::io::Write as _;
+    use std::os::unix::fs::DirBuilderExt as _;
+    use std::os::unix::fs::MetadataExt as _;
+    use std::os::unix::net::UnixListener;
+    use std::os::unix::net::UnixStream;
+    use std::os::unix::process::CommandExt as _;
+    use std::path::Path;
+    use std::path::PathBuf;
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::sync::mpsc::Receiver;
+    use std::sync::mpsc::TryRecvError;
+    use std::thread;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    use anyhow::Context as _;
+    use anyhow::bail;
+    use pyrefly_util::args::clap_env;
+    use pyrefly_util::events::CategorizedEvents;
+    use pyrefly_util::thread_pool::ThreadCount;
+    use pyrefly_util::watcher::Watcher;
+    use tracing::error;
+    use tracing::info;
+
+    use super::DaemonArgs;
+    use super::DaemonCommand;
+    use super::DaemonConfigError;
+    use super::Request;
+    use super::Response;
+    use crate::commands::check::IncrementalChecker;
+    use crate::commands::check::get_watcher_events;
+    use crate::commands::config_finder::ConfigConfigurerWrapper;
+    use crate::commands::util::CommandExitStatus;
+    use crate::error::persisted::PersistedModuleErrors;
+    use crate::state::require::Require;
+    use crate::state::require::RequireLevels;
+
+    /// How long `start` waits for a new daemon to begin listening. Listening starts
+    /// before the initial check, so this only covers finding the files to check.
+    const START_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// How long a check waits for the watcher to report recent writes. The watcher
+    /// batches events for 0.1s, so a file saved just before the request would otherwise
+    /// be missed.
+    const SETTLE_TIME: Duration = Duration::from_millis(200);
+
+    /// How long the daemon waits for a client to send its request or accept the response,
+    /// so that a stalled client cannot block the clients queued behind it.
+    const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// How long a client waits for the daemon's response, so that a hung daemon doesn't block
+    /// the command forever. Generous, since a request can queue behind the initial check of a
+    /// large project.
+    const RESPONSE_TIMEOUT: Duration = Duration::from_secs(600);
+
+    /// The id of the user running this process, taken from the owner of a file it creates.
+    fn current_uid() -> anyhow::Result<u32> {
+        let file = tempfile::tempfile().context("while creating a temporary file")?;
+        Ok(file.metadata()?.uid())
+    }
+
+    /// A directory for sockets that only the current user can access: `pyrefly` in
+    /// `$XDG_RUNTIME_DIR`, or `pyrefly-<uid>` in the temporary directory otherwise.
+    pub(super) fn private_socket_dir() -> anyhow::Result<PathBuf> {
+        let uid = current_uid()?;
+        let dir = match std::env::var_os("XDG_RUNTIME_DIR").filter(|x| !x.is_empty()) {
+            Some(runtime_dir) => PathBuf::from(runtime_dir).join("pyrefly"),
+            None => std::env::temp_dir().join(format!("pyrefly-{uid}")),
+        };
+        match DirBuilder::new().mode(0o700).create(&dir) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(e) => {
+                return Err(e).with_context(|| format!("while creating `{}`", dir.display()));
+            }
+        }
+        // Another user may have created the directory first, to read or replace our sockets.
+        let metadata = fs::symlink_metadata(&dir)
+            .with_context(|| format!("while inspecting `{}`", dir.display()))?;
+        if !metadata.is_dir() || metadata.uid() != uid || metadata.mode() & 0o077 != 0 {
+            bail!(
+                "`{}` must be a directory owned and only accessible by the current user",
+                dir.display()
+            );
+        }
+        Ok(dir)
+    }
+
+    /// Refuse to use a socket created by another user, which could impersonate the daemon.
+    fn check_owner(socket: &Path) -> anyhow::Result<()> {
+        if let Ok(metadata) = fs::symlink_metadata(socket)
+            && metadata.uid() != current_uid()?
+        {
+            bail!("`{}` is owned by another user", socket.display());
+        }
+        Ok(())
+    }
+
+    pub(super) fn run(
+        command: DaemonCommand,
+        version: &str,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        match command {
+            DaemonCommand::Start(args) if args.foreground => {
+                serve(args, version, wrapper, thread_count)
+            }
+            DaemonCommand::Start(args) => start(&args.socket.path()?),
+            DaemonCommand::Stop(socket) => {
+                let path = socket.path()?;
+                match send_request(&path, &Request::Stop) {
+                    Ok(_) => info!("Stopped the daemon at `{}`", path.display()),
+                    Err(_) => info!("No daemon is running at `{}`", path.display()),
+                }
+                Ok(CommandExitStatus::Success)
+            }
+            DaemonCommand::Status(socket) => {
+                let path = socket.path()?;
+                match send_request(&path, &Request::Status) {
+                    Ok(Response::Status {
+                        version,
+                        pid,
+                        cwd,
+                        checks,
+                    }) => {
+                        println!(
+                            "Daemon {version} running at `{}` (pid {pid}, started in `{}`, {checks} checks served)",
+                            path.display(),
+                            cwd.display()
+                        );
+                        Ok(CommandExitStatus::Success)
+                    }
+                    Ok(response) => bail!("Unexpected response from the daemon: {response:?}"),
+                    Err(_) => {
+                        println!("No daemon is running at `{}`", path.display());
+                        Ok(CommandExitStatus::UserError)
+                    }
+                }
+            }
+        }
+    }
+
+    pub(super) fn send_request(socket: &Path, request: &Request) -> anyhow::Result<Response> {
+        send_request_with_timeout(socket, request, RESPONSE_TIMEOUT)
+    }
+
+    /// Like `send_request`, but waiting at most `timeout` for the response.
+    pub(super) fn send_request_with_timeout(
+        socket: &Path,
+        request: &Request,
+        timeout: Duration,
+    ) -> anyhow::Result<Response> {
+        check_owner(socket)?;
+        let mut stream = UnixStream::connect(socket)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+        let mut line = serde_json::to_string(request)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes())?;
+        let mut response = String::new();
+        match BufReader::new(stream).read_line(&mut response) {
+            Ok(_) => {}
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                bail!(
+                    "the daemon did not respond within {}s",
+                    timeout.as_secs_f64()
+                );
+            }
+            Err(e) => return Err(e.into()),
+        }
+        if response.is_empty() {
+            bail!("the daemon closed the connection without responding");
+        }
+        Ok(serde_json::from_str(&response)?)
+    }
+
+    /// Launch our own command line again as a detached process, with `--foreground` set.
+    fn start(socket: &Path) -> anyhow::Result<CommandExitStatus> {
+        check_owner(socket)?;
+        if UnixStream::connect(socket).is_ok() {
+            info!("A daemon is already running at `{}`", socket.display());
+            return Ok(CommandExitStatus::Success);
+        }
+        let log_path = socket.with_extension("log");
+        let log = File::create(&log_path)
+            .with_context(|| format!("while creating `{}`", log_path.display()))?;
+        let mut child = std::process::Command::new(std::env::current_exe()?)
+            .args(std::env::args_os().skip(1))
+            .env(clap_env("DAEMON_FOREGROUND"), "true")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(log)
+            // Keep the daemon alive when the terminal that started it sends a signal
+            // to its foreground process group.
+            .process_group(0)
+            .spawn()
+            .context("while launching the daemon")?;
+        let deadline = Instant::now() + START_TIMEOUT;
+        while Instant::now() < deadline {
+            if UnixStream::connect(socket).is_ok() {
+                info!(
+                    "Started a daemon at `{}` (logs in `{}`)",
+                    socket.display(),
+                    log_path.display()
+                );
+                return Ok(CommandExitStatus::Success);
+            }
+            if let Some(status) = child.try_wait()? {
+                bail!(
+                    "The daemon exited with {status}; see `{}` for details",
+                    log_path.display()
+                );
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        bail!(
+            "The daemon did not start listening within {}s; see `{}` for details",
+            START_TIMEOUT.as_secs(),
+            log_path.display()
+        )
+    }
+
+    /// Claim `socket`, removing it first if it is left over from a daemon that is gone.
+    fn bind(socket: &Path) -> anyhow::Result<UnixListener> {
+        check_owner(socket)?;
+        if socket.exists() {
+            if UnixStream::connect(socket).is_ok() {
+                bail!("A daemon is already running at `{}`", socket.display());
+            }
+            std::fs::remove_file(socket)
+                .with_context(|| format!("while removing stale socket `{}`", socket.display()))?;
+        }
+        UnixListener::bind(socket).with_context(|| format!("while binding `{}`", socket.display()))
+    }
+
+    /// Forward batches of filesystem events under `roots` until the daemon stops listening.
+    fn spawn_watcher(roots: Vec<PathBuf>) -> anyhow::Result<Receiver<CategorizedEvents>> {
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher = Watcher::notify(&roots)?;
+        thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread().build() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    error!("Failed to start the file watcher: {e:#}");
+                    return;
+                }
+            };
+            loop {
+                match runtime.block_on(get_watcher_events(&mut watcher)) {
+                    Ok(events) => {
+                        if sender.send(events).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        // Dropping the sender tells the daemon that its results can no
+                        // longer be trusted.
+                        error!("File watching stopped: {e:#}");
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
+    struct Daemon {
+        version: String,
+        checker: IncrementalChecker,
+        events: Receiver<CategorizedEvents>,
+        checks: usize,
+    }
+
+    impl Daemon {
+        fn handle(&mut self, request: Request) -> Response {
+            match request {
+                Request::Check => self.check(),
+                Request::Status => Response::Status {
+                    version: self.version.clone(),
+                    pid: std::process::id(),
+                    cwd: std::env::current_dir().unwrap_or_default(),
+                    checks: self.checks,
+                },
+                Request::Stop => Response::Stopping,
+            }
+        }
+
+        fn check(&mut self) -> Response {
+            thread::sleep(SETTLE_TIME);
+            let mut events = CategorizedEvents::default();
+            loop {
+                match self.events.try_recv() {
+                    Ok(batch) => events.extend(batch),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        return Response::Error {
+                            message: "The daemon is no longer watching for file changes; restart it with `pyrefly daemon stop` and `pyrefly daemon start`".to_owned(),
+                        };
+                    }
+                }
+            }
+            let result = self.checker.check(&events);
+            self.checks += 1;
+            Response::Check {
+                version: self.version.clone(),
+                modules: PersistedModuleErrors::from_errors(&result.diagnostics),
+                config_errors: result
+                    .config_errors
+                    .iter()
+                    .map(|e| DaemonConfigError {
+                        severity: e.severity(),
+                        message: e.get_full_message(),
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    /// Run the daemon in this process until asked to stop.
+    fn serve(
+        args: DaemonArgs,
+        version: &str,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        args.config_override.validate()?;
+        let socket = args.socket.path()?;
+        let (files_to_check, config_finder, _) =
+            args.files.resolve(args.config_override, wrapper)?;
+        let events = spawn_watcher(files_to_check.roots())?;
+        let mut daemon = Daemon {
+            version: version.to_owned(),
+            checker: IncrementalChecker::new(
+                RequireLevels {
+                    specified: Require::Errors,
+                    default: Require::Exports,
+                },
+                files_to_check,
+                config_finder,
+                thread_count,
+            )?,
+            events,
+            checks: 0,
+        };
+        let listener = bind(&socket)?;
+        info!("Listening on `{}`", socket.display());
+        // Clients that connect during the cold check queue up until it finishes.
+        daemon.checker.check(&CategorizedEvents::default());
+
+        // Connections are served one at a time; a check holds the state exclusively anyway.
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    error!("Failed to accept a connection: {e:#}");
+                    continue;
+                }
+            };
+            if let Err(e) = stream
+                .set_read_timeout(Some(CLIENT_TIMEOUT))
+                .and_then(|()| stream.set_write_timeout(Some(CLIENT_TIMEOUT)))
+            {
+                error!("Failed to configure a connection: {e:#}");
+                continue;
+            }
+            let mut line = String::new();
+            let response = match BufReader::new(&stream).read_line(&mut line) {
+                Ok(_) => match serde_json::from_str(&line) {
+                    Ok(request) => daemon.handle(request),
+                    Err(e) => Response::Error {
+                        message: format!("Invalid request: {e}"),
+                    },
+                },
+                Err(e) => {
+                    error!("Failed to read a request: {e:#}");
+                    continue;
+                }
+            };
+            let stopping = response == Response::Stopping;
+            if let Err(e) = write_response(&stream, &response) {
+                error!("Failed to send a response: {e:#}");
+            }
+            if stopping {
+                break;
+            }
+        }
+        let _ = std::fs::remove_file(&socket);
+        info!("Stopped after serving {} checks", daemon.checks);
+        Ok(CommandExitStatus::Success)
+    }
+
+    fn write_response(mut stream: &UnixStream, response: &Response) -> anyhow::Result<()> {
+        let mut line = serde_json::to_string(response)?;
+        line.push('\n');
+        stream.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use pyrefly_config::error_kind::ErrorKind;
+
+    use super::*;
+    use crate::error::persisted::PersistedError;
+
+    #[test]
+    fn protocol_round_trips() {
+        let response = Response::Check {
+            version: "1.0".to_owned(),
+            modules: vec![PersistedModuleErrors {
+                name: "foo".to_owned(),
+                path: PathBuf::from("/repo/foo.py"),
+                errors: vec![PersistedError {
+                    start: 0,
+                    end: 1,
+                    kind: ErrorKind::BadAssignment,
+                    severity: Severity::Error,
+                    header: "bad".to_owned(),
+                    details: None,
+                    annotations: Vec::new(),
+                    quick_fixes: Vec::new(),
+                }],
+            }],
+            config_errors: vec![DaemonConfigError {
+                severity: Severity::Warn,
+                message: "odd config".to_owned(),
+            }],
+        };
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(!json.contains('\n'));
+        assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), response);
+        assert_eq!(
+            serde_json::to_string(&Request::Check).unwrap(),
+            r#"{"request":"check"}"#
+        );
+    }
+
+    #[test]
+    fn default_socket_depends_on_directory() {
+        let a = socket_file_name(Path::new("/repo/a"));
+        let b = socket_file_name(Path::new("/repo/b"));
+        assert_ne!(a, b);
+        assert_eq!(a, socket_file_name(Path::new("/repo/a")));
+        assert!(a.ends_with(".sock"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn send_request_times_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket = dir.path().join("hung.sock");
+        // Connections are queued, but never accepted or answered.
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        let err = unix::send_request_with_timeout(
+            &socket,
+            &Request::Status,
+            std::time::Duration::from_millis(100),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("did not respond"), "{err:#}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn default_socket_dir_is_private() {
+        use std::os::unix::fs::PermissionsExt as _;
+
+        let dir = socket_dir().unwrap();
+        let metadata = std::fs::metadata(&dir).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o077, 0);
+    }
+}
@@ -0,0 +1,222 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! `pyrefly explain <error-kind>`: print a long-form explanation of an error kind,
+//! together with an example that triggers it and a fixed version of that example.
+//!
+//! The explanations are the `## <error-kind>` sections of the error kinds page of the
+//! website. `explain.md` is a copy of `website/docs/error-kinds.mdx`, kept inside the crate so
+//! that it can be packaged, and `test::explain` checks that the two are identical. Each
+//! section is prose followed by an optional ```` ```python title="Example" ```` block and an
+//! optional ```` ```python title="Fixed" ```` block. The blocks are also checked by
+//! `test::explain`, so they must be kept in sync with the checker.
+
+use std::sync::LazyLock;
+
+use anyhow::anyhow;
+use clap::Parser;
+use pyrefly_config::error_kind::ErrorKind;
+use pyrefly_config::error_kind::Severity;
+use starlark_map::small_map::SmallMap;
+
+use crate::commands::util::CommandExitStatus;
+
+/// The explanation of one error kind, parsed from `explain.md`.
+#[derive(Debug, Default)]
+pub struct Explanation {
+    /// Long-form explanation, as Markdown.
+    pub text: String,
+    /// Code that produces the error.
+    pub bad: Option<String>,
+    /// A fixed version of `bad` that does not produce the error.
+    pub good: Option<String>,
+}
+
+static EXPLANATIONS: LazyLock<SmallMap<String, Explanation>> =
+    LazyLock::new(|| parse_explanations(include_str!("explain.md")));
+
+/// The explanation for `kind`, if there is one.
+pub fn explanation(kind: ErrorKind) -> Option<&'static Explanation> {
+    EXPLANATIONS.get(kind.to_name())
+}
+
+fn parse_explanations(source: &str) -> SmallMap<String, Explanation> {
+    #[derive(Clone, Copy)]
+    enum Fence {
+        Bad,
+        Good,
+        Other,
+    }
+
+    fn finish(res: &mut SmallMap<String, Explanation>, entry: Option<(String, Explanation)>) {
+        if let Some((name, mut explanation)) = entry {
+            explanation.text = explanation.text.trim().to_owned();
+            res.insert(name, explanation);
+        }
+    }
+
+    let mut res = SmallMap::new();
+    let mut entry: Option<(String, Explanation)> = None;
+    let mut fence: Option<(Fence, String)> = None;
+    for line in source.lines() {
+        if let Some((kind, code)) = &mut fence {
+            if line.trim_end() == "```" {
+                let kind = *kind;
+                let code = std::mem::take(code);
+                fence = None;
+                if let Some((_, explanation)) = &mut entry {
+                    match kind {
+                        Fence::Bad => explanation.bad = Some(code),
+                        Fence::Good => explanation.good = Some(code),
+                        Fence::Other => {
+                            explanation.text.push_str(&code);
+                            explanation.text.push_str("```\n");
+                        }
+                    }
+                }
+            } else {
+                code.push_str(line);
+                code.push('\n');
+            }
+        } else if let Some(tag) = line.strip_prefix("```") {
+            let kind = match tag.trim() {
+                r#"python title="Example""# => Fence::Bad,
+                r#"python title="Fixed""# => Fence::Good,
+                _ => Fence::Other,
+            };
+            let mut code = String::new();
+            if matches!(kind, Fence::Other) {
+                code.push_str(line);
+                code.push('\n');
+            }
+            fence = Some((kind, code));
+        } else if let Some(name) = line.strip_prefix("## ") {
+            finish(&mut res, entry.take());
+            entry = Some((name.trim().to_owned(), Explanation::default()));
+        } else if line.starts_with("Default severity:") {
+            // `pyrefly explain` prints the severity itself.
+        } else if let Some((_, explanation)) = &mut entry {
+            explanation.text.push_str(line);
+            explanation.text.push('\n');
+        }
+    }
+    finish(&mut res, entry);
+    res
+}
+
+fn severity_name(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Ignore => "ignore (disabled unless enabled in the configuration)",
+        Severity::Info => "info",
+        Severity::Warn => "warn",
+        Severity::Error => "error",
+    }
+}
+
+fn indent(code: &str) -> String {
+    code.lines()
+        .map(|line| {
+            if line.is_empty() {
+                String::new()
+            } else {
+                format!("    {line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Explain an error kind.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct ExplainArgs {
+    /// The error kind to explain, as shown in brackets at the end of an error, e.g. `bad-override`.
+    #[arg(hide_possible_values = true)]
+    kind: ErrorKind,
+}
+
+impl ExplainArgs {
+    pub fn run(&self) -> anyhow::Result<CommandExitStatus> {
+        let kind = self.kind;
+        let explanation = explanation(kind)
+            .ok_or_else(|| anyhow!("No explanation available for `{}`", kind.to_name()))?;
+        println!("{}", kind.to_name());
+        println!();
+        println!(
+            "Default severity: {}",
+            severity_name(kind.default_severity())
+        );
+        println!();
+        println!("{}", explanation.text);
+        if let Some(bad) = &explanation.bad {
+            println!();
+            println!("Example:");
+            println!();
+            println!("{}", indent(bad));
+        }
+        if let Some(good) = &explanation.good {
+            println!();
+            println!("Fixed:");
+            println!();
+            println!("{}", indent(good));
+        }
+        println!();
+        println!("See {} for more.", kind.docs_url());
+        Ok(CommandExitStatus::Success)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_explanations() {
+        let parsed = parse_explanations(
+            r#"# Title
+
+Introduction.
+
+## first-kind
+
+Default severity: `warn`
+
+Some text.
+
+```
+not an example
+```
+
+```python title="Example"
+x: int = ""
+```
+
+```python title="Fixed"
+x: int = 0
+```
+
+## second-kind
+
+Only text.
+"#,
+        );
+        assert_eq!(parsed.len(), 2);
+        let first = parsed.get("first-kind").unwrap();
+        assert_eq!(first.text, "Some text.\n\n```\nnot an example\n```");
+        assert_eq!(first.bad.as_deref(), Some("x: int = \"\"\n"));
+        assert_eq!(first.good.as_deref(), Some("x: int = 0\n"));
+        let second = parsed.get("second-kind").unwrap();
+        assert_eq!(second.text, "Only text.");
+        assert!(second.bad.is_none());
+        assert!(second.good.is_none());
+    }
+
+    #[test]
+    fn test_indent() {
+        assert_eq!(indent("a\n\n  b\n"), "    a\n\n      b");
+    }
+}
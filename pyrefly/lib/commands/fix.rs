@@ -0,0 +1,169 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! `pyrefly fix`: apply the IDE quick fixes that need no human judgement to a whole project.
+
+use std::collections::BTreeMap;
+
+use clap::Parser;
+use dupe::Dupe;
+use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_python::module::Module;
+use pyrefly_util::forgetter::Forgetter;
+use pyrefly_util::fs_anyhow;
+use pyrefly_util::text_edits::TextEdits;
+use pyrefly_util::thread_pool::ThreadCount;
+use tracing::info;
+use tracing::warn;
+
+use crate::commands::check::Handles;
+use crate::commands::config_finder::ConfigConfigurerWrapper;
+use crate::commands::files::FilesArgs;
+use crate::commands::util::CommandExitStatus;
+use crate::state::lsp::FixKind;
+use crate::state::lsp::ImportFormat;
+use crate::state::require::Require;
+use crate::state::state::State;
+
+/// Apply quick fixes that have a single obvious edit to every file in a project.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct FixArgs {
+    /// Which files to check and fix.
+    #[command(flatten)]
+    files: FilesArgs,
+
+    /// Configuration override options.
+    #[command(flatten, next_help_heading = "Config Overrides")]
+    config_override: ConfigOverrideArgs,
+
+    /// Only apply fixes of these kinds (comma-separated). Defaults to every kind except
+    /// `unused-import`, which has to be requested.
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "FIX_KIND")]
+    only: Vec<FixKind>,
+
+    /// Print a unified diff of the fixes instead of writing them. Exits with a non-zero
+    /// status if there is anything to fix.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// The fixes chosen for one file.
+struct FileFixes {
+    source: String,
+    edits: TextEdits,
+}
+
+impl FixArgs {
+    pub fn run(
+        self,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        self.config_override.validate()?;
+        let (files_to_check, config_finder, _) =
+            self.files.resolve(self.config_override, wrapper)?;
+        let kinds = if self.only.is_empty() {
+            enum_iterator::all::<FixKind>()
+                .filter(|kind| kind.is_default())
+                .collect()
+        } else {
+            self.only
+        };
+
+        let expanded_file_list = config_finder.checkpoint(files_to_check.files_iter())?;
+        let state = State::new(config_finder, thread_count);
+        let holder = Forgetter::new(state, false);
+        let handles = Handles::new(expanded_file_list);
+        let mut forgetter = Forgetter::new(
+            holder.as_ref().new_transaction(Require::Exports, None),
+            true,
+        );
+        let transaction = forgetter.as_mut();
+        let (handles, _, sourcedb_errors) = handles.all(holder.as_ref().config_finder());
+        if !sourcedb_errors.is_empty() {
+            for error in sourcedb_errors {
+                error.print();
+            }
+            return Err(anyhow::anyhow!("Failed to query sourcedb."));
+        }
+        transaction.run(&handles, Require::Everything, None);
+
+        // Sorted by path, so the output is deterministic.
+        let mut files: BTreeMap<_, FileFixes> = BTreeMap::new();
+        let mut applied: BTreeMap<FixKind, usize> = BTreeMap::new();
+        let mut conflicts = 0;
+        for handle in &handles {
+            for fix in transaction.machine_applicable_fixes(handle, &kinds, ImportFormat::default())
+            {
+                // Edits to notebooks are relative to the combined cells, not to the file.
+                if fix.edits.iter().any(|(module, _, _)| module.is_notebook()) {
+                    continue;
+                }
+                // A fix can edit several files, and is applied to all of them or none.
+                let mut by_file: BTreeMap<_, (Module, Vec<_>)> = BTreeMap::new();
+                for (module, range, text) in fix.edits {
+                    by_file
+                        .entry(module.path().dupe())
+                        .or_insert_with(|| (module.dupe(), Vec::new()))
+                        .1
+                        .push((range, text));
+                }
+                if !by_file.iter().all(|(path, (_, edits))| {
+                    files.get(path).is_none_or(|x| x.edits.accepts(edits))
+                }) {
+                    conflicts += 1;
+                    continue;
+                }
+                for (path, (module, edits)) in by_file {
+                    files
+                        .entry(path)
+                        .or_insert_with(|| FileFixes {
+                            source: module.contents().as_str().to_owned(),
+                            edits: TextEdits::new(),
+                        })
+                        .edits
+                        .add(edits);
+                }
+                *applied.entry(fix.kind).or_default() += 1;
+            }
+        }
+
+        files.retain(|_, file| !file.edits.is_empty());
+        for (path, file) in &files {
+            if self.dry_run {
+                print!(
+                    "{}",
+                    file.edits
+                        .unified_diff(&path.as_path().display().to_string(), &file.source)
+                );
+            } else {
+                fs_anyhow::write(path.as_path(), file.edits.apply(&file.source))?;
+            }
+        }
+        let total: usize = applied.values().sum();
+        let verb = if self.dry_run {
+            "Would apply"
+        } else {
+            "Applied"
+        };
+        info!("{verb} {total} fix(es) to {} file(s)", files.len());
+        for (kind, count) in &applied {
+            info!("  {}: {count}", kind.to_name());
+        }
+        if conflicts > 0 {
+            warn!(
+                "Skipped {conflicts} fix(es) that overlap other fixes; run `pyrefly fix` again to apply them"
+            );
+        }
+        Ok(if self.dry_run && total > 0 {
+            CommandExitStatus::UserError
+        } else {
+            CommandExitStatus::Success
+        })
+    }
+}
@@ -48,7 +48,7 @@ pub struct InitArgs {
     #[arg(long)]
     non_interactive: bool,
     /// Which type checker config to migrate from when multiple are present.
-    /// When set to "auto" (the default), tries mypy first, then pyright.
+    /// When set to "auto" (the default), tries mypy first, then pyright, then Pyre.
     #[arg(long, value_enum, default_value_t = MigrationSource::Auto)]
     migrate_from: MigrationSource,
     /// Preview what `pyrefly init` would do without writing or modifying any
@@ -265,18 +265,27 @@ impl InitArgs {
             }
         }
 
-        // 1. Check for mypy or pyright configuration
+        // 1. Check for mypy, pyright or Pyre configuration
         let found_mypy = ConfigFileKind::MyPy.check_for_existing_config(&path)?;
         let found_pyright = ConfigFileKind::Pyright.check_for_existing_config(&path)?;
+        let found_pyre = ConfigFileKind::Pyre.check_for_existing_config(&path)?;
 
         // 2. Migrate existing configuration to Pyrefly configuration
-        if found_mypy || found_pyright {
+        if found_mypy || found_pyright || found_pyre {
             info!("Found an existing type checking configuration - setting up pyrefly ...");
+            // A pyproject.toml without mypy or pyright sections would otherwise be found
+            // before a `.pyre_configuration` in the same directory.
+            let migrate_from =
+                if self.migrate_from == MigrationSource::Auto && !found_mypy && !found_pyright {
+                    MigrationSource::Pyre
+                } else {
+                    self.migrate_from
+                };
             return Ok((
                 CommandExitStatus::Success,
                 Some(config_migration(
                     &path,
-                    self.migrate_from,
+                    migrate_from,
                     self.dry_run,
                     self.print_config,
                 )?),
@@ -558,6 +567,34 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_dir_with_pyre_config() -> anyhow::Result<()> {
+        let tmp = tempfile::tempdir()?;
+        create_file_in(
+            tmp.path(),
+            ".pyre_configuration",
+            Some(
+                b"\
+{
+    \"source_directories\": [\"abc\"]
+}",
+            ),
+        )?;
+        // Has no mypy or pyright section, so mustn't be migrated instead.
+        create_file_in(
+            tmp.path(),
+            "pyproject.toml",
+            Some(b"[project]\nname = \"x\"\n"),
+        )?;
+        let status = run_init_on_dir(&tmp)?;
+        assert_success(status);
+        check_file_in(
+            tmp.path(),
+            "pyrefly.toml",
+            &["project-includes = [\"abc\"]", "preset = \"legacy\""],
+        )
+    }
+
     #[test]
     fn test_path_to_pyright_config() -> anyhow::Result<()> {
         let tmp = tempfile::tempdir()?;
@@ -0,0 +1,393 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! `pyrefly query`: ask the checker about a single location, file or pair of types from the
+//! command line. Every subcommand prints one JSON object to stdout.
+
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use clap::Parser;
+use clap::Subcommand;
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_python::module::TextRangeWithModule;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_util::absolutize::Absolutize as _;
+use pyrefly_util::args::clap_env;
+use pyrefly_util::lined_buffer::DisplayPos;
+use pyrefly_util::lined_buffer::DisplayRange;
+use pyrefly_util::lined_buffer::LineNumber;
+use pyrefly_util::lined_buffer::LinedBuffer;
+use pyrefly_util::lined_buffer::PythonASTRange;
+use pyrefly_util::thread_pool::ThreadCount;
+use ruff_text_size::TextSize;
+use serde::Serialize;
+
+use crate::commands::check::Handles;
+use crate::commands::config_finder::ConfigConfigurerWrapper;
+use crate::commands::files::FilesArgs;
+use crate::commands::files::get_config_finder_for_snippet;
+use crate::commands::util::CommandExitStatus;
+use crate::query::Query;
+
+/// Subcommands of `pyrefly query`.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Subcommand)]
+pub enum QueryCommand {
+    /// Print the type of the expression at a location.
+    TypeAt(LocationArgs),
+    /// Print where the symbol at a location is defined.
+    Definition(LocationArgs),
+    /// Print every reference to the symbol at a location, searching the files of the project.
+    References(ReferencesArgs),
+    /// Print the type of every expression in a file.
+    DumpTypes(DumpTypesArgs),
+    /// Print whether one type is a subtype of another.
+    IsSubtype(IsSubtypeArgs),
+}
+
+/// A position in a file, written `FILE:LINE:COL` with a 1-based line and column, as in
+/// Pyrefly's error output.
+#[derive(Debug, Clone)]
+pub struct FilePosition {
+    path: PathBuf,
+    line: LineNumber,
+    column: NonZeroU32,
+}
+
+impl FromStr for FilePosition {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.rsplitn(3, ':');
+        let (Some(column), Some(line), Some(path)) = (parts.next(), parts.next(), parts.next())
+        else {
+            anyhow::bail!("expected `FILE:LINE:COL`, got `{s}`");
+        };
+        let line = line
+            .parse::<u32>()
+            .ok()
+            .and_then(LineNumber::new)
+            .with_context(|| format!("invalid line `{line}` in `{s}`"))?;
+        let column = column
+            .parse::<u32>()
+            .ok()
+            .and_then(NonZeroU32::new)
+            .with_context(|| format!("invalid column `{column}` in `{s}`"))?;
+        Ok(Self {
+            path: PathBuf::from(path),
+            line,
+            column,
+        })
+    }
+}
+
+/// Where to find the configuration for a query.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct QueryConfigArgs {
+    /// Explicitly set the Pyrefly configuration to use. When not set, the configuration is
+    /// found by searching upwards from the queried file, or from the current directory.
+    #[arg(long, short, value_name = "FILE", env = clap_env("CONFIG"))]
+    config: Option<PathBuf>,
+    /// Configuration override options.
+    #[command(flatten, next_help_heading = "Config Overrides")]
+    config_override: ConfigOverrideArgs,
+}
+
+/// Arguments for queries about a single location.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct LocationArgs {
+    /// The location to query, as `FILE:LINE:COL`.
+    position: FilePosition,
+    /// Configuration options.
+    #[command(flatten)]
+    config: QueryConfigArgs,
+}
+
+/// Arguments for `pyrefly query references`.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct ReferencesArgs {
+    /// The location of the symbol, as `FILE:LINE:COL`.
+    position: FilePosition,
+    /// Files to search for references (glob supported). Defaults to the files of the project
+    /// configured for the current directory.
+    #[arg(long = "search", value_name = "FILES")]
+    search: Vec<String>,
+    /// Configuration options.
+    #[command(flatten)]
+    config: QueryConfigArgs,
+}
+
+/// Arguments for `pyrefly query dump-types`.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct DumpTypesArgs {
+    /// The file to dump types for.
+    file: PathBuf,
+    /// Configuration options.
+    #[command(flatten)]
+    config: QueryConfigArgs,
+}
+
+/// Arguments for `pyrefly query is-subtype`.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct IsSubtypeArgs {
+    /// The candidate subtype, as a type expression. Qualified names like `foo.bar.Baz` are
+    /// imported automatically.
+    subtype: String,
+    /// The candidate supertype, as a type expression.
+    supertype: String,
+    /// Configuration options.
+    #[command(flatten)]
+    config: QueryConfigArgs,
+}
+
+/// A range in a file, as printed by the location-based queries.
+#[derive(Debug, Serialize)]
+struct Location {
+    path: String,
+    #[serde(flatten)]
+    range: DisplayRange,
+}
+
+impl Location {
+    fn new(x: &TextRangeWithModule) -> Self {
+        Self {
+            path: x.module.path().as_path().display().to_string(),
+            range: x.module.display_range(x.range),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct TypeAtOutput {
+    path: String,
+    line: u32,
+    column: u32,
+    /// `None` if there is no expression with a type at the location.
+    #[serde(rename = "type")]
+    ty: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct DefinitionOutput {
+    definitions: Vec<Location>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReferencesOutput {
+    references: Vec<Location>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExprType {
+    /// The range of the expression, using the conventions of Python's `ast` module:
+    /// 1-based lines and 0-based UTF-8 byte columns.
+    location: PythonASTRange,
+    #[serde(rename = "type")]
+    ty: String,
+}
+
+#[derive(Debug, Serialize)]
+struct DumpTypesOutput {
+    path: String,
+    types: Vec<ExprType>,
+}
+
+#[derive(Debug, Serialize)]
+struct IsSubtypeOutput {
+    subtype: String,
+    supertype: String,
+    is_subtype: bool,
+}
+
+/// A query loaded with the given files, and the handle of `target` among them.
+fn load(
+    target: &Path,
+    search: Vec<String>,
+    config: QueryConfigArgs,
+    wrapper: Option<ConfigConfigurerWrapper>,
+    thread_count: ThreadCount,
+) -> anyhow::Result<(Query, Handle)> {
+    config.config_override.validate()?;
+    let target = target.absolutize();
+    let (files, config_finder, _) =
+        FilesArgs::get(search, config.config, config.config_override, wrapper)?;
+    let mut files: Vec<PathBuf> = config_finder.checkpoint(files.files_iter())?.collect();
+    if !files.contains(&target) {
+        files.push(target.clone());
+    }
+    let (handles, _, _) = Handles::new(files).all(&config_finder);
+    let handle = handles
+        .iter()
+        .find(|h| h.path().as_path() == target)
+        .with_context(|| format!("`{}` is not a Python file", target.display()))?
+        .dupe();
+    let query = Query::new(config_finder, thread_count).with_sys_info(handle.sys_info().dupe());
+    query.add_files(
+        handles
+            .into_iter()
+            .map(|h| (h.module(), h.path().dupe()))
+            .collect(),
+    );
+    Ok((query, handle))
+}
+
+/// A query loaded with only the file containing `position`, and the offset of `position` in it.
+fn load_position(
+    position: &FilePosition,
+    search: Vec<String>,
+    config: QueryConfigArgs,
+    wrapper: Option<ConfigConfigurerWrapper>,
+    thread_count: ThreadCount,
+) -> anyhow::Result<(Query, Handle, TextSize)> {
+    let (query, handle) = load(&position.path, search, config, wrapper, thread_count)?;
+    let contents = std::fs::read_to_string(handle.path().as_path())
+        .with_context(|| format!("reading `{}`", handle.path()))?;
+    let lined_buffer = LinedBuffer::new(Arc::new(contents));
+    if position.line.to_zero_indexed() as usize >= lined_buffer.line_count() {
+        anyhow::bail!(
+            "line {} is past the end of `{}`",
+            position.line,
+            handle.path()
+        );
+    }
+    let offset = lined_buffer.from_display_pos(DisplayPos::Source {
+        line: position.line,
+        column: position.column,
+    });
+    Ok((query, handle, offset))
+}
+
+fn print_json(output: &impl Serialize) -> anyhow::Result<CommandExitStatus> {
+    println!("{}", serde_json::to_string_pretty(output)?);
+    Ok(CommandExitStatus::Success)
+}
+
+impl QueryCommand {
+    pub fn run(
+        self,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        match self {
+            QueryCommand::TypeAt(args) => {
+                let (query, handle, offset) = load_position(
+                    &args.position,
+                    vec![args.position.path.display().to_string()],
+                    args.config,
+                    wrapper,
+                    thread_count,
+                )?;
+                print_json(&TypeAtOutput {
+                    path: handle.path().as_path().display().to_string(),
+                    line: args.position.line.get(),
+                    column: args.position.column.get(),
+                    ty: query.get_type_at(handle.module(), handle.path().dupe(), offset),
+                })
+            }
+            QueryCommand::Definition(args) => {
+                let (query, handle, offset) = load_position(
+                    &args.position,
+                    vec![args.position.path.display().to_string()],
+                    args.config,
+                    wrapper,
+                    thread_count,
+                )?;
+                let definitions =
+                    query.get_definitions(handle.module(), handle.path().dupe(), offset);
+                print_json(&DefinitionOutput {
+                    definitions: definitions.iter().map(Location::new).collect(),
+                })
+            }
+            QueryCommand::References(args) => {
+                let (query, handle, offset) = load_position(
+                    &args.position,
+                    args.search,
+                    args.config,
+                    wrapper,
+                    thread_count,
+                )?;
+                let mut references =
+                    query.get_references(handle.module(), handle.path().dupe(), offset);
+                references.sort_by_key(|r| (r.module.path().dupe(), r.range.start()));
+                print_json(&ReferencesOutput {
+                    references: references.iter().map(Location::new).collect(),
+                })
+            }
+            QueryCommand::DumpTypes(args) => {
+                let (query, handle) = load(
+                    &args.file,
+                    vec![args.file.display().to_string()],
+                    args.config,
+                    wrapper,
+                    thread_count,
+                )?;
+                let types = query
+                    .get_types_in_file(handle.module(), handle.path().dupe())
+                    .with_context(|| format!("could not load `{}`", handle.path()))?;
+                print_json(&DumpTypesOutput {
+                    path: handle.path().as_path().display().to_string(),
+                    types: types
+                        .into_iter()
+                        .map(|(location, ty)| ExprType { location, ty })
+                        .collect(),
+                })
+            }
+            QueryCommand::IsSubtype(args) => {
+                args.config.config_override.validate()?;
+                let config_finder =
+                    get_config_finder_for_snippet(args.config.config, args.config.config_override)?;
+                let cwd = std::env::current_dir().context("cannot identify current dir")?;
+                let query = Query::new(config_finder, thread_count);
+                let is_subtype = query
+                    .is_subtype(
+                        ModuleName::from_str("__pyrefly_query__"),
+                        cwd.join("__pyrefly_query__.py"),
+                        &args.subtype,
+                        &args.supertype,
+                    )
+                    .map_err(|e| anyhow::anyhow!("Could not resolve the types:\n{e}"))?;
+                print_json(&IsSubtypeOutput {
+                    subtype: args.subtype,
+                    supertype: args.supertype,
+                    is_subtype,
+                })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_position() {
+        let position = "dir/main.py:3:7".parse::<FilePosition>().unwrap();
+        assert_eq!(position.path, PathBuf::from("dir/main.py"));
+        assert_eq!(position.line.get(), 3);
+        assert_eq!(position.column.get(), 7);
+
+        let position = "C:/dir/main.py:1:1".parse::<FilePosition>().unwrap();
+        assert_eq!(position.path, PathBuf::from("C:/dir/main.py"));
+
+        assert!("main.py:3".parse::<FilePosition>().is_err());
+        assert!("main.py:0:1".parse::<FilePosition>().is_err());
+        assert!("main.py:1:x".parse::<FilePosition>().is_err());
+    }
+}
@@ -0,0 +1,447 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! `pyrefly refactor`: project-wide renames and moves, using the same machinery as the
+//! corresponding IDE refactorings.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use clap::Parser;
+use clap::Subcommand;
+use dupe::Dupe;
+use pyrefly_build::handle::Handle;
+use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_python::keywords::is_reserved_keyword;
+use pyrefly_python::module::Module;
+use pyrefly_python::module::TextRangeWithModule;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_name::is_python_identifier;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_util::args::clap_env;
+use pyrefly_util::forgetter::Forgetter;
+use pyrefly_util::fs_anyhow;
+use pyrefly_util::text_edits::TextEdits;
+use pyrefly_util::thread_pool::ThreadCount;
+use ruff_python_ast::Expr;
+use ruff_python_ast::Stmt;
+use ruff_text_size::TextRange;
+use tracing::info;
+
+use crate::commands::check::Handles;
+use crate::commands::config_finder::ConfigConfigurerWrapper;
+use crate::commands::files::FilesArgs;
+use crate::commands::util::CommandExitStatus;
+use crate::state::lsp::FindPreference;
+use crate::state::lsp::ImportBehavior;
+use crate::state::lsp::ImportFormat;
+use crate::state::lsp::ReferenceOptions;
+use crate::state::require::Require;
+use crate::state::state::State;
+use crate::state::state::Transaction;
+
+/// Subcommands of `pyrefly refactor`.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Subcommand)]
+pub enum RefactorCommand {
+    /// Rename a symbol and every reference to it in the project.
+    Rename(RenameArgs),
+    /// Move a top-level symbol to another module, updating the imports that refer to it.
+    /// The old module re-exports the symbol, so imports outside the project keep working.
+    Move(MoveArgs),
+}
+
+/// Arguments shared by the refactorings.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct RefactorCommonArgs {
+    /// Explicitly set the Pyrefly configuration to use. When not set, the configuration of
+    /// the current directory is used, and every file in that project is updated.
+    #[arg(long, short, value_name = "FILE", env = clap_env("CONFIG"))]
+    config: Option<PathBuf>,
+    /// Configuration override options.
+    #[command(flatten, next_help_heading = "Config Overrides")]
+    config_override: ConfigOverrideArgs,
+    /// Print a unified diff of the changes instead of writing them.
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Arguments for `pyrefly refactor rename`.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct RenameArgs {
+    /// The symbol to rename, e.g. `pkg.module.Class` or `pkg.module.Class.method`.
+    qualified_name: String,
+    /// The new name, without any module prefix.
+    new_name: String,
+    /// Common options.
+    #[command(flatten)]
+    common: RefactorCommonArgs,
+}
+
+/// Arguments for `pyrefly refactor move`.
+#[deny(clippy::missing_docs_in_private_items)]
+#[derive(Debug, Clone, Parser)]
+pub struct MoveArgs {
+    /// The top-level symbol to move, e.g. `pkg.module.function`.
+    qualified_name: String,
+    /// The module to move it to, e.g. `pkg.other`. Created if it does not exist.
+    target_module: String,
+    /// Common options.
+    #[command(flatten)]
+    common: RefactorCommonArgs,
+}
+
+/// The edits to make to each file, by path.
+#[derive(Default)]
+struct Changes(BTreeMap<PathBuf, (String, TextEdits)>);
+
+impl Changes {
+    fn add(
+        &mut self,
+        edits: impl IntoIterator<Item = (Module, TextRange, String)>,
+    ) -> anyhow::Result<()> {
+        for (module, range, text) in edits {
+            let path = module.path().as_path().to_owned();
+            let (_, file) = self
+                .0
+                .entry(path)
+                .or_insert_with(|| (module.contents().as_str().to_owned(), TextEdits::new()));
+            if !file.add([(range, text)]) {
+                anyhow::bail!(
+                    "Conflicting edits in `{}`, nothing was changed",
+                    module.path()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn add_new_file(&mut self, path: PathBuf, contents: String) {
+        let mut edits = TextEdits::new();
+        edits.add([(TextRange::default(), contents)]);
+        self.0.insert(path, (String::new(), edits));
+    }
+
+    fn finish(self, dry_run: bool) -> anyhow::Result<CommandExitStatus> {
+        for (path, (source, edits)) in &self.0 {
+            if dry_run {
+                print!(
+                    "{}",
+                    edits.unified_diff(&path.display().to_string(), source)
+                );
+            } else {
+                fs_anyhow::write(path, edits.apply(source))?;
+            }
+        }
+        let verb = if dry_run { "Would change" } else { "Changed" };
+        info!("{verb} {} file(s)", self.0.len());
+        Ok(CommandExitStatus::Success)
+    }
+}
+
+/// The range of the name of the definition at `path` within `body`, where `path` is a
+/// sequence of nested class and member names.
+fn find_member(body: &[Stmt], path: &[&str]) -> Option<TextRange> {
+    let (name, rest) = path.split_first()?;
+    for stmt in body {
+        match stmt {
+            Stmt::ClassDef(x) if x.name.id.as_str() == *name => {
+                return if rest.is_empty() {
+                    Some(x.name.range)
+                } else {
+                    find_member(&x.body, rest)
+                };
+            }
+            _ if !rest.is_empty() => {}
+            Stmt::FunctionDef(x) if x.name.id.as_str() == *name => return Some(x.name.range),
+            Stmt::Assign(x) => {
+                for target in &x.targets {
+                    if let Expr::Name(target) = target
+                        && target.id.as_str() == *name
+                    {
+                        return Some(target.range);
+                    }
+                }
+            }
+            Stmt::AnnAssign(x) => {
+                if let Expr::Name(target) = &*x.target
+                    && target.id.as_str() == *name
+                {
+                    return Some(target.range);
+                }
+            }
+            Stmt::TypeAlias(x) => {
+                if let Expr::Name(target) = &*x.name
+                    && target.id.as_str() == *name
+                {
+                    return Some(target.range);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Find the handle of the project module that defines `qualified_name`, the range of the
+/// definition's name, and the number of components after the module name.
+fn resolve_qualified_name(
+    transaction: &Transaction,
+    handles: &[Handle],
+    qualified_name: &str,
+) -> anyhow::Result<(Handle, TextRange, usize)> {
+    let components: Vec<&str> = qualified_name.split('.').collect();
+    // Prefer the longest module prefix, so `pkg.mod.x` is looked up in `pkg.mod`, not `pkg`.
+    for split in (1..components.len()).rev() {
+        let module = ModuleName::from_str(&components[..split].join("."));
+        let Some(handle) = handles.iter().find(|h| h.module() == module) else {
+            continue;
+        };
+        let ast = transaction
+            .get_ast(handle)
+            .with_context(|| format!("Could not parse `{}`", handle.path()))?;
+        if let Some(range) = find_member(&ast.body, &components[split..]) {
+            return Ok((handle.dupe(), range, components.len() - split));
+        }
+    }
+    anyhow::bail!("Could not find a definition of `{qualified_name}` in the project")
+}
+
+/// Check the project in a fresh transaction.
+fn load_project<'a>(
+    holder: &'a Forgetter<State>,
+    handles: Handles,
+) -> anyhow::Result<(Forgetter<Transaction<'a>>, Vec<Handle>)> {
+    let mut forgetter = Forgetter::new(
+        holder.as_ref().new_transaction(Require::Exports, None),
+        true,
+    );
+    let (handles, _, sourcedb_errors) = handles.all(holder.as_ref().config_finder());
+    if !sourcedb_errors.is_empty() {
+        for error in sourcedb_errors {
+            error.print();
+        }
+        return Err(anyhow::anyhow!("Failed to query sourcedb."));
+    }
+    forgetter.as_mut().run(&handles, Require::Everything, None);
+    Ok((forgetter, handles))
+}
+
+/// The state for the project of the current directory, and its files.
+fn project(
+    common: &RefactorCommonArgs,
+    wrapper: Option<ConfigConfigurerWrapper>,
+    thread_count: ThreadCount,
+) -> anyhow::Result<(Forgetter<State>, Handles)> {
+    common.config_override.validate()?;
+    let (files, config_finder, _) = FilesArgs::get(
+        Vec::new(),
+        common.config.clone(),
+        common.config_override.clone(),
+        wrapper,
+    )?;
+    let files = config_finder.checkpoint(files.files_iter())?;
+    let handles = Handles::new(files);
+    let state = State::new(config_finder, thread_count);
+    Ok((Forgetter::new(state, false), handles))
+}
+
+/// Whether `name` can be written as a name in Python source, so it is an identifier and
+/// not a keyword.
+fn is_identifier(name: &str) -> bool {
+    is_python_identifier(name) && !is_reserved_keyword(name)
+}
+
+impl RenameArgs {
+    fn run(
+        self,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        if !is_identifier(&self.new_name) {
+            anyhow::bail!("`{}` is not a valid Python identifier", self.new_name);
+        }
+        let (holder, handles) = project(&self.common, wrapper, thread_count)?;
+        let (mut forgetter, handles) = load_project(&holder, handles)?;
+        let transaction = forgetter.as_mut();
+        let (handle, range, _) =
+            resolve_qualified_name(transaction, &handles, &self.qualified_name)?;
+        let definitions = transaction
+            .find_definition(
+                &handle,
+                range.start(),
+                FindPreference {
+                    import_behavior: ImportBehavior::StopAtRenamedImports,
+                    resolve_call_dunders: false,
+                    ..Default::default()
+                },
+            )
+            .map_err(|_| {
+                anyhow::anyhow!("Could not find the definition of `{}`", self.qualified_name)
+            })?;
+        let mut changes = Changes::default();
+        for definition in definitions {
+            let references = transaction
+                .find_global_references_from_definition(
+                    handle.sys_info().dupe(),
+                    definition.metadata,
+                    TextRangeWithModule::new(definition.module, definition.definition_range),
+                    ReferenceOptions::textual_only(true),
+                )
+                .map_err(|_| anyhow::anyhow!("Finding references was cancelled"))?;
+            for (module, ranges) in references {
+                changes.add(
+                    ranges
+                        .into_iter()
+                        .map(|range| (module.dupe(), range, self.new_name.clone())),
+                )?;
+            }
+        }
+        changes.finish(self.common.dry_run)
+    }
+}
+
+/// The path of a new module `target`, placed in the same import root as `source`.
+fn new_module_path(source: &Handle, target: ModuleName) -> anyhow::Result<PathBuf> {
+    let depth = source.module().components().len() + usize::from(source.path().is_init());
+    let root = source
+        .path()
+        .as_path()
+        .ancestors()
+        .nth(depth)
+        .with_context(|| format!("Could not find the import root of `{}`", source.path()))?;
+    let mut path = root.to_path_buf();
+    for component in target.components() {
+        path.push(component.as_str());
+    }
+    path.set_extension("py");
+    if !path.parent().is_some_and(|parent| parent.is_dir()) {
+        anyhow::bail!(
+            "Cannot create `{}`: the directory does not exist",
+            path.display()
+        );
+    }
+    Ok(path)
+}
+
+impl MoveArgs {
+    fn run(
+        self,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        if !self.target_module.split('.').all(is_identifier) {
+            anyhow::bail!("`{}` is not a valid module name", self.target_module);
+        }
+        let target = ModuleName::from_str(&self.target_module);
+        let (holder, handles) = project(&self.common, wrapper, thread_count)?;
+        let (forgetter, handles) = load_project(&holder, handles)?;
+        let transaction = forgetter.as_ref();
+        let (handle, range, depth) =
+            resolve_qualified_name(transaction, &handles, &self.qualified_name)?;
+        if depth != 1 {
+            anyhow::bail!(
+                "Only top-level definitions can be moved, `{}` is nested",
+                self.qualified_name
+            );
+        }
+        if handle.module() == target {
+            anyhow::bail!("`{}` is already in `{target}`", self.qualified_name);
+        }
+        let context = transaction
+            .module_member_move_context(&handle, TextRange::empty(range.start()))
+            .with_context(|| format!("`{}` cannot be moved", self.qualified_name))?;
+        let mut changes = Changes::default();
+        let target_handle = match handles.iter().find(|h| h.module() == target) {
+            Some(target_handle) => {
+                let insertion = transaction
+                    .module_member_insertion_edit(target_handle, &context.member_text)
+                    .with_context(|| format!("Could not load `{target}`"))?;
+                changes.add([insertion])?;
+                target_handle.dupe()
+            }
+            None => {
+                let path = new_module_path(&handle, target)?;
+                if path.exists() {
+                    anyhow::bail!("`{}` exists but is not part of the project", path.display());
+                }
+                changes.add_new_file(path.clone(), context.member_text.clone());
+                Handle::new(
+                    target,
+                    ModulePath::filesystem(path),
+                    handle.sys_info().dupe(),
+                )
+            }
+        };
+        let edits = transaction
+            .module_member_move_edits(&handle, &context, &target_handle, ImportFormat::default())
+            .with_context(|| format!("`{}` cannot be moved", self.qualified_name))?;
+        changes.add(edits)?;
+        changes.finish(self.common.dry_run)
+    }
+}
+
+impl RefactorCommand {
+    pub fn run(
+        self,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        match self {
+            RefactorCommand::Rename(args) => args.run(wrapper, thread_count),
+            RefactorCommand::Move(args) => args.run(wrapper, thread_count),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pyrefly_python::ast::Ast;
+    use ruff_python_ast::PySourceType;
+
+    use super::*;
+
+    #[test]
+    fn test_find_member() {
+        let code = r#"
+x = 1
+y: int = 2
+def f(): ...
+class C:
+    z = 3
+    def m(self): ...
+    class D: ...
+"#;
+        let (ast, _, _) = Ast::parse(code, PySourceType::Python);
+        let find = |path: &[&str]| find_member(&ast.body, path).map(|r| &code[r]);
+        assert_eq!(find(&["x"]), Some("x"));
+        assert_eq!(find(&["y"]), Some("y"));
+        assert_eq!(find(&["f"]), Some("f"));
+        assert_eq!(find(&["C"]), Some("C"));
+        assert_eq!(find(&["C", "z"]), Some("z"));
+        assert_eq!(find(&["C", "m"]), Some("m"));
+        assert_eq!(find(&["C", "D"]), Some("D"));
+        assert_eq!(find(&["C", "x"]), None);
+        assert_eq!(find(&["f", "x"]), None);
+        assert_eq!(find(&["missing"]), None);
+    }
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("foo_1"));
+        assert!(is_identifier("_Bar"));
+        assert!(!is_identifier(""));
+        assert!(!is_identifier("1foo"));
+        assert!(!is_identifier("foo.bar"));
+        assert!(!is_identifier("class"));
+        assert!(!is_identifier("None"));
+        assert!(is_identifier("match"));
+    }
+}
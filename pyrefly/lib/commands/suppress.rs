@@ -5,19 +5,28 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use std::iter;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use clap::Parser;
 use pyrefly_config::args::ConfigOverrideArgs;
+use pyrefly_python::ignore::Tool;
+use pyrefly_util::arc_id::ArcId;
 use pyrefly_util::thread_pool::ThreadCount;
 
 use crate::commands::check::CheckArgs;
+use crate::commands::config_finder::ConfigConfigurer;
 use crate::commands::config_finder::ConfigConfigurerWrapper;
 use crate::commands::files::FilesArgs;
 use crate::commands::util::CommandExitStatus;
+use crate::config::config::ConfigFile;
+use crate::config::finder::ConfigError;
 use crate::error::suppress;
 use crate::error::suppress::CommentLocation;
 use crate::error::suppress::SerializedError;
+use crate::error::suppress::SuppressionSource;
 use crate::error::suppress::UnusedIgnoreKind;
 
 /// Suppress type errors by adding ignore comments to source files.
@@ -52,6 +61,42 @@ pub struct SuppressArgs {
     /// (`line-before`, the default) or on the same line (`same-line`).
     #[arg(long, default_value = "line-before")]
     comment_location: CommentLocation,
+
+    /// Convert another type checker's suppression comments into `# pyrefly: ignore` comments,
+    /// keeping only the errors Pyrefly reports under each and removing comments that suppress nothing.
+    #[arg(
+        long,
+        value_enum,
+        value_name = "CHECKER",
+        conflicts_with_all = ["json", "remove_unused"]
+    )]
+    convert_from: Option<SuppressionSource>,
+}
+
+/// Wraps a [`ConfigConfigurer`] to stop honoring one tool's suppression comments,
+/// so that the errors they hide are reported.
+struct IgnoresDisabled {
+    inner: Arc<dyn ConfigConfigurer>,
+    tool: Tool,
+}
+
+impl ConfigConfigurer for IgnoresDisabled {
+    fn configure(
+        &self,
+        root: Option<&Path>,
+        config: ConfigFile,
+        errors: Vec<ConfigError>,
+    ) -> (ArcId<ConfigFile>, Vec<ConfigError>) {
+        let (config, errors) = self.inner.configure(root, config, errors);
+        let mut config = (*config).clone();
+        let sub_configs = config.sub_configs.iter_mut().map(|c| &mut c.settings);
+        for base in iter::once(&mut config.root).chain(sub_configs) {
+            if let Some(tools) = &mut base.enabled_ignores {
+                tools.shift_remove(&self.tool);
+            }
+        }
+        (ArcId::new(config), errors)
+    }
 }
 
 impl SuppressArgs {
@@ -61,6 +106,9 @@ impl SuppressArgs {
         wrapper: Option<ConfigConfigurerWrapper>,
         thread_count: ThreadCount,
     ) -> anyhow::Result<CommandExitStatus> {
+        if let Some(from) = self.convert_from {
+            return self.run_convert(from, version, wrapper, thread_count);
+        }
         if let Some(kind) = self.remove_unused {
             // Remove unused ignores mode
             let unused_errors: Vec<SerializedError> = if let Some(json_path) = &self.json {
@@ -150,6 +198,44 @@ impl SuppressArgs {
 
         Ok(CommandExitStatus::Success)
     }
+
+    /// Check with `from`'s suppression comments disabled, then rewrite those comments
+    /// based on the errors that show up.
+    fn run_convert(
+        &self,
+        from: SuppressionSource,
+        version: &str,
+        wrapper: Option<ConfigConfigurerWrapper>,
+        thread_count: ThreadCount,
+    ) -> anyhow::Result<CommandExitStatus> {
+        self.config_override.validate()?;
+        let tool = from.tool();
+        let disable: ConfigConfigurerWrapper = Arc::new(move |inner| {
+            let inner = match &wrapper {
+                Some(wrap) => wrap(inner),
+                None => inner,
+            };
+            Arc::new(IgnoresDisabled { inner, tool })
+        });
+        let (files_to_check, config_finder, upsell) = self
+            .files
+            .clone()
+            .resolve(self.config_override.clone(), Some(disable))?;
+        let paths: Vec<PathBuf> = files_to_check.files_iter()?.collect();
+
+        let check_args = CheckArgs::parse_from(["check", "--output-format", "omit-errors"]);
+        let (_, errors, _check_result) =
+            check_args.run_once(version, files_to_check, config_finder, upsell, thread_count)?;
+        let errors = errors
+            .into_iter()
+            .filter(|e| !e.error_kind().is_directive())
+            .filter_map(|e| SerializedError::from_error(&e))
+            .filter(|e| !e.is_unused_ignore() && !e.is_unused_type_ignore())
+            .collect();
+
+        suppress::convert_suppressions(errors, &paths, from);
+        Ok(CommandExitStatus::Success)
+    }
 }
 
 #[cfg(test)]
@@ -175,4 +261,14 @@ mod tests {
             assert_eq!(args.remove_unused, expected);
         }
     }
+
+    #[test]
+    fn convert_from_cli_values() {
+        let args = SuppressArgs::parse_from(["suppress", "--convert-from", "pyre"]);
+        assert_eq!(args.convert_from, Some(SuppressionSource::Pyre));
+        assert!(
+            SuppressArgs::try_parse_from(["suppress", "--convert-from", "mypy", "--remove-unused"])
+                .is_err()
+        );
+    }
 }
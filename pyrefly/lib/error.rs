@@ -13,6 +13,7 @@ pub mod display;
 pub mod error;
 pub mod expectation;
 pub mod legacy;
+pub mod persisted;
 pub mod signature_diff;
 pub mod style;
 pub mod summarize;
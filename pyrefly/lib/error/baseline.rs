@@ -7,17 +7,30 @@
 
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::hash::Hash as _;
+use std::hash::Hasher as _;
 use std::path::Path;
 use std::path::PathBuf;
 
 use anyhow::Result;
+use dupe::Dupe;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module_path::ModulePath;
 use pyrefly_util::absolutize::Absolutize;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::ModModule;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use xxhash_rust::xxh64::Xxh64;
 
 use crate::error::error::Error;
 use crate::error::legacy::BaselineError;
 use crate::error::legacy::BaselineErrors;
+use crate::error::legacy::BaselineLimit;
 
-/// If an error with an exactly matching path, error slug, and starting column exist in the baseline, we ignore it.
+/// Baseline entries written before fingerprints existed are matched by an exactly matching
+/// path, error slug, and starting column.
 /// Keys always use absolute paths internally so that comparison is decoupled from path format in baseline file.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct BaselineKey {
@@ -26,6 +39,24 @@ struct BaselineKey {
     column: usize,
 }
 
+/// Baseline entries with a fingerprint match errors of the same kind and message, in the same
+/// file and enclosing function or class, that point at the same code in the same statement.
+/// They keep matching when the code moves to another line or is reformatted.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FingerprintKey {
+    path: String,
+    name: String,
+    scope: String,
+    fingerprint: String,
+}
+
+/// Per-file limits match any error of the kind in the file, while the limit is not used up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct LimitKey {
+    path: String,
+    name: String,
+}
+
 /// Normalize a path to an absolute, forward-slash string.
 pub(crate) fn normalize_baseline_path(path: &Path, relative_to: &Path) -> String {
     path.absolutize_from(relative_to)
@@ -51,9 +82,249 @@ impl BaselineKey {
     }
 }
 
-/// A lightweight, keys-only baseline matcher for the language server.
+impl LimitKey {
+    fn from_baseline_limit(limit: &BaselineLimit, relative_to: &Path) -> Self {
+        Self {
+            path: normalize_baseline_path(Path::new(&limit.path), relative_to),
+            name: limit.name.clone(),
+        }
+    }
+}
+
+/// The location of an error, described by its content rather than its position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ErrorFingerprint {
+    /// The qualified name of the enclosing function or class, empty at the top level.
+    pub scope: String,
+    /// A hash of the error message, the code the error points at, and the statement
+    /// containing it, ignoring formatting.
+    pub fingerprint: String,
+}
+
+/// The length of the string literal at the start of `code`, up to and including its closing
+/// quote, or the rest of `code` if the literal is not closed.
+fn string_literal_len(code: &str) -> usize {
+    let quote = if code.starts_with("'''") || code.starts_with("\"\"\"") {
+        &code[..3]
+    } else {
+        &code[..1]
+    };
+    let char_len = |i: usize| code[i..].chars().next().map_or(1, char::len_utf8);
+    let mut i = quote.len();
+    while i < code.len() {
+        if code[i..].starts_with('\\') {
+            i += 1;
+            if i < code.len() {
+                i += char_len(i);
+            }
+        } else if code[i..].starts_with(quote) {
+            return i + quote.len();
+        } else {
+            i += char_len(i);
+        }
+    }
+    code.len()
+}
+
+/// Strip the formatting from a snippet of code, so that it is unchanged by a formatter
+/// splitting or joining lines. The contents of string literals are kept as written.
+fn normalize_snippet(code: &str) -> String {
+    let mut res = String::with_capacity(code.len());
+    // Formatters add a trailing comma when they split a bracketed list over several lines,
+    // so a comma is only kept once we know it is not followed by a closing bracket.
+    let mut pending_comma = false;
+    let mut rest = code;
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        if pending_comma && !matches!(c, ')' | ']' | '}') {
+            res.push(',');
+        }
+        pending_comma = c == ',';
+        let len = match c {
+            ',' => 1,
+            '\'' | '"' => string_literal_len(rest),
+            _ => c.len_utf8(),
+        };
+        if !pending_comma {
+            res.push_str(&rest[..len]);
+        }
+        rest = &rest[len..];
+    }
+    if pending_comma {
+        res.push(',');
+    }
+    res
+}
+
+/// The end of the header of a compound statement, which is where its body starts.
+fn header_end(node: AnyNodeRef) -> Option<TextSize> {
+    match node {
+        AnyNodeRef::StmtFunctionDef(x) => x.body.first().map(|x| x.start()),
+        AnyNodeRef::StmtClassDef(x) => x.body.first().map(|x| x.start()),
+        AnyNodeRef::StmtIf(x) => x.body.first().map(|x| x.start()),
+        AnyNodeRef::StmtFor(x) => x.body.first().map(|x| x.start()),
+        AnyNodeRef::StmtWhile(x) => x.body.first().map(|x| x.start()),
+        AnyNodeRef::StmtWith(x) => x.body.first().map(|x| x.start()),
+        AnyNodeRef::StmtTry(x) => x.body.first().map(|x| x.start()),
+        AnyNodeRef::StmtMatch(x) => x.cases.first().map(|x| x.start()),
+        _ => None,
+    }
+}
+
+/// The code of the innermost statement containing `error`. Only the header of a compound
+/// statement is included, so that changes elsewhere in its body don't affect the result.
+fn statement_at<'a>(ast: &ModModule, error: &'a Error) -> &'a str {
+    let Some(stmt) = Ast::locate_node(ast, error.range().start())
+        .into_iter()
+        .find(|node| node.as_stmt_ref().is_some())
+    else {
+        return "";
+    };
+    let end = header_end(stmt).unwrap_or(stmt.end());
+    error
+        .module()
+        .code_at(TextRange::new(stmt.start(), end.max(stmt.start())))
+}
+
+/// The names of the functions and classes enclosing `error`, outermost first.
+fn scope_at(ast: &ModModule, error: &Error) -> String {
+    let mut names: Vec<&str> = Ast::locate_node(ast, error.range().start())
+        .into_iter()
+        .filter_map(|node| match node {
+            AnyNodeRef::StmtFunctionDef(x) => Some(x.name.as_str()),
+            AnyNodeRef::StmtClassDef(x) => Some(x.name.as_str()),
+            _ => None,
+        })
+        .collect();
+    names.reverse();
+    names.join(".")
+}
+
+/// Computes fingerprints of errors, parsing each file at most once.
+#[derive(Debug, Default)]
+pub(crate) struct Fingerprinter {
+    asts: HashMap<ModulePath, ModModule>,
+}
+
+impl Fingerprinter {
+    /// The fingerprint of `error`, or `None` for errors in notebooks, which are matched by
+    /// position.
+    pub(crate) fn fingerprint(&mut self, error: &Error) -> Option<ErrorFingerprint> {
+        let module = error.module();
+        if module.is_notebook() {
+            return None;
+        }
+        let ast = self
+            .asts
+            .entry(error.path().dupe())
+            .or_insert_with(|| Ast::parse(module.contents(), module.source_type()).0);
+        let mut hasher = Xxh64::new(0);
+        error.msg_header().hash(&mut hasher);
+        normalize_snippet(module.code_at(error.range())).hash(&mut hasher);
+        normalize_snippet(statement_at(ast, error)).hash(&mut hasher);
+        Some(ErrorFingerprint {
+            scope: scope_at(ast, error),
+            fingerprint: format!("{:016x}", hasher.finish()),
+        })
+    }
+}
+
+/// How many errors a baseline key allows, and how many of them have occurred. Each entry
+/// allows one error, and a per-file limit allows its count.
+#[derive(Debug, Clone, Copy, Default)]
+struct Uses {
+    count: usize,
+    used: usize,
+}
+
+impl Uses {
+    /// Record an error against the key, if it allows any more.
+    fn take(&mut self) -> bool {
+        let available = self.used < self.count;
+        if available {
+            self.used += 1;
+        }
+        available
+    }
+}
+
+/// The entries of a baseline, indexed for matching, with how many errors each key has matched.
+#[derive(Debug, Default)]
+struct BaselineMatcher {
+    positions: HashMap<BaselineKey, Uses>,
+    fingerprints: HashMap<FingerprintKey, Uses>,
+    limits: HashMap<LimitKey, Uses>,
+    fingerprinter: Fingerprinter,
+}
+
+impl BaselineMatcher {
+    fn new(baseline: &BaselineErrors, relative_to: &Path) -> Self {
+        let mut res = Self::default();
+        for error in &baseline.errors {
+            let uses = match Self::fingerprint_key(error, relative_to) {
+                Some(key) => res.fingerprints.entry(key).or_default(),
+                None => res
+                    .positions
+                    .entry(BaselineKey::from_baseline_error(error, relative_to))
+                    .or_default(),
+            };
+            uses.count += 1;
+        }
+        for limit in &baseline.limits {
+            res.limits
+                .entry(LimitKey::from_baseline_limit(limit, relative_to))
+                .or_default()
+                .count += limit.count;
+        }
+        res
+    }
+
+    fn fingerprint_key(error: &BaselineError, relative_to: &Path) -> Option<FingerprintKey> {
+        Some(FingerprintKey {
+            path: normalize_baseline_path(Path::new(&error.path), relative_to),
+            name: error.name.clone(),
+            scope: error.scope.clone(),
+            fingerprint: error.fingerprint.clone()?,
+        })
+    }
+
+    /// Whether `error` is in the baseline, recording the entry that it matched. Each entry
+    /// matches at most one error, so duplicate entries are needed for repeated errors.
+    fn matches(&mut self, error: &Error) -> bool {
+        let key = BaselineKey::from_error(error);
+        if self.positions.get_mut(&key).is_some_and(Uses::take) {
+            return true;
+        }
+        if !self.fingerprints.is_empty()
+            && let Some(ErrorFingerprint { scope, fingerprint }) =
+                self.fingerprinter.fingerprint(error)
+            && self
+                .fingerprints
+                .get_mut(&FingerprintKey {
+                    path: key.path.clone(),
+                    name: key.name.clone(),
+                    scope,
+                    fingerprint,
+                })
+                .is_some_and(Uses::take)
+        {
+            return true;
+        }
+        self.limits
+            .get_mut(&LimitKey {
+                path: key.path,
+                name: key.name,
+            })
+            .is_some_and(Uses::take)
+    }
+}
+
+/// A lightweight matcher for the language server, which does not track stale entries.
 pub struct BaselineProcessor {
-    baseline_keys: HashSet<BaselineKey>,
+    matcher: BaselineMatcher,
 }
 
 impl BaselineProcessor {
@@ -68,20 +339,22 @@ impl BaselineProcessor {
 
     fn from_baseline_errors(baseline_errors: BaselineErrors, relative_to: &Path) -> Self {
         Self {
-            baseline_keys: baseline_errors
-                .errors
-                .iter()
-                .map(|error| BaselineKey::from_baseline_error(error, relative_to))
-                .collect(),
+            matcher: BaselineMatcher::new(&baseline_errors, relative_to),
         }
     }
 
-    pub fn matches_baseline(&self, error: &Error) -> bool {
-        self.baseline_keys.contains(&BaselineKey::from_error(error))
+    /// Whether `error` is in the baseline. Each error matched against a per-file limit uses
+    /// up part of it, so each error should only be passed once.
+    pub fn matches_baseline(&mut self, error: &Error) -> bool {
+        self.matcher.matches(error)
     }
 
     /// Baseline suppressions are processed last, after inline and config suppressions.
-    pub fn process_errors(&self, shown_errors: &mut Vec<Error>, baseline_errors: &mut Vec<Error>) {
+    pub fn process_errors(
+        &mut self,
+        shown_errors: &mut Vec<Error>,
+        baseline_errors: &mut Vec<Error>,
+    ) {
         let (matched, remaining) = shown_errors
             .drain(..)
             .partition(|error| self.matches_baseline(error));
@@ -92,8 +365,12 @@ impl BaselineProcessor {
 
 /// The result of classifying unmatched baseline entries after a CLI check.
 pub struct BaselinePruningResult {
+    /// The number of errors the baseline allows that did not occur. Per-file limits
+    /// contribute the part of the limit that was not used.
     pub unused_entry_count: usize,
-    pub retained_entries: Vec<BaselineError>,
+    /// The baseline without unused entries, with per-file limits lowered to the number of
+    /// errors that occurred.
+    pub retained_entries: BaselineErrors,
 }
 
 fn is_definitely_unused(
@@ -106,8 +383,8 @@ fn is_definitely_unused(
 
 /// A baseline matcher that also retains rows and tracks matches for CLI maintenance actions.
 pub struct TrackedBaselineProcessor {
-    entries: Vec<BaselineError>,
-    keys: HashMap<BaselineKey, bool>,
+    entries: BaselineErrors,
+    matcher: BaselineMatcher,
     relative_to: PathBuf,
 }
 
@@ -118,14 +395,9 @@ impl TrackedBaselineProcessor {
     }
 
     fn from_baseline_errors(baseline_errors: BaselineErrors, relative_to: &Path) -> Self {
-        let entries = baseline_errors.errors;
-        let keys = entries
-            .iter()
-            .map(|error| (BaselineKey::from_baseline_error(error, relative_to), false))
-            .collect();
         Self {
-            entries,
-            keys,
+            matcher: BaselineMatcher::new(&baseline_errors, relative_to),
+            entries: baseline_errors,
             relative_to: relative_to.to_owned(),
         }
     }
@@ -139,8 +411,7 @@ impl TrackedBaselineProcessor {
         let mut remaining_errors = Vec::new();
 
         for error in shown_errors.drain(..) {
-            if let Some(matched) = self.keys.get_mut(&BaselineKey::from_error(&error)) {
-                *matched = true;
+            if self.matcher.matches(&error) {
                 baseline_errors.push(error);
             } else {
                 remaining_errors.push(error);
@@ -154,18 +425,57 @@ impl TrackedBaselineProcessor {
     ///
     /// An unmatched row is unused only when its file was checked, or when the file
     /// is conclusively absent. Existing unchecked files and filesystem errors are
-    /// retained. Duplicate rows sharing a key are classified individually.
+    /// retained. Duplicate rows sharing a key are classified individually, with the errors
+    /// that matched the key assigned to them in order.
+    /// Per-file limits in checked files are lowered to the number of errors that used them,
+    /// so that the limits ratchet down as errors are fixed.
     pub fn into_pruning_result(self, checked_paths: &HashSet<String>) -> BaselinePruningResult {
+        let Self {
+            entries,
+            matcher,
+            relative_to,
+        } = self;
+        let BaselineMatcher {
+            mut positions,
+            mut fingerprints,
+            limits: mut limit_uses,
+            ..
+        } = matcher;
+        // Take one of the errors matched by a key for each of its rows.
+        let take_match = |uses: &mut Uses| {
+            let matched = uses.used > 0;
+            if matched {
+                uses.used -= 1;
+            }
+            matched
+        };
         let mut unused_entry_count = 0;
-        let retained_entries = self
-            .entries
+        let errors = entries
+            .errors
             .into_iter()
             .filter_map(|entry| {
-                let key = BaselineKey::from_baseline_error(&entry, &self.relative_to);
-                let matched = self.keys[&key];
+                let (path, matched) = match BaselineMatcher::fingerprint_key(&entry, &relative_to) {
+                    Some(key) => {
+                        let matched = take_match(
+                            fingerprints
+                                .get_mut(&key)
+                                .expect("every entry in the baseline is indexed"),
+                        );
+                        (key.path, matched)
+                    }
+                    None => {
+                        let key = BaselineKey::from_baseline_error(&entry, &relative_to);
+                        let matched = take_match(
+                            positions
+                                .get_mut(&key)
+                                .expect("every entry in the baseline is indexed"),
+                        );
+                        (key.path, matched)
+                    }
+                };
                 let definitely_unused =
-                    is_definitely_unused(matched, checked_paths.contains(&key.path), || {
-                        Path::new(&key.path).try_exists()
+                    is_definitely_unused(matched, checked_paths.contains(&path), || {
+                        Path::new(&path).try_exists()
                     });
                 if definitely_unused {
                     unused_entry_count += 1;
@@ -175,9 +485,34 @@ impl TrackedBaselineProcessor {
                 }
             })
             .collect();
+        // Several rows for the same file and kind share one limit, which is split between
+        // them in order.
+        let limits = entries
+            .limits
+            .into_iter()
+            .filter_map(|mut limit| {
+                let key = LimitKey::from_baseline_limit(&limit, &relative_to);
+                let limit_use = limit_uses
+                    .get_mut(&key)
+                    .expect("every limit in the baseline is indexed");
+                let used = limit.count.min(limit_use.used);
+                limit_use.used -= used;
+                let definitely_unused = is_definitely_unused(
+                    used == limit.count,
+                    checked_paths.contains(&key.path),
+                    || Path::new(&key.path).try_exists(),
+                );
+                if !definitely_unused {
+                    return Some(limit);
+                }
+                unused_entry_count += limit.count - used;
+                limit.count = used;
+                (used > 0).then_some(limit)
+            })
+            .collect();
         BaselinePruningResult {
             unused_entry_count,
-            retained_entries,
+            retained_entries: BaselineErrors { errors, limits },
         }
     }
 }
@@ -259,7 +594,7 @@ mod tests {
         "#;
 
         let baseline_file: BaselineErrors = serde_json::from_str(baseline_json).unwrap();
-        let processor =
+        let mut processor =
             BaselineProcessor::from_baseline_errors(baseline_file, Path::new("/workspace"));
 
         let module = Module::new(
@@ -400,15 +735,21 @@ mod tests {
             ModulePath::filesystem(PathBuf::from("/workspace/test.py")),
             Arc::new("test content 123456789".to_owned()),
         );
-        let mut shown_errors = vec![Error::new(
-            module,
-            TextRange::new(TextSize::new(2), TextSize::new(5)),
-            "Any error message".to_owned(),
-            Vec::new(),
-            ErrorKind::BadReturn,
-        )];
+        // Each row allows one error, so the third of three identical errors is shown.
+        let mut shown_errors = vec![
+            Error::new(
+                module,
+                TextRange::new(TextSize::new(2), TextSize::new(5)),
+                "Any error message".to_owned(),
+                Vec::new(),
+                ErrorKind::BadReturn,
+            );
+            3
+        ];
         let mut baseline_errors = Vec::new();
         processor.process_errors(&mut shown_errors, &mut baseline_errors);
+        assert_eq!(baseline_errors.len(), 2);
+        assert_eq!(shown_errors.len(), 1);
 
         let result =
             processor.into_pruning_result(&HashSet::from(["/workspace/test.py".to_owned()]));
@@ -419,10 +760,11 @@ mod tests {
 
         // The surviving entries are the two `test.py` rows, returned in file
         // order rather than as a single deduplicated key.
-        assert_eq!(result.retained_entries.len(), 2);
+        assert_eq!(result.retained_entries.errors.len(), 2);
         assert!(
             result
                 .retained_entries
+                .errors
                 .iter()
                 .all(|e| e.path == "/workspace/test.py")
         );
@@ -443,7 +785,7 @@ mod tests {
         });
 
         let baseline_file: BaselineErrors = serde_json::from_value(baseline_json).unwrap();
-        let processor = BaselineProcessor::from_baseline_errors(baseline_file, &cwd);
+        let mut processor = BaselineProcessor::from_baseline_errors(baseline_file, &cwd);
 
         let module = Module::new(
             ModuleName::from_str("foo"),
@@ -485,7 +827,7 @@ mod tests {
         });
 
         let baseline_file: BaselineErrors = serde_json::from_value(baseline_json).unwrap();
-        let processor =
+        let mut processor =
             BaselineProcessor::from_baseline_errors(baseline_file, Path::new("/workspace"));
 
         // Simulate a Windows-style path with backslashes in the error.
@@ -519,7 +861,7 @@ mod tests {
             }]
         });
         let baseline_file: BaselineErrors = serde_json::from_value(baseline_json).unwrap();
-        let processor = BaselineProcessor::from_baseline_errors(baseline_file, &relative_to);
+        let mut processor = BaselineProcessor::from_baseline_errors(baseline_file, &relative_to);
 
         let module = Module::new(
             ModuleName::from_str("foo"),
@@ -535,4 +877,225 @@ mod tests {
         );
         assert!(processor.matches_baseline(&error));
     }
+
+    /// An error of `kind` on the first occurrence of `code` in `source`.
+    fn error_at(path: &str, source: &str, code: &str, kind: ErrorKind) -> Error {
+        let module = Module::new(
+            ModuleName::from_str("test_module"),
+            ModulePath::filesystem(PathBuf::from(path)),
+            Arc::new(source.to_owned()),
+        );
+        let start = source.find(code).unwrap() as u32;
+        Error::new(
+            module,
+            TextRange::new(
+                TextSize::new(start),
+                TextSize::new(start + code.len() as u32),
+            ),
+            "err".to_owned(),
+            Vec::new(),
+            kind,
+        )
+    }
+
+    #[test]
+    fn test_normalize_snippet() {
+        assert_eq!(normalize_snippet("f(\n    a,\n    b,\n)"), "f(a,b)");
+        assert_eq!(normalize_snippet("f(a, b)"), "f(a,b)");
+        assert_ne!(normalize_snippet("f(a)"), normalize_snippet("f(b)"));
+        assert_eq!(normalize_snippet("f('a b', \"c,)\")"), "f('a b',\"c,)\")");
+        assert_eq!(
+            normalize_snippet("f(\n    '''a\n b''',\n)"),
+            "f('''a\n b''')"
+        );
+        assert_eq!(normalize_snippet(r"f('a\' b')"), r"f('a\' b')");
+        assert_ne!(normalize_snippet("'a b'"), normalize_snippet("'ab'"));
+    }
+
+    #[test]
+    fn test_fingerprint_depends_on_message_and_statement() {
+        // Fingerprinters parse each file once, so each version of the file needs its own.
+        let fingerprint = |error: &Error| {
+            Fingerprinter::default()
+                .fingerprint(error)
+                .unwrap()
+                .fingerprint
+        };
+        let source = "x: int = ''\ny: str = ''\n";
+        let x = error_at("/workspace/test.py", source, "''", ErrorKind::BadAssignment);
+        // The same code in a different statement.
+        let y = error_at(
+            "/workspace/test.py",
+            &source.replace("x: int", "x: bytes"),
+            "''",
+            ErrorKind::BadAssignment,
+        );
+        assert_ne!(fingerprint(&x), fingerprint(&y));
+        // The same code and statement, but a different message.
+        let other_message = Error::new(
+            x.module().dupe(),
+            x.range(),
+            "other".to_owned(),
+            Vec::new(),
+            ErrorKind::BadAssignment,
+        );
+        assert_ne!(fingerprint(&x), fingerprint(&other_message));
+        // Only the header of a compound statement is included.
+        let in_header = |body| {
+            let source = format!("def f(x: int = ''):\n    {body}\n");
+            error_at(
+                "/workspace/test.py",
+                &source,
+                "''",
+                ErrorKind::BadFunctionDefinition,
+            )
+        };
+        assert_eq!(
+            fingerprint(&in_header("pass")),
+            fingerprint(&in_header("return 1"))
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_scope() {
+        let source = "class A:\n    def f(self):\n        def g():\n            return ''\nx = 1\n";
+        let mut fingerprints = Fingerprinter::default();
+        let fingerprint = |fingerprints: &mut Fingerprinter, code| {
+            fingerprints
+                .fingerprint(&error_at(
+                    "/workspace/test.py",
+                    source,
+                    code,
+                    ErrorKind::BadReturn,
+                ))
+                .unwrap()
+        };
+        assert_eq!(fingerprint(&mut fingerprints, "''").scope, "A.f.g");
+        assert_eq!(fingerprint(&mut fingerprints, "x = 1").scope, "");
+    }
+
+    #[test]
+    fn test_fingerprint_matches_moved_code() {
+        let before = "def f():\n    x: int = ''\n";
+        let baseline = BaselineErrors::from_errors(
+            Path::new("/workspace"),
+            &[error_at(
+                "/workspace/test.py",
+                before,
+                "''",
+                ErrorKind::BadAssignment,
+            )],
+            |_| false,
+        );
+        let json = serde_json::to_string(&baseline).unwrap();
+        assert_eq!(baseline.errors[0].path, "test.py");
+        assert_eq!(baseline.errors[0].scope, "f");
+        // Processors parse each file once, so each version of the file needs its own.
+        let matches = |source: &str, code: &str| {
+            BaselineProcessor::from_json(&json, Path::new("/workspace"))
+                .unwrap()
+                .matches_baseline(&error_at(
+                    "/workspace/test.py",
+                    source,
+                    code,
+                    ErrorKind::BadAssignment,
+                ))
+        };
+
+        // Moved down and to the right.
+        assert!(matches(
+            "import os\n\ndef f():\n    y = 1\n    x: int  =  ''\n",
+            "''"
+        ));
+        // The same code and column, but in another function.
+        assert!(!matches("def g():\n    x: int = ''\n", "''"));
+        // The same function and column, but different code.
+        assert!(!matches("def f():\n    x: int = b''\n", "b''"));
+    }
+
+    #[test]
+    fn test_limits_are_used_up() {
+        let baseline_json = serde_json::json!({
+            "errors": [],
+            "limits": [
+                {"path": "test.py", "name": "bad-return", "count": 2}
+            ]
+        });
+        let baseline_file: BaselineErrors = serde_json::from_value(baseline_json).unwrap();
+        let mut processor =
+            TrackedBaselineProcessor::from_baseline_errors(baseline_file, Path::new("/workspace"));
+        let source = "return 1\nreturn 2\nreturn 3\n";
+        let mut shown_errors = ["1", "2", "3"]
+            .into_iter()
+            .map(|code| error_at("/workspace/test.py", source, code, ErrorKind::BadReturn))
+            .collect();
+        let mut baseline_errors = Vec::new();
+        processor.process_errors(&mut shown_errors, &mut baseline_errors);
+        assert_eq!(baseline_errors.len(), 2);
+        assert_eq!(shown_errors.len(), 1);
+        let result =
+            processor.into_pruning_result(&HashSet::from(["/workspace/test.py".to_owned()]));
+        assert_eq!(result.unused_entry_count, 0);
+        assert_eq!(result.retained_entries.limits[0].count, 2);
+    }
+
+    #[test]
+    fn test_limits_ratchet_down() {
+        let baseline_json = serde_json::json!({
+            "errors": [],
+            "limits": [
+                {"path": "test.py", "name": "bad-return", "count": 3},
+                {"path": "other.py", "name": "bad-return", "count": 1}
+            ]
+        });
+        let baseline_file: BaselineErrors = serde_json::from_value(baseline_json).unwrap();
+        let mut processor =
+            TrackedBaselineProcessor::from_baseline_errors(baseline_file, Path::new("/workspace"));
+        let mut shown_errors = vec![error_at(
+            "/workspace/test.py",
+            "return 1\n",
+            "1",
+            ErrorKind::BadReturn,
+        )];
+        let mut baseline_errors = Vec::new();
+        processor.process_errors(&mut shown_errors, &mut baseline_errors);
+        assert!(shown_errors.is_empty());
+
+        let result = processor.into_pruning_result(&HashSet::from([
+            "/workspace/test.py".to_owned(),
+            "/workspace/other.py".to_owned(),
+        ]));
+        assert_eq!(result.unused_entry_count, 3);
+        assert_eq!(
+            result.retained_entries.limits,
+            vec![BaselineLimit {
+                path: "test.py".to_owned(),
+                name: "bad-return".to_owned(),
+                count: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_from_errors_counts() {
+        let source = "return 1\nreturn 2\nx: int = ''\n";
+        let errors = [
+            error_at("/workspace/test.py", source, "1", ErrorKind::BadReturn),
+            error_at("/workspace/test.py", source, "''", ErrorKind::BadAssignment),
+            error_at("/workspace/test.py", source, "2", ErrorKind::BadReturn),
+        ];
+        let baseline = BaselineErrors::from_errors(Path::new("/workspace"), &errors, |error| {
+            error.name == "bad-return"
+        });
+        assert_eq!(baseline.errors.len(), 1);
+        assert_eq!(baseline.errors[0].name, "bad-assignment");
+        assert_eq!(
+            baseline.limits,
+            vec![BaselineLimit {
+                path: "test.py".to_owned(),
+                name: "bad-return".to_owned(),
+                count: 2,
+            }]
+        );
+    }
 }
@@ -16,9 +16,11 @@ use lsp_types::CodeDescription;
 use lsp_types::Diagnostic;
 use lsp_types::DiagnosticTag;
 use lsp_types::Url;
+use pyrefly_python::ast::Ast;
 use pyrefly_python::ignore::Tool;
 use pyrefly_python::module::Module;
 use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::sys_info::SysInfo;
 use pyrefly_util::display::number_thousands;
 use pyrefly_util::lined_buffer::DisplayRange;
 use pyrefly_util::lined_buffer::LineNumber;
@@ -27,8 +29,12 @@ use ruff_annotate_snippets::Level;
 use ruff_annotate_snippets::Message;
 use ruff_annotate_snippets::Renderer;
 use ruff_annotate_snippets::Snippet;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::ModModule;
 use ruff_text_size::Ranged;
 use ruff_text_size::TextRange;
+use serde::Deserialize;
+use serde::Serialize;
 use starlark_map::small_map::SmallMap;
 use starlark_map::small_set::SmallSet;
 use yansi::Paint;
@@ -44,9 +50,85 @@ pub struct SecondaryAnnotation {
     pub label: Box<str>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ErrorQuickFix {
-    ReplaceWithEnumMember { replacement: String },
+    ReplaceWithEnumMember {
+        replacement: String,
+    },
+    /// Edits already located in the module, such as the IDE quick fixes attached to errors
+    /// for reports outside the IDE.
+    Edits {
+        title: String,
+        edits: Vec<QuickFixEdit>,
+    },
+}
+
+/// Replace the text from `start` to `end` with `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct QuickFixEdit {
+    pub start: u32,
+    pub end: u32,
+    pub replacement: String,
+}
+
+impl QuickFixEdit {
+    pub fn new(range: TextRange, replacement: String) -> Self {
+        Self {
+            start: range.start().to_u32(),
+            end: range.end().to_u32(),
+            replacement,
+        }
+    }
+
+    pub fn range(&self) -> TextRange {
+        TextRange::new(self.start.into(), self.end.into())
+    }
+}
+
+impl ErrorQuickFix {
+    pub fn title(&self) -> String {
+        match self {
+            Self::ReplaceWithEnumMember { replacement } => format!("Replace with `{replacement}`"),
+            Self::Edits { title, .. } => title.clone(),
+        }
+    }
+
+    /// Whether [`ErrorQuickFix::edits_in`] needs the parsed module.
+    fn needs_ast(&self) -> bool {
+        match self {
+            Self::ReplaceWithEnumMember { .. } => true,
+            Self::Edits { .. } => false,
+        }
+    }
+
+    /// The ranges to replace and the text to replace them with. `ast` is the parsed module
+    /// that `error` belongs to, and no edits are found without it if `needs_ast`.
+    pub fn edits_in(&self, ast: Option<&ModModule>, error: &Error) -> Vec<(TextRange, &str)> {
+        match self {
+            Self::ReplaceWithEnumMember { replacement } => {
+                let Some(ast) = ast else {
+                    return Vec::new();
+                };
+                // The error points somewhere inside the string literal to replace.
+                Ast::locate_node(ast, error.range().start())
+                    .into_iter()
+                    .find_map(|node| match node {
+                        AnyNodeRef::ExprStringLiteral(literal)
+                            if literal.range().contains_range(error.range()) =>
+                        {
+                            Some((literal.range(), replacement.as_str()))
+                        }
+                        _ => None,
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            Self::Edits { edits, .. } => edits
+                .iter()
+                .map(|edit| (edit.range(), edit.replacement.as_str()))
+                .collect(),
+        }
+    }
 }
 
 /// Whether an error was compared with the configured baseline.
@@ -99,6 +181,24 @@ pub struct Error {
     secondary_annotations: Vec<SecondaryAnnotation>,
     /// Structured fixes that can be exposed by editor integrations.
     quick_fixes: Vec<ErrorQuickFix>,
+    /// When checking a matrix of Python versions and platforms, the configurations this error
+    /// appears in. Empty if it appears in all of them.
+    configurations: Vec<SysInfo>,
+}
+
+/// A short name for a configuration in a matrix check, such as `3.9/win32`.
+pub fn configuration_name(sys_info: &SysInfo) -> String {
+    let version = sys_info.version();
+    if version.micro == 0 {
+        format!(
+            "{}.{}/{}",
+            version.major,
+            version.minor,
+            sys_info.platform()
+        )
+    } else {
+        format!("{version}/{}", sys_info.platform())
+    }
 }
 
 impl Ranged for Error {
@@ -172,11 +272,12 @@ impl<W: Write> ErrorRenderer<W> {
         match self.mode {
             ErrorRenderMode::Plain => writeln!(
                 self.writer,
-                "{} {} [{}]{}",
+                "{} {} [{}]{}{}",
                 error.severity.label(),
                 error.msg_header,
                 error.error_kind.to_name(),
                 error.baseline_status.display_suffix(),
+                error.configurations_suffix(),
             ),
             ErrorRenderMode::Color => {
                 write!(
@@ -189,6 +290,13 @@ impl<W: Write> ErrorRenderer<W> {
                 if error.baseline_status == BaselineStatus::Matched {
                     write!(self.writer, " {}", Paint::dim("[baselined]"))?;
                 }
+                if !error.configurations.is_empty() {
+                    write!(
+                        self.writer,
+                        " {}",
+                        Paint::dim(error.configurations_suffix().trim_start())
+                    )?;
+                }
                 writeln!(self.writer)
             }
         }
@@ -199,13 +307,14 @@ impl<W: Write> ErrorRenderer<W> {
         match self.mode {
             ErrorRenderMode::Plain => writeln!(
                 self.writer,
-                "{} {}:{}: {} [{}]{}",
+                "{} {}:{}: {} [{}]{}{}",
                 error.severity.label(),
                 origin,
                 error.display_range,
                 header,
                 error.error_kind.to_name(),
                 error.baseline_status.display_suffix(),
+                error.configurations_suffix(),
             ),
             ErrorRenderMode::Color => {
                 write!(
@@ -220,6 +329,13 @@ impl<W: Write> ErrorRenderer<W> {
                 if error.baseline_status == BaselineStatus::Matched {
                     write!(self.writer, " {}", Paint::dim("[baselined]"))?;
                 }
+                if !error.configurations.is_empty() {
+                    write!(
+                        self.writer,
+                        " {}",
+                        Paint::dim(error.configurations_suffix().trim_start())
+                    )?;
+                }
                 writeln!(self.writer)
             }
         }
@@ -374,6 +490,30 @@ impl Error {
         self.baseline_status
     }
 
+    pub fn with_configurations(mut self, configurations: Vec<SysInfo>) -> Self {
+        self.configurations = configurations;
+        self
+    }
+
+    /// The matrix configurations this error appears in, or empty if it appears in all of them.
+    pub fn configurations(&self) -> &[SysInfo] {
+        &self.configurations
+    }
+
+    /// The names of the matrix configurations this error appears in, such as `3.9/win32`.
+    pub fn configuration_names(&self) -> Vec<String> {
+        self.configurations.iter().map(configuration_name).collect()
+    }
+
+    /// Suffix appended in text renderers, e.g. `" [only on 3.9/win32, 3.10/win32]"`.
+    fn configurations_suffix(&self) -> String {
+        if self.configurations.is_empty() {
+            String::new()
+        } else {
+            format!(" [only on {}]", self.configuration_names().join(", "))
+        }
+    }
+
     /// Create a diagnostic suitable for use in LSP.
     pub fn to_diagnostic(&self) -> Diagnostic {
         let code = self.error_kind().to_name().to_owned();
@@ -486,6 +626,7 @@ impl Error {
             msg_details,
             secondary_annotations: Vec::new(),
             quick_fixes: Vec::new(),
+            configurations: Vec::new(),
         }
     }
 
@@ -504,6 +645,13 @@ impl Error {
         self
     }
 
+    /// Replace the message details with text already formatted for pretty-printing,
+    /// as returned by `msg_details`. Used when restoring a previously reported error.
+    pub fn with_formatted_msg_details(mut self, details: Option<&str>) -> Self {
+        self.msg_details = details.map(Box::from);
+        self
+    }
+
     pub fn display_range(&self) -> &DisplayRange {
         &self.display_range
     }
@@ -558,6 +706,30 @@ impl Error {
     pub fn quick_fixes(&self) -> &[ErrorQuickFix] {
         &self.quick_fixes
     }
+
+    /// The edits proposed by this error's quick fixes, for reports outside the IDE.
+    /// May parse the module, so only call this when the AST is not already at hand.
+    pub fn quick_fix_edits(&self) -> Vec<(TextRange, &str)> {
+        self.quick_fixes_with_edits()
+            .into_iter()
+            .flat_map(|(_, edits)| edits)
+            .collect()
+    }
+
+    /// The title and edits of each of this error's quick fixes that can be located, for
+    /// reports outside the IDE. May parse the module, like [`Error::quick_fix_edits`].
+    pub fn quick_fixes_with_edits(&self) -> Vec<(String, Vec<(TextRange, &str)>)> {
+        let ast = self
+            .quick_fixes
+            .iter()
+            .any(ErrorQuickFix::needs_ast)
+            .then(|| Ast::parse(self.module.contents(), self.module.source_type()).0);
+        self.quick_fixes
+            .iter()
+            .map(|fix| (fix.title(), fix.edits_in(ast.as_ref(), self)))
+            .filter(|(_, edits)| !edits.is_empty())
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -567,6 +739,8 @@ mod tests {
     use std::sync::Arc;
 
     use pyrefly_python::module_name::ModuleName;
+    use pyrefly_python::sys_info::PythonPlatform;
+    use pyrefly_python::sys_info::PythonVersion;
     use ruff_text_size::TextSize;
 
     use super::*;
@@ -661,6 +835,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_configurations_render() {
+        let module_info = Module::new(
+            ModuleName::from_str("test"),
+            ModulePath::filesystem(PathBuf::from("test.py")),
+            Arc::new("x: str = 1".to_owned()),
+        );
+        let error = Error::new(
+            module_info,
+            TextRange::new(TextSize::new(9), TextSize::new(10)),
+            "bad assignment".to_owned(),
+            Vec::new(),
+            ErrorKind::BadAssignment,
+        )
+        .with_configurations(vec![
+            SysInfo::new(PythonVersion::new(3, 9, 0), PythonPlatform::new("win32")),
+            SysInfo::new(PythonVersion::new(3, 9, 7), PythonPlatform::linux()),
+        ]);
+
+        assert_eq!(
+            render_error(&error, Path::new(""), false),
+            "ERROR test.py:1:10-11: bad assignment [bad-assignment] [only on 3.9/win32, 3.9.7/linux]\n"
+        );
+    }
+
     #[test]
     fn test_error_too_long() {
         let contents = format!("Start\n{}\nEnd", "X\n".repeat(1000));
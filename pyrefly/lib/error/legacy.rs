@@ -12,7 +12,10 @@ use pyrefly_util::absolutize::Absolutize;
 use pyrefly_util::prelude::SliceExt;
 use serde::Deserialize;
 use serde::Serialize;
+use starlark_map::small_map::SmallMap;
 
+use crate::error::baseline::ErrorFingerprint;
+use crate::error::baseline::Fingerprinter;
 use crate::error::error::Error;
 
 pub(crate) fn severity_to_str(severity: Severity) -> String {
@@ -54,6 +57,31 @@ pub struct LegacyError {
     /// Optional notebook cell number for errors in notebook files
     #[serde(skip_serializing_if = "Option::is_none")]
     cell: Option<usize>,
+    /// Quick fixes for the error. Not part of Pyre1 error format.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    fixes: Vec<LegacyFix>,
+    /// When checking a matrix of Python versions and platforms, the configurations the error
+    /// appears in, such as `3.9/win32`. Omitted if it appears in all of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    configurations: Vec<String>,
+}
+
+/// A quick fix, made of edits that must all be applied together.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct LegacyFix {
+    title: String,
+    edits: Vec<LegacyEdit>,
+}
+
+/// Replace the text between two positions, using the same numbering as [`LegacyError`].
+/// When `line` and `column` equal `stop_line` and `stop_column`, the edit is an insertion.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct LegacyEdit {
+    line: usize,
+    column: usize,
+    stop_line: usize,
+    stop_column: usize,
+    replacement: String,
 }
 
 impl LegacyError {
@@ -77,8 +105,35 @@ impl LegacyError {
             concise_description: error.msg_header().to_owned(),
             severity: severity_to_str(error.severity()),
             baselined: error.baseline_status().legacy_baselined_flag(),
+            fixes: Vec::new(),
+            configurations: error.configuration_names(),
         }
     }
+
+    /// Include the quick fixes attached to `error`, which this was made from.
+    pub fn with_fixes(mut self, error: &Error) -> Self {
+        self.fixes = error
+            .quick_fixes_with_edits()
+            .into_iter()
+            .map(|(title, edits)| LegacyFix {
+                title,
+                edits: edits
+                    .into_iter()
+                    .map(|(range, replacement)| {
+                        let range = error.module().display_range(range);
+                        LegacyEdit {
+                            line: range.start.line_within_cell().get() as usize,
+                            column: range.start.column().get() as usize,
+                            stop_line: range.end.line_within_cell().get() as usize,
+                            stop_column: range.end.column().get() as usize,
+                            replacement: replacement.to_owned(),
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        self
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
@@ -89,7 +144,7 @@ pub struct LegacyErrors {
 impl LegacyErrors {
     pub fn from_errors(relative_to: &Path, errors: &[Error]) -> Self {
         Self {
-            errors: errors.map(|e| LegacyError::from_error(relative_to, e)),
+            errors: errors.map(|e| LegacyError::from_error(relative_to, e).with_fixes(e)),
         }
     }
 }
@@ -106,12 +161,28 @@ pub struct BaselineError {
     /// Optional notebook cell number for errors in notebook files
     #[serde(skip_serializing_if = "Option::is_none")]
     cell: Option<usize>,
+    /// A hash of the code the error points at, ignoring formatting. Entries with a
+    /// fingerprint are matched by content and `scope` rather than by column.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
+    /// The qualified name of the function or class enclosing the error, empty at the top
+    /// level of a module.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub scope: String,
 }
 
 impl BaselineError {
-    fn from_error(relative_to: &Path, error: &Error) -> Self {
+    fn from_error(
+        relative_to: &Path,
+        error: &Error,
+        fingerprint: Option<ErrorFingerprint>,
+    ) -> Self {
         let error_range = error.display_range();
         let error_path = error.path().as_path();
+        let (fingerprint, scope) = match fingerprint {
+            Some(x) => (Some(x.fingerprint), x.scope),
+            None => (None, String::new()),
+        };
         Self {
             column: error_range.start.column().get() as usize,
             cell: error_range.start.cell().map(|cell| cell.get() as usize),
@@ -122,19 +193,56 @@ impl BaselineError {
             name: error.error_kind().to_name().to_owned(),
             concise_description: error.msg_header().to_owned(),
             severity: severity_to_str(error.severity()),
+            fingerprint,
+            scope,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+/// Allows up to `count` errors of one kind in one file, however they move around. Used to
+/// ratchet down the errors in legacy code without listing each of them.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BaselineLimit {
+    pub path: String,
+    /// The kebab-case name of the error kind.
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
 pub struct BaselineErrors {
     pub errors: Vec<BaselineError>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub limits: Vec<BaselineLimit>,
 }
 
 impl BaselineErrors {
-    pub fn from_errors(relative_to: &Path, errors: &[Error]) -> Self {
+    /// A baseline for `errors`. Errors for which `counted` returns `true` are recorded as
+    /// a per-file limit for their kind, rather than individually.
+    pub fn from_errors(
+        relative_to: &Path,
+        errors: &[Error],
+        counted: impl Fn(&BaselineError) -> bool,
+    ) -> Self {
+        let mut fingerprints = Fingerprinter::default();
+        let mut entries = Vec::new();
+        // Kept in order of first occurrence, like the entries.
+        let mut counts: SmallMap<(String, String), usize> = SmallMap::new();
+        for error in errors {
+            let error =
+                BaselineError::from_error(relative_to, error, fingerprints.fingerprint(error));
+            if counted(&error) {
+                *counts.entry((error.path, error.name)).or_default() += 1;
+            } else {
+                entries.push(error);
+            }
+        }
         Self {
-            errors: errors.map(|e| BaselineError::from_error(relative_to, e)),
+            errors: entries,
+            limits: counts
+                .into_iter()
+                .map(|((path, name), count)| BaselineLimit { path, name, count })
+                .collect(),
         }
     }
 }
@@ -153,6 +261,8 @@ mod tests {
     use super::*;
     use crate::config::error_kind::ErrorKind;
     use crate::error::error::BaselineStatus;
+    use crate::error::error::ErrorQuickFix;
+    use crate::error::error::QuickFixEdit;
 
     #[test]
     fn test_relativize_when_error_is_not_under_relative_to() {
@@ -204,4 +314,45 @@ mod tests {
             false
         );
     }
+
+    #[test]
+    fn test_fixes() {
+        let module = Module::new(
+            ModuleName::from_str("foo"),
+            ModulePath::filesystem(PathBuf::from("/repo/foo.py")),
+            Arc::new("import os\nx = cast(int, 1)\n".to_owned()),
+        );
+        let error = Error::new(
+            module,
+            TextRange::new(TextSize::new(14), TextSize::new(26)),
+            "err".to_owned(),
+            Vec::new(),
+            ErrorKind::RedundantCast,
+        );
+        let legacy =
+            serde_json::to_value(LegacyError::from_error(Path::new("/repo"), &error)).unwrap();
+        assert!(legacy.get("fixes").is_none());
+
+        let error = error.with_quick_fix(ErrorQuickFix::Edits {
+            title: "Remove redundant cast".to_owned(),
+            edits: vec![QuickFixEdit::new(
+                TextRange::new(TextSize::new(14), TextSize::new(26)),
+                "1".to_owned(),
+            )],
+        });
+        let legacy = LegacyError::from_error(Path::new("/repo"), &error).with_fixes(&error);
+        assert_eq!(
+            serde_json::to_value(legacy).unwrap()["fixes"],
+            serde_json::json!([{
+                "title": "Remove redundant cast",
+                "edits": [{
+                    "line": 2,
+                    "column": 5,
+                    "stop_line": 2,
+                    "stop_column": 17,
+                    "replacement": "1",
+                }],
+            }])
+        );
+    }
 }
@@ -0,0 +1,177 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! A serializable form of `Error`, for handing diagnostics to another process or run.
+//!
+//! An `Error` refers to its `Module`, which holds the whole source. Rather than
+//! serializing the source, we record the module name and path, and restoring re-reads
+//! the file from disk. This only works for plain source files on disk, and only while
+//! the file is unchanged, which callers are expected to check.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use dupe::Dupe as _;
+use pyrefly_config::error_kind::ErrorKind;
+use pyrefly_config::error_kind::Severity;
+use pyrefly_python::module::Module;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::module_path::ModulePathDetails;
+use pyrefly_util::fs_anyhow;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::error::Error;
+use crate::error::error::ErrorQuickFix;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedError {
+    pub start: u32,
+    pub end: u32,
+    pub kind: ErrorKind,
+    pub severity: Severity,
+    pub header: String,
+    /// The details, as formatted for pretty-printing by `Error::msg_details`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<PersistedAnnotation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub quick_fixes: Vec<ErrorQuickFix>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedAnnotation {
+    pub start: u32,
+    pub end: u32,
+    pub label: String,
+}
+
+fn text_range(start: u32, end: u32) -> TextRange {
+    TextRange::new(TextSize::new(start), TextSize::new(end))
+}
+
+impl PersistedError {
+    pub fn new(error: &Error) -> Self {
+        Self {
+            start: error.range().start().to_u32(),
+            end: error.range().end().to_u32(),
+            kind: error.error_kind(),
+            severity: error.severity(),
+            header: error.msg_header().to_owned(),
+            details: error.msg_details().map(str::to_owned),
+            annotations: error
+                .secondary_annotations()
+                .iter()
+                .map(|annotation| PersistedAnnotation {
+                    start: annotation.range.start().to_u32(),
+                    end: annotation.range.end().to_u32(),
+                    label: annotation.label.to_string(),
+                })
+                .collect(),
+            quick_fixes: error.quick_fixes().to_vec(),
+        }
+    }
+
+    /// Whether every range of this error lies within a source of `len` bytes.
+    pub fn fits(&self, len: usize) -> bool {
+        self.end as usize <= len && self.annotations.iter().all(|x| x.end as usize <= len)
+    }
+
+    pub fn restore(&self, module: &Module) -> Error {
+        let mut error = Error::new(
+            module.dupe(),
+            text_range(self.start, self.end),
+            self.header.clone(),
+            Vec::new(),
+            self.kind,
+        )
+        .with_formatted_msg_details(self.details.as_deref())
+        .with_severity(self.severity);
+        for annotation in &self.annotations {
+            error = error.with_annotation(
+                text_range(annotation.start, annotation.end),
+                annotation.label.clone(),
+            );
+        }
+        for quick_fix in &self.quick_fixes {
+            error = error.with_quick_fix(quick_fix.clone());
+        }
+        error
+    }
+}
+
+/// The errors of a single module on disk.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PersistedModuleErrors {
+    pub name: String,
+    pub path: PathBuf,
+    pub errors: Vec<PersistedError>,
+}
+
+/// The path of a module whose errors can be persisted: a plain source file on disk.
+/// In-memory and bundled modules have no stable identity across processes, and
+/// notebook offsets do not refer to the raw file.
+pub fn persistable_path(path: &ModulePath) -> Option<&Path> {
+    match path.details() {
+        ModulePathDetails::FileSystem(_)
+            if path.as_path().extension().is_some_and(|x| x != "ipynb") =>
+        {
+            Some(path.as_path())
+        }
+        _ => None,
+    }
+}
+
+impl PersistedModuleErrors {
+    /// Group consecutive `errors` from the same module, dropping errors whose module
+    /// cannot be persisted.
+    pub fn from_errors<'a>(errors: impl IntoIterator<Item = &'a Error>) -> Vec<Self> {
+        let mut res: Vec<Self> = Vec::new();
+        for error in errors {
+            let Some(path) = persistable_path(error.path()) else {
+                continue;
+            };
+            let persisted = PersistedError::new(error);
+            match res.last_mut() {
+                Some(last) if last.path == path => last.errors.push(persisted),
+                _ => res.push(Self {
+                    name: error.module().name().as_str().to_owned(),
+                    path: path.to_path_buf(),
+                    errors: vec![persisted],
+                }),
+            }
+        }
+        res
+    }
+
+    /// Re-read the module source and rebuild its errors. Returns `None` if the file
+    /// can no longer be read or no longer fits the recorded ranges.
+    pub fn restore(&self) -> Option<Vec<Error>> {
+        let module = load_module(&self.name, &self.path)?;
+        let len = module.lined_buffer().contents().len();
+        if !self.errors.iter().all(|e| e.fits(len)) {
+            return None;
+        }
+        Some(self.errors.iter().map(|e| e.restore(&module)).collect())
+    }
+}
+
+/// Re-read the source of the module `name` at `path`, to restore errors against.
+pub fn load_module(name: &str, path: &Path) -> Option<Module> {
+    let contents = fs_anyhow::read_to_string(path).ok()?;
+    Some(Module::new(
+        ModuleName::from_str(name),
+        ModulePath::filesystem(path.to_path_buf()),
+        Arc::new(contents),
+    ))
+}
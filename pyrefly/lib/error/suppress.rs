@@ -15,7 +15,10 @@ use std::sync::LazyLock;
 use anyhow::anyhow;
 use clap::ValueEnum;
 use pyrefly_config::error_kind::ErrorKind;
+use pyrefly_config::migration::suppression_codes;
 use pyrefly_python::ast::Ast;
+use pyrefly_python::ignore::Ignore;
+use pyrefly_python::ignore::Tool;
 use pyrefly_python::ignore::find_comment_start_in_line;
 use pyrefly_python::module::GENERATED_TOKEN;
 use pyrefly_python::module::Module;
@@ -52,6 +55,8 @@ static PYRE_IGNORE_COMMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     )
     .unwrap()
 });
+static PYRIGHT_IGNORE_COMMENT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"#\s*pyright:\s*ignore\s*(\[[^\]]*\])?[^#]*").unwrap());
 
 /// Where to place suppression comments relative to the error line.
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
@@ -75,6 +80,67 @@ pub enum UnusedIgnoreKind {
     All,
 }
 
+/// Another type checker whose suppression comments can be converted into Pyrefly ignores.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SuppressionSource {
+    /// Convert `# type: ignore` comments, reading their codes as mypy error codes.
+    Mypy,
+    /// Convert `# pyright: ignore` comments, reading their codes as pyright rules.
+    Pyright,
+    /// Convert `# pyre-fixme` and `# pyre-ignore` comments, reading their codes as Pyre error codes.
+    Pyre,
+}
+
+impl SuppressionSource {
+    /// The tool whose suppression comments are converted.
+    pub fn tool(self) -> Tool {
+        match self {
+            Self::Mypy => Tool::Type,
+            Self::Pyright => Tool::Pyright,
+            Self::Pyre => Tool::Pyre,
+        }
+    }
+
+    /// The Pyrefly error kinds that this checker's `code` covers.
+    fn error_kinds(self, code: &str) -> Vec<ErrorKind> {
+        match self {
+            Self::Mypy => suppression_codes::mypy_error_kinds(code).to_vec(),
+            Self::Pyright => suppression_codes::pyright_error_kinds(code),
+            Self::Pyre => suppression_codes::pyre_error_kinds(code).to_vec(),
+        }
+    }
+
+    fn comment_regex(self) -> &'static Regex {
+        match self {
+            Self::Mypy => &TYPE_IGNORE_COMMENT_REGEX,
+            Self::Pyright => &PYRIGHT_IGNORE_COMMENT_REGEX,
+            Self::Pyre => &PYRE_IGNORE_COMMENT_REGEX,
+        }
+    }
+
+    /// The Pyrefly error codes a suppression with `codes` should keep, out of the `reported`
+    /// ones on the line it applies to. A blanket suppression, or one naming a code without a
+    /// Pyrefly equivalent, keeps everything since we can't tell what it was meant to cover.
+    fn kept_codes<'a>(
+        self,
+        codes: &[String],
+        reported: impl Iterator<Item = &'a String>,
+    ) -> Vec<String> {
+        let mut mapped = SmallSet::new();
+        for code in codes {
+            let kinds = self.error_kinds(code);
+            if kinds.is_empty() {
+                return reported.cloned().collect();
+            }
+            mapped.extend(kinds.into_iter().map(|kind| kind.to_name()));
+        }
+        reported
+            .filter(|name| codes.is_empty() || mapped.contains(name.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
 impl UnusedIgnoreKind {
     /// Whether unused Pyrefly and Pyre ignores should be removed.
     pub fn includes_pyrefly_or_pyre(self) -> bool {
@@ -727,6 +793,123 @@ pub fn remove_unused_ignores_from_serialized(
     removals
 }
 
+/// Rewrites a line's `from` suppression comments. The first becomes a Pyrefly ignore for
+/// `codes`, merged into any Pyrefly ignore already on the line, and the rest are removed.
+fn convert_suppression_line(line: &str, from: SuppressionSource, codes: &[String]) -> String {
+    let Some(comment_start) = find_comment_start_in_line(line) else {
+        return line.to_owned();
+    };
+    let code_part = &line[..comment_start];
+    let comment_part = &line[comment_start..];
+    let existing = PYREFLY_IGNORE_COMMENT_REGEX.is_match(comment_part);
+    let mut replacement = if codes.is_empty() || existing {
+        None
+    } else {
+        Some(merge_error_codes(Vec::new(), codes))
+    };
+    let new_comment = from
+        .comment_regex()
+        .replace_all(comment_part, |caps: &regex::Captures| {
+            match replacement.take() {
+                // Keep a space before any comment that followed the one we replaced.
+                Some(comment) if caps.get(0).unwrap().end() < comment_part.len() => {
+                    format!("{comment} ")
+                }
+                Some(comment) => comment,
+                None => String::new(),
+            }
+        });
+    let updated = format!("{code_part}{new_comment}").trim_end().to_owned();
+    match parse_ignore_comment(&updated) {
+        Some(existing_codes) if !codes.is_empty() => {
+            replace_ignore_comment(&updated, &merge_error_codes(existing_codes, codes))
+        }
+        _ => updated,
+    }
+}
+
+/// Converts another type checker's suppression comments into Pyrefly ignores.
+/// `errors` are the errors Pyrefly reports with `from`'s comments disabled. Each comment becomes
+/// a `# pyrefly: ignore` listing the reported errors it covers, found by mapping its codes onto
+/// Pyrefly error kinds, and comments that cover no reported error are removed.
+/// Returns the number of comments converted and the number removed.
+pub fn convert_suppressions(
+    errors: Vec<SerializedError>,
+    paths: &[PathBuf],
+    from: SuppressionSource,
+) -> (usize, usize) {
+    let mut errors_by_path: SmallMap<&Path, SmallMap<usize, SmallSet<String>>> = SmallMap::new();
+    for error in &errors {
+        errors_by_path
+            .entry(&error.path)
+            .or_default()
+            .entry(error.line)
+            .or_default()
+            .insert(error.name.clone());
+    }
+
+    let mut converted = 0;
+    let mut removed = 0;
+    let mut changed_files = 0;
+    let no_errors = SmallMap::new();
+    for path in paths {
+        let Ok((file, _ast)) = read_and_validate_file(path) else {
+            continue;
+        };
+        let line_errors = errors_by_path.get(path.as_path()).unwrap_or(&no_errors);
+        // The codes to keep for each line holding a comment we're converting.
+        let mut line_codes: SmallMap<usize, SmallSet<String>> = SmallMap::new();
+        for (applies_to, suppressions) in Ignore::new(&file).iter() {
+            let reported = line_errors.get(&(applies_to.to_zero_indexed() as usize));
+            for suppression in suppressions {
+                if suppression.tool() != from.tool() {
+                    continue;
+                }
+                let kept =
+                    from.kept_codes(suppression.error_codes(), reported.into_iter().flatten());
+                if kept.is_empty() {
+                    removed += 1;
+                } else {
+                    converted += 1;
+                }
+                line_codes
+                    .entry(suppression.comment_line().to_zero_indexed() as usize)
+                    .or_default()
+                    .extend(kept);
+            }
+        }
+        if line_codes.is_empty() {
+            continue;
+        }
+
+        let line_ending = detect_line_ending(&file);
+        let mut buf = String::with_capacity(file.len());
+        for (idx, line) in file.lines().enumerate() {
+            if let Some(codes) = line_codes.get(&idx) {
+                let mut codes: Vec<String> = codes.iter().cloned().collect();
+                codes.sort();
+                let updated = convert_suppression_line(line, from, &codes);
+                if updated.trim().is_empty() {
+                    continue;
+                }
+                buf.push_str(&updated);
+            } else {
+                buf.push_str(line);
+            }
+            buf.push_str(line_ending);
+        }
+        if fs_anyhow::write(path, buf).is_ok() {
+            changed_files += 1;
+        }
+    }
+
+    info!(
+        "Converted {} and removed {} suppression(s) in {} file(s)",
+        converted, removed, changed_files,
+    );
+    (converted, removed)
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -2629,4 +2812,120 @@ def foo() -> int:
 "#,
         );
     }
+
+    fn assert_convert_suppressions(
+        before: &str,
+        from: SuppressionSource,
+        errors: &[(usize, &str)],
+        after: &str,
+        expected: (usize, usize),
+    ) {
+        let tdir = tempfile::tempdir().unwrap();
+        let path = get_path(&tdir);
+        fs_anyhow::write(&path, before).unwrap();
+        let errors = errors
+            .iter()
+            .map(|(line, name)| SerializedError {
+                path: path.clone(),
+                line: *line,
+                name: (*name).to_owned(),
+                message: String::new(),
+            })
+            .collect();
+        let counts = suppress::convert_suppressions(errors, &[path.clone()], from);
+        let got_file = fs_anyhow::read_to_string(&path).unwrap();
+        assert_eq!(after, got_file);
+        assert_eq!(counts, expected);
+    }
+
+    #[test]
+    fn test_convert_mypy_suppressions() {
+        assert_convert_suppressions(
+            r#"
+def f(x: int) -> None: ...
+f("") # type: ignore[arg-type]
+# type: ignore[attr-defined]
+x: int = ""
+y: int = 1 # type: ignore
+"#,
+            SuppressionSource::Mypy,
+            &[(2, "bad-argument-type"), (4, "bad-assignment")],
+            r#"
+def f(x: int) -> None: ...
+f("") # pyrefly: ignore [bad-argument-type]
+x: int = ""
+y: int = 1
+"#,
+            (1, 2),
+        );
+    }
+
+    #[test]
+    fn test_convert_blanket_and_unmapped_suppressions() {
+        assert_convert_suppressions(
+            r#"
+def f(x: int) -> str:
+    return x.foo # type: ignore
+def g(x: int) -> str:
+    return x # type: ignore[misc]
+"#,
+            SuppressionSource::Mypy,
+            &[
+                (2, "missing-attribute"),
+                (2, "bad-return"),
+                (4, "bad-return"),
+            ],
+            r#"
+def f(x: int) -> str:
+    return x.foo # pyrefly: ignore [bad-return, missing-attribute]
+def g(x: int) -> str:
+    return x # pyrefly: ignore [bad-return]
+"#,
+            (2, 0),
+        );
+    }
+
+    #[test]
+    fn test_convert_pyre_suppressions() {
+        assert_convert_suppressions(
+            r#"
+def f(x: int) -> None: ...
+def g() -> None:
+    # pyre-fixme[6]: Expected `int` but got `str`.
+    f("")
+"#,
+            SuppressionSource::Pyre,
+            &[(4, "bad-argument-type")],
+            r#"
+def f(x: int) -> None: ...
+def g() -> None:
+    # pyrefly: ignore [bad-argument-type]
+    f("")
+"#,
+            (1, 0),
+        );
+    }
+
+    #[test]
+    fn test_convert_pyright_suppressions_merges_existing_ignore() {
+        assert_convert_suppressions(
+            "x: int = (1).foo # pyrefly: ignore[bad-assignment] # pyright: ignore[reportAttributeAccessIssue] # noqa\n",
+            SuppressionSource::Pyright,
+            &[(0, "missing-attribute")],
+            "x: int = (1).foo # pyrefly: ignore [bad-assignment, missing-attribute] # noqa\n",
+            (1, 0),
+        );
+    }
+
+    #[test]
+    fn test_convert_suppressions_only_selected_tool() {
+        let input = "x: int = \"\" # type: ignore # pyright: ignore\n";
+        assert_convert_suppressions(
+            input,
+            SuppressionSource::Pyre,
+            &[(0, "bad-assignment")],
+            input,
+            (0, 0),
+        );
+    }
 }
@@ -15,6 +15,8 @@ use starlark_map::small_set::SmallSet;
 #[cfg(not(target_arch = "wasm32"))]
 use tracing::warn;
 
+#[cfg(not(target_arch = "wasm32"))]
+use crate::module::archive::materialize_archive_entry;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::module::bundled::BundledStub;
 #[cfg(not(target_arch = "wasm32"))]
@@ -34,7 +36,8 @@ pub fn to_real_path(path: &ModulePath) -> Option<PathBuf> {
         #[cfg(target_arch = "wasm32")]
         ModulePathDetails::BundledTypeshed(_)
         | ModulePathDetails::BundledTypeshedThirdParty(_)
-        | ModulePathDetails::BundledThirdParty(_) => None,
+        | ModulePathDetails::BundledThirdParty(_)
+        | ModulePathDetails::Archive(_) => None,
         #[cfg(not(target_arch = "wasm32"))]
         ModulePathDetails::BundledTypeshed(path) => {
             let typeshed = typeshed().ok()?;
@@ -74,6 +77,14 @@ pub fn to_real_path(path: &ModulePath) -> Option<PathBuf> {
             }?;
             Some(bundled_path.join(&**path))
         }
+        #[cfg(not(target_arch = "wasm32"))]
+        ModulePathDetails::Archive(path) => match materialize_archive_entry(path) {
+            Ok(path) => Some(path),
+            Err(err) => {
+                warn!("Archived module unable to be extracted to disk, {:#}", err);
+                None
+            }
+        },
     }
 }
 
@@ -87,7 +98,8 @@ pub fn collect_symbol_def_paths(t: &Type) -> Vec<(QName, PathBuf)> {
             let file_path = match module_path.details() {
                 ModulePathDetails::BundledTypeshed(_)
                 | ModulePathDetails::BundledTypeshedThirdParty(_)
-                | ModulePathDetails::BundledThirdParty(_) => {
+                | ModulePathDetails::BundledThirdParty(_)
+                | ModulePathDetails::Archive(_) => {
                     to_real_path(module_path).unwrap_or_else(|| module_path.as_path().to_path_buf())
                 }
                 _ => module_path.as_path().to_path_buf(),
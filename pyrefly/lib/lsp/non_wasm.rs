@@ -10,6 +10,7 @@ pub mod call_hierarchy;
 pub mod code_lens;
 pub mod connection;
 pub mod convert_module_package;
+pub mod document_links;
 pub mod document_symbols;
 pub mod external_provider;
 pub mod folding_ranges;
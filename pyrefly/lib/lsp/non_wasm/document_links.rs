@@ -0,0 +1,323 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+
+use pyrefly_build::handle::Handle;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePathDetails;
+use pyrefly_util::visit::Visit;
+use ruff_python_ast::Expr;
+use ruff_python_ast::ExprCall;
+use ruff_python_ast::ExprStringLiteral;
+use ruff_python_ast::Operator;
+use ruff_python_ast::Stmt;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+
+use crate::lsp::module_helpers::to_real_path;
+use crate::lsp::wasm::signature_help::ActiveArgument;
+use crate::state::lsp::resolve_relative_module_name;
+use crate::state::state::Transaction;
+use crate::types::callable::Param;
+use crate::types::types::Type;
+
+/// Strings longer than this are not worth checking against the file system.
+const MAX_PATH_LEN: usize = 260;
+
+/// Which paths a string literal may link to.
+#[derive(Clone, Copy)]
+enum PathKind {
+    /// Any string: only a relative path to an existing file, which must look like a path.
+    Any,
+    /// A string used as a path: an existing file or directory, relative or absolute.
+    Path,
+}
+
+/// Where relative paths in a module are looked up, and the links found so far.
+struct LinkCollector<'a> {
+    file: &'a Path,
+    /// The directory of the file, then its import root, which is usually the directory
+    /// the program runs from.
+    dirs: Vec<PathBuf>,
+    links: Vec<(TextRange, PathBuf)>,
+    linked: HashSet<TextRange>,
+}
+
+impl LinkCollector<'_> {
+    fn push(&mut self, literal: &ExprStringLiteral, path: PathBuf) {
+        if let Some(literal) = literal.as_single_part_string()
+            && self.linked.insert(literal.content_range())
+        {
+            self.links.push((literal.content_range(), path));
+        }
+    }
+
+    fn resolve(&self, literal: &ExprStringLiteral, kind: PathKind) -> Option<PathBuf> {
+        let value = literal.as_single_part_string()?.value.as_ref();
+        if value.is_empty() || value.len() > MAX_PATH_LEN || value.contains(['\n', '\0']) {
+            return None;
+        }
+        if matches!(kind, PathKind::Any) && !value.contains(['/', '.']) {
+            return None;
+        }
+        let path = Path::new(value);
+        let exists = |path: &Path| match kind {
+            PathKind::Any => path.is_file(),
+            PathKind::Path => path.exists(),
+        };
+        if path.is_absolute() {
+            return (matches!(kind, PathKind::Path) && exists(path)).then(|| path.to_path_buf());
+        }
+        self.dirs
+            .iter()
+            .map(|dir| dir.join(path))
+            .find(|path| exists(path))
+    }
+}
+
+/// The path `expr` evaluates to, for the expressions commonly used to find files next to a
+/// module, such as `Path(__file__).parent / "data"` or `os.path.dirname(__file__)`.
+fn static_path(expr: &Expr, file: &Path) -> Option<PathBuf> {
+    match expr {
+        Expr::Name(x) if x.id.as_str() == "__file__" => Some(file.to_path_buf()),
+        Expr::Attribute(x) if x.attr.as_str() == "parent" => {
+            Some(static_path(&x.value, file)?.parent()?.to_path_buf())
+        }
+        Expr::BinOp(x) if x.op == Operator::Div => {
+            let segment = x.right.as_string_literal_expr()?.as_single_part_string()?;
+            Some(static_path(&x.left, file)?.join(segment.value.as_ref()))
+        }
+        Expr::Call(call) => {
+            let args = &call.arguments.args;
+            match (&*call.func, callee_name(call)?, args.len()) {
+                (Expr::Attribute(x), "resolve" | "absolute", 0) => static_path(&x.value, file),
+                (_, "Path" | "PurePath" | "abspath" | "realpath" | "normpath", 1) => {
+                    static_path(&args[0], file)
+                }
+                (_, "dirname", 1) => Some(static_path(&args[0], file)?.parent()?.to_path_buf()),
+                (_, "join", n) if n > 0 => {
+                    let mut path = static_path(&args[0], file)?;
+                    for arg in &args[1..] {
+                        path.push(
+                            arg.as_string_literal_expr()?
+                                .as_single_part_string()?
+                                .value
+                                .as_ref(),
+                        );
+                    }
+                    Some(path)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn callee_name(call: &ExprCall) -> Option<&str> {
+    match &*call.func {
+        Expr::Name(x) => Some(x.id.as_str()),
+        Expr::Attribute(x) => Some(x.attr.as_str()),
+        _ => None,
+    }
+}
+
+/// Whether a parameter of type `ty` takes a file system path.
+fn is_path_type(ty: &Type) -> bool {
+    let mut found = false;
+    ty.universe(&mut |ty| {
+        if let Type::ClassType(cls) = ty
+            && (cls.has_qname("os", "PathLike")
+                || ["PurePath", "PurePosixPath", "PureWindowsPath", "Path"]
+                    .iter()
+                    .any(|name| cls.has_qname("pathlib", name)))
+        {
+            found = true;
+        }
+    });
+    found
+}
+
+impl Transaction<'_> {
+    /// The ranges of `handle` that link to other files: the module names of imports, and
+    /// string literals that name files, either because they are passed as an `os.PathLike`
+    /// argument, are joined onto the module's own path, or match an existing relative file.
+    pub fn document_links(&self, handle: &Handle) -> Option<Vec<(TextRange, PathBuf)>> {
+        let ast = self.get_ast(handle)?;
+        let mut links = Vec::new();
+        for stmt in &ast.body {
+            self.collect_import_links(handle, stmt, &mut links);
+        }
+        if matches!(
+            handle.path().details(),
+            ModulePathDetails::FileSystem(_) | ModulePathDetails::Memory(_)
+        ) {
+            let file = handle.path().as_path();
+            let mut collector = LinkCollector {
+                file,
+                dirs: file
+                    .parent()
+                    .map(Path::to_path_buf)
+                    .into_iter()
+                    .chain(handle.path().root_of(handle.module()))
+                    .collect(),
+                links: Vec::new(),
+                linked: HashSet::new(),
+            };
+            collector.dirs.dedup();
+            ast.visit(&mut |x| self.collect_string_links(handle, x, &mut collector));
+            links.extend(collector.links);
+        }
+        links.sort_by_key(|(range, _)| range.start());
+        Some(links)
+    }
+
+    fn collect_import_links(
+        &self,
+        handle: &Handle,
+        stmt: &Stmt,
+        links: &mut Vec<(TextRange, PathBuf)>,
+    ) {
+        match stmt {
+            Stmt::Import(import) => {
+                for alias in &import.names {
+                    if let Some(path) =
+                        self.module_link_target(handle, ModuleName::from_name(&alias.name.id))
+                    {
+                        links.push((alias.name.range, path));
+                    }
+                }
+            }
+            Stmt::ImportFrom(import_from) => {
+                if let Some(module) = &import_from.module
+                    && let Some(path) = self.module_link_target(
+                        handle,
+                        resolve_relative_module_name(
+                            handle,
+                            ModuleName::from_name(&module.id),
+                            import_from.level,
+                        ),
+                    )
+                {
+                    links.push((module.range, path));
+                }
+            }
+            _ => stmt.recurse(&mut |x| self.collect_import_links(handle, x, links)),
+        }
+    }
+
+    fn module_link_target(&self, handle: &Handle, module: ModuleName) -> Option<PathBuf> {
+        let target = self.import_handle(handle, module, None).finding()?;
+        if matches!(target.path().details(), ModulePathDetails::Namespace(_)) {
+            return None;
+        }
+        to_real_path(target.path())
+    }
+
+    fn collect_string_links(&self, handle: &Handle, x: &Expr, collector: &mut LinkCollector) {
+        match x {
+            Expr::Call(call) => {
+                if let Some(base) = call
+                    .arguments
+                    .args
+                    .first()
+                    .filter(|_| callee_name(call) == Some("join"))
+                    .and_then(|first| static_path(first, collector.file))
+                {
+                    let mut path = base;
+                    for arg in &call.arguments.args[1..] {
+                        let Some(literal) = arg.as_string_literal_expr() else {
+                            break;
+                        };
+                        path.push(literal.value.to_str());
+                        if path.exists() {
+                            collector.push(literal, path.clone());
+                        }
+                    }
+                }
+                self.collect_path_argument_links(handle, call, collector);
+            }
+            Expr::BinOp(binop) if binop.op == Operator::Div => {
+                if let Expr::StringLiteral(literal) = &*binop.right
+                    && let Some(path) = static_path(x, collector.file)
+                    && path.exists()
+                {
+                    collector.push(literal, path);
+                }
+            }
+            Expr::StringLiteral(literal) => {
+                if let Some(path) = collector.resolve(literal, PathKind::Any) {
+                    collector.push(literal, path);
+                }
+            }
+            _ => {}
+        }
+        x.recurse(&mut |x| self.collect_string_links(handle, x, collector));
+    }
+
+    /// Link the string literal arguments of `call` whose parameter takes a path.
+    fn collect_path_argument_links(
+        &self,
+        handle: &Handle,
+        call: &ExprCall,
+        collector: &mut LinkCollector,
+    ) {
+        let arguments = call
+            .arguments
+            .args
+            .iter()
+            .enumerate()
+            .filter_map(|(i, arg)| {
+                Some((ActiveArgument::Positional(i), arg.as_string_literal_expr()?))
+            })
+            .chain(call.arguments.keywords.iter().filter_map(|keyword| {
+                Some((
+                    ActiveArgument::Keyword(keyword.arg.as_ref()?.id.clone()),
+                    keyword.value.as_string_literal_expr()?,
+                ))
+            }))
+            .collect::<Vec<_>>();
+        if arguments.is_empty() {
+            return;
+        }
+        let Some(params) = self.call_params(handle, call) else {
+            return;
+        };
+        for (argument, literal) in arguments {
+            let param = match Self::active_parameter_index(&params, &argument) {
+                Some(i) => params.get(i),
+                // Extra positional arguments go to `*args`.
+                None if matches!(argument, ActiveArgument::Positional(_)) => params
+                    .iter()
+                    .find(|param| matches!(param, Param::Varargs(..))),
+                None => None,
+            };
+            if let Some(param) = param
+                && is_path_type(param.as_type())
+                && let Some(path) = collector.resolve(literal, PathKind::Path)
+            {
+                collector.push(literal, path);
+            }
+        }
+    }
+
+    /// The parameters of the signature `call` resolved to.
+    fn call_params(&self, handle: &Handle, call: &ExprCall) -> Option<Vec<Param>> {
+        let answers = self.get_answers(handle)?;
+        let callable = answers
+            .get_chosen_overload_trace(call.arguments.range)
+            .or_else(|| {
+                answers
+                    .get_type_trace(call.func.range())
+                    .map(|ty| self.coerce_type_to_callable(handle, ty))
+            })?;
+        Self::normalize_singleton_function_type_into_params(callable)
+    }
+}
@@ -69,6 +69,9 @@ use lsp_types::DocumentDiagnosticReportKind;
 use lsp_types::DocumentHighlight;
 use lsp_types::DocumentHighlightKind;
 use lsp_types::DocumentHighlightParams;
+use lsp_types::DocumentLink;
+use lsp_types::DocumentLinkOptions;
+use lsp_types::DocumentLinkParams;
 use lsp_types::DocumentSymbolParams;
 use lsp_types::DocumentSymbolResponse;
 use lsp_types::FileEvent;
@@ -180,6 +183,7 @@ use lsp_types::request::CodeLensRequest;
 use lsp_types::request::Completion;
 use lsp_types::request::DocumentDiagnosticRequest;
 use lsp_types::request::DocumentHighlightRequest;
+use lsp_types::request::DocumentLinkRequest;
 use lsp_types::request::DocumentSymbolRequest;
 use lsp_types::request::FoldingRangeRequest;
 use lsp_types::request::GotoDeclaration;
@@ -300,6 +304,7 @@ use crate::lsp::non_wasm::module_helpers::ThriftRemapper;
 use crate::lsp::non_wasm::module_helpers::handle_from_module_path;
 use crate::lsp::non_wasm::module_helpers::make_open_handle;
 use crate::lsp::non_wasm::module_helpers::module_info_to_uri;
+use crate::lsp::non_wasm::module_helpers::path_to_uri;
 use crate::lsp::non_wasm::move_symbol_new_file::move_symbol_to_new_file_code_action;
 use crate::lsp::non_wasm::mru::CompletionMru;
 use crate::lsp::non_wasm::protocol::Message;
@@ -1359,6 +1364,7 @@ pub fn capabilities(
                 CodeActionKind::REFACTOR_INLINE,
                 CodeActionKind::SOURCE_FIX_ALL,
                 CodeActionKind::new(SOURCE_FIX_ALL_PYREFLY),
+                CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
             ]),
             ..Default::default()
         })),
@@ -1397,6 +1403,10 @@ pub fn capabilities(
         )),
         inlay_hint_provider: Some(OneOf::Left(true)),
         document_symbol_provider: Some(OneOf::Left(true)),
+        document_link_provider: Some(DocumentLinkOptions {
+            resolve_provider: Some(false),
+            work_done_progress_options: Default::default(),
+        }),
         workspace_symbol_provider: Some(OneOf::Left(true)),
         folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
         selection_range_provider: Some(SelectionRangeProviderCapability::Simple(true)),
@@ -2478,6 +2488,21 @@ impl Server {
                         };
                         self.send_response(new_response(x.id, Ok(result)));
                     }
+                } else if let Some(params) = as_request::<DocumentLinkRequest>(&x) {
+                    if let Some(params) = self
+                        .extract_request_params_or_send_err_response::<DocumentLinkRequest>(
+                            params, &x.id,
+                        )
+                    {
+                        let response = match self.document_links(&transaction, params) {
+                            Ok(response) => response,
+                            Err(reason) => {
+                                telemetry_event.set_empty_response_reason(reason);
+                                None
+                            }
+                        };
+                        self.send_response(new_response(x.id, Ok(response)));
+                    }
                 } else if let Some(params) = as_request::<SelectionRangeRequest>(&x) {
                     if let Some(params) = self
                         .extract_request_params_or_send_err_response::<SelectionRangeRequest>(
@@ -4547,6 +4572,11 @@ impl Server {
         let allow_quickfix = only_kinds
             .is_none_or(|kinds| kinds.iter().any(|kind| kind == &CodeActionKind::QUICKFIX));
         let allow_fix_all = only_kinds.is_none_or(|kinds| kinds.iter().any(matches_fix_all_kind));
+        let allow_organize_imports = only_kinds.is_none_or(|kinds| {
+            kinds
+                .iter()
+                .any(|kind| kind == &CodeActionKind::SOURCE_ORGANIZE_IMPORTS)
+        });
         let allow_refactor = only_kinds.is_none_or(|kinds| {
             kinds
                 .iter()
@@ -4662,6 +4692,40 @@ impl Server {
             }
             record_code_action_telemetry("fix_all", start);
         }
+        if allow_organize_imports {
+            let start = Instant::now();
+            let settings = transaction
+                .get_config(&handle)
+                .map(|config| config.organize_imports.settings())
+                .unwrap_or_default();
+            if let Some(edits) = transaction.organize_imports_edits(&handle, &settings) {
+                let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+                for (module, edit_range, new_text) in edits {
+                    let Some(lsp_location) = self.to_lsp_location(&TextRangeWithModule {
+                        module,
+                        range: edit_range,
+                    }) else {
+                        continue;
+                    };
+                    changes.entry(lsp_location.uri).or_default().push(TextEdit {
+                        range: lsp_location.range,
+                        new_text,
+                    });
+                }
+                if !changes.is_empty() {
+                    actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                        title: "Organize imports".to_owned(),
+                        kind: Some(CodeActionKind::SOURCE_ORGANIZE_IMPORTS),
+                        edit: Some(WorkspaceEdit {
+                            changes: Some(changes),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }));
+                }
+            }
+            record_code_action_telemetry("organize_imports", start);
+        }
         // Optimization: do not calculate refactors for automated codeactions since they're expensive
         // If we had lazy code actions, we could keep them.
         if let Some(trigger_kind) = params.context.trigger_kind
@@ -5681,6 +5745,39 @@ impl Server {
         ))
     }
 
+    fn document_links(
+        &self,
+        transaction: &Transaction<'_>,
+        params: DocumentLinkParams,
+    ) -> Result<Option<Vec<DocumentLink>>, EmptyResponseReason> {
+        let uri = &params.text_document.uri;
+        let maybe_cell_idx = self.maybe_get_code_cell_index(uri);
+        let handle = self.make_handle_if_enabled(uri, Some(DocumentLinkRequest::METHOD))?;
+        let module = transaction
+            .get_module_info(&handle)
+            .ok_or(EmptyResponseReason::ModuleInfoNotFound)?;
+        let Some(links) = transaction.document_links(&handle) else {
+            return Ok(None);
+        };
+        Ok(Some(
+            links
+                .into_iter()
+                .filter(|(range, _)| {
+                    maybe_cell_idx.is_none()
+                        || module.to_cell_for_lsp(range.start()) == maybe_cell_idx
+                })
+                .filter_map(|(range, path)| {
+                    Some(DocumentLink {
+                        range: module.to_lsp_range(range),
+                        target: Some(path_to_uri(&path, self.path_remapper.as_ref())?),
+                        tooltip: None,
+                        data: None,
+                    })
+                })
+                .collect(),
+        ))
+    }
+
     fn selection_ranges(
         &self,
         transaction: &Transaction<'_>,
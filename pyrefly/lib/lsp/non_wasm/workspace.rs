@@ -35,6 +35,7 @@ use crate::commands::config_finder::apply_unconfigured_resolver_if_applicable;
 use crate::commands::config_finder::standard_config_finder;
 use crate::config::config::ConfigFile;
 use crate::config::config::ConfigSource;
+use crate::config::environment::environment::EnvironmentDiscovery;
 use crate::config::environment::environment::PythonEnvironment;
 use crate::config::finder::ConfigFinder;
 use crate::state::lsp::DisplayTypeErrors;
@@ -45,23 +46,27 @@ use crate::state::lsp::TypeCheckingMode;
 /// Information about the Python environment provided by this workspace.
 #[derive(Debug, Clone)]
 pub struct PythonInfo {
-    /// The path to the interpreter used to query this `PythonInfo`'s [`PythonEnvironment`].
+    /// The path to the interpreter used to find this `PythonInfo`'s [`PythonEnvironment`].
     interpreter: PathBuf,
-    /// The [`PythonEnvironment`] values all [`ConfigFile`]s in a given workspace should
-    /// use if no explicit [`ConfigFile::python_interpreter`] is provided, or any
-    /// `PythonEnvironment` values in that `ConfigFile` are unfiled. If the `interpreter
-    /// provided fails to execute or is invalid, this `PythonEnvironment` might instead
-    /// be a system interpreter or [`PythonEnvironment::pyrefly_default()`].
-    env: PythonEnvironment,
 }
 
 impl PythonInfo {
     pub fn new(interpreter: PathBuf) -> Self {
-        let (env, query_error) = PythonEnvironment::get_interpreter_env(&interpreter);
+        Self { interpreter }
+    }
+
+    /// The [`PythonEnvironment`] values all [`ConfigFile`]s in a given workspace should
+    /// use if no explicit [`ConfigFile::python_interpreter`] is provided, or any
+    /// `PythonEnvironment` values in that `ConfigFile` are unfiled, found as the config's
+    /// `discovery` selects. If the interpreter provided fails to execute or is invalid, this
+    /// is [`PythonEnvironment::pyrefly_default()`]. The result is cached per interpreter.
+    fn env(&self, discovery: EnvironmentDiscovery) -> PythonEnvironment {
+        let (env, query_error) =
+            PythonEnvironment::get_interpreter_env(&self.interpreter, discovery);
         if let Some(error) = query_error {
             error!("{error}");
         }
-        Self { interpreter, env }
+        env
     }
 }
 
@@ -172,16 +177,16 @@ impl ConfigConfigurer for WorkspaceConfigConfigurer {
                     config.fallback_search_path =
                         FallbackSearchPath::Explicit(Arc::new(new_fallback_search_path));
                 }
-                if let Some(PythonInfo {
-                    interpreter,
-                    mut env,
-                }) = w.python_info.clone()
+                if let Some(python_info) = &w.python_info
                     && config.interpreters.is_empty()
                 {
+                    let mut env = python_info.env(config.interpreters.environment_discovery());
                     let site_package_path: Option<Vec<PathBuf>> =
                         config.python_environment.site_package_path.take();
                     env.site_package_path = site_package_path;
-                    config.interpreters.set_lsp_python_interpreter(interpreter);
+                    config
+                        .interpreters
+                        .set_lsp_python_interpreter(python_info.interpreter.clone());
                     config.python_environment = env;
                     // skip interpreter query because we already have the interpreter from the workspace
                     config.interpreters.skip_interpreter_query = true;
@@ -317,6 +322,8 @@ pub struct DisabledLanguageServices {
     #[serde(default)]
     pub document_symbol: bool,
     #[serde(default)]
+    pub document_link: bool,
+    #[serde(default)]
     pub code_lens: bool,
     #[serde(default)]
     pub semantic_tokens: bool,
@@ -341,6 +348,7 @@ impl DisabledLanguageServices {
             "textDocument/hover" => self.hover,
             "textDocument/inlayHint" => self.inlay_hint,
             "textDocument/documentSymbol" => self.document_symbol,
+            "textDocument/documentLink" => self.document_link,
             "textDocument/codeLens" => self.code_lens,
             "textDocument/semanticTokens/full" | "textDocument/semanticTokens/range" => {
                 self.semantic_tokens
@@ -1190,11 +1190,13 @@ impl Transaction<'_> {
                 if (is_method_def || matches!(context, IdentifierContext::Expr(ExprContext::Store)))
                     && let Some(covering_nodes) = covering_nodes.as_deref()
                 {
-                    let mut saw_function = false;
+                    let mut enclosing_functions = Vec::new();
                     let mut enclosing_class = None;
                     for node in covering_nodes.iter() {
                         match node {
-                            AnyNodeRef::StmtFunctionDef(_) => saw_function = true,
+                            AnyNodeRef::StmtFunctionDef(function_def) => {
+                                enclosing_functions.push(*function_def)
+                            }
                             AnyNodeRef::StmtClassDef(class_def) => {
                                 enclosing_class = Some(class_def);
                                 break;
@@ -1203,7 +1205,7 @@ impl Transaction<'_> {
                         }
                     }
                     if let Some(class_def) = enclosing_class
-                        && (!saw_function || is_method_def)
+                        && (enclosing_functions.is_empty() || is_method_def)
                     {
                         let key = Key::Definition(ShortIdentifier::new(&class_def.name));
                         if let Some(class_type) = self.get_type(handle, &key) {
@@ -1215,6 +1217,18 @@ impl Transaction<'_> {
                             );
                         }
                     }
+                    if let Some(class_def) = enclosing_class
+                        && is_method_def
+                        && let [method_def] = enclosing_functions.as_slice()
+                    {
+                        let overrides =
+                            self.override_completions(handle, class_def, method_def, import_format);
+                        // The override supersedes the plain name offered for the same method.
+                        result.retain(|ranked| {
+                            !overrides.iter().any(|item| item.label == ranked.item.label)
+                        });
+                        result.extend(overrides.into_iter().map(RankedCompletion::new));
+                    }
                 }
                 self.add_kwargs_completions(handle, position, &mut result);
                 // In `func(foo=1, ba|` the cursor can only be a keyword-argument
@@ -5,6 +5,7 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+pub mod archive;
 pub mod bundled;
 pub mod finder;
 pub mod parse;
@@ -0,0 +1,341 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Zip archives on `sys.path`. Python's `zipimport` makes the contents of a `.zip`, `.egg`,
+//! `.whl` or `.pyz` file importable when the archive itself is on the path, as if it were a
+//! directory. We treat such archives as read-only module trees: a module inside one gets a
+//! [`ModulePath::archive`] whose path runs through the archive, e.g. `/deps.zip/pkg/mod.py`.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+#[cfg(not(target_arch = "wasm32"))]
+use std::env;
+use std::fs;
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::hash::Hash;
+#[cfg(not(target_arch = "wasm32"))]
+use std::hash::Hasher;
+use std::io::BufReader;
+use std::io::Read;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::LazyLock;
+use std::time::SystemTime;
+
+use anyhow::Context as _;
+use anyhow::anyhow;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::module_path::ModuleStyle;
+#[cfg(not(target_arch = "wasm32"))]
+use pyrefly_util::fs_anyhow;
+use pyrefly_util::lock::Mutex;
+#[cfg(not(target_arch = "wasm32"))]
+use tempfile::NamedTempFile;
+#[cfg(not(target_arch = "wasm32"))]
+use xxhash_rust::xxh64::Xxh64;
+use zip::ZipArchive;
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::module::bundled::set_readonly;
+
+/// The extensions of archives `zipimport` can import from.
+const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "egg", "whl", "pyz"];
+
+/// The entries of each archive we have looked in, along with the modification time they were
+/// read at, so a rebuilt archive is indexed again.
+static ARCHIVE_INDEX_CACHE: LazyLock<Mutex<HashMap<PathBuf, (SystemTime, Arc<ArchiveIndex>)>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The names of the files in an archive, and the directories that contain them.
+#[derive(Debug, Default)]
+struct ArchiveIndex {
+    files: HashSet<String>,
+    dirs: HashSet<String>,
+}
+
+impl ArchiveIndex {
+    fn read(archive: &Path) -> anyhow::Result<Self> {
+        let file = File::open(archive)
+            .with_context(|| format!("When opening archive `{}`", archive.display()))?;
+        let zip = ZipArchive::new(BufReader::new(file))
+            .with_context(|| format!("When reading archive `{}`", archive.display()))?;
+        let mut index = Self::default();
+        for name in zip.file_names() {
+            let mut parent = match name.strip_suffix('/') {
+                Some(dir) => {
+                    index.dirs.insert(dir.to_owned());
+                    dir
+                }
+                None => {
+                    index.files.insert(name.to_owned());
+                    name
+                }
+            };
+            while let Some((dir, _)) = parent.rsplit_once('/')
+                && index.dirs.insert(dir.to_owned())
+            {
+                parent = dir;
+            }
+        }
+        Ok(index)
+    }
+}
+
+/// Whether `path` is an archive we can import from.
+pub fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| ARCHIVE_EXTENSIONS.contains(&x))
+        && path.is_file()
+}
+
+/// The index of `archive`, or `None` if it isn't an archive we can read.
+fn archive_index(archive: &Path) -> Option<Arc<ArchiveIndex>> {
+    if !is_archive(archive) {
+        return None;
+    }
+    let modified = fs::metadata(archive).and_then(|x| x.modified()).ok()?;
+    if let Some((read_at, index)) = ARCHIVE_INDEX_CACHE.lock().get(archive)
+        && *read_at == modified
+    {
+        return Some(index.clone());
+    }
+    // Read without holding the lock, so lookups in other archives don't wait on this one. If
+    // two threads race, both read the archive and the last one to finish is cached.
+    let index = Arc::new(ArchiveIndex::read(archive).ok()?);
+    ARCHIVE_INDEX_CACHE
+        .lock()
+        .insert(archive.to_owned(), (modified, index.clone()));
+    Some(index)
+}
+
+/// Split a path through an archive into the archive and the name of the entry within it.
+fn split_archive_path(path: &Path) -> Option<(&Path, String)> {
+    let archive = path.ancestors().skip(1).find(|x| is_archive(x))?;
+    let entry = path
+        .strip_prefix(archive)
+        .ok()?
+        .components()
+        .map(|x| x.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+    Some((archive, entry))
+}
+
+/// A module found in an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveModule {
+    /// A `.py` or `.pyi` file, or the `__init__` file of a package.
+    File(ModulePath),
+    /// A directory without an `__init__` file, which is a namespace package.
+    Namespace(PathBuf),
+}
+
+/// Look for `module` in the archives among `roots`, which are the entries of `sys.path`. If
+/// `stubs` is set, we look in `<package>-stubs` instead of the package itself. Only modules
+/// matching `style_filter` are returned, but a namespace package is returned if no archive
+/// has a module file.
+pub fn find_archive_module<'a>(
+    module: ModuleName,
+    roots: impl Iterator<Item = &'a PathBuf>,
+    stubs: bool,
+    style_filter: Option<ModuleStyle>,
+) -> Option<ArchiveModule> {
+    let mut components = module
+        .components()
+        .into_iter()
+        .map(|x| x.to_string())
+        .collect::<Vec<_>>();
+    if stubs {
+        components[0].push_str("-stubs");
+    }
+    let prefix = components.join("/");
+    let candidates = [
+        format!("{prefix}/__init__.pyi"),
+        format!("{prefix}/__init__.py"),
+        format!("{prefix}.pyi"),
+        format!("{prefix}.py"),
+    ];
+    let mut namespace = None;
+    for root in roots {
+        let Some(index) = archive_index(root) else {
+            continue;
+        };
+        if let Some(entry) = candidates.iter().find(|candidate| {
+            index.files.contains(*candidate)
+                && style_filter
+                    .is_none_or(|style| ModuleStyle::of_path(Path::new(candidate)) == style)
+        }) {
+            return Some(ArchiveModule::File(ModulePath::archive(root.join(entry))));
+        }
+        if namespace.is_none() && index.dirs.contains(&prefix) {
+            namespace = Some(root.join(&prefix));
+        }
+    }
+    namespace.map(ArchiveModule::Namespace)
+}
+
+/// Whether the package `module` belongs to is marked as typed with a `py.typed` file, where
+/// `found` is the path we found `module` at.
+pub fn archive_package_has_py_typed(module: ModuleName, found: &ModulePath) -> bool {
+    let Some((archive, _)) = split_archive_path(found.as_path()) else {
+        return false;
+    };
+    archive_index(archive).is_some_and(|index| {
+        index
+            .files
+            .contains(&format!("{}/py.typed", module.first_component()))
+    })
+}
+
+/// Read the module at `path`, which runs through an archive.
+pub fn read_archive_entry(path: &Path) -> anyhow::Result<String> {
+    let (archive, entry) = split_archive_path(path)
+        .ok_or_else(|| anyhow!("`{}` is not inside an archive", path.display()))?;
+    let file = File::open(archive)
+        .with_context(|| format!("When opening archive `{}`", archive.display()))?;
+    let mut zip = ZipArchive::new(BufReader::new(file))
+        .with_context(|| format!("When reading archive `{}`", archive.display()))?;
+    let mut contents = String::new();
+    zip.by_name(&entry)
+        .with_context(|| format!("When finding `{entry}` in `{}`", archive.display()))?
+        .read_to_string(&mut contents)
+        .with_context(|| format!("When reading `{entry}` from `{}`", archive.display()))?;
+    Ok(contents)
+}
+
+/// Extract the module at `path`, which runs through an archive, to a read-only file in the
+/// temporary directory, so editors can show it. The file is rewritten if the archive changes.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn materialize_archive_entry(path: &Path) -> anyhow::Result<PathBuf> {
+    let (archive, entry) = split_archive_path(path)
+        .ok_or_else(|| anyhow!("`{}` is not inside an archive", path.display()))?;
+    let modified = fs::metadata(archive)
+        .and_then(|x| x.modified())
+        .with_context(|| format!("When reading metadata of `{}`", archive.display()))?;
+    let mut hasher = Xxh64::new(0);
+    archive.hash(&mut hasher);
+    modified.hash(&mut hasher);
+    let output = env::temp_dir()
+        .join("pyrefly_archives")
+        .join(format!("{:016x}", hasher.finish()))
+        .join(&entry);
+    if fs::exists(&output)
+        .with_context(|| format!("When checking existence of file `{}`", output.display()))?
+    {
+        return Ok(output);
+    }
+    if let Some(parent) = output.parent() {
+        fs_anyhow::create_dir_all(parent)?;
+    }
+    // Write to a tempfile and rename it, so a concurrent reader never sees a partial file.
+    let mut temp_file = NamedTempFile::new()
+        .with_context(|| format!("When creating temp file for `{}`", output.display()))?;
+    temp_file
+        .write_all(read_archive_entry(path)?.as_bytes())
+        .with_context(|| format!("When writing to temp file for `{}`", output.display()))?;
+    match temp_file.persist(&output) {
+        Ok(_) => {
+            // Edits to the extracted file would never make it back into the archive.
+            let _ = set_readonly(&output, true);
+            Ok(output)
+        }
+        Err(_) if fs::exists(&output).is_ok_and(|b| b) => Ok(output),
+        Err(e) => {
+            Err(e).with_context(|| format!("When persisting temp file to `{}`", output.display()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zip::ZipWriter;
+    use zip::write::SimpleFileOptions;
+
+    use super::*;
+
+    fn write_archive(path: &Path, files: &[(&str, &str)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, contents) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn test_find_archive_module() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let archive = tempdir.path().join("deps.zip");
+        write_archive(
+            &archive,
+            &[
+                ("foo/__init__.py", ""),
+                ("foo/bar.py", "x = 1"),
+                ("foo/bar.pyi", "x: int"),
+                ("foo-stubs/__init__.pyi", ""),
+                ("ns/inner/mod.py", ""),
+            ],
+        );
+        let roots = [tempdir.path().to_path_buf(), archive.clone()];
+        let find = |module, stubs, style_filter| {
+            find_archive_module(
+                ModuleName::from_str(module),
+                roots.iter(),
+                stubs,
+                style_filter,
+            )
+        };
+        let file = |entry: &str| {
+            Some(ArchiveModule::File(ModulePath::archive(
+                archive.join(entry),
+            )))
+        };
+
+        assert_eq!(find("foo", false, None), file("foo/__init__.py"));
+        assert_eq!(find("foo", true, None), file("foo-stubs/__init__.pyi"));
+        assert_eq!(find("foo.bar", false, None), file("foo/bar.pyi"));
+        assert_eq!(
+            find("foo.bar", false, Some(ModuleStyle::Executable)),
+            file("foo/bar.py")
+        );
+        assert_eq!(find("ns.inner.mod", false, None), file("ns/inner/mod.py"));
+        assert_eq!(
+            find("ns.inner", false, None),
+            Some(ArchiveModule::Namespace(archive.join("ns/inner")))
+        );
+        assert_eq!(find("missing", false, None), None);
+    }
+
+    #[test]
+    fn test_read_archive_entry() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let archive = tempdir.path().join("app.pyz");
+        write_archive(&archive, &[("pkg/mod.py", "x = 1\n"), ("pkg/py.typed", "")]);
+        let path = archive.join("pkg/mod.py");
+        assert_eq!(read_archive_entry(&path).unwrap(), "x = 1\n");
+        assert!(read_archive_entry(&archive.join("pkg/missing.py")).is_err());
+        assert!(archive_package_has_py_typed(
+            ModuleName::from_str("pkg.mod"),
+            &ModulePath::archive(path.clone())
+        ));
+
+        let materialized = materialize_archive_entry(&path).unwrap();
+        assert_eq!(fs::read_to_string(&materialized).unwrap(), "x = 1\n");
+        assert!(
+            fs::metadata(&materialized)
+                .unwrap()
+                .permissions()
+                .readonly()
+        );
+    }
+}
@@ -30,6 +30,11 @@ static STDLIB_SUGGESTION_CACHE: LazyLock<LockedMap<ModuleName, Option<ModuleName
     LazyLock::new(LockedMap::new);
 
 use crate::config::config::ConfigFile;
+use crate::config::environment::editable::EditableRoots;
+use crate::module::archive::ArchiveModule;
+use crate::module::archive::archive_package_has_py_typed;
+use crate::module::archive::find_archive_module;
+use crate::module::archive::is_archive;
 use crate::module::bundled::BundledStub;
 use crate::module::third_party::get_bundled_third_party;
 use crate::module::typeshed::typeshed;
@@ -245,6 +250,65 @@ where
     )
 }
 
+/// Like [`find_module`], but `include` may contain zip archives, as `sys.path` can. The roots
+/// are searched in order, as `zipimport` does: each run of directories is searched with
+/// [`find_module`], and each run of archives with [`find_archive_import`].
+fn find_module_in_path_order<'a, I>(
+    module: ModuleName,
+    include: I,
+    namespaces_found: &mut Vec<PathBuf>,
+    style_filter: Option<ModuleStyle>,
+    typeshed_third_party_stub: Option<FindingOrError<ModulePath>>,
+    from_real_config_file: bool,
+    phantom_paths: &mut Option<&mut Vec<PathBuf>>,
+    dir_cache: &DirEntryCache,
+    timing: Option<&TransactionTimingCounters>,
+) -> Option<FindingOrError<ModulePath>>
+where
+    I: Iterator<Item = &'a PathBuf> + Clone,
+{
+    if !include.clone().any(|root| is_archive(root)) {
+        return find_module(
+            module,
+            include,
+            namespaces_found,
+            style_filter,
+            typeshed_third_party_stub,
+            from_real_config_file,
+            phantom_paths,
+            dir_cache,
+            timing,
+        );
+    }
+    let include = include.collect::<Vec<_>>();
+    for roots in include.chunk_by(|x, y| is_archive(x) == is_archive(y)) {
+        let found = if is_archive(roots[0]) {
+            find_archive_import(
+                module,
+                roots.iter().copied(),
+                style_filter,
+                namespaces_found,
+            )
+        } else {
+            find_module(
+                module,
+                roots.iter().copied(),
+                namespaces_found,
+                style_filter,
+                typeshed_third_party_stub.clone(),
+                from_real_config_file,
+                phantom_paths,
+                dir_cache,
+                timing,
+            )
+        };
+        if found.is_some() {
+            return found;
+        }
+    }
+    None
+}
+
 /// Configerator file extensions that use the keyword-escaping convention.
 /// When a path component matches a Python keyword (e.g. `if`), module names
 /// escape it with a trailing underscore (`if_`). This convention is specific
@@ -437,6 +501,7 @@ pub fn find_import_internal(
     style_filter: Option<ModuleStyle>,
     phantom_paths: &mut Option<&mut Vec<PathBuf>>,
     dir_cache: &DirEntryCache,
+    editable_roots: &EditableRoots,
     timing: Option<&TransactionTimingCounters>,
 ) -> FindingOrError<ModulePath> {
     let mut namespaces_found = vec![];
@@ -463,7 +528,7 @@ pub fn find_import_internal(
         && let Some(path) = sourcedb.lookup(module, origin, style_filter)
     {
         FindingOrError::new_finding(path.clone())
-    } else if let Some(path) = find_module(
+    } else if let Some(path) = find_module_in_path_order(
         module,
         config.search_path(),
         &mut namespaces_found,
@@ -526,9 +591,12 @@ pub fn find_import_internal(
         )
     {
         path
-    } else if let Some(path) = find_module(
+    } else if let Some(path) = find_module_in_path_order(
         module,
-        config.site_package_path(),
+        // Editable installs that use import hooks are consulted after `sys.path`.
+        config
+            .site_package_path()
+            .chain(&editable_roots.install_roots(config.site_package_path(), module)),
         &mut namespaces_found,
         style_filter,
         find_third_party_stub(module, style_filter),
@@ -537,7 +605,7 @@ pub fn find_import_internal(
         dir_cache,
         timing,
     ) {
-        path
+        check_script_dependency(config, module, path)
     } else if config.has_extra_file_extensions()
         && let Some(path) = find_extra_extension_module(
             module,
@@ -565,11 +633,65 @@ pub fn find_import_internal(
     }
 }
 
+/// Find `module` in the zip archives among `roots`. As in a directory, a `<package>-stubs`
+/// package takes priority over the package itself, and namespace packages are added to
+/// `namespaces_found` rather than returned.
+fn find_archive_import<'a>(
+    module: ModuleName,
+    roots: impl Iterator<Item = &'a PathBuf> + Clone,
+    style_filter: Option<ModuleStyle>,
+    namespaces_found: &mut Vec<PathBuf>,
+) -> Option<FindingOrError<ModulePath>> {
+    let stub = find_archive_module(module, roots.clone(), true, style_filter);
+    let normal = find_archive_module(module, roots, false, style_filter);
+    match (normal, stub) {
+        (Some(_), Some(ArchiveModule::File(stub))) => Some(FindingOrError::new_finding(stub)),
+        (None, Some(ArchiveModule::File(stub))) => {
+            Some(FindingOrError::new_finding(stub).with_error(FindError::MissingSource(module)))
+        }
+        (Some(ArchiveModule::File(normal)), _) => {
+            if let Some(missing_stub_result) = recommended_stubs_package(module)
+                && !archive_package_has_py_typed(module, &normal)
+            {
+                Some(
+                    FindingOrError::new_finding(normal).with_error(FindError::UntypedImport(
+                        module,
+                        missing_stub_result.as_str().to_owned().into(),
+                    )),
+                )
+            } else {
+                Some(FindingOrError::new_finding(normal))
+            }
+        }
+        (Some(ArchiveModule::Namespace(namespace)), _) => {
+            namespaces_found.push(namespace);
+            None
+        }
+        (None, _) => None,
+    }
+}
+
+/// If `config` is for a PEP 723 script, flag a third-party `module` that the script's
+/// dependencies don't provide.
+fn check_script_dependency(
+    config: &ConfigFile,
+    module: ModuleName,
+    found: FindingOrError<ModulePath>,
+) -> FindingOrError<ModulePath> {
+    match &config.script {
+        Some(script) if !script.lists_dependency(module, config.site_package_path()) => {
+            found.with_error(FindError::UnlistedScriptDependency(module))
+        }
+        _ => found,
+    }
+}
+
 /// Get the given [`ModuleName`] from this config's search and site package paths.
 /// We take the [`Handle`] of the file we're searching for the module from to determine if
 /// we should replace imports with `typing.Any` and to perform lookups within a
 /// `SourceDatabase`.
 /// Return `Err` when indicating the module could not be found.
+/// Editable install roots are found afresh, rather than from a `ConfigFinder`'s cache.
 pub fn find_import(
     config: &ConfigFile,
     module: ModuleName,
@@ -585,6 +707,7 @@ pub fn find_import(
         None,
         &mut phantom_paths,
         dir_cache,
+        &EditableRoots::default(),
         timing,
     )
 }
@@ -604,6 +727,7 @@ pub fn find_import_filtered(
         style_filter,
         &mut None,
         dir_cache,
+        &EditableRoots::default(),
         timing,
     )
 }
@@ -2306,6 +2430,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_find_import_from_archive() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::dir(
+                    "search_path",
+                    vec![TestPath::dir("early", vec![TestPath::file("__init__.py")])],
+                ),
+                TestPath::dir(
+                    "site_package_path",
+                    vec![TestPath::dir("shadow", vec![TestPath::file("__init__.py")])],
+                ),
+            ],
+        );
+        let archive = root.join("deps.whl");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        for name in [
+            "early/__init__.py",
+            "shadow/__init__.py",
+            "pkg/__init__.py",
+            "pkg/py.typed",
+            "stubbed-stubs/__init__.pyi",
+        ] {
+            zip.start_file(name, zip::write::SimpleFileOptions::default())
+                .unwrap();
+        }
+        zip.finish().unwrap();
+
+        let mut interpreters = Interpreters::default();
+        interpreters.skip_interpreter_query = true;
+        let mut config = ConfigFile {
+            search_path_from_file: vec![root.join("search_path"), archive.clone()],
+            interpreters,
+            python_environment: PythonEnvironment {
+                site_package_path: Some(vec![root.join("site_package_path")]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        config.configure();
+        let find = |module| {
+            find_import(
+                &config,
+                ModuleName::from_str(module),
+                None,
+                None,
+                &DirEntryCache::new(),
+                None,
+            )
+        };
+        assert_eq!(
+            find("pkg"),
+            FindingOrError::new_finding(ModulePath::archive(archive.join("pkg/__init__.py")))
+        );
+        // Roots are searched in `sys.path` order, so the archive shadows the site package
+        // directory, but not the directory before it on the search path.
+        assert_eq!(
+            find("early"),
+            FindingOrError::new_finding(ModulePath::filesystem(
+                root.join("search_path/early/__init__.py")
+            ))
+        );
+        assert_eq!(
+            find("shadow"),
+            FindingOrError::new_finding(ModulePath::archive(archive.join("shadow/__init__.py")))
+        );
+        assert_eq!(
+            find("stubbed"),
+            FindingOrError::new_finding(ModulePath::archive(
+                archive.join("stubbed-stubs/__init__.pyi")
+            ))
+            .with_error(FindError::MissingSource(ModuleName::from_str("stubbed")))
+        );
+    }
+
     #[test]
     fn test_find_site_package_path_no_source() {
         let tempdir = tempfile::tempdir().unwrap();
@@ -4176,4 +4378,58 @@ mod tests {
             FindingOrError::new_finding(ModulePath::filesystem(root.join("rules/if.config.cconf")))
         );
     }
+
+    #[test]
+    fn test_find_import_through_editable_finder() {
+        let tempdir = tempfile::tempdir().unwrap();
+        let root = tempdir.path();
+        let finder = format!(
+            "MAPPING: dict[str, str] = {{'mypkg': {:?}}}\n",
+            root.join("src/mypkg").to_str().unwrap()
+        );
+        TestPath::setup_test_directory(
+            root,
+            vec![
+                TestPath::dir(
+                    "site_packages",
+                    vec![
+                        TestPath::file_with_contents(
+                            "__editable__.mypkg-1.0.pth",
+                            "import __editable___mypkg_1_0_finder; __editable___mypkg_1_0_finder.install()\n",
+                        ),
+                        TestPath::file_with_contents("__editable___mypkg_1_0_finder.py", &finder),
+                    ],
+                ),
+                TestPath::dir(
+                    "src",
+                    vec![
+                        TestPath::dir("mypkg", vec![TestPath::file("__init__.py")]),
+                        TestPath::dir("unmapped", vec![TestPath::file("__init__.py")]),
+                    ],
+                ),
+            ],
+        );
+
+        let mut config = get_config(ConfigSource::File("".into()));
+        config.python_environment.site_package_path = Some(vec![root.join("site_packages")]);
+        config.configure();
+        let find = |module: &str| {
+            find_import_filtered(
+                &config,
+                ModuleName::from_str(module),
+                None,
+                None,
+                &DirEntryCache::new(),
+                None,
+            )
+            .finding()
+        };
+
+        assert_eq!(
+            find("mypkg"),
+            Some(ModulePath::filesystem(root.join("src/mypkg/__init__.py")))
+        );
+        // Only the packages in the mapping are importable, not everything beside them.
+        assert_eq!(find("unmapped"), None);
+    }
 }
@@ -22,6 +22,7 @@ use pyrefly_build::handle::Handle;
 use pyrefly_python::ast::Ast;
 use pyrefly_python::dunder;
 use pyrefly_python::module::Module;
+use pyrefly_python::module::TextRangeWithModule;
 use pyrefly_python::module_name::ModuleName;
 use pyrefly_python::module_name::ModuleNameWithKind;
 use pyrefly_python::module_path::ModulePath;
@@ -90,6 +91,7 @@ use crate::module::module_info::ModuleInfo;
 use crate::state::load::FileContents;
 use crate::state::lsp::DefinitionMetadata;
 use crate::state::lsp::FindPreference;
+use crate::state::lsp::ReferenceOptions;
 use crate::state::require::Require;
 use crate::state::state::State;
 use crate::state::state::Transaction;
@@ -1062,6 +1064,12 @@ impl Query {
         }
     }
 
+    /// Use `sys_info` for the handles of queried files, instead of the default.
+    pub fn with_sys_info(mut self, sys_info: SysInfo) -> Self {
+        self.sys_info = sys_info;
+        self
+    }
+
     fn make_handle(&self, name: ModuleName, path: ModulePath) -> Handle {
         let config = self
             .state
@@ -1250,6 +1258,80 @@ impl Query {
         }
     }
 
+    /// The type of the expression or name at `position`.
+    pub fn get_type_at(
+        &self,
+        name: ModuleName,
+        path: ModulePath,
+        position: TextSize,
+    ) -> Option<String> {
+        let transaction = self.state.transaction();
+        let handle = self.make_handle(name, path);
+        let ty = transaction.get_type_at(&handle, position)?;
+        Some(type_to_string(&ty))
+    }
+
+    /// The definitions of the symbol at `position`.
+    pub fn get_definitions(
+        &self,
+        name: ModuleName,
+        path: ModulePath,
+        position: TextSize,
+    ) -> Vec<TextRangeWithModule> {
+        let transaction = self.state.transaction();
+        let handle = self.make_handle(name, path);
+        transaction
+            .goto_definition(&handle, position)
+            .unwrap_or_default()
+    }
+
+    /// The references to the symbol at `position` in all the files that have been added,
+    /// including its definitions.
+    pub fn get_references(
+        &self,
+        name: ModuleName,
+        path: ModulePath,
+        position: TextSize,
+    ) -> Vec<TextRangeWithModule> {
+        let mut transaction = self.state.transaction();
+        let handle = self.make_handle(name, path);
+        let definitions = transaction
+            .find_definition(&handle, position, FindPreference::default())
+            .map(Vec1::into_vec)
+            .unwrap_or_default();
+        let mut res: Vec<TextRangeWithModule> = Vec::new();
+        for definition in definitions {
+            let references = transaction
+                .find_global_references_from_definition(
+                    self.sys_info.dupe(),
+                    definition.metadata,
+                    TextRangeWithModule::new(definition.module, definition.definition_range),
+                    ReferenceOptions::all(true),
+                )
+                .unwrap_or_default();
+            for (module, ranges) in references {
+                for range in ranges {
+                    if !res
+                        .iter()
+                        .any(|r| r.module.path() == module.path() && r.range == range)
+                    {
+                        res.push(TextRangeWithModule::new(module.dupe(), range));
+                    }
+                }
+            }
+        }
+        res
+    }
+
+    /// The type of every expression in a file, displayed as in the other queries.
+    pub fn get_types_in_file(
+        &self,
+        name: ModuleName,
+        path: ModulePath,
+    ) -> Option<Vec<(PythonASTRange, String)>> {
+        self.get_types_in_file_transformed(name, path, None, |_, ty| type_to_string(ty))
+    }
+
     // fetches information about callees of a callable in a module
     pub fn get_callees_with_location(
         &self,
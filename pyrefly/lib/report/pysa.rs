@@ -455,7 +455,9 @@ fn build_module_mapping(
         let module_name = handle.module();
         let module_path = handle.path();
         let relative_source_path = match module_path.details() {
-            ModulePathDetails::FileSystem(path) | ModulePathDetails::Namespace(path) => module_path
+            ModulePathDetails::FileSystem(path)
+            | ModulePathDetails::Namespace(path)
+            | ModulePathDetails::Archive(path) => module_path
                 .root_of(module_name)
                 .and_then(|root| path.as_path().strip_prefix(root).ok())
                 .map(|path| path.to_path_buf()),
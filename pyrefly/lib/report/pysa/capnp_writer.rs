@@ -57,7 +57,10 @@ use crate::report::pysa::types::TypeModifier;
 fn set_source_path(mut builder: pysa_report_capnp::source_path::Builder, path: &ModulePathDetails) {
     let path_str = |p: &std::path::PathBuf| p.to_string_lossy().into_owned();
     match path {
-        ModulePathDetails::FileSystem(p) => builder.set_file_system(path_str(p)),
+        // Archive entries have no dedicated variant; their path runs through the archive.
+        ModulePathDetails::FileSystem(p) | ModulePathDetails::Archive(p) => {
+            builder.set_file_system(path_str(p))
+        }
         ModulePathDetails::Namespace(p) => builder.set_namespace(path_str(p)),
         ModulePathDetails::Memory(p) => builder.set_memory(path_str(p)),
         ModulePathDetails::BundledTypeshed(p) => builder.set_bundled_typeshed(path_str(p)),
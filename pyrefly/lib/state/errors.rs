@@ -45,7 +45,7 @@ use crate::error::collector::CollectedErrors;
 use crate::error::error::BaselineStatus;
 use crate::error::error::Error;
 use crate::error::expectation::Expectation;
-use crate::error::legacy::BaselineError;
+use crate::error::legacy::BaselineErrors;
 use crate::error::style::ErrorStyle;
 use crate::state::load::Load;
 
@@ -290,7 +290,7 @@ pub enum BaselineApplyResult {
     /// When `classify_stale_entries` is false, `unused=0` and `retained=[]`.
     Applied {
         unused_entry_count: usize,
-        retained_entries: Vec<BaselineError>,
+        retained_entries: BaselineErrors,
     },
 }
 
@@ -303,10 +303,12 @@ impl BaselineApplyResult {
     pub fn resolve(
         self,
         tolerate_read_error: bool,
-    ) -> anyhow::Result<(BaselineStatus, usize, Vec<BaselineError>)> {
+    ) -> anyhow::Result<(BaselineStatus, usize, BaselineErrors)> {
         match self {
-            Self::NotConfigured => Ok((BaselineStatus::NotConfigured, 0, Vec::new())),
-            Self::NotFound => Ok((BaselineStatus::NotCompared, 0, Vec::new())),
+            Self::NotConfigured => {
+                Ok((BaselineStatus::NotConfigured, 0, BaselineErrors::default()))
+            }
+            Self::NotFound => Ok((BaselineStatus::NotCompared, 0, BaselineErrors::default())),
             Self::Applied {
                 unused_entry_count,
                 retained_entries,
@@ -321,13 +323,71 @@ impl BaselineApplyResult {
                 eprintln!(
                     "Ignoring unreadable baseline while regenerating it with `--update-baseline`: {e:#}"
                 );
-                Ok((BaselineStatus::NotCompared, 0, Vec::new()))
+                Ok((BaselineStatus::NotCompared, 0, BaselineErrors::default()))
             }
             Self::FailedToRead(e) => Err(e),
         }
     }
 }
 
+/// Apply baseline filtering to already-collected errors in place.
+/// `relative_to` resolves relative paths stored in the baseline file.
+/// A missing file is mapped to `NotFound` (benign), not `FailedToRead`,
+/// so there is no TOCTOU race between `exists()` and reading the file.
+/// When `checked_paths` is given, baseline entries for those paths that matched
+/// nothing are classified as unused.
+pub fn apply_baseline_file(
+    errors: &mut CollectedErrors,
+    baseline_path: Option<&Path>,
+    relative_to: &Path,
+    checked_paths: Option<&HashSet<String>>,
+) -> BaselineApplyResult {
+    let Some(baseline_path) = baseline_path else {
+        return BaselineApplyResult::NotConfigured;
+    };
+
+    let fail_ctx = || format!("failed to read baseline file `{}`", baseline_path.display());
+
+    // Read via `std::fs` rather than `fs_anyhow` so that a missing baseline is
+    // identified by the `ErrorKind` of this exact call, rather than by searching
+    // an `anyhow` chain where an unrelated `NotFound` could be mistaken for it.
+    let content = match fs::read_to_string(baseline_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return BaselineApplyResult::NotFound;
+        }
+        Err(e) => {
+            return BaselineApplyResult::FailedToRead(anyhow::Error::new(e).context(fail_ctx()));
+        }
+    };
+
+    if let Some(checked_paths) = checked_paths {
+        let mut processor = match TrackedBaselineProcessor::from_json(&content, relative_to)
+            .with_context(fail_ctx)
+        {
+            Ok(p) => p,
+            Err(e) => return BaselineApplyResult::FailedToRead(e),
+        };
+        processor.process_errors(&mut errors.ordinary, &mut errors.baseline);
+        let result = processor.into_pruning_result(checked_paths);
+        BaselineApplyResult::Applied {
+            unused_entry_count: result.unused_entry_count,
+            retained_entries: result.retained_entries,
+        }
+    } else {
+        let mut processor =
+            match BaselineProcessor::from_json(&content, relative_to).with_context(fail_ctx) {
+                Ok(p) => p,
+                Err(e) => return BaselineApplyResult::FailedToRead(e),
+            };
+        processor.process_errors(&mut errors.ordinary, &mut errors.baseline);
+        BaselineApplyResult::Applied {
+            unused_entry_count: 0,
+            retained_entries: BaselineErrors::default(),
+        }
+    }
+}
+
 impl Errors {
     pub fn new(mut loads: Vec<(Arc<Load>, Option<Arc<ModuleRanges>>, ArcId<ConfigFile>)>) -> Self {
         loads.sort_by_key(|x| (x.0.module_info.name(), x.0.module_info.path().dupe()));
@@ -368,10 +428,9 @@ impl Errors {
         errors
     }
 
-    /// Apply baseline filtering to already-collected errors in place.
-    /// `relative_to` resolves relative paths stored in the baseline file.
-    /// A missing file is mapped to `NotFound` (benign), not `FailedToRead`,
-    /// so there is no TOCTOU race between `exists()` and reading the file.
+    /// Apply baseline filtering to already-collected errors in place, as
+    /// `apply_baseline_file` does. When `classify_stale_entries` is set, baseline
+    /// entries for the loaded modules that matched nothing are counted as unused.
     pub fn apply_baseline(
         &self,
         errors: &mut CollectedErrors,
@@ -379,60 +438,16 @@ impl Errors {
         relative_to: &Path,
         classify_stale_entries: bool,
     ) -> BaselineApplyResult {
-        let Some(baseline_path) = baseline_path else {
-            return BaselineApplyResult::NotConfigured;
-        };
-
-        let fail_ctx = || format!("failed to read baseline file `{}`", baseline_path.display());
-
-        // Read via `std::fs` rather than `fs_anyhow` so that a missing baseline is
-        // identified by the `ErrorKind` of this exact call, rather than by searching
-        // an `anyhow` chain where an unrelated `NotFound` could be mistaken for it.
-        let content = match fs::read_to_string(baseline_path) {
-            Ok(content) => content,
-            Err(e) if e.kind() == io::ErrorKind::NotFound => {
-                return BaselineApplyResult::NotFound;
-            }
-            Err(e) => {
-                return BaselineApplyResult::FailedToRead(
-                    anyhow::Error::new(e).context(fail_ctx()),
-                );
-            }
-        };
-
-        if classify_stale_entries {
-            let mut processor = match TrackedBaselineProcessor::from_json(&content, relative_to)
-                .with_context(fail_ctx)
-            {
-                Ok(p) => p,
-                Err(e) => return BaselineApplyResult::FailedToRead(e),
-            };
-            processor.process_errors(&mut errors.ordinary, &mut errors.baseline);
-            let checked_paths: HashSet<_> = self
-                .loads
+        let checked_paths = classify_stale_entries.then(|| {
+            self.loads
                 .iter()
                 .filter(|(load, _, _)| load.errors.style() != ErrorStyle::Never)
                 .map(|(load, _, _)| {
                     normalize_baseline_path(load.module_info.path().as_path(), relative_to)
                 })
-                .collect();
-            let result = processor.into_pruning_result(&checked_paths);
-            BaselineApplyResult::Applied {
-                unused_entry_count: result.unused_entry_count,
-                retained_entries: result.retained_entries,
-            }
-        } else {
-            let processor =
-                match BaselineProcessor::from_json(&content, relative_to).with_context(fail_ctx) {
-                    Ok(p) => p,
-                    Err(e) => return BaselineApplyResult::FailedToRead(e),
-                };
-            processor.process_errors(&mut errors.ordinary, &mut errors.baseline);
-            BaselineApplyResult::Applied {
-                unused_entry_count: 0,
-                retained_entries: Vec::new(),
-            }
-        }
+                .collect::<HashSet<_>>()
+        });
+        apply_baseline_file(errors, baseline_path, relative_to, checked_paths.as_ref())
     }
 
     /// Collect display errors for the language server, partitioned by whether or not they
@@ -472,7 +487,7 @@ impl Errors {
                 BaselineProcessor::from_json(&content, relative_to).ok()
             });
             if processor
-                .as_ref()
+                .as_mut()
                 .is_some_and(|processor| processor.matches_baseline(&error))
             {
                 errors.baseline.push(error);
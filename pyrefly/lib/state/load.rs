@@ -24,6 +24,7 @@ use ruff_text_size::TextRange;
 use crate::config::error_kind::ErrorKind;
 use crate::error::collector::ErrorCollector;
 use crate::error::style::ErrorStyle;
+use crate::module::archive::read_archive_entry;
 use crate::module::bundled::BundledStub;
 use crate::module::third_party::get_bundled_third_party;
 use crate::module::typeshed::typeshed;
@@ -114,6 +115,9 @@ impl Load {
                     .ok_or_else(|| anyhow!("bundled third party problem"))
                     .map(FileContents::Source)
             }),
+            ModulePathDetails::Archive(path) => {
+                read_archive_entry(path).map(FileContents::from_source)
+            }
         };
         match res {
             Err(err) => (FileContents::from_source(String::new()), Some(err)),
@@ -23,10 +23,10 @@ use crate::config::config::ConfigFile;
 use crate::config::config::ConfigSource;
 use crate::config::config::FallbackSearchPath;
 use crate::config::config::ImportLookupPathPart;
+use crate::config::environment::editable::EditableRoots;
 use crate::error::context::ErrorContext;
 use crate::module::finder::DirEntryCache;
-use crate::module::finder::find_import;
-use crate::module::finder::find_import_filtered;
+use crate::module::finder::find_import_internal;
 use crate::module::finder::suggest_stdlib_import;
 use crate::state::state::TransactionTimingCounters;
 
@@ -45,6 +45,9 @@ pub enum FindError {
     UntypedImport(ModuleName, Arc<String>),
     /// This is the condition where we are using stubs but we do not have the source files
     MissingSourceForStubs(ModuleName),
+    /// A PEP 723 script imports this third-party module, but its metadata doesn't list
+    /// a dependency that provides it.
+    UnlistedScriptDependency(ModuleName),
 }
 
 impl FindError {
@@ -123,6 +126,14 @@ impl FindError {
                 Some(Box::new(|| ErrorContext::ImportNotTyped(*source_package))),
                 vec1![format!("Hint: install the `{stubs_package}` package")],
             ),
+            Self::UnlistedScriptDependency(module) => (
+                None,
+                vec1![format!(
+                    "`{}` is not a declared dependency of this script. Add the distribution \
+                    that provides it to the script's `dependencies`.",
+                    module.first_component()
+                )],
+            ),
         }
     }
 
@@ -132,6 +143,7 @@ impl FindError {
             Self::MissingSource(..) => Some(ErrorKind::MissingSource),
             Self::MissingSourceForStubs(..) => Some(ErrorKind::MissingSourceForStubs),
             Self::UntypedImport(..) => Some(ErrorKind::UntypedImport),
+            Self::UnlistedScriptDependency(..) => Some(ErrorKind::UnlistedScriptDependency),
             Self::Ignored => None,
         }
     }
@@ -210,10 +222,11 @@ pub struct LoaderFindCache {
     // If a python executable module (excludes .pyi) exists and differs from the imported python module, store it here
     executable_cache: LockedMap<(ModuleName, Option<ModulePath>), Option<ModulePath>>,
     dir_cache: DirEntryCache,
+    editable_roots: Arc<EditableRoots>,
 }
 
 impl LoaderFindCache {
-    pub fn new(config: ArcId<ConfigFile>) -> Self {
+    pub fn new(config: ArcId<ConfigFile>, editable_roots: Arc<EditableRoots>) -> Self {
         // When no config feature uses origin, all import resolutions produce
         // the same result regardless of which file is importing. We can then
         // cache by ModuleName alone, reducing millions of cache entries
@@ -230,9 +243,29 @@ impl LoaderFindCache {
             cache: Default::default(),
             executable_cache: Default::default(),
             dir_cache: DirEntryCache::new(),
+            editable_roots,
         }
     }
 
+    fn find_import_uncached(
+        &self,
+        module: ModuleName,
+        origin: Option<&ModulePath>,
+        style_filter: Option<ModuleStyle>,
+        timing: Option<&TransactionTimingCounters>,
+    ) -> FindingOrError<ModulePath> {
+        find_import_internal(
+            &self.config,
+            module,
+            origin,
+            style_filter,
+            &mut None,
+            &self.dir_cache,
+            &self.editable_roots,
+            timing,
+        )
+    }
+
     pub fn find_import_prefer_executable(
         &self,
         module: ModuleName,
@@ -244,12 +277,10 @@ impl LoaderFindCache {
             Some(Some(module)) => FindingOrError::new_finding(module.dupe()),
             Some(None) => self.find_import(module, origin, timing),
             None => {
-                match find_import_filtered(
-                    &self.config,
+                match self.find_import_uncached(
                     module,
                     origin,
                     Some(ModuleStyle::Executable),
-                    &self.dir_cache,
                     timing,
                 ) {
                     FindingOrError::Finding(import) => {
@@ -293,8 +324,7 @@ impl LoaderFindCache {
             .cache
             .ensure(&(module.dupe(), effective_origin.clone()), || {
                 let phantom_paths = Vec::new();
-                let result =
-                    find_import(&self.config, module, origin, None, &self.dir_cache, timing);
+                let result = self.find_import_uncached(module, origin, None, timing);
                 (result, Arc::new(phantom_paths))
             })
             .0
@@ -350,8 +380,7 @@ impl LoaderFindCache {
             .cache
             .ensure(&(module.dupe(), origin.cloned()), || {
                 let phantom_paths = Vec::new();
-                let result =
-                    find_import(&self.config, module, origin, None, &self.dir_cache, timing);
+                let result = self.find_import_uncached(module, origin, None, timing);
                 (result, Arc::new(phantom_paths))
             })
             .0;
@@ -384,7 +413,7 @@ mod tests {
         config.source_db = Some(ArcId::new(Box::new(sourcedb)));
         config.configure();
 
-        let loader = LoaderFindCache::new(ArcId::new(config));
+        let loader = LoaderFindCache::new(ArcId::new(config), Arc::new(EditableRoots::default()));
         let origin_a = ModulePath::memory(PathBuf::from("a.py"));
         let origin_b = ModulePath::memory(PathBuf::from("b.py"));
 
@@ -54,6 +54,7 @@ use ruff_python_ast::ExprName;
 use ruff_python_ast::Identifier;
 use ruff_python_ast::Keyword;
 use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
 use ruff_python_ast::StmtImportFrom;
 use ruff_python_ast::UnaryOp;
 use ruff_python_ast::name::Name;
@@ -74,6 +75,10 @@ use crate::alt::attr::AttrInfo;
 use crate::binding::binding::Binding;
 use crate::binding::binding::Key;
 use crate::config::error_kind::ErrorKind;
+use crate::config::organize_imports::OrganizeImportsSettings;
+use crate::error::error::Error;
+use crate::error::error::ErrorQuickFix;
+use crate::error::error::QuickFixEdit;
 use crate::error::suppress::detect_line_ending;
 use crate::export::exports::Export;
 use crate::export::exports::ExportLocation;
@@ -98,11 +103,15 @@ use crate::types::types::Type;
 
 mod dict_completions;
 mod extra_extensions;
+mod override_completions;
 mod pytest;
 mod quick_fixes;
 
 pub(crate) use self::quick_fixes::move_module::MoveModuleMemberContext;
+use self::quick_fixes::organize_imports::ImportSection;
+pub(crate) use self::quick_fixes::types::FixKind;
 pub(crate) use self::quick_fixes::types::LocalRefactorCodeAction;
+pub(crate) use self::quick_fixes::types::MachineFix;
 
 #[derive(Debug)]
 pub(crate) enum CalleeKind {
@@ -825,6 +834,7 @@ impl<'a> Transaction<'a> {
             ModulePathDetails::FileSystem(_)
                 | ModulePathDetails::Namespace(_)
                 | ModulePathDetails::Memory(_)
+                | ModulePathDetails::Archive(_)
         )
     }
 
@@ -3590,6 +3600,262 @@ impl<'a> Transaction<'a> {
         }
     }
 
+    /// Edits for the `source.organizeImports` action: remove the imports of `handle` that the
+    /// checker found unused and are not intentional, then sort, group and merge the rest.
+    pub fn organize_imports_edits(
+        &self,
+        handle: &Handle,
+        settings: &OrganizeImportsSettings,
+    ) -> Option<Vec<(Module, TextRange, String)>> {
+        let module_info = self.get_module_info(handle)?;
+        // Edits to notebooks are made per cell, but a run of imports may span cells.
+        if module_info.is_notebook() {
+            return None;
+        }
+        let ast = self.get_ast(handle)?;
+        let bindings = self.get_bindings(handle)?;
+        let line_ending = detect_line_ending(module_info.contents().as_str());
+        let (range, text) = quick_fixes::organize_imports::organize_imports_edit(
+            &module_info,
+            &ast,
+            bindings.unused_imports(),
+            settings,
+            &|module| self.import_section(handle, module),
+        )?;
+        Some(vec![(
+            module_info.dupe(),
+            range,
+            // The unchanged lines in the edit already have the file's line endings.
+            text.replace("\r\n", "\n").replace('\n', line_ending),
+        )])
+    }
+
+    /// The section of an absolute import, from where its top-level package resolves.
+    fn import_section(&self, handle: &Handle, module: ModuleName) -> ImportSection {
+        let package = ModuleName::from_name(&module.first_component());
+        let Some(target) = self.import_handle(handle, package, None).finding() else {
+            return ImportSection::ThirdParty;
+        };
+        let path = target.path();
+        match path.details() {
+            ModulePathDetails::BundledTypeshed(_) => return ImportSection::Stdlib,
+            ModulePathDetails::BundledTypeshedThirdParty(_)
+            | ModulePathDetails::BundledThirdParty(_)
+            | ModulePathDetails::Archive(_) => return ImportSection::ThirdParty,
+            ModulePathDetails::FileSystem(_)
+            | ModulePathDetails::Namespace(_)
+            | ModulePathDetails::Memory(_) => {}
+        }
+        let Some(config) = self.get_config(handle) else {
+            return ImportSection::FirstParty;
+        };
+        if config
+            .typeshed_stdlib_path()
+            .is_some_and(|stdlib| path.as_path().starts_with(stdlib))
+        {
+            ImportSection::Stdlib
+        } else if config
+            .site_package_path()
+            .any(|site_package| path.as_path().starts_with(site_package))
+        {
+            ImportSection::ThirdParty
+        } else {
+            ImportSection::FirstParty
+        }
+    }
+
+    /// Every quick fix in the file that has a single obvious edit, restricted to `kinds`.
+    /// Unused imports that may be intentional are kept, and the other fixes are only offered
+    /// for errors that are enabled. Star imports are expanded, and errors without any other fix
+    /// can be suppressed with `PyreflyIgnore`. Fixes may overlap each other.
+    pub fn machine_applicable_fixes(
+        &self,
+        handle: &Handle,
+        kinds: &[FixKind],
+        import_format: ImportFormat,
+    ) -> Vec<MachineFix> {
+        let (Some(module_info), Some(ast)) = (self.get_module_info(handle), self.get_ast(handle))
+        else {
+            return Vec::new();
+        };
+        fn single(
+            kind: FixKind,
+            (title, module, range, insert_text): (String, Module, TextRange, String),
+        ) -> MachineFix {
+            MachineFix {
+                kind,
+                title,
+                edits: vec![(module, range, insert_text)],
+            }
+        }
+
+        let mut fixes = Vec::new();
+        if kinds.contains(&FixKind::UnusedImport)
+            && let Some(bindings) = self.get_bindings(handle)
+        {
+            for unused in bindings.unused_imports() {
+                if !quick_fixes::unused_import::may_be_intentional(&module_info, &ast, unused)
+                    && let Some(action) =
+                        quick_fixes::unused_import::remove_unused_import_code_action(
+                            &module_info,
+                            &ast,
+                            unused,
+                        )
+                {
+                    fixes.push(single(FixKind::UnusedImport, action));
+                }
+            }
+        }
+        if kinds.contains(&FixKind::StarImport) {
+            for stmt in &ast.body {
+                let Stmt::ImportFrom(import_from) = stmt else {
+                    continue;
+                };
+                if !import_from.names.iter().any(|alias| &alias.name == "*") {
+                    continue;
+                }
+                let actions = quick_fixes::convert_star_import::convert_star_import_code_actions(
+                    self,
+                    handle,
+                    TextRange::empty(import_from.range().start()),
+                );
+                fixes.extend(actions.into_iter().flatten().map(|action| MachineFix {
+                    kind: FixKind::StarImport,
+                    title: action.title,
+                    edits: action.edits,
+                }));
+            }
+        }
+        for error in self.get_errors(vec![handle]).collect_errors().ordinary {
+            match self.machine_applicable_fix_for_error(
+                handle,
+                &module_info,
+                &ast,
+                &error,
+                kinds,
+                import_format,
+            ) {
+                Some(fix) => fixes.push(fix),
+                // Only errors that cannot be fixed are suppressed.
+                None if kinds.contains(&FixKind::PyreflyIgnore) => fixes.extend(
+                    quick_fixes::pyrefly_ignore::add_pyrefly_ignore_code_action(
+                        &module_info,
+                        &error,
+                    )
+                    .map(|action| single(FixKind::PyreflyIgnore, action)),
+                ),
+                None => {}
+            }
+        }
+        let line_ending = detect_line_ending(module_info.contents().as_str());
+        if line_ending != "\n" {
+            for fix in &mut fixes {
+                for (_, _, insert_text) in &mut fix.edits {
+                    *insert_text = insert_text.replace('\n', line_ending);
+                }
+            }
+        }
+        fixes
+    }
+
+    /// The machine-applicable fix for an `error` in `handle`, if it has one of `kinds`.
+    fn machine_applicable_fix_for_error(
+        &self,
+        handle: &Handle,
+        module_info: &Module,
+        ast: &ModModule,
+        error: &Error,
+        kinds: &[FixKind],
+        import_format: ImportFormat,
+    ) -> Option<MachineFix> {
+        let (kind, (title, module, range, insert_text)) = match error.error_kind() {
+            ErrorKind::RedundantCast if kinds.contains(&FixKind::RedundantCast) => (
+                FixKind::RedundantCast,
+                quick_fixes::redundant_cast::redundant_cast_code_action(
+                    module_info,
+                    ast,
+                    error.range(),
+                )?,
+            ),
+            ErrorKind::UnnecessaryTypeConversion
+                if kinds.contains(&FixKind::UnnecessaryTypeConversion) =>
+            {
+                (
+                    FixKind::UnnecessaryTypeConversion,
+                    quick_fixes::unnecessary_type_conversion::unnecessary_type_conversion_code_action(
+                        module_info,
+                        ast,
+                        error.range(),
+                    )?,
+                )
+            }
+            ErrorKind::MissingOverrideDecorator if kinds.contains(&FixKind::AddOverride) => (
+                FixKind::AddOverride,
+                quick_fixes::add_override::add_override_code_action(
+                    module_info,
+                    ast,
+                    error.range(),
+                )?,
+            ),
+            _ => return None,
+        };
+        let mut edits = vec![(module, range, insert_text)];
+        if kind == FixKind::AddOverride && !quick_fixes::add_override::override_in_scope(ast) {
+            // Without the import, the fix would introduce an error.
+            edits.push(self.override_import_edit(handle, module_info, ast, import_format, None)?);
+        }
+        Some(MachineFix { kind, title, edits })
+    }
+
+    /// Attach the machine-applicable quick fixes for each of `errors`, which all belong to
+    /// `handle`, so reports outside the IDE can include them. Suppressing the error with a
+    /// `# pyrefly: ignore` comment is not included, since it is not a fix.
+    pub fn with_report_quick_fixes(
+        &self,
+        handle: &Handle,
+        errors: Vec<Error>,
+        import_format: ImportFormat,
+    ) -> Vec<Error> {
+        let Some(module_info) = errors.first().map(|error| error.module().dupe()) else {
+            return errors;
+        };
+        // Edits to notebooks are relative to the combined cells, not to the file.
+        if module_info.is_notebook() {
+            return errors;
+        }
+        // The AST is usually not retained after a command-line check.
+        let ast = self.get_ast(handle).unwrap_or_else(|| {
+            Arc::new(Ast::parse(module_info.contents(), module_info.source_type()).0)
+        });
+        let kinds = enum_iterator::all::<FixKind>().collect::<Vec<_>>();
+        let line_ending = detect_line_ending(module_info.contents().as_str());
+        errors
+            .into_iter()
+            .map(|error| {
+                let Some(fix) = self.machine_applicable_fix_for_error(
+                    handle,
+                    &module_info,
+                    &ast,
+                    &error,
+                    &kinds,
+                    import_format,
+                ) else {
+                    return error;
+                };
+                error.with_quick_fix(ErrorQuickFix::Edits {
+                    title: fix.title,
+                    edits: fix
+                        .edits
+                        .into_iter()
+                        .map(|(_, range, text)| {
+                            QuickFixEdit::new(range, text.replace('\n', line_ending))
+                        })
+                        .collect(),
+                })
+            })
+            .collect()
+    }
+
     pub fn pytest_fixture_type_annotation_code_actions(
         &self,
         handle: &Handle,
@@ -3698,6 +3964,14 @@ impl<'a> Transaction<'a> {
         )
     }
 
+    pub(crate) fn module_member_insertion_edit(
+        &self,
+        target_handle: &Handle,
+        member_text: &str,
+    ) -> Option<(ModuleInfo, TextRange, String)> {
+        quick_fixes::move_module::module_member_insertion_edit(self, target_handle, member_text)
+    }
+
     pub fn make_local_function_top_level_code_actions(
         &self,
         handle: &Handle,
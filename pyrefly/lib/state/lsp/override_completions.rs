@@ -0,0 +1,528 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use dupe::Dupe;
+use lsp_types::CompletionItem;
+use lsp_types::CompletionItemKind;
+use lsp_types::CompletionTextEdit;
+use lsp_types::InsertTextMode;
+use lsp_types::TextEdit;
+use pyrefly_build::handle::Handle;
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module::Module;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::module_path::ModulePath;
+use pyrefly_python::short_identifier::ShortIdentifier;
+use ruff_python_ast::AnyNodeRef;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Parameter;
+use ruff_python_ast::ParameterWithDefault;
+use ruff_python_ast::Stmt;
+use ruff_python_ast::StmtClassDef;
+use ruff_python_ast::StmtFunctionDef;
+use ruff_python_ast::name::Name;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use crate::binding::binding::Key;
+use crate::state::ide::insert_import_edit;
+use crate::state::lsp::ImportFormat;
+use crate::state::lsp::quick_fixes::add_override::override_in_scope;
+use crate::state::state::Transaction;
+use crate::types::callable::Callable;
+use crate::types::callable::Param;
+use crate::types::callable::Params;
+use crate::types::callable::Required;
+use crate::types::class::Class;
+use crate::types::stdlib::Stdlib;
+use crate::types::type_output::AnnotationPart;
+use crate::types::types::Type;
+
+/// Decorators that change how a method is called, and so are repeated on the override.
+const COPIED_DECORATORS: &[&str] = &["classmethod", "staticmethod", "property", "cached_property"];
+
+/// Decorators that mean a method cannot, or should not, be overridden by a plain `def`.
+const SKIPPED_DECORATORS: &[&str] = &["final", "overload", "setter", "deleter"];
+
+fn decorator_names(function_def: &StmtFunctionDef) -> impl Iterator<Item = &str> {
+    function_def
+        .decorator_list
+        .iter()
+        .filter_map(|d| Ast::decorator_trailing_name(&d.expression))
+}
+
+/// An inherited method that the class does not define yet.
+struct Candidate {
+    owner: Class,
+    name: Name,
+    /// The range of the method's name in the owner's module.
+    range: TextRange,
+    /// The method's signature, with the owner's type parameters replaced by the arguments the
+    /// class passes them. `None` if the method is not a plain function, e.g. when a decorator
+    /// wraps it in another object.
+    signature: Option<Callable>,
+    /// Whether the method is abstract or a protocol member with no body, so cannot be called
+    /// through `super()`.
+    lacks_implementation: bool,
+}
+
+/// An inherited method, rendered as a complete overriding definition.
+struct Override {
+    owner: Name,
+    /// The decorators, signature and body, with every line after the first indented by `indent`.
+    text: String,
+    /// The names the annotations refer to, with the module each is imported from.
+    imports: Vec<(ModuleName, String)>,
+}
+
+/// Renders types as annotations for the class's module, recording the names they refer to.
+struct Annotations<'a> {
+    stdlib: &'a Stdlib,
+    imports: Vec<(ModuleName, String)>,
+}
+
+impl Annotations<'_> {
+    fn render(&mut self, ty: &Type) -> String {
+        ty.get_annotation_parts(Some(self.stdlib))
+            .into_iter()
+            .map(|part| match part {
+                AnnotationPart::Text(text) => text,
+                AnnotationPart::Reference { module, name } => {
+                    // A nested class is reached through the outermost class.
+                    let import = (module, name.split('.').next().unwrap_or(&name).to_owned());
+                    if !self.imports.contains(&import) {
+                        self.imports.push(import);
+                    }
+                    name
+                }
+            })
+            .collect()
+    }
+
+    /// `p`, annotated with `ty` if the base method annotates it.
+    fn param(&mut self, p: &Parameter, ty: Option<&Type>) -> String {
+        match ty {
+            Some(ty) if p.annotation.is_some() => format!("{}: {}", p.name, self.render(ty)),
+            _ => p.name.to_string(),
+        }
+    }
+}
+
+/// The default of `p`: literals as written in the base method, and otherwise as recovered
+/// from its type, since other expressions may refer to names only the base module has.
+fn render_default(module: &Module, p: &ParameterWithDefault, typed: Option<&Param>) -> String {
+    match (&p.default, typed) {
+        (Some(default), _) if default.is_literal_expr() => {
+            module.code_at(default.range()).to_owned()
+        }
+        (
+            _,
+            Some(
+                typed @ (Param::PosOnly(_, _, Required::Optional(default))
+                | Param::Pos(_, _, Required::Optional(default))
+                | Param::KwOnly(_, _, Required::Optional(default))),
+            ),
+        ) => typed.fmt_default(default),
+        _ => "...".to_owned(),
+    }
+}
+
+/// `p`, with its annotation and default rendered from `typed`, its solved form.
+fn render_param(
+    module: &Module,
+    p: &ParameterWithDefault,
+    typed: Option<&Param>,
+    annotations: &mut Annotations,
+) -> String {
+    let param = annotations.param(&p.parameter, typed.map(Param::as_type));
+    if p.default.is_none() {
+        return param;
+    }
+    let default = render_default(module, p, typed);
+    if p.parameter.annotation.is_some() {
+        format!("{param} = {default}")
+    } else {
+        format!("{param}={default}")
+    }
+}
+
+/// Renders the override of `base_def`, defined in `module`, for the class named `class_name`.
+fn render_override(
+    module: &Module,
+    base_def: &StmtFunctionDef,
+    candidate: &Candidate,
+    class_name: &str,
+    indent: &str,
+    annotations: &mut Annotations,
+) -> String {
+    let decorators = decorator_names(base_def).collect::<Vec<_>>();
+    let is_staticmethod = decorators.contains(&"staticmethod");
+    let is_property = decorators
+        .iter()
+        .any(|d| matches!(*d, "property" | "cached_property"));
+    let name = base_def.name.as_str();
+    // `__new__` is an implicit staticmethod that still takes `cls` explicitly.
+    let passes_first = is_staticmethod || name == "__new__";
+
+    let parameters = &base_def.parameters;
+    // The solved parameters, when they line up with the ones written in the base method.
+    let typed = candidate
+        .signature
+        .as_ref()
+        .and_then(|signature| match &signature.params {
+            Params::List(params) if params.len() == parameters.len() => Some(params.items()),
+            _ => None,
+        });
+    let return_type = typed.and(candidate.signature.as_ref()).map(|x| &x.ret);
+    let mut typed = typed.into_iter().flatten();
+    let mut params = Vec::new();
+    let mut args = Vec::new();
+    for (i, p) in parameters
+        .posonlyargs
+        .iter()
+        .chain(&parameters.args)
+        .enumerate()
+    {
+        params.push(render_param(module, p, typed.next(), annotations));
+        if i > 0 || passes_first {
+            args.push(p.parameter.name.to_string());
+        }
+        if i + 1 == parameters.posonlyargs.len() {
+            params.push("/".to_owned());
+        }
+    }
+    if let Some(vararg) = &parameters.vararg {
+        let ty = typed.next().map(Param::as_type);
+        params.push(format!("*{}", annotations.param(vararg, ty)));
+        args.push(format!("*{}", vararg.name));
+    } else if !parameters.kwonlyargs.is_empty() {
+        params.push("*".to_owned());
+    }
+    for p in &parameters.kwonlyargs {
+        params.push(render_param(module, p, typed.next(), annotations));
+        args.push(format!("{0}={0}", p.parameter.name));
+    }
+    if let Some(kwarg) = &parameters.kwarg {
+        let ty = typed.next().map(Param::as_type);
+        params.push(format!("**{}", annotations.param(kwarg, ty)));
+        args.push(format!("**{}", kwarg.name));
+    }
+
+    let mut text = String::new();
+    for decorator in &base_def.decorator_list {
+        if Ast::decorator_trailing_name(&decorator.expression)
+            .is_some_and(|d| COPIED_DECORATORS.contains(&d))
+        {
+            text.push_str(&format!(
+                "@{}\n{indent}",
+                module.code_at(decorator.expression.range())
+            ));
+        }
+    }
+    if base_def.is_async {
+        text.push_str("async ");
+    }
+    text.push_str(&format!("def {name}({})", params.join(", ")));
+    let returns = match (&base_def.returns, return_type) {
+        (Some(_), Some(ty)) => Some(annotations.render(ty)),
+        _ => None,
+    };
+    if let Some(returns) = &returns {
+        text.push_str(&format!(" -> {returns}"));
+    }
+    let body = if candidate.lacks_implementation {
+        "raise NotImplementedError".to_owned()
+    } else {
+        let target = if is_staticmethod {
+            format!("super({class_name}, {class_name})")
+        } else {
+            "super()".to_owned()
+        };
+        let mut call = format!("{target}.{name}");
+        if !is_property {
+            call.push_str(&format!("({})", args.join(", ")));
+        }
+        if base_def.is_async {
+            call = format!("await {call}");
+        }
+        if returns.as_deref() == Some("None") {
+            call
+        } else {
+            format!("return {call}")
+        }
+    };
+    text.push_str(&format!(":\n{indent}    {body}"));
+    text
+}
+
+/// Whether `ast` already has `from module import name`.
+fn imports_name(ast: &ModModule, module: ModuleName, name: &str) -> bool {
+    ast.body.iter().any(|stmt| match stmt {
+        Stmt::ImportFrom(import_from) => {
+            import_from.level == 0
+                && import_from
+                    .module
+                    .as_ref()
+                    .is_some_and(|x| x.id.as_str() == module.as_str())
+                && import_from.names.iter().any(|alias| {
+                    alias.name.id.as_str() == name
+                        && alias.asname.as_ref().is_none_or(|x| x.id.as_str() == name)
+                })
+        }
+        _ => false,
+    })
+}
+
+impl Transaction<'_> {
+    /// Completions for `def name` in the body of `class_def` that write out an override of
+    /// an inherited method: its signature, a call to the parent (or a placeholder when the
+    /// parent has no implementation), and `@override` when the target Python version has it.
+    pub(crate) fn override_completions(
+        &self,
+        handle: &Handle,
+        class_def: &StmtClassDef,
+        method_def: &StmtFunctionDef,
+        import_format: ImportFormat,
+    ) -> Vec<CompletionItem> {
+        self.override_completions_opt(handle, class_def, method_def, import_format)
+            .unwrap_or_default()
+    }
+
+    fn override_completions_opt(
+        &self,
+        handle: &Handle,
+        class_def: &StmtClassDef,
+        method_def: &StmtFunctionDef,
+        import_format: ImportFormat,
+    ) -> Option<Vec<CompletionItem>> {
+        let module_info = self.get_module_info(handle)?;
+        let ast = self.get_ast(handle)?;
+        let source = module_info.contents();
+        let identifier = &method_def.name;
+
+        // Only complete a bare `def name` (or `async def name`) with nothing after it.
+        let line_start = TextSize::try_from(
+            source[..identifier.start().to_usize()]
+                .rfind('\n')
+                .map_or(0, |i| i + 1),
+        )
+        .ok()?;
+        let line_end = source[identifier.end().to_usize()..]
+            .find('\n')
+            .map_or(source.len(), |i| identifier.end().to_usize() + i);
+        if !source[identifier.end().to_usize()..line_end]
+            .trim()
+            .is_empty()
+        {
+            return None;
+        }
+        let prefix = &source[line_start.to_usize()..identifier.start().to_usize()];
+        let indent = &prefix[..prefix.len() - prefix.trim_start().len()];
+        let keywords = prefix.split_whitespace().collect::<Vec<_>>();
+        if !matches!(keywords.as_slice(), ["def"] | ["async", "def"]) {
+            return None;
+        }
+        let edit_range = TextRange::new(line_start + TextSize::of(indent), identifier.end());
+
+        let Some(Type::ClassDef(cls)) = self.get_type(
+            handle,
+            &Key::Definition(ShortIdentifier::new(&class_def.name)),
+        ) else {
+            return None;
+        };
+        let candidates = self.override_candidates(handle, &cls, &identifier.id)?;
+
+        let stdlib = self.get_stdlib(handle);
+        let mut asts: HashMap<ModulePath, Arc<ModModule>> = HashMap::new();
+        let mut overrides = Vec::new();
+        for candidate in candidates {
+            let module = candidate.owner.module();
+            let owner_ast = asts
+                .entry(module.path().dupe())
+                .or_insert_with(|| self.get_ast_or_parse_module(handle, module));
+            let Some(base_def) = Ast::locate_node(owner_ast, candidate.range.start())
+                .into_iter()
+                .find_map(|node| match node {
+                    AnyNodeRef::StmtFunctionDef(x) if x.name.range == candidate.range => Some(x),
+                    _ => None,
+                })
+            else {
+                continue;
+            };
+            if decorator_names(base_def).any(|d| SKIPPED_DECORATORS.contains(&d)) {
+                continue;
+            }
+            let mut annotations = Annotations {
+                stdlib: &stdlib,
+                imports: Vec::new(),
+            };
+            let text = render_override(
+                module,
+                base_def,
+                &candidate,
+                class_def.name.as_str(),
+                indent,
+                &mut annotations,
+            );
+            overrides.push((
+                candidate.name,
+                Override {
+                    owner: candidate.owner.name().clone(),
+                    text,
+                    imports: annotations.imports,
+                },
+            ));
+        }
+        if overrides.is_empty() {
+            return Some(Vec::new());
+        }
+
+        let mut additional_text_edits = Vec::new();
+        if handle.sys_info().version().at_least(3, 12) {
+            additional_text_edits.push(TextEdit {
+                range: module_info.to_lsp_range(TextRange::empty(line_start)),
+                new_text: format!("{indent}@override\n"),
+            });
+            if !override_in_scope(&ast)
+                && let Some(typing) = self
+                    .import_handle(handle, ModuleName::typing(), None)
+                    .finding()
+            {
+                let edit = insert_import_edit(
+                    &ast,
+                    self.config_finder(),
+                    handle.dupe(),
+                    typing,
+                    "override",
+                    import_format,
+                );
+                additional_text_edits.push(TextEdit {
+                    range: module_info.to_lsp_range(edit.range),
+                    new_text: edit.insert_text,
+                });
+            }
+        }
+
+        Some(
+            overrides
+                .into_iter()
+                .map(|(name, method)| {
+                    let mut additional_text_edits = additional_text_edits.clone();
+                    for (module, name) in &method.imports {
+                        if *module == handle.module()
+                            || *module == ModuleName::builtins()
+                            || imports_name(&ast, *module, name)
+                        {
+                            continue;
+                        }
+                        let Some(target) = self.import_handle(handle, *module, None).finding()
+                        else {
+                            continue;
+                        };
+                        let edit = insert_import_edit(
+                            &ast,
+                            self.config_finder(),
+                            handle.dupe(),
+                            target,
+                            name,
+                            import_format,
+                        );
+                        additional_text_edits.push(TextEdit {
+                            range: module_info.to_lsp_range(edit.range),
+                            new_text: edit.insert_text,
+                        });
+                    }
+                    CompletionItem {
+                        label: name.to_string(),
+                        kind: Some(CompletionItemKind::METHOD),
+                        detail: Some(format!("Override {}.{name}", method.owner)),
+                        filter_text: Some(format!("{} {name}", keywords.join(" "))),
+                        text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                            range: module_info.to_lsp_range(edit_range),
+                            new_text: method.text,
+                        })),
+                        insert_text_mode: Some(InsertTextMode::AS_IS),
+                        additional_text_edits: (!additional_text_edits.is_empty())
+                            .then_some(additional_text_edits),
+                        ..Default::default()
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// The methods `cls` inherits and does not yet define. `completing` is the name being
+    /// typed, which the class only defines because of the partial `def`.
+    fn override_candidates(
+        &self,
+        handle: &Handle,
+        cls: &Class,
+        completing: &Name,
+    ) -> Option<Vec<Candidate>> {
+        self.ad_hoc_solve(handle, "override_completions", |solver| {
+            let mut seen = solver
+                .get_class_fields(cls)
+                .map(|fields| {
+                    fields
+                        .names()
+                        .filter(|name| *name != completing)
+                        .cloned()
+                        .collect::<HashSet<_>>()
+                })
+                .unwrap_or_default();
+            let mut candidates = Vec::new();
+            for ancestor in solver.get_mro_for_class(cls).ancestors_no_object() {
+                let owner = ancestor.class_object();
+                let Some(fields) = solver.get_class_fields(owner) else {
+                    continue;
+                };
+                let targs = ancestor.targs().substitution_map();
+                for name in fields.class_body_fields() {
+                    let is_private = name.starts_with("__") && !name.ends_with("__");
+                    if is_private || !seen.insert(name.clone()) {
+                        continue;
+                    }
+                    let Some(range) = fields.field_decl_range(name) else {
+                        continue;
+                    };
+                    let field = solver.get_field_from_current_class_only(owner, name);
+                    let signature = field.as_ref().and_then(|field| {
+                        let mut ty = field.ty();
+                        ty.subst_mut_fn(&mut |q| targs.get(q).map(|ty| (*ty).clone()));
+                        let signatures = ty.callable_signatures();
+                        let [signature] = signatures.as_slice() else {
+                            return None;
+                        };
+                        let mut signature = (*signature).clone();
+                        // An async method's return type is the coroutine it creates.
+                        if ty.visit_toplevel_func_metadata(&|meta| meta.flags.is_async)
+                            && let Some((_, _, ret)) = solver.unwrap_coroutine(&signature.ret)
+                        {
+                            signature.ret = ret;
+                        }
+                        Some(signature)
+                    });
+                    candidates.push(Candidate {
+                        owner: owner.dupe(),
+                        name: name.clone(),
+                        range,
+                        signature,
+                        lacks_implementation: field.is_some_and(|field| {
+                            solver.lacks_runtime_implementation(&field, owner)
+                        }),
+                    });
+                }
+            }
+            candidates
+        })
+    }
+}
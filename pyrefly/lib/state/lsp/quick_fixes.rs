@@ -23,6 +23,7 @@ pub(crate) mod introduce_parameter;
 pub(crate) mod invert_boolean;
 pub(crate) mod move_members;
 pub(crate) mod move_module;
+pub(crate) mod organize_imports;
 pub(crate) mod pyrefly_ignore;
 pub(crate) mod pytest_fixture;
 pub(crate) mod redundant_cast;
@@ -187,6 +187,17 @@ pub(crate) fn build_module_member_move_edits(
     Some(edits)
 }
 
+/// Builds the edit inserting a moved member into `target_handle`, which must already exist.
+pub(crate) fn module_member_insertion_edit(
+    transaction: &Transaction<'_>,
+    target_handle: &Handle,
+    member_text: &str,
+) -> Option<(Module, TextRange, String)> {
+    let target_info = transaction.get_module_info(target_handle)?;
+    let target_ast = transaction.get_ast(target_handle)?;
+    build_module_insertion_edit(&target_info, target_ast.as_ref(), member_text, None)
+}
+
 /// Builds best-effort edits for consumers that directly import the moved member
 /// from the source module.
 ///
@@ -0,0 +1,559 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use std::cmp::Reverse;
+use std::mem;
+
+use pyrefly_python::ast::Ast;
+use pyrefly_python::module_name::ModuleName;
+use pyrefly_python::sys_info::SysInfo;
+use ruff_python_ast::ModModule;
+use ruff_python_ast::Stmt;
+use ruff_text_size::Ranged;
+use ruff_text_size::TextRange;
+use ruff_text_size::TextSize;
+
+use super::extract_shared::line_end_position;
+use super::extract_shared::line_indent_and_start;
+use super::unused_import::imports_removal_ranges;
+use super::unused_import::may_be_intentional;
+use crate::ModuleInfo;
+use crate::binding::scope::UnusedImport;
+use crate::config::organize_imports::MultiLineOutput;
+use crate::config::organize_imports::OrganizeImportsSettings;
+
+/// The groups imports are sorted into, in the order they are written. Each group is separated
+/// from the next by a blank line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum ImportSection {
+    /// `from __future__ import ...`, which must come first.
+    Future,
+    /// Modules that resolve to the standard library stubs.
+    Stdlib,
+    /// Modules that resolve to installed packages or bundled third-party stubs, or that do not
+    /// resolve at all.
+    ThirdParty,
+    /// Modules that resolve to the project's own sources.
+    FirstParty,
+    /// Relative imports.
+    LocalFolder,
+}
+
+#[derive(Default)]
+struct Comments {
+    /// Full-line comments directly above the import.
+    leading: Vec<String>,
+    /// A comment at the end of the import's last line.
+    trailing: Vec<String>,
+}
+
+impl Comments {
+    fn append(&mut self, other: Comments) {
+        self.leading.extend(other.leading);
+        self.trailing.extend(other.trailing);
+    }
+}
+
+enum ImportBody {
+    /// `import module as asname`.
+    Module(Option<String>),
+    /// `from module import name as asname, ...`.
+    From(Vec<(String, Option<String>)>),
+    /// A statement with comments between its names, which is kept as it was written.
+    Verbatim(String),
+}
+
+/// Section, then `import` before `from` unless sorting within sections, then module, then names.
+type SortKey = (
+    ImportSection,
+    bool,
+    Reverse<u32>,
+    String,
+    String,
+    bool,
+    Vec<(u8, String, String)>,
+);
+
+struct Import {
+    section: ImportSection,
+    is_from: bool,
+    level: u32,
+    module: String,
+    body: ImportBody,
+    comments: Comments,
+}
+
+impl Import {
+    fn sort_key(&self, settings: &OrganizeImportsSettings) -> SortKey {
+        let names = match &self.body {
+            ImportBody::Module(asname) => asname
+                .iter()
+                .map(|asname| name_key(asname, false))
+                .collect(),
+            ImportBody::From(names) => names
+                .iter()
+                .map(|(name, _)| name_key(name, settings.order_by_type))
+                .collect(),
+            ImportBody::Verbatim(_) => Vec::new(),
+        };
+        (
+            self.section,
+            self.is_from && !settings.force_sort_within_sections,
+            Reverse(self.level),
+            self.module.to_lowercase(),
+            self.module.clone(),
+            self.is_from,
+            names,
+        )
+    }
+
+    /// The lines of the statement, without indentation or comments.
+    fn lines(&self, indent: &str, settings: &OrganizeImportsSettings) -> Vec<String> {
+        let names = match &self.body {
+            ImportBody::Module(asname) => {
+                return vec![format!("import {}", with_asname(&self.module, asname))];
+            }
+            ImportBody::Verbatim(text) => return vec![text.clone()],
+            ImportBody::From(names) => names,
+        };
+        let head = format!(
+            "from {}{} import ",
+            ".".repeat(self.level as usize),
+            self.module
+        );
+        let names = names
+            .iter()
+            .map(|(name, asname)| with_asname(name, asname))
+            .collect::<Vec<_>>();
+        let line = format!("{head}{}", names.join(", "));
+        if indent.len() + line.len() <= settings.line_length || names.iter().any(|x| x == "*") {
+            return vec![line];
+        }
+        let last_comma = if settings.include_trailing_comma {
+            ","
+        } else {
+            ""
+        };
+        match settings.multi_line_output {
+            MultiLineOutput::Grid => {
+                let align = " ".repeat(head.len() + 1);
+                let mut lines = vec![format!("{head}(")];
+                for (i, name) in names.iter().enumerate() {
+                    let piece = if i + 1 == names.len() {
+                        format!("{name}{last_comma})")
+                    } else {
+                        format!("{name},")
+                    };
+                    let current = lines.last_mut().unwrap();
+                    if i == 0 {
+                        current.push_str(&piece);
+                    } else if indent.len() + current.len() + 1 + piece.len() <= settings.line_length
+                    {
+                        current.push(' ');
+                        current.push_str(&piece);
+                    } else {
+                        lines.push(format!("{align}{piece}"));
+                    }
+                }
+                lines
+            }
+            MultiLineOutput::VerticalHangingIndent => {
+                let mut lines = vec![format!("{head}(")];
+                for (i, name) in names.iter().enumerate() {
+                    let comma = if i + 1 == names.len() {
+                        last_comma
+                    } else {
+                        ","
+                    };
+                    lines.push(format!("    {name}{comma}"));
+                }
+                lines.push(")".to_owned());
+                lines
+            }
+        }
+    }
+
+    fn render(&self, indent: &str, settings: &OrganizeImportsSettings, out: &mut String) {
+        for comment in &self.comments.leading {
+            out.push_str(&format!("{indent}{comment}\n"));
+        }
+        for (i, line) in self.lines(indent, settings).into_iter().enumerate() {
+            out.push_str(indent);
+            out.push_str(&line);
+            if i == 0 && !self.comments.trailing.is_empty() {
+                out.push_str("  ");
+                out.push_str(&self.comments.trailing.join("  "));
+            }
+            out.push('\n');
+        }
+    }
+}
+
+fn with_asname(name: &str, asname: &Option<String>) -> String {
+    match asname {
+        Some(asname) => format!("{name} as {asname}"),
+        None => name.to_owned(),
+    }
+}
+
+/// Sort imported names case-insensitively, and with `order_by_type`, `CONSTANTS` before
+/// `Classes` before everything else, like isort.
+fn name_key(name: &str, order_by_type: bool) -> (u8, String, String) {
+    let rank = if !order_by_type {
+        0
+    } else if name.len() > 1
+        && name.chars().any(char::is_uppercase)
+        && !name.chars().any(char::is_lowercase)
+    {
+        0
+    } else if name.starts_with(char::is_uppercase) {
+        1
+    } else {
+        2
+    };
+    (rank, name.to_lowercase(), name.to_owned())
+}
+
+fn matches_package(module: &str, packages: &[String]) -> bool {
+    packages.iter().any(|package| {
+        module
+            .strip_prefix(package.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+struct Organizer<'a> {
+    source: &'a str,
+    settings: &'a OrganizeImportsSettings,
+    section_of: &'a dyn Fn(ModuleName) -> ImportSection,
+    edits: Vec<(TextRange, String)>,
+}
+
+impl Organizer<'_> {
+    fn section(&self, level: u32, module: &str) -> ImportSection {
+        if level > 0 {
+            ImportSection::LocalFolder
+        } else if module == "__future__" {
+            ImportSection::Future
+        } else if matches_package(module, &self.settings.known_first_party) {
+            ImportSection::FirstParty
+        } else if matches_package(module, &self.settings.known_third_party) {
+            ImportSection::ThirdParty
+        } else {
+            (self.section_of)(ModuleName::from_str(module))
+        }
+    }
+
+    /// Organize each run of consecutive imports in `body`, and in the bodies of
+    /// `if TYPE_CHECKING:` blocks, which keep their own imports.
+    fn visit_body(&mut self, body: &[Stmt]) {
+        let mut i = 0;
+        while i < body.len() {
+            if matches!(body[i], Stmt::Import(_) | Stmt::ImportFrom(_)) {
+                let end = body[i..]
+                    .iter()
+                    .position(|x| !matches!(x, Stmt::Import(_) | Stmt::ImportFrom(_)))
+                    .map_or(body.len(), |n| i + n);
+                if let Some(edit) = self.organize_run(&body[i..end]) {
+                    self.edits.push(edit);
+                }
+                i = end;
+            } else {
+                if let Stmt::If(x) = &body[i]
+                    && SysInfo::is_type_checking_guard(&x.test)
+                {
+                    self.visit_body(&x.body);
+                    for clause in &x.elif_else_clauses {
+                        self.visit_body(&clause.body);
+                    }
+                }
+                i += 1;
+            }
+        }
+    }
+
+    /// Rewrite a run of import statements, each of which must be on lines of its own. Returns
+    /// `None` if the run is already organized, or cannot be rewritten safely.
+    fn organize_run(&self, stmts: &[Stmt]) -> Option<(TextRange, String)> {
+        let source = self.source;
+        let first = stmts.first()?;
+        let (indent, start) = line_indent_and_start(source, first.range().start())?;
+        if source[start.to_usize()..first.range().start().to_usize()] != indent {
+            return None;
+        }
+        let mut cursor = start.to_usize();
+        let mut imports = Vec::new();
+        for stmt in stmts {
+            let mut comments = Comments::default();
+            let (between, before_stmt) = source[cursor..stmt.range().start().to_usize()]
+                .rsplit_once('\n')
+                .unwrap_or(("", &source[cursor..stmt.range().start().to_usize()]));
+            if !before_stmt.trim().is_empty() {
+                return None;
+            }
+            for line in between.lines().map(str::trim).filter(|x| !x.is_empty()) {
+                if !line.starts_with('#') {
+                    return None;
+                }
+                comments.leading.push(line.to_owned());
+            }
+            let line_end = line_end_position(source, stmt.range().end());
+            let after = source[stmt.range().end().to_usize()..line_end.to_usize()].trim();
+            if !after.is_empty() {
+                if !after.starts_with('#') {
+                    return None;
+                }
+                comments.trailing.push(after.to_owned());
+            }
+            cursor = line_end.to_usize();
+
+            let text = &source[stmt.range()];
+            let verbatim = text.contains('#');
+            match stmt {
+                Stmt::Import(x) if verbatim => imports.push(Import {
+                    section: self.section(0, &x.names[0].name.id),
+                    is_from: false,
+                    level: 0,
+                    module: x.names[0].name.id.to_string(),
+                    body: ImportBody::Verbatim(text.to_owned()),
+                    comments,
+                }),
+                Stmt::ImportFrom(x) if verbatim => {
+                    let module = x.module.as_ref().map_or("", |x| x.id.as_str());
+                    imports.push(Import {
+                        section: self.section(x.level, module),
+                        is_from: true,
+                        level: x.level,
+                        module: module.to_owned(),
+                        body: ImportBody::Verbatim(text.to_owned()),
+                        comments,
+                    })
+                }
+                Stmt::Import(x) => {
+                    let mut comments = Some(comments);
+                    for alias in &x.names {
+                        imports.push(Import {
+                            section: self.section(0, &alias.name.id),
+                            is_from: false,
+                            level: 0,
+                            module: alias.name.id.to_string(),
+                            body: ImportBody::Module(
+                                alias.asname.as_ref().map(|x| x.id.to_string()),
+                            ),
+                            comments: comments.take().unwrap_or_default(),
+                        });
+                    }
+                }
+                Stmt::ImportFrom(x) => {
+                    let module = x.module.as_ref().map_or("", |x| x.id.as_str());
+                    let names = x
+                        .names
+                        .iter()
+                        .map(|alias| {
+                            (
+                                alias.name.id.to_string(),
+                                alias.asname.as_ref().map(|x| x.id.to_string()),
+                            )
+                        })
+                        .collect::<Vec<_>>();
+                    imports.push(Import {
+                        section: self.section(x.level, module),
+                        is_from: true,
+                        level: x.level,
+                        module: module.to_owned(),
+                        body: ImportBody::From(names),
+                        comments,
+                    });
+                }
+                _ => return None,
+            }
+        }
+
+        let mut imports = self.split(merge(imports));
+        imports.sort_by_cached_key(|x| x.sort_key(self.settings));
+
+        let mut out = String::new();
+        for (i, import) in imports.iter().enumerate() {
+            if i > 0 && imports[i - 1].section != import.section {
+                out.push('\n');
+            }
+            import.render(&indent, self.settings, &mut out);
+        }
+        let original = &source[start.to_usize()..cursor];
+        if !original.ends_with('\n') {
+            out.pop();
+        }
+        let range = TextRange::new(start, TextSize::try_from(cursor).ok()?);
+        (out != original).then_some((range, out))
+    }
+
+    /// Split `from` imports into the statements they are written as: star imports on their own,
+    /// and with `force_single_line` or without `combine_as_imports`, some names on their own.
+    fn split(&self, imports: Vec<Import>) -> Vec<Import> {
+        let mut result = Vec::new();
+        for mut import in imports {
+            let ImportBody::From(names) = &mut import.body else {
+                result.push(import);
+                continue;
+            };
+            let mut names = mem::take(names);
+            names.sort_by_cached_key(|(name, _)| name_key(name, self.settings.order_by_type));
+            let single_line = self.settings.force_single_line
+                && !self
+                    .settings
+                    .single_line_exclusions
+                    .contains(&import.module);
+            let mut groups: Vec<Vec<(String, Option<String>)>> = Vec::new();
+            let mut combined = Vec::new();
+            for name in names {
+                if single_line
+                    || name.0 == "*"
+                    || (name.1.is_some() && !self.settings.combine_as_imports)
+                {
+                    groups.push(vec![name]);
+                } else {
+                    combined.push(name);
+                }
+            }
+            if !combined.is_empty() {
+                groups.insert(0, combined);
+            }
+            let mut comments = Some(import.comments);
+            for names in groups {
+                result.push(Import {
+                    section: import.section,
+                    is_from: true,
+                    level: import.level,
+                    module: import.module.clone(),
+                    body: ImportBody::From(names),
+                    comments: comments.take().unwrap_or_default(),
+                });
+            }
+        }
+        result
+    }
+}
+
+/// Merge imports of the same module, and drop repeated names.
+fn merge(imports: Vec<Import>) -> Vec<Import> {
+    let mut result: Vec<Import> = Vec::new();
+    for import in imports {
+        let existing = result.iter_mut().find(|x| {
+            x.is_from == import.is_from
+                && x.level == import.level
+                && x.module == import.module
+                && match (&x.body, &import.body) {
+                    (ImportBody::Module(a), ImportBody::Module(b)) => a == b,
+                    (ImportBody::From(_), ImportBody::From(_)) => true,
+                    _ => false,
+                }
+        });
+        match existing {
+            Some(existing) => {
+                if let (ImportBody::From(names), ImportBody::From(new)) =
+                    (&mut existing.body, import.body)
+                {
+                    for name in new {
+                        if !names.contains(&name) {
+                            names.push(name);
+                        }
+                    }
+                }
+                existing.comments.append(import.comments);
+            }
+            None => result.push(import),
+        }
+    }
+    result
+}
+
+/// Edits that sort and group the imports of a module, and merge imports of the same module.
+/// Each run of consecutive import statements is organized on its own, so imports never move
+/// past other code. `section_of` places an absolute import in its section, after `__future__`
+/// and the `known_*` settings have been consulted.
+fn organize_imports_edits(
+    source: &str,
+    ast: &ModModule,
+    settings: &OrganizeImportsSettings,
+    section_of: &dyn Fn(ModuleName) -> ImportSection,
+) -> Vec<(TextRange, String)> {
+    let mut organizer = Organizer {
+        source,
+        settings,
+        section_of,
+        edits: Vec::new(),
+    };
+    organizer.visit_body(&ast.body);
+    organizer.edits
+}
+
+/// Apply `edits`, which must not overlap and be in order, to `source`.
+fn apply_edits(
+    source: &str,
+    edits: impl DoubleEndedIterator<Item = (TextRange, String)>,
+) -> String {
+    let mut result = source.to_owned();
+    for (range, text) in edits.rev() {
+        result.replace_range(range.start().to_usize()..range.end().to_usize(), &text);
+    }
+    result
+}
+
+/// The smallest run of whole lines of `before` to replace to get `after`.
+fn changed_lines(before: &str, after: &str) -> Option<(TextRange, String)> {
+    if before == after {
+        return None;
+    }
+    let prefix = before
+        .split_inclusive('\n')
+        .zip(after.split_inclusive('\n'))
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len())
+        .sum::<usize>();
+    let suffix = before[prefix..]
+        .split_inclusive('\n')
+        .rev()
+        .zip(after[prefix..].split_inclusive('\n').rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a.len())
+        .sum::<usize>();
+    let range = TextRange::new(
+        TextSize::try_from(prefix).ok()?,
+        TextSize::try_from(before.len() - suffix).ok()?,
+    );
+    Some((range, after[prefix..after.len() - suffix].to_owned()))
+}
+
+/// The edit for the `source.organizeImports` action. It removes the imports in `unused`, as
+/// their quick fix does, except those that may be intentional, then sorts and groups the
+/// imports that remain.
+pub(crate) fn organize_imports_edit(
+    module_info: &ModuleInfo,
+    ast: &ModModule,
+    unused: &[UnusedImport],
+    settings: &OrganizeImportsSettings,
+    section_of: &dyn Fn(ModuleName) -> ImportSection,
+) -> Option<(TextRange, String)> {
+    let source = module_info.contents().as_str();
+    let unused = unused
+        .iter()
+        .filter(|unused| !may_be_intentional(module_info, ast, unused))
+        .cloned()
+        .collect::<Vec<_>>();
+    let removed = apply_edits(
+        source,
+        imports_removal_ranges(source, ast, &unused)
+            .into_iter()
+            .map(|range| (range, String::new())),
+    );
+    let (removed_ast, _, _) = Ast::parse(&removed, module_info.source_type());
+    let organized = apply_edits(
+        &removed,
+        organize_imports_edits(&removed, &removed_ast, settings, section_of).into_iter(),
+    );
+    changed_lines(source, &organized)
+}
@@ -5,6 +5,8 @@
  * LICENSE file in the root directory of this source tree.
  */
 
+use clap::ValueEnum;
+use enum_iterator::Sequence;
 use lsp_types::CodeActionKind;
 use pyrefly_python::module::Module;
 use ruff_text_size::TextRange;
@@ -16,3 +18,53 @@ pub struct LocalRefactorCodeAction {
     pub edits: Vec<(Module, TextRange, String)>,
     pub kind: CodeActionKind,
 }
+
+/// A quick fix that can be applied without a human choosing between alternatives, as done
+/// by `pyrefly fix`.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Sequence, ValueEnum
+)]
+pub enum FixKind {
+    /// Remove an import that is never used. Redundant aliases like `import x as x` and
+    /// imports in an `__init__.py` without `__all__`, which re-export their names, and
+    /// `__future__` imports are kept.
+    UnusedImport,
+    /// Replace `cast(T, x)` with `x` when `x` already has type `T`.
+    RedundantCast,
+    /// Replace `T(x)` with `x` when `x` already has type `T`.
+    UnnecessaryTypeConversion,
+    /// Add `@override` to a method that overrides a parent class method.
+    AddOverride,
+    /// Replace `from m import *` with the names the module uses from `m`.
+    StarImport,
+    /// Suppress an error that has no other fix with a `# pyrefly: ignore` comment.
+    PyreflyIgnore,
+}
+
+impl FixKind {
+    pub fn to_name(self) -> &'static str {
+        match self {
+            FixKind::UnusedImport => "unused-import",
+            FixKind::RedundantCast => "redundant-cast",
+            FixKind::UnnecessaryTypeConversion => "unnecessary-type-conversion",
+            FixKind::AddOverride => "add-override",
+            FixKind::StarImport => "star-import",
+            FixKind::PyreflyIgnore => "pyrefly-ignore",
+        }
+    }
+
+    /// Whether `pyrefly fix` applies this kind when no kinds are requested. Removing
+    /// imports is opt-in, since a module can need an import that it never uses, and so is
+    /// suppressing errors, which hides them rather than fixing them.
+    pub fn is_default(self) -> bool {
+        !matches!(self, FixKind::UnusedImport | FixKind::PyreflyIgnore)
+    }
+}
+
+/// A machine-applicable quick fix, and the edits that make it.
+#[derive(Clone, Debug)]
+pub struct MachineFix {
+    pub kind: FixKind,
+    pub title: String,
+    pub edits: Vec<(Module, TextRange, String)>,
+}
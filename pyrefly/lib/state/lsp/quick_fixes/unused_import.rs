@@ -6,8 +6,10 @@
  */
 
 use dupe::Dupe;
+use pyrefly_python::dunder;
 use pyrefly_python::module::Module;
 use ruff_python_ast::Alias;
+use ruff_python_ast::Expr;
 use ruff_python_ast::ModModule;
 use ruff_python_ast::Stmt;
 use ruff_text_size::Ranged;
@@ -33,49 +35,121 @@ pub(crate) fn remove_unused_import_code_action(
     ))
 }
 
-fn import_removal_range(source: &str, ast: &ModModule, unused: &UnusedImport) -> Option<TextRange> {
-    for stmt in &ast.body {
-        match stmt {
-            Stmt::Import(import) if import.range().contains_range(unused.range) => {
-                return alias_removal_range(source, import.range(), &import.names, unused.range);
-            }
-            Stmt::ImportFrom(import_from) if import_from.range().contains_range(unused.range) => {
-                return alias_removal_range(
-                    source,
-                    import_from.range(),
-                    &import_from.names,
-                    unused.range,
-                );
-            }
-            _ => {}
+/// Whether `unused` may be imported on purpose even though the module never uses it: a
+/// redundant alias like `import x as x` marks an explicit re-export, an `__init__.py`
+/// without `__all__` re-exports every name it imports, and a `__future__` import changes how
+/// the module is compiled.
+pub(crate) fn may_be_intentional(
+    module_info: &ModuleInfo,
+    ast: &ModModule,
+    unused: &UnusedImport,
+) -> bool {
+    if module_info.path().is_init() && !defines_dunder_all(ast) {
+        return true;
+    }
+    let is_reexport = |aliases: &[Alias]| {
+        aliases.iter().any(|alias| {
+            alias.range().contains_range(unused.range)
+                && alias
+                    .asname
+                    .as_ref()
+                    .is_some_and(|asname| asname.id == alias.name.id)
+        })
+    };
+    ast.body.iter().any(|stmt| match stmt {
+        Stmt::Import(import) => is_reexport(&import.names),
+        Stmt::ImportFrom(import_from) => {
+            (import_from
+                .module
+                .as_ref()
+                .is_some_and(|module| module.id.as_str() == "__future__")
+                && import_from.range().contains_range(unused.range))
+                || is_reexport(&import_from.names)
         }
+        _ => false,
+    })
+}
+
+fn defines_dunder_all(ast: &ModModule) -> bool {
+    let is_dunder_all = |target: &Expr| matches!(target, Expr::Name(x) if x.id == dunder::ALL);
+    ast.body.iter().any(|stmt| match stmt {
+        Stmt::Assign(x) => x.targets.iter().any(is_dunder_all),
+        Stmt::AnnAssign(x) => is_dunder_all(&x.target),
+        Stmt::AugAssign(x) => is_dunder_all(&x.target),
+        _ => false,
+    })
+}
+
+fn import_removal_range(source: &str, ast: &ModModule, unused: &UnusedImport) -> Option<TextRange> {
+    let ranges = imports_removal_ranges(source, ast, std::slice::from_ref(unused));
+    match ranges.as_slice() {
+        [range] => Some(*range),
+        _ => None,
     }
-    None
 }
 
-fn alias_removal_range(
+/// Ranges that together remove every import in `unused`, which don't overlap, in order. Each
+/// statement is removed whole if all of its names are unused, and otherwise loses just the
+/// unused names and the commas that separate them.
+pub(crate) fn imports_removal_ranges(
     source: &str,
-    stmt_range: TextRange,
-    aliases: &[Alias],
-    unused_range: TextRange,
-) -> Option<TextRange> {
-    let index = aliases
-        .iter()
-        .position(|alias| alias.range().contains_range(unused_range))?;
-    if aliases.len() == 1 {
-        return statement_removal_range(source, stmt_range);
+    ast: &ModModule,
+    unused: &[UnusedImport],
+) -> Vec<TextRange> {
+    let mut ranges = Vec::new();
+    for stmt in &ast.body {
+        let (stmt_range, aliases) = match stmt {
+            Stmt::Import(import) => (import.range(), &import.names),
+            Stmt::ImportFrom(import_from) => (import_from.range(), &import_from.names),
+            _ => continue,
+        };
+        let is_unused = aliases
+            .iter()
+            .map(|alias| {
+                unused
+                    .iter()
+                    .any(|unused| alias.range().contains_range(unused.range))
+            })
+            .collect::<Vec<_>>();
+        if !is_unused.contains(&true) {
+            continue;
+        }
+        if !is_unused.contains(&false) {
+            ranges.extend(statement_removal_range(source, stmt_range));
+            continue;
+        }
+        ranges.extend(aliases_removal_ranges(aliases, &is_unused));
     }
-    if index == 0 {
-        Some(TextRange::new(
-            aliases[index].range().start(),
-            aliases[index + 1].range().start(),
-        ))
-    } else {
-        Some(TextRange::new(
-            aliases[index - 1].range().end(),
-            aliases[index].range().end(),
-        ))
+    ranges
+}
+
+/// Remove each run of unused names, along with the comma before the next name, or for a run
+/// at the end, the comma after the previous name. At least one name must be kept.
+fn aliases_removal_ranges(aliases: &[Alias], is_unused: &[bool]) -> Vec<TextRange> {
+    let mut ranges = Vec::new();
+    let mut index = 0;
+    while index < aliases.len() {
+        if !is_unused[index] {
+            index += 1;
+            continue;
+        }
+        let start = index;
+        while index < aliases.len() && is_unused[index] {
+            index += 1;
+        }
+        ranges.push(if index < aliases.len() {
+            TextRange::new(
+                aliases[start].range().start(),
+                aliases[index].range().start(),
+            )
+        } else {
+            TextRange::new(
+                aliases[start - 1].range().end(),
+                aliases[index - 1].range().end(),
+            )
+        });
     }
+    ranges
 }
 
 /// Range covering an import statement that is being removed in its entirety.
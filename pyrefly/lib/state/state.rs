@@ -50,6 +50,8 @@ use pyrefly_util::lock::RwLock;
 use pyrefly_util::locked_map::LockedMap;
 use pyrefly_util::no_hash::BuildNoHash;
 use pyrefly_util::prelude::VecExt;
+use pyrefly_util::profile::ProfileCollector;
+use pyrefly_util::profile::ProfileStart;
 use pyrefly_util::task_heap::CancellationHandle;
 use pyrefly_util::task_heap::Cancelled;
 use pyrefly_util::task_heap::TaskHeap;
@@ -699,6 +701,7 @@ impl<'a> TransactionData<'a> {
                 readable,
                 timing: Default::default(),
                 demand_collector: None,
+                profile_collector: None,
             })
         } else {
             Err(state_lock_blocked)
@@ -722,6 +725,9 @@ pub struct Transaction<'a> {
     /// [`Transaction::set_demand_collector`] before `run`; read by
     /// `TransactionHandle` event sites.
     demand_collector: Option<DemandCollector>,
+    /// Optional timeline profile for this run. Set via
+    /// [`Transaction::set_profile_collector`] before `run`.
+    profile_collector: Option<ProfileCollector>,
 }
 
 impl<'a> Transaction<'a> {
@@ -734,6 +740,7 @@ impl<'a> Transaction<'a> {
             readable,
             timing,
             demand_collector: _,
+            profile_collector: _,
         } = self;
         drop(readable);
         let mut stats = stats.into_inner();
@@ -759,6 +766,17 @@ impl<'a> Transaction<'a> {
         self.demand_collector = collector;
     }
 
+    /// Install a collector that records a timeline span for each step computed, and for
+    /// each wait on a step being computed by another thread. Pass `None` to disable.
+    pub fn set_profile_collector(&mut self, collector: Option<ProfileCollector>) {
+        self.profile_collector = collector;
+    }
+
+    /// Remove the profile collector, so no more spans are recorded.
+    pub fn take_profile_collector(&mut self) -> Option<ProfileCollector> {
+        self.profile_collector.take()
+    }
+
     /// Take the demand-tree roots collected during this run. Returns an
     /// empty vec if no collector was installed.
     pub fn take_demand_roots(&self) -> Vec<DemandEdge> {
@@ -813,6 +831,33 @@ impl<'a> Transaction<'a> {
             .map(|c| c.enter(from, target, key))
     }
 
+    /// Start cleaning `module_data`, as `try_start_clean`, recording the time spent waiting
+    /// for another thread that is cleaning or computing the module in the profile.
+    fn start_clean<'m>(&self, module_data: &'m ModuleDataMut) -> Option<CleanGuard<'m>> {
+        let wait_start = Timer::start();
+        let profile_start = self.profile_collector.as_ref().map(|c| c.now());
+        let guard = module_data.state.try_start_clean(self.data.now);
+        if wait_start.elapsed().as_nanos() > 1000 {
+            self.record_profile_span(profile_start, "Clean", "wait", module_data);
+        }
+        guard
+    }
+
+    /// Record a profile span from `start` until now, if a profile collector is installed.
+    fn record_profile_span(
+        &self,
+        start: Option<ProfileStart>,
+        name: &'static str,
+        category: &'static str,
+        module_data: &ModuleDataMut,
+    ) {
+        if let Some(c) = self.profile_collector.as_ref()
+            && let Some(start) = start
+        {
+            c.finish(start, name, category, module_data.handle.module());
+        }
+    }
+
     /// Set the pysa reporter for inline extraction during type checking.
     pub fn set_pysa_reporter(&mut self, reporter: Option<Box<crate::report::pysa::PysaReporter>>) {
         self.data.pysa_reporter = reporter;
@@ -1080,6 +1125,11 @@ impl<'a> Transaction<'a> {
         transitive_rdeps
     }
 
+    /// Return the direct dependencies recorded for `handle`, in a non-deterministic order.
+    pub fn get_deps(&self, handle: &Handle) -> Vec<Handle> {
+        self.get_module(handle).deps.read().keys().cloned().collect()
+    }
+
     /// Return all handles for which there is data, in a non-deterministic order.
     pub fn handles(&self) -> Vec<Handle> {
         if self.data.updated_modules.is_empty() {
@@ -1386,10 +1436,12 @@ impl<'a> Transaction<'a> {
         // We check the epoch optimistically before calling try_start_clean
         // to avoid taking the computing mutex.
         if !module_data.state.is_checked(self.data.now)
-            && let Some(guard) = module_data.state.try_start_clean(self.data.now)
+            && let Some(guard) = self.start_clean(module_data)
         {
             let clean_start = Timer::start();
+            let profile_start = self.profile_collector.as_ref().map(|c| c.now());
             self.clean(module_data, guard);
+            self.record_profile_span(profile_start, "Clean", "step", module_data);
             self.timing
                 .clean_ns
                 .fetch_add(clean_start.elapsed().as_nanos() as u64, Ordering::Relaxed);
@@ -1406,9 +1458,12 @@ impl<'a> Transaction<'a> {
 
             // Try to acquire exclusive compute access for the next step.
             let wait_start = Timer::start();
+            let profile_start = self.profile_collector.as_ref().map(|c| c.now());
             let result = module_data.state.try_start_compute(step);
             let wait_ns = wait_start.elapsed().as_nanos() as u64;
             if wait_ns > 1000 {
+                // Waiting for another thread to finish computing a step of this module.
+                self.record_profile_span(profile_start, step.label(), "wait", module_data);
                 self.timing
                     .demand_wait_ns
                     .fetch_add(wait_ns, Ordering::Relaxed);
@@ -1483,7 +1538,9 @@ impl<'a> Transaction<'a> {
             // Post-compute work (diffing, invalidation, eviction) runs without
             // the flag held.
             let compute_start = Timer::start();
+            let profile_start = self.profile_collector.as_ref().map(|c| c.now());
             let post = guard.compute(&ctx);
+            self.record_profile_span(profile_start, todo.label(), "step", module_data);
             let elapsed_ns = compute_start.elapsed().as_nanos() as u64;
             let (ns_counter, count_counter) = match todo {
                 Step::Load => (&self.timing.step_load_ns, &self.timing.step_load_count),
@@ -1898,7 +1955,10 @@ impl<'a> Transaction<'a> {
             .updated_loaders
             .ensure(loader, || match self.readable.loaders.get(loader) {
                 Some(v) => v.dupe(),
-                None => Arc::new(LoaderFindCache::new(loader.dupe())),
+                None => Arc::new(LoaderFindCache::new(
+                    loader.dupe(),
+                    self.data.state.config_finder.editable_roots(),
+                )),
             })
             .0
             .dupe()
@@ -2303,11 +2363,18 @@ impl<'a> Transaction<'a> {
     /// E.g. you have include paths, and a new file appeared earlier on the path.
     fn invalidate_find(&mut self) {
         let new_loaders = LockedMap::new();
+        let editable_roots = self.data.state.config_finder.editable_roots();
         for loader in self.data.updated_loaders.keys() {
-            new_loaders.insert(loader.dupe(), Arc::new(LoaderFindCache::new(loader.dupe())));
+            new_loaders.insert(
+                loader.dupe(),
+                Arc::new(LoaderFindCache::new(loader.dupe(), editable_roots.dupe())),
+            );
         }
         for loader in self.readable.loaders.keys() {
-            new_loaders.insert(loader.dupe(), Arc::new(LoaderFindCache::new(loader.dupe())));
+            new_loaders.insert(
+                loader.dupe(),
+                Arc::new(LoaderFindCache::new(loader.dupe(), editable_roots.dupe())),
+            );
         }
         self.data.updated_loaders = new_loaders;
 
@@ -2320,11 +2387,17 @@ impl<'a> Transaction<'a> {
         // We clear the global config cache, rather than making a dedicated copy.
         // This is reasonable, because we will cache the result on ModuleData.
         self.data.state.config_finder.clear();
+        self.refresh_configs(|_| true);
+    }
 
-        // Wipe the copy of ConfigFile on each module that has changed.
-        // If they change, set find to dirty.
+    /// Wipe the copy of ConfigFile on each module matching `pred` whose config has changed,
+    /// setting find to dirty.
+    fn refresh_configs(&mut self, pred: impl Fn(&Handle) -> bool) {
         let mut dirty_set = self.data.dirty.lock();
         for (handle, module_data) in self.data.updated_modules.iter_unordered() {
+            if !pred(handle) {
+                continue;
+            }
             let config2 = self.data.state.get_config(handle);
             if config2 != *module_data.config.read() {
                 *module_data.config.write() = config2;
@@ -2333,7 +2406,7 @@ impl<'a> Transaction<'a> {
             }
         }
         for (handle, module_data) in self.readable.modules.iter() {
-            if self.data.updated_modules.get(handle).is_none() {
+            if pred(handle) && self.data.updated_modules.get(handle).is_none() {
                 let config2 = self.data.state.get_config(handle);
                 if module_data.config != config2 {
                     let module_data = self.get_module(handle);
@@ -2366,8 +2439,12 @@ impl<'a> Transaction<'a> {
             .for_each(|(c, l)| {
                 new_loaders.insert(c.dupe(), l.dupe());
             });
+        let editable_roots = self.data.state.config_finder.editable_roots();
         configs.iter().for_each(|config| {
-            new_loaders.insert(config.dupe(), Arc::new(LoaderFindCache::new(config.dupe())));
+            new_loaders.insert(
+                config.dupe(),
+                Arc::new(LoaderFindCache::new(config.dupe(), editable_roots.dupe())),
+            );
         });
         self.data.updated_loaders = new_loaders;
 
@@ -2396,6 +2473,15 @@ impl<'a> Transaction<'a> {
         let mut changed = SmallSet::new();
         for (path, contents) in files {
             if self.memory_lookup().get(&path) != contents.as_ref() {
+                // A script's metadata, and so its config, can change with an edit.
+                let source = match contents.as_deref() {
+                    Some(FileContents::Source(source)) => Some(source.dupe()),
+                    Some(FileContents::Notebook(_)) | None => None,
+                };
+                self.data
+                    .state
+                    .config_finder
+                    .set_script_source(&path, source);
                 self.data.memory_overlay.set(path.clone(), contents);
                 changed.insert(ModulePath::memory(path));
             }
@@ -2404,6 +2490,7 @@ impl<'a> Transaction<'a> {
         if changed.is_empty() {
             return;
         }
+        self.refresh_configs(|handle| changed.contains(handle.path()));
         self.invalidate(
             |handle| changed.contains(handle.path()),
             |state| state.set_dirty_load(),
@@ -2417,12 +2504,17 @@ impl<'a> Transaction<'a> {
         if files.is_empty() {
             return;
         }
+        self.data
+            .state
+            .config_finder
+            .invalidate_scripts(files.iter().map(|x| x.as_path()));
         // We create the set out of ModulePath as it allows us to reuse the fact `ModulePath` has cheap hash
         // when checking the modules.
         let files = files
             .iter()
             .map(|x| ModulePath::filesystem(x.clone()))
             .collect::<SmallSet<_>>();
+        self.refresh_configs(|handle| files.contains(handle.path()));
         self.invalidate(
             |handle| files.contains(handle.path()),
             |state| state.set_dirty_load(),
@@ -3318,6 +3410,7 @@ impl State {
             sub_task_telemetry: None,
             timing: Default::default(),
             demand_collector: None,
+            profile_collector: None,
             data: TransactionData {
                 state: self,
                 stdlib,
@@ -3391,6 +3484,7 @@ impl State {
                     sub_task_telemetry: _,
                     timing,
                     demand_collector: _,
+                    profile_collector: _,
                     data:
                         TransactionData {
                             stdlib,
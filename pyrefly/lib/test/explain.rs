@@ -0,0 +1,142 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+//! Checks the examples printed by `pyrefly explain`.
+
+use std::fs;
+use std::path::Path;
+
+use pyrefly_config::error_kind::ErrorKind;
+
+use crate::commands::explain::explanation;
+use crate::test::util::TestEnv;
+
+/// Kinds whose examples can't be checked here, because they depend on third-party packages,
+/// the environment or Pyrefly internals rather than on the code being checked.
+const UNCHECKED: &[ErrorKind] = &[
+    // Require Polars to be installed.
+    ErrorKind::ColumnSchemaMismatch,
+    ErrorKind::ColumnTypeMismatch,
+    ErrorKind::DuplicateColumn,
+    ErrorKind::UnknownColumn,
+    // Require PyTorch to be installed.
+    ErrorKind::PytorchEfficiencyLintCudaCall,
+    ErrorKind::PytorchEfficiencyLintItemCall,
+    ErrorKind::PytorchEfficiencyLintPrintTensor,
+    ErrorKind::PytorchEfficiencyLintRedundantToCall,
+    ErrorKind::PytorchEfficiencyLints,
+    // Depend on which packages are installed.
+    ErrorKind::MissingSource,
+    ErrorKind::MissingSourceForStubs,
+    ErrorKind::UntypedImport,
+    ErrorKind::UnlistedScriptDependency,
+    // Only reported for bugs in Pyrefly.
+    ErrorKind::InternalError,
+    // Depends on fixpoint iteration limits.
+    ErrorKind::NonConvergentRecursion,
+    // Only reported for features that are unavailable on the tested Python version.
+    ErrorKind::Unsupported,
+];
+
+/// Split an example into `(module name, path, code)` on `# file: <path>` lines. The last
+/// module is the one that is checked, and is called `main` if the example has no markers.
+fn modules(example: &str) -> Vec<(String, String, String)> {
+    let mut res: Vec<(String, String, String)> = Vec::new();
+    for line in example.lines() {
+        if let Some(path) = line.strip_prefix("# file: ") {
+            let name = path
+                .trim_end_matches(".pyi")
+                .trim_end_matches(".py")
+                .trim_end_matches("/__init__")
+                .replace('/', ".");
+            res.push((name, path.to_owned(), String::new()));
+        } else {
+            if res.is_empty() {
+                res.push(("main".to_owned(), "main.py".to_owned(), String::new()));
+            }
+            let code = &mut res.last_mut().unwrap().2;
+            code.push_str(line);
+            code.push('\n');
+        }
+    }
+    res
+}
+
+/// Does checking `example` produce an error that can be suppressed as `kind`?
+fn produces(kind: ErrorKind, example: &str) -> bool {
+    let mut env = TestEnv::new().enable_all_errors();
+    let modules = modules(example);
+    for (name, path, code) in &modules {
+        env.add_with_path(name, path, code);
+    }
+    let main = modules.last().unwrap().0.clone();
+    let (state, handle) = env.to_state();
+    let transaction = state.transaction();
+    let errors = transaction.get_errors([&handle(&main)]);
+    let collected = errors.collect_errors();
+    let unused_ignores = errors.collect_unused_ignore_errors(&collected);
+    collected
+        .ordinary
+        .iter()
+        .chain(&collected.directives)
+        .chain(&unused_ignores)
+        .any(|e| {
+            e.error_kind()
+                .suppression_names()
+                .any(|name| name == kind.to_name())
+        })
+}
+
+#[test]
+fn test_explanations_match_docs() {
+    let docs = Path::new(env!("CARGO_MANIFEST_DIR")).join("../website/docs/error-kinds.mdx");
+    // The website is not part of the packaged crate.
+    let Ok(docs) = fs::read_to_string(&docs) else {
+        return;
+    };
+    assert!(
+        docs == include_str!("../commands/explain.md"),
+        "pyrefly/lib/commands/explain.md is out of date, copy website/docs/error-kinds.mdx over it"
+    );
+}
+
+#[test]
+fn test_every_kind_is_explained() {
+    // Coverage kinds are only emitted by `pyrefly coverage check` and are not documented.
+    for kind in enum_iterator::all::<ErrorKind>().filter(|k| !k.is_coverage()) {
+        let explanation = explanation(kind)
+            .unwrap_or_else(|| panic!("No explanation for `{}` in explain.md", kind.to_name()));
+        assert!(
+            !explanation.text.is_empty(),
+            "Empty explanation for `{}`",
+            kind.to_name()
+        );
+    }
+}
+
+#[test]
+fn test_explain_examples() {
+    for kind in enum_iterator::all::<ErrorKind>() {
+        if kind.is_coverage() || UNCHECKED.contains(&kind) {
+            continue;
+        }
+        let explanation = explanation(kind).unwrap();
+        let (Some(bad), Some(good)) = (&explanation.bad, &explanation.good) else {
+            panic!("Missing bad or good example for `{}`", kind.to_name());
+        };
+        assert!(
+            produces(kind, bad),
+            "The bad example for `{}` does not produce the error",
+            kind.to_name()
+        );
+        assert!(
+            !produces(kind, good),
+            "The good example for `{}` produces the error",
+            kind.to_name()
+        );
+    }
+}
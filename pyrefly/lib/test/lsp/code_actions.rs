@@ -10,10 +10,14 @@ use std::fs;
 use pretty_assertions::assert_eq;
 use pyrefly_build::handle::Handle;
 use pyrefly_python::module::Module;
+use pyrefly_util::text_edits::TextEdits;
 use ruff_text_size::TextRange;
 use ruff_text_size::TextSize;
 
+use crate::config::organize_imports::ImportProfile;
+use crate::config::organize_imports::OrganizeImportsSettings;
 use crate::module::module_info::ModuleInfo;
+use crate::state::lsp::FixKind;
 use crate::state::lsp::ImportFormat;
 use crate::state::lsp::LocalRefactorCodeAction;
 use crate::state::require::Require;
@@ -1469,6 +1473,84 @@ fn redundant_cast_fix_all() {
     );
 }
 
+fn apply_machine_applicable_fixes(code: &str, kinds: &[FixKind]) -> String {
+    apply_machine_applicable_fixes_at("main.py", code, kinds)
+}
+
+fn apply_machine_applicable_fixes_at(path: &str, code: &str, kinds: &[FixKind]) -> String {
+    let mut env = TestEnv::new().with_default_require_level(Require::Exports);
+    env.add_with_path("main", path, code);
+    let (state, handle) = env.to_state();
+    let transaction = state.transaction();
+    let mut edits = TextEdits::new();
+    for fix in transaction.machine_applicable_fixes(&handle("main"), kinds, ImportFormat::Absolute)
+    {
+        assert!(edits.add(fix.edits.into_iter().map(|(_, range, text)| (range, text))));
+    }
+    edits.apply(code)
+}
+
+#[test]
+fn machine_applicable_fixes_apply_together() {
+    let code = "from os import path\nfrom typing import cast\ndef f(x: int, y: str) -> None:\n    a = cast(int, x)\n    b = str(y)\n";
+    assert_eq!(
+        "from typing import cast\ndef f(x: int, y: str) -> None:\n    a = x\n    b = y\n",
+        apply_machine_applicable_fixes(code, &enum_iterator::all::<FixKind>().collect::<Vec<_>>())
+    );
+    assert_eq!(
+        "from os import path\nfrom typing import cast\ndef f(x: int, y: str) -> None:\n    a = x\n    b = str(y)\n",
+        apply_machine_applicable_fixes(code, &[FixKind::RedundantCast])
+    );
+}
+
+#[test]
+fn machine_applicable_fixes_keep_redundant_aliases() {
+    let code = "import os\nimport sys as sys\nfrom typing import List as List, cast\n";
+    assert_eq!(
+        "import sys as sys\nfrom typing import List as List\n",
+        apply_machine_applicable_fixes(code, &[FixKind::UnusedImport])
+    );
+}
+
+#[test]
+fn machine_applicable_fixes_keep_init_reexports() {
+    let code = "from os import path\nfrom typing import List\n";
+    assert_eq!(
+        code,
+        apply_machine_applicable_fixes_at("pkg/__init__.py", code, &[FixKind::UnusedImport])
+    );
+    let code = "from os import path\nfrom typing import List\n__all__ = [\"path\"]\n";
+    assert_eq!(
+        "from os import path\n__all__ = [\"path\"]\n",
+        apply_machine_applicable_fixes_at("pkg/__init__.py", code, &[FixKind::UnusedImport])
+    );
+}
+
+#[test]
+fn unused_import_fixes_are_opt_in() {
+    assert!(!FixKind::UnusedImport.is_default());
+    assert!(!FixKind::PyreflyIgnore.is_default());
+    assert!(FixKind::RedundantCast.is_default());
+}
+
+#[test]
+fn machine_applicable_fixes_convert_star_imports() {
+    let code = "from os.path import *\nprint(join(\"a\", \"b\"))\n";
+    assert_eq!(
+        "from os.path import join\nprint(join(\"a\", \"b\"))\n",
+        apply_machine_applicable_fixes(code, &[FixKind::StarImport])
+    );
+}
+
+#[test]
+fn machine_applicable_fixes_suppress_errors_without_fixes() {
+    let code = "from typing import cast\nx: int = \"a\"\ny = cast(int, 1)\n";
+    assert_eq!(
+        "from typing import cast\n# pyrefly: ignore [bad-assignment]\nx: int = \"a\"\ny = 1\n",
+        apply_machine_applicable_fixes(code, &[FixKind::RedundantCast, FixKind::PyreflyIgnore])
+    );
+}
+
 #[test]
 fn unnecessary_str_call_quickfix() {
     let report = get_batched_lsp_operations_report_allow_error(
@@ -1497,6 +1579,186 @@ def f(x: str) -> None:
     );
 }
 
+fn organize_imports_after(code: &str, settings: &OrganizeImportsSettings) -> Option<String> {
+    let (handles, state) = mk_multi_file_state(
+        &[("main", code), ("helper", "x = 1\n")],
+        Require::Exports,
+        false,
+    );
+    let handle = handles.get("main").unwrap();
+    let transaction = state.transaction();
+    let module_info = transaction.get_module_info(handle).unwrap();
+    let edits = transaction.organize_imports_edits(handle, settings)?;
+    Some(apply_refactor_edits_for_module(&module_info, &edits))
+}
+
+#[test]
+fn organize_imports_sorts_into_sections() {
+    let code = r#"from __future__ import annotations
+import sys
+from helper import x
+import requests
+from typing import List, Dict
+import os
+from typing import cast
+
+print(sys, os, requests, x, Dict, cast)
+"#;
+    assert_eq!(
+        r#"from __future__ import annotations
+
+import os
+import sys
+from typing import Dict, cast
+
+import requests
+
+from helper import x
+
+print(sys, os, requests, x, Dict, cast)
+"#,
+        organize_imports_after(code, &OrganizeImportsSettings::default()).unwrap()
+    );
+}
+
+#[test]
+fn organize_imports_keeps_comments_and_type_checking_blocks() {
+    let code = r#"from typing import TYPE_CHECKING
+# the helper
+from helper import x  # keep
+import os
+
+if TYPE_CHECKING:
+    from typing import Sequence
+    from collections.abc import Mapping
+
+def f(a: Mapping[str, int], b: Sequence[int]) -> None:
+    print(x, os, a, b)
+"#;
+    assert_eq!(
+        r#"import os
+from typing import TYPE_CHECKING
+
+# the helper
+from helper import x  # keep
+
+if TYPE_CHECKING:
+    from collections.abc import Mapping
+    from typing import Sequence
+
+def f(a: Mapping[str, int], b: Sequence[int]) -> None:
+    print(x, os, a, b)
+"#,
+        organize_imports_after(code, &OrganizeImportsSettings::default()).unwrap()
+    );
+}
+
+#[test]
+fn organize_imports_merges_duplicates() {
+    let code = "from typing import List\nimport os\nfrom typing import Dict, List\nprint(os, List, Dict)\n";
+    assert_eq!(
+        "import os\nfrom typing import Dict, List\nprint(os, List, Dict)\n",
+        organize_imports_after(code, &OrganizeImportsSettings::default()).unwrap()
+    );
+    assert_eq!(
+        None,
+        organize_imports_after(
+            "import os\nfrom typing import Dict\nprint(os, Dict)\n",
+            &OrganizeImportsSettings::default()
+        )
+    );
+}
+
+#[test]
+fn organize_imports_wraps_long_imports_by_profile() {
+    let code = "from typing import Optional, Dict, List\nprint(Dict, List, Optional)\n";
+    assert_eq!(
+        "from typing import (Dict,\n                    List,\n                    Optional)\nprint(Dict, List, Optional)\n",
+        organize_imports_after(
+            code,
+            &OrganizeImportsSettings {
+                line_length: 30,
+                ..OrganizeImportsSettings::from_profile(ImportProfile::Isort)
+            }
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        "from typing import (\n    Dict,\n    List,\n    Optional,\n)\nprint(Dict, List, Optional)\n",
+        organize_imports_after(
+            code,
+            &OrganizeImportsSettings {
+                line_length: 30,
+                ..OrganizeImportsSettings::from_profile(ImportProfile::Black)
+            }
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn organize_imports_removes_unused_imports() {
+    let code = r#"from __future__ import annotations
+import os
+# the typing imports
+from typing import List, cast
+import sys as sys
+import json as j
+print(cast)
+"#;
+    assert_eq!(
+        r#"from __future__ import annotations
+
+import sys as sys
+# the typing imports
+from typing import cast
+print(cast)
+"#,
+        organize_imports_after(code, &OrganizeImportsSettings::default()).unwrap()
+    );
+}
+
+#[test]
+fn organize_imports_google_profile() {
+    let code = r#"from os import sep, path
+import sys
+from typing import List, Dict
+import collections
+print(path, sep, sys, Dict, List, collections)
+"#;
+    assert_eq!(
+        r#"import collections
+from os import path
+from os import sep
+import sys
+from typing import Dict, List
+print(path, sep, sys, Dict, List, collections)
+"#,
+        organize_imports_after(
+            code,
+            &OrganizeImportsSettings::from_profile(ImportProfile::Google)
+        )
+        .unwrap()
+    );
+}
+
+#[test]
+fn organize_imports_known_packages() {
+    let code = "from helper import x\nimport requests\nimport os\nprint(x, requests, os)\n";
+    assert_eq!(
+        "import os\n\nfrom helper import x\n\nimport requests\nprint(x, requests, os)\n",
+        organize_imports_after(
+            code,
+            &OrganizeImportsSettings {
+                known_first_party: vec!["requests".to_owned()],
+                known_third_party: vec!["helper".to_owned()],
+                ..OrganizeImportsSettings::default()
+            }
+        )
+        .unwrap()
+    );
+}
+
 fn redundant_cast_action_after(code: &str, cursor_offset: usize) -> Option<String> {
     let (handles, state) = mk_multi_file_state(&[("main", code)], Require::Exports, false);
     let handle = handles.get("main")?;
@@ -94,6 +94,205 @@ class Foo:
     assert_eq!(get_value_suggestions, 1);
 }
 
+/// The override completions offered at the cursor in `code`, as `(label, new_text, additional
+/// edits)`.
+fn override_completions(version: PythonVersion, code: &str) -> Vec<(String, String, Vec<String>)> {
+    override_completions_with(version, &[], code)
+}
+
+/// Like `override_completions`, with `modules` alongside `main`.
+fn override_completions_with(
+    version: PythonVersion,
+    modules: &[(&str, &str)],
+    code: &str,
+) -> Vec<(String, String, Vec<String>)> {
+    let mut env = TestEnv::new_with_version(version).with_default_require_level(Require::Exports);
+    for (name, contents) in modules {
+        env.add(name, contents);
+    }
+    env.add("main", code);
+    let (state, handle_for) = env.to_state();
+    let position = extract_cursors_for_test(code)[0];
+    state
+        .transaction()
+        .completion(
+            &handle_for("main"),
+            position,
+            ImportFormat::Absolute,
+            true,
+            None,
+        )
+        .into_iter()
+        .filter_map(|item| {
+            let Some(lsp_types::CompletionTextEdit::Edit(edit)) = item.text_edit else {
+                return None;
+            };
+            Some((
+                item.label,
+                edit.new_text,
+                item.additional_text_edits
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|edit| edit.new_text)
+                    .collect(),
+            ))
+        })
+        .collect()
+}
+
+#[test]
+fn completion_override_method_with_signature_and_decorator() {
+    let code = r#"
+class Shape:
+    def area(self, scale: float = 1.0, *, exact: bool = False) -> float:
+        return 0.0
+
+    def reset(self, /, *args: int, **kwargs: str) -> None:
+        pass
+
+class Square(Shape):
+    def ar
+#         ^
+"#;
+    let completions = override_completions(PythonVersion::new(3, 13, 0), code);
+    let area = completions
+        .iter()
+        .find(|(label, _, _)| label == "area")
+        .expect("expected an override completion for `area`");
+    assert_eq!(
+        area.1,
+        "def area(self, scale: float = 1.0, *, exact: bool = False) -> float:\n        return super().area(scale, exact=exact)"
+    );
+    assert_eq!(
+        area.2,
+        vec![
+            "    @override\n".to_owned(),
+            "from typing import override\n".to_owned()
+        ]
+    );
+    let reset = completions
+        .iter()
+        .find(|(label, _, _)| label == "reset")
+        .expect("expected an override completion for `reset`");
+    assert_eq!(
+        reset.1,
+        "def reset(self, /, *args: int, **kwargs: str) -> None:\n        super().reset(*args, **kwargs)"
+    );
+}
+
+#[test]
+fn completion_override_protocol_and_abstract_methods() {
+    let code = r#"
+from abc import ABC, abstractmethod
+from typing import Protocol
+
+class Closeable(Protocol):
+    async def close(self, force: bool) -> None: ...
+
+class Base(ABC):
+    @abstractmethod
+    def run(self) -> int: ...
+
+    @classmethod
+    def create(cls, name: str) -> "Base": ...
+
+    @staticmethod
+    def helper(x: int) -> int:
+        return x
+
+class Impl(Base, Closeable):
+    def r
+#        ^
+"#;
+    let completions = override_completions(PythonVersion::new(3, 11, 0), code);
+    let text_of = |name: &str| {
+        let (_, text, edits) = completions
+            .iter()
+            .find(|(label, _, _)| label == name)
+            .unwrap_or_else(|| panic!("expected an override completion for `{name}`"));
+        // `typing.override` is new in Python 3.12.
+        assert!(edits.is_empty());
+        text.clone()
+    };
+    // Neither `run` nor `close` has an implementation to call through `super()`.
+    assert_eq!(
+        text_of("run"),
+        "def run(self) -> int:\n        raise NotImplementedError"
+    );
+    assert_eq!(
+        text_of("create"),
+        "@classmethod\n    def create(cls, name: str) -> Base:\n        return super().create(name)"
+    );
+    assert_eq!(
+        text_of("helper"),
+        "@staticmethod\n    def helper(x: int) -> int:\n        return super(Impl, Impl).helper(x)"
+    );
+    assert_eq!(
+        text_of("close"),
+        "async def close(self, force: bool) -> None:\n        raise NotImplementedError"
+    );
+}
+
+#[test]
+fn completion_override_substitutes_type_arguments_and_imports_names() {
+    let base = r#"
+from typing import Generic, TypeVar
+
+_KT = TypeVar("_KT")
+
+class Key: ...
+
+class Store(Generic[_KT]):
+    def get(self, key: _KT, fallback: Key | None = None) -> _KT:
+        return key
+"#;
+    let code = r#"
+from base import Store
+
+class IntStore(Store[int]):
+    def g
+#        ^
+"#;
+    let completions =
+        override_completions_with(PythonVersion::new(3, 11, 0), &[("base", base)], code);
+    let (_, text, edits) = completions
+        .iter()
+        .find(|(label, _, _)| label == "get")
+        .expect("expected an override completion for `get`");
+    assert_eq!(
+        text,
+        "def get(self, key: int, fallback: Key | None = None) -> int:\n        return super().get(key, fallback)"
+    );
+    assert_eq!(edits, &vec!["from base import Key\n".to_owned()]);
+}
+
+#[test]
+fn completion_override_skips_defined_and_final_methods() {
+    let code = r#"
+from typing import final, override
+
+class Base:
+    def keep(self) -> None: ...
+    def done(self) -> None: ...
+    @final
+    def locked(self) -> None: ...
+
+class Child(Base):
+    def done(self) -> None: ...
+
+    def k
+#        ^
+"#;
+    let completions = override_completions(PythonVersion::new(3, 13, 0), code);
+    let labels = completions
+        .iter()
+        .map(|(label, _, _)| label.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(labels, vec!["keep"]);
+    // `override` is already imported, so only the decorator is added.
+    assert_eq!(completions[0].2, vec!["    @override\n".to_owned()]);
+}
+
 fn get_default_test_report() -> impl Fn(&State, &Handle, TextSize) -> String {
     get_test_report(ResultsFilter::default(), ImportFormat::Absolute)
 }
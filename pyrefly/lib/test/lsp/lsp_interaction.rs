@@ -20,6 +20,7 @@ mod cross_file_invalidation_project_mode;
 mod definition;
 mod diagnostic;
 mod did_change;
+mod document_link;
 mod document_symbols;
 mod empty_response_reason;
 mod file_watcher;
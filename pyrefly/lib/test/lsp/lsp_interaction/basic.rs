@@ -40,7 +40,7 @@ fn test_initialize_basic() {
             "definitionProvider": true,
             "typeDefinitionProvider": true,
             "codeActionProvider": {
-                "codeActionKinds": ["quickfix", "refactor.extract", "refactor.rewrite", "refactor.delete", "refactor.move", "refactor.inline", "source.fixAll", "source.fixAll.pyrefly"]
+                "codeActionKinds": ["quickfix", "refactor.extract", "refactor.rewrite", "refactor.delete", "refactor.move", "refactor.inline", "source.fixAll", "source.fixAll.pyrefly", "source.organizeImports"]
             },
             "codeLensProvider": {
                 "resolveProvider": false,
@@ -59,6 +59,9 @@ fn test_initialize_basic() {
             "inlayHintProvider": true,
             "notebookDocumentSync":{"notebookSelector":[{"cells":[{"language":"python"}]}]},
             "documentSymbolProvider": true,
+            "documentLinkProvider": {
+                "resolveProvider": false
+            },
             "foldingRangeProvider":true,
             "selectionRangeProvider": true,
             "workspaceSymbolProvider": true,
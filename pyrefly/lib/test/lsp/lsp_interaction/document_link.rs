@@ -0,0 +1,91 @@
+/*
+ * Copyright (c) Meta Platforms, Inc. and affiliates.
+ *
+ * This source code is licensed under the MIT license found in the
+ * LICENSE file in the root directory of this source tree.
+ */
+
+use lsp_types::DocumentLink;
+use lsp_types::Url;
+use lsp_types::request::DocumentLinkRequest;
+use pyrefly_lsp_test::object_model::InitializeSettings;
+use pyrefly_lsp_test::object_model::LspInteraction;
+use serde_json::json;
+use tempfile::TempDir;
+
+#[test]
+fn document_links_for_imports_and_paths() {
+    let root = TempDir::new().unwrap();
+    std::fs::write(root.path().join("helper.py"), "").unwrap();
+    std::fs::create_dir(root.path().join("data")).unwrap();
+    std::fs::write(root.path().join("data/config.yaml"), "").unwrap();
+    std::fs::create_dir(root.path().join("templates")).unwrap();
+    std::fs::write(
+        root.path().join("main.py"),
+        r#"import helper
+from pathlib import Path
+
+with open("data/config.yaml") as f:
+    pass
+TEMPLATES = Path(__file__).parent / "templates"
+MISSING = "data/missing.txt"
+"#,
+    )
+    .unwrap();
+
+    let root_uri = Url::from_file_path(root.path()).unwrap();
+    let mut interaction = LspInteraction::new();
+    interaction.set_root(root.path().to_path_buf());
+    interaction
+        .initialize(InitializeSettings {
+            workspace_folders: Some(vec![("test".to_owned(), root_uri)]),
+            ..Default::default()
+        })
+        .unwrap();
+    interaction.client.did_open("main.py");
+
+    let uri = Url::from_file_path(root.path().join("main.py")).unwrap();
+    interaction
+        .client
+        .send_request::<DocumentLinkRequest>(json!({
+            "textDocument": {"uri": uri},
+        }))
+        .expect_response_with(|response: Option<Vec<DocumentLink>>| {
+            let Some(links) = response else {
+                return false;
+            };
+            let links = links
+                .into_iter()
+                .map(|link| {
+                    (
+                        (
+                            link.range.start.line,
+                            link.range.start.character,
+                            link.range.end.character,
+                        ),
+                        link.target.and_then(|x| x.to_file_path().ok()),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let [
+                (helper_range, Some(helper)),
+                (pathlib_range, Some(pathlib)),
+                (config_range, Some(config)),
+                (templates_range, Some(templates)),
+            ] = links.as_slice()
+            else {
+                return false;
+            };
+            *helper_range == (0, 7, 13)
+                && helper.ends_with("helper.py")
+                && *pathlib_range == (1, 5, 12)
+                && pathlib.ends_with("pathlib/__init__.pyi")
+                && *config_range == (3, 11, 27)
+                && config.ends_with("data/config.yaml")
+                && *templates_range == (5, 37, 46)
+                && templates.ends_with("templates")
+        })
+        .unwrap();
+
+    interaction.shutdown().unwrap();
+}
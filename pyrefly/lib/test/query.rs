@@ -13,6 +13,7 @@ use pyrefly_python::module_path::ModulePath;
 use pyrefly_util::arc_id::ArcId;
 use pyrefly_util::fs_anyhow;
 use pyrefly_util::thread_pool::TEST_THREAD_COUNT;
+use ruff_text_size::TextSize;
 use serde_json::Value;
 use tempfile::TempDir;
 
@@ -544,3 +545,88 @@ class Config:
         "expected an attribute named `name`"
     );
 }
+
+#[test]
+fn test_get_type_at_and_definitions() {
+    let tdir = TempDir::new().unwrap();
+    let file_path = tdir.path().join("main.py");
+    let code = r#"def f(x: int) -> str:
+    return str(x)
+
+y = f(1)
+"#;
+    fs_anyhow::write(&file_path, code).unwrap();
+
+    let query = create_query();
+    let module_name = ModuleName::from_str("main");
+    let path = ModulePath::filesystem(file_path.clone());
+    let errors = query.add_files(vec![(module_name, path.clone())]);
+    assert!(errors.is_empty(), "Unexpected errors: {:?}", errors);
+
+    let use_of_y = TextSize::new(code.find("y =").unwrap() as u32);
+    assert_eq!(
+        query.get_type_at(module_name, path.clone(), use_of_y),
+        Some("str".to_owned())
+    );
+
+    let use_of_f = TextSize::new(code.find("f(1)").unwrap() as u32);
+    let definitions = query.get_definitions(module_name, path, use_of_f);
+    assert_eq!(definitions.len(), 1);
+    assert_eq!(
+        definitions[0].range.start(),
+        TextSize::new(code.find("f(x").unwrap() as u32)
+    );
+}
+
+#[test]
+fn test_get_references_across_files() {
+    let tdir = TempDir::new().unwrap();
+    let lib_path = tdir.path().join("lib.py");
+    let main_path = tdir.path().join("main.py");
+    let lib_code = "def helper() -> None: ...\n";
+    let main_code = "from lib import helper\n\nhelper()\nhelper()\n";
+    fs_anyhow::write(&lib_path, lib_code).unwrap();
+    fs_anyhow::write(&main_path, main_code).unwrap();
+
+    let query = create_query();
+    let lib = ModuleName::from_str("lib");
+    let main = ModuleName::from_str("main");
+    let lib_path = ModulePath::filesystem(lib_path);
+    let main_path = ModulePath::filesystem(main_path);
+    let errors = query.add_files(vec![(lib, lib_path.clone()), (main, main_path.clone())]);
+    assert!(errors.is_empty(), "Unexpected errors: {:?}", errors);
+
+    let references = query.get_references(lib, lib_path.clone(), TextSize::new(4));
+    let count_in = |path: &ModulePath| {
+        references
+            .iter()
+            .filter(|r| r.module.path() == path)
+            .count()
+    };
+    // The definition itself, the import and the two calls.
+    assert_eq!(count_in(&lib_path), 1);
+    assert_eq!(count_in(&main_path), 3);
+}
+
+#[test]
+fn test_get_types_in_file() {
+    let tdir = TempDir::new().unwrap();
+    let file_path = tdir.path().join("main.py");
+    fs_anyhow::write(&file_path, "x = [1]\n").unwrap();
+
+    let query = create_query();
+    let module_name = ModuleName::from_str("main");
+    let path = ModulePath::filesystem(file_path.clone());
+    let errors = query.add_files(vec![(module_name, path.clone())]);
+    assert!(errors.is_empty(), "Unexpected errors: {:?}", errors);
+
+    let types = query.get_types_in_file(module_name, path).unwrap();
+    assert!(
+        types.iter().any(|(_, ty)| ty == "list[int]"),
+        "Expected a list[int] expression:\n{types:#?}"
+    );
+    assert!(
+        types.iter().any(|(_, ty)| ty == "Literal[1]"),
+        "Expected a Literal[1] expression:\n{types:#?}"
+    );
+}
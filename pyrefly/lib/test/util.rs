@@ -142,6 +142,7 @@ pub struct TestEnv {
     unknown_argument_type_error: bool,
     unknown_variable_type_error: bool,
     implicit_reexport_error: bool,
+    all_errors: bool,
     default_require_level: Require,
     extra_file_extensions: Vec<String>,
     /// The `Require` level passed to `run()` in `to_state()`. Controls whether
@@ -197,6 +198,7 @@ impl TestEnv {
             unknown_argument_type_error: false,
             unknown_variable_type_error: false,
             implicit_reexport_error: false,
+            all_errors: false,
             default_require_level: Require::Exports,
             extra_file_extensions: Vec::new(),
             run_require: Require::Everything,
@@ -469,6 +471,12 @@ impl TestEnv {
         self
     }
 
+    /// Report every error kind, including those that are off by default, as an error.
+    pub fn enable_all_errors(mut self) -> Self {
+        self.all_errors = true;
+        self
+    }
+
     pub fn with_default_require_level(mut self, level: Require) -> Self {
         self.default_require_level = level;
         self
@@ -568,6 +576,11 @@ impl TestEnv {
             config.root.errors = Some(ErrorDisplayConfig::new(HashMap::new()));
         };
         let errors = config.root.errors.as_mut().unwrap();
+        if self.all_errors {
+            for kind in enum_iterator::all::<ErrorKind>() {
+                errors.set_error_severity(kind, Severity::Error);
+            }
+        }
         if self.implicitly_defined_attribute_error {
             errors.set_error_severity(ErrorKind::ImplicitlyDefinedAttribute, Severity::Error);
         }